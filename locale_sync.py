@@ -8,13 +8,17 @@ MAIN_LOCALE_FILE = "en-GB.ftl"
 def parse_locale(raw_text):
     messages = {}
     current_heading = ""
+    key = None
     for line in raw_text.splitlines():
         if len(line) > 0:
             if line[0] == "#": # Treat individual comments as headings
                 current_heading = line
+                key = None
+            elif line[0] in " \t" and key is not None: # Indented lines continue the message, e.g. plural variants
+                messages[key][0] += "\n" + line.split(" # TODO: Translate")[0]
             else:
                 line = line.split("#")[0] # Ignore comments
-                key, value = line.split(" = ")
+                key, value = line.split(" = ", 1)
                 messages[key] = [value, current_heading]
 
     return messages
@@ -7,6 +7,11 @@ sounds = Sounds
 images = Images
 rbxm-files = RBXM Files
 ktx-files = KTX Files
+mesh-files = Meshes
+fonts = Fonts
+json-files = JSON
+all = All
+statistics = Statistics
 settings = Settings
 about = About
 logs = Logs
@@ -19,7 +24,7 @@ button-extract-all = Extract all <F3>
 button-change-cache-dir = Change cache directory
 button-reset-cache-dir = Reset cache directory
 button-change-sql-db = Change SQL Database
-button-reset-sql-db = Reset SQL Database
+button-reset-sql-db = Re-detect SQL Database
 button-finish = Finish
 button-yes = Yes
 button-no = No
@@ -34,6 +39,47 @@ button-extract-file = Extract <Ctrl+E>
 button-display-image-preview = Display image previews
 button-disable-display-image-preview = Stop displaying image previews
 input-preview-size = Preview size
+input-alias-display-length = Longest alias shown in the list
+button-clear-history = Clear history
+button-show-failures = ⚠ { $count } failed
+button-clear-failures = Clear
+input-search-history = Search history by asset ID
+button-import-swap-pack = Import swap pack
+button-revert-modifications = Revert all my modifications
+button-copy-base64 = Copy as base64
+button-copy-data-uri = Copy as data URI
+button-preview = Preview in new window
+button-run-health-check = Run health check
+button-use-detected = Use { $path }
+button-group-by-day = Group by day
+button-ungroup = Don't group
+button-extract-group = Extract
+button-batch-alias = Batch alias…
+button-apply = Apply
+button-cancel = Cancel
+button-undo = Undo
+button-change-sounds-dir = Change sounds directory
+button-reset-sounds-dir = Reset sounds directory
+button-cancel-and-exit = Cancel and exit
+button-keep-running = Keep running
+button-exit-now = Exit now
+button-find-orphaned-notes = Find notes on missing assets
+button-remove = Remove
+button-remove-all = Remove all
+input-note = Notes, e.g. where this asset was found
+button-contact-sheet = Make contact sheets
+button-change-temp-fallback-dir = Change temp fallback directory
+button-reset-temp-fallback-dir = Reset temp fallback directory
+button-reclassify = Re-classify
+button-export-archive = Export as ZIP…
+button-copy-hash = Copy SHA-256
+button-force-rescan = Force full rescan
+button-auto-extract = Auto-extract new…
+button-sort-ascending = Ascending
+button-sort-descending = Descending
+button-recount = Count again
+button-open-as-database = Open as database
+button-open-as-cache-directory = Open as cache directory
 
 # Confirmations
 confirmation-generic-confirmation-title = Confirmation
@@ -49,6 +95,30 @@ confirmation-custom-sql-title = Choose a SQL Database
 confirmation-custom-sql-description = Do you want to choose a different SQL Database?
 confirmation-ban-warning-title = Potential ban alert
 confirmation-ban-warning-description = Editing assets in games can cause your client to behave differently leading to potential game bans! Use at your own risk. Do you understand?
+confirmation-swap-pack-description = { $resolvable ->
+        [one] { $resolvable } swap
+       *[other] { $resolvable } swaps
+    } can be applied ({ $hash_matched } matched by content), { $missing ->
+        [one] { $missing } asset is
+       *[other] { $missing } assets are
+    } missing from your cache and { $mismatches ->
+        [one] { $mismatches } swap looks
+       *[other] { $mismatches } swaps look
+    } incompatible (different types or very different sizes). Only the swaps that can be applied will be written. Continue?
+confirmation-risky-write-title = These assets don't look compatible
+confirmation-risky-write-description = { $warnings } Roblox may behave oddly after this. Continue anyways?
+confirmation-revert-modifications-title = Revert all modifications?
+confirmation-revert-modifications-description = { $count ->
+        [one] { $count } asset will be restored from its backup
+       *[other] { $count } assets will be restored from their backups
+    }. Continue?
+task-running-title = Extraction in progress
+task-running-description = RoExtract is still extracting or deleting assets. Cancelling stops it after the current file, exiting now may leave a file half written.
+confirmation-extract-filtered-title = Extract search results
+confirmation-extract-filtered-description = Only the { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } matching the search will be extracted, not the whole list. Continue?
 
 # Errors
 no-files = No files to list.
@@ -63,6 +133,10 @@ error-invalid-directory-description = Please make sure the path you provided is
 error-invalid-database-title = Invalid database!
 error-invalid-database-description = Please make sure the path you provided is an SQLite Database
 generic-error-critical = Critical error
+error-sql-detection-details = No database could be opened:
+error-invalid-alias-pattern = Invalid pattern: { $error }
+error-clear-refused-title = Cache not cleared
+error-clear-refused-description = Nothing was deleted as { $path } doesn't look safe to clear: { $reason }. Check the cache directory and database in the settings.
 
 # Headings
 actions = Actions
@@ -72,6 +146,15 @@ new-updates = New updates available
 contributors = Contributors
 dependencies = Dependencies
 behavior = Behavior
+extraction-history = Extraction history
+modifications = Modified assets
+preview = Preview
+extract-options = Extraction options
+health-check = Health check
+batch-alias = Batch alias
+notes = Notes
+extract-failures = Couldn't be extracted
+open-dropped-title = Open until RoExtract is closed?
 
 # Checkboxes
 check-for-updates = Check for updates
@@ -79,8 +162,26 @@ automatically-install-updates = Automatically install updates
 use-alias = Export your renamed filenames
 use-topbar-buttons = Enable toolbar
 refresh-before-extract = Refresh file list before extracting
+merge-duplicate-sources = List assets that are in both the database and the cache directory under different names once (slower, their contents are compared)
 download-development-build = Use development builds to get the latest features early (These builds may be unstable)
 checkbox-hide-user-logs = Hide username from logs
+record-extraction-history = Record extraction history
+skip-previously-extracted = Skip assets that have been extracted before
+export-playlist = Write a playlist of extracted audio
+playlist-sort-by-alias = Sort the playlist by alias instead of extraction order
+image-power-of-two = Snap image sizes down to a power of two
+convert-images-to-png = Convert WebP and KTX textures to PNG
+convert-audio-to-wav = Convert OGG audio to WAV
+trim-silence = Trim silence at the start and end of converted audio
+normalize-audio = Normalise the volume of converted audio
+checkbox-read-only = 🔒 Read-only mode
+show-json-tab = Show a tab for JSON assets
+incremental-extract = Skip files that haven't changed since they were last extracted
+sort-into-subfolders = Sort extracted files into a folder for each type
+export-manifest = Write a manifest.json listing which file came from which asset
+verify-on-extract = Don't extract assets that don't match the checksum Roblox stored with them
+auto-refresh = Refresh the list automatically when the cache changes
+search-regex = Regex
 
 
 # Descriptions
@@ -94,6 +195,24 @@ swap-with = Double click a file to swap with "{ $asset }"
 logs-description = The logs show how the program is performing, if any errors happen, they will show up here
 copy-choose-file = Double click a file to copy
 overwrite-with = Double click a file to overwrite with "{ $asset }"
+extraction-history-description = Every asset you extract can be recorded so you can look up when and where you extracted it. The history is stored next to your config file.
+import-swap-pack-description = Swap packs are lists of swaps shared by other users. Every asset in the pack is checked against your cache before anything is changed.
+modifications-description = Every asset RoExtract swaps or copies over is backed up first, so all of your changes can be reverted at once.
+extract-options-description = Options for each type of asset when extracting. Command line flags override these.
+extract-template-description = Output path template, used instead of naming and layout for every category when set. Placeholders: id, alias, category, ext, hash, hash8, date, date:%Y-%m, counter, counter:4 in curly brackets. Leave empty to use the options above.
+health-check-description = Checks that RoExtract can find Roblox's cache and that it has assets in it.
+image-resize-description = Images bigger than the maximum size are downscaled when extracting, keeping their aspect ratio. 0 extracts them as they are.
+batch-alias-description = Aliases the selected assets in the order they were selected, select more with Ctrl+click or Shift+click. { $counter } counts up from 1, { $padded } pads it to 3 digits and { $id } is the first 8 characters of the asset id.
+custom-sounds-dir-description = The Music tab lists the sounds folder, which is usually inside the cache directory. If your install keeps it somewhere else, choose it below.
+read-only-description = Read-only mode makes sure nothing in the cache or database is changed, for inspecting or archiving it. Extracting still works, but nothing is added to the extraction history.
+notes-description = Notes can be added to an asset in its preview window, and are matched by the search box. Notes on assets that are no longer cached can be found and removed here.
+contact-sheet-description = Contact sheets lay the listed images out on a grid with their aliases, images that can't be decoded are crossed out.
+contact-sheet-columns = Columns
+contact-sheet-rows = Rows per page
+contact-sheet-thumbnail-size = Thumbnail size
+temp-directory-description = Files opened from the list are written to the temporary directory. Large files, or any file when it's nearly full, are written to the fallback directory instead, as the temporary directory can be stored in RAM.
+force-rescan-description = Assets that haven't changed since the last scan are listed without being read again. Force a full rescan if the list looks out of date.
+statistics-description = How many assets the cache has of each category, how much space they take and when they were cached.
 
 
 # Statuses
@@ -102,10 +221,64 @@ deleting-files = Deleting files ({ $item }/{ $total })
 reading-files = Reading files ({ $item }/{ $total })
 extracting-files = Extracting files ({ $item }/{ $total })
 filtering-files = Filtering files ({ $item }/{ $total })
+merging-duplicates = Looking for assets in both the database and the cache directory
 all-extracted = All files extracted
 stage = Stage { $stage }/{ $max }: { $status }
 swapped = Swapped { $item_a } with { $item_b }
 copied = Overwritten { $item_b } with { $item_a }
+checking-swap-pack = Checking swap pack
+modifications-reverted = Modifications reverted, { $failed } could not be restored
+copied-to-clipboard = Copied to clipboard
+loading-category = Loading { $category }…
+cancelled-after = Cancelled after { $item } of { $total ->
+        [one] { $total } file
+       *[other] { $total } files
+    }
+skipped-duplicates = Skipped { $count ->
+        [one] { $count } duplicate
+       *[other] { $count } duplicates
+    }
+database-busy = The database is busy, try again in a moment
+batch-alias-applied = Aliased { $count ->
+        [one] { $count } asset
+       *[other] { $count } assets
+    }
+extract-summary = Extracted { $written ->
+        [one] { $written } file
+       *[other] { $written } files
+    } ({ $size }) in { $duration }s, { $skipped } skipped, { $failed } failed
+extract-summary-failed = Extracted { $written } of { $total }, { $failed } failed ({ $size }) in { $duration }s, { $skipped } skipped
+extract-summary-duplicates = { $summary }, { $unique } unique, { $duplicates } duplicates skipped
+extract-summary-not-converted = { $summary }, { $count } couldn't be converted
+extract-summary-corrupt = { $summary }, { $count } corrupt
+refresh-summary = Last refresh: { $listed } listed, { $skipped } skipped, { $errors ->
+        [one] { $errors } error
+       *[other] { $errors } errors
+    } in { $duration }s
+initializing-sources = Initialising sources…
+cancelling-task = Cancelling, waiting for the current file to finish…
+making-contact-sheet = Making contact sheets ({ $item }/{ $total })
+contact-sheet-written = Wrote { $pages } contact sheet pages with { $images } images, { $failed } couldn't be decoded
+reclassifying-files = Re-classifying files ({ $item }/{ $total })
+reclassify-summary = Re-classified { $checked ->
+        [one] { $checked } asset
+       *[other] { $checked } assets
+    } in { $duration }s, { $moved } changed category, { $reformatted } changed format, { $errors ->
+        [one] { $errors } error
+       *[other] { $errors } errors
+    }
+no-files-to-extract = No files to extract.
+auto-refreshed-ago = auto-refreshed { $seconds }s ago
+auto-extracted-count = auto-extracted { $count } assets
+listed-total = { $count } files, { $size }
+statistics-stopped = Counting was stopped, not every asset is counted.
+statistics-no-database = No database was found, only the cache directory was counted.
+statistics-duplicates = { $count ->
+        [one] { $count } asset is in both the database and the cache directory, it was counted once.
+       *[other] { $count } assets are in both the database and the cache directory, each was counted once.
+    }
+opened-for-session = Opened { $path } until RoExtract is closed
+dropped-file-ignored = { $file } was ignored, only a database or a cache folder can be dropped
 
 # Error Statuses
 failed-deleting-file = ERROR: Failed to delete ({ $item }/{ $total })
@@ -113,6 +286,23 @@ failed-opening-file = ERROR: Failed to open file
 failed-not-file = ERROR: '{ $file }' Not a file
 error-extracting-file = ERROR: Failed to extract: { $error }
 error-check-logs = ERROR: Check logs for more details.
+error-reading-swap-pack = ERROR: Failed to read swap pack: { $error }
+error-cache-directory-unreachable = ERROR: The cache directory { $directory } can't be reached, check that the drive is connected
+error-too-large-for-clipboard = ERROR: This asset is too big to copy to the clipboard, the limit is { $max_size }
+error-asset-evicted = ERROR: This entry's data is no longer in the cache
+error-asset-corrupt = ERROR: This asset doesn't match the checksum stored with it
+error-invalid-template = Invalid extraction template: { $error }
+error-read-only = Read-only mode is on, nothing was changed
+error-temp-space-exhausted = ERROR: Not enough space in the temporary directory for { $size }
+error-header-not-found = ERROR: No known file type was found in this asset
+error-asset-not-found = ERROR: This asset isn't in the cache
+error-invalid-asset-id = ERROR: '{ $id }' isn't a valid asset ID
+error-no-database = ERROR: No database is open
+error-with-code = ERROR: { $error }
+error-task-running = ERROR: Another task is running, try again when it has finished
+error-list-replaced = ERROR: The list was refreshed before it could be extracted, nothing was extracted
+invalid-regex = Invalid regular expression: { $error }
+error-open-dropped = ERROR: Couldn't open { $path }: { $error }
 
 # Misc
 no-function = (Not functional yet)
@@ -124,4 +314,124 @@ download-update-question = Would you like to download the update?
 update-changelog = Update changelog below
 support-sponsor = ♥ Sponsor
 support-project-donate = ♥ Donate
-setting-below-restart-required = Note: Changing the setting below requires restarting the program for it to apply.
\ No newline at end of file
+setting-below-restart-required = Note: Changing the setting below requires restarting the program for it to apply.
+column-name = Name
+column-format = Format
+column-thumbnail = Thumbnail
+thumbnail-avatar = Avatar
+thumbnail-headshot = Headshot
+thumbnail-bust = Bust
+thumbnail-asset = Asset
+column-size-on-disk = Size (on disk)
+column-size-actual = Size (actual)
+history-entries = Entries: { $count }
+swap-pack-title = Swap pack
+swap-pack-nothing-to-apply = None of the swaps in this pack can be applied. { $missing ->
+        [one] { $missing } asset is
+       *[other] { $missing } assets are
+    } missing from your cache and { $mismatches ->
+        [one] { $mismatches } swap looks
+       *[other] { $mismatches } swaps look
+    } incompatible (different types or very different sizes).
+warning-category-mismatch = The asset being replaced is { $destination } but the new content is { $payload }.
+warning-size-disparity = The asset being replaced is { $destination } but the new content is { $payload }.
+modified-assets = Modified assets: { $count }
+modified-badge = modified
+hex-view-truncated = Showing the first { $shown } of { $total }
+preview-format = Format: { $format }
+preview-format-compressed = Format: { $format }, stored compressed with { $compression }
+option-naming = File names
+option-dedupe = Skip duplicates
+option-layout = Folders
+other-assets = Other
+naming-id = Asset ID
+naming-alias = Alias
+naming-hash = Content hash
+naming-thumbnail = Thumbnail user or asset ID
+layout-flat = One folder
+layout-category = Folder per type
+evicted-badge = evicted
+health-database = Database
+health-cache-directory = Cache folder
+health-classification = Asset types
+health-database-missing = No Roblox database was found.
+health-database-empty = The database at { $path } has no assets.
+health-database-ok = { $count ->
+        [one] { $count } asset
+       *[other] { $count } assets
+    } in { $path }.
+health-cache-directory-unreachable = { $directory } can't be reached.
+health-cache-directory-missing-folders = { $directory } is missing these folders: { $folders }.
+health-cache-directory-empty = { $directory } has no cached files.
+health-cache-directory-ok = { $count ->
+        [one] { $count } cached file
+       *[other] { $count } cached files
+    } in { $directory }.
+health-classification-skipped = There were no assets to sample.
+health-classification-none = None of the { $total } sampled assets have a known type.
+health-classification-ok = { $known } of the { $total } sampled assets have a known type.
+health-action-launch-roblox = Launch Roblox and join a game to populate the cache.
+health-action-choose-directory = Choose the Roblox or Sober cache folder in settings.
+health-action-choose-database = Choose the Roblox or Sober database in settings.
+group-today = Today
+group-yesterday = Yesterday
+group-this-week = This week
+group-unknown-date = Unknown date
+group-header = { $group } ({ $count }, { $size })
+image-max-dimension = Maximum size (px)
+image-resize-filter = Filter
+resize-filter-nearest = Nearest
+resize-filter-triangle = Triangle
+resize-filter-catmullrom = Catmull-Rom
+resize-filter-gaussian = Gaussian
+resize-filter-lanczos3 = Lanczos3
+sql-database-info = { $state }, { $rows ->
+        [one] { $rows } row
+       *[other] { $rows } rows
+    }, { $size }
+sql-database-read-write = Connected
+sql-database-read-only = Connected read-only
+sql-database-disconnected = Not connected
+batch-alias-pattern = Pattern
+batch-alias-list = List of names, one per line
+batch-alias-current = Current alias
+batch-alias-new = New alias
+batch-alias-collision-empty = empty
+batch-alias-collision-existing = already used by { $asset }
+batch-alias-collision-batch = used more than once
+batch-alias-invalid-control = contains control characters
+batch-alias-invalid-too-long = longer than { $length } characters
+sounds-directory = Sounds directory: { $directory }
+sounds-directory-missing = No sounds directory detected at { $directory }, configure it in settings.
+music-tooltip = Audio files from the sounds folder, listed as they are. These are usually longer tracks such as music.
+sounds-tooltip = Audio found in the http cache and the database by its contents. These are usually short sound effects, but music can show up here too.
+health-sounds-directory = Sounds directory
+health-sounds-directory-ok = Music is listed from { $directory }.
+health-sounds-directory-missing = No sounds directory detected at { $directory }, the Music tab will be empty.
+health-action-choose-sounds-directory = Choose the sounds folder in settings if your install keeps it elsewhere.
+read-only-mode = read-only
+read-only-tooltip = Turn off read-only mode in settings to use this
+notes-count = Notes: { $count }
+orphaned-notes = Notes on missing assets: { $count }
+format-unknown = unknown
+temp-directory-usage = Temporary directory: { $directory } ({ $usage } used by RoExtract, { $available } free)
+temp-fallback-directory = Fallback directory: { $directory }
+overwrite-policy = If a file already exists
+overwrite-overwrite = Overwrite it
+overwrite-skip = Skip it
+overwrite-rename = Add a number to the name
+decimal-separator = .
+sort-name = Sort by name
+sort-size = Sort by size
+sort-modified = Sort by date modified
+sort-category = Sort by category
+filter-min-size = At least
+filter-max-size = At most
+filter-newer-than = Modified in the last
+filter-thumbnails = Only avatar and asset thumbnails
+statistics-category = Category
+statistics-count = Assets
+statistics-size = Size
+statistics-oldest = Oldest
+statistics-newest = Newest
+statistics-total = Total
@@ -2,7 +2,12 @@
 # Language info  
 language-name = Pirate Speak  
 
-# Tabs  
+# Tabs
+statistics = Statistics # TODO: Translate
+json-files = JSON # TODO: Translate
+fonts = Fonts # TODO: Translate
+mesh-files = Meshes # TODO: Translate
+all = All # TODO: Translate  
 music = Sea Shanties  
 sounds = Cannon Blasts  
 images = Treasure Maps  
@@ -13,6 +18,47 @@ about = 'Bout This Vessel
 logs = Ship’s Log  
 
 # Buttons
+button-open-as-cache-directory = Open as cache directory # TODO: Translate
+button-open-as-database = Open as database # TODO: Translate
+button-recount = Count again # TODO: Translate
+button-sort-ascending = Ascending # TODO: Translate
+button-sort-descending = Descending # TODO: Translate
+button-auto-extract = Auto-extract new… # TODO: Translate
+button-force-rescan = Force full rescan # TODO: Translate
+button-show-failures = ⚠ { $count } failed # TODO: Translate
+button-clear-failures = Clear # TODO: Translate
+button-copy-hash = Copy SHA-256 # TODO: Translate
+button-export-archive = Export as ZIP… # TODO: Translate
+input-alias-display-length = Longest alias shown in the list # TODO: Translate
+button-reclassify = Re-classify # TODO: Translate
+button-change-temp-fallback-dir = Change temp fallback directory # TODO: Translate
+button-reset-temp-fallback-dir = Reset temp fallback directory # TODO: Translate
+button-contact-sheet = Make contact sheets # TODO: Translate
+button-remove = Remove # TODO: Translate
+button-find-orphaned-notes = Find notes on missing assets # TODO: Translate
+button-remove-all = Remove all # TODO: Translate
+input-note = Notes, e.g. where this asset was found # TODO: Translate
+button-exit-now = Exit now # TODO: Translate
+button-cancel-and-exit = Cancel and exit # TODO: Translate
+button-keep-running = Keep running # TODO: Translate
+button-change-sounds-dir = Change sounds directory # TODO: Translate
+button-reset-sounds-dir = Reset sounds directory # TODO: Translate
+button-batch-alias = Batch alias… # TODO: Translate
+button-undo = Undo # TODO: Translate
+button-cancel = Cancel # TODO: Translate
+button-apply = Apply # TODO: Translate
+button-extract-group = Extract # TODO: Translate
+button-group-by-day = Group by day # TODO: Translate
+button-ungroup = Don't group # TODO: Translate
+button-use-detected = Use { $path } # TODO: Translate
+button-run-health-check = Run health check # TODO: Translate
+button-preview = Preview in new window # TODO: Translate
+button-copy-data-uri = Copy as data URI # TODO: Translate
+button-copy-base64 = Copy as base64 # TODO: Translate
+button-revert-modifications = Revert all my modifications # TODO: Translate
+button-import-swap-pack = Import swap pack # TODO: Translate
+button-clear-history = Clear history # TODO: Translate
+input-search-history = Search history by asset ID # TODO: Translate
 button-reset-sql-db = Scuttle the SQL Ledger
 button-change-sql-db = Chart a New Course fer the SQL Ledger
 button-delete-this-dir = Scuttle This Hold <Del>  
@@ -38,6 +84,30 @@ button-disable-display-image-preview = Hide the Treasure Maps
 input-preview-size = Size o’ the Lookout  
 
 # Confirmations
+confirmation-extract-filtered-description = Only the { $count ->
+        [one] { $count } file
+       *[other] { $count } files
+    } matching the search will be extracted, not the whole list. Continue? # TODO: Translate
+confirmation-extract-filtered-title = Extract search results # TODO: Translate
+task-running-title = Extraction in progress # TODO: Translate
+task-running-description = RoExtract is still extracting or deleting assets. Cancelling stops it after the current file, exiting now may leave a file half written. # TODO: Translate
+confirmation-revert-modifications-title = Revert all modifications? # TODO: Translate
+confirmation-revert-modifications-description = { $count ->
+        [one] { $count } asset will be restored from its backup
+       *[other] { $count } assets will be restored from their backups
+    }. Continue? # TODO: Translate
+confirmation-risky-write-description = { $warnings } Roblox may behave oddly after this. Continue anyways? # TODO: Translate
+confirmation-risky-write-title = These assets don't look compatible # TODO: Translate
+confirmation-swap-pack-description = { $resolvable ->
+        [one] { $resolvable } swap
+       *[other] { $resolvable } swaps
+    } can be applied ({ $hash_matched } matched by content), { $missing ->
+        [one] { $missing } asset is
+       *[other] { $missing } assets are
+    } missing from your cache and { $mismatches ->
+        [one] { $mismatches } swap looks
+       *[other] { $mismatches } swaps look
+    } incompatible (different types or very different sizes). Only the swaps that can be applied will be written. Continue? # TODO: Translate
 confirmation-custom-sql-title = Choose a SQL Database # TODO: Translate
 confirmation-custom-sql-description = Do you want to choose a different SQL Database? # TODO: Translate  
 confirmation-generic-confirmation-title = Aye, Be Ye Sure?  
@@ -53,6 +123,10 @@ confirmation-ban-warning-title = Beware! A Curse May Be Upon Ye!
 confirmation-ban-warning-description = Tamperin’ with booty in games could get ye marooned! Sail at yer own risk! Do ye understand, matey?  
 
 # Errors
+error-clear-refused-description = Nothing was deleted as { $path } doesn't look safe to clear: { $reason }. Check the cache directory and database in the settings. # TODO: Translate
+error-clear-refused-title = Cache not cleared # TODO: Translate
+error-invalid-alias-pattern = Invalid pattern: { $error } # TODO: Translate
+error-sql-detection-details = No database could be opened: # TODO: Translate
 error-invalid-database-title = That Be No Proper Ledger!
 error-invalid-database-description = Make sure the path leads to a proper SQLite treasure map!
 error-sql-detection-title = Can’t Find the Ledger!
@@ -66,7 +140,16 @@ error-invalid-directory-title = That Ain’t a Proper Course!
 error-invalid-directory-description = Avast! The path ye set ain’t leadin’ to a proper hold!  
 generic-error-critical = A Most Dire Curse!  
 
-# Headings  
+# Headings
+open-dropped-title = Open until RoExtract is closed? # TODO: Translate
+extract-failures = Couldn't be extracted # TODO: Translate
+notes = Notes # TODO: Translate
+batch-alias = Batch alias # TODO: Translate
+health-check = Health check # TODO: Translate
+extract-options = Extraction options # TODO: Translate
+preview = Preview # TODO: Translate
+modifications = Modified assets # TODO: Translate
+extraction-history = Extraction history # TODO: Translate  
 actions = Orders  
 updates = Messages in a Bottle  
 language-settings = Speak Like a Pirate  
@@ -75,7 +158,25 @@ contributors = Loyal Crew
 dependencies = Ship’s Riggings  
 behavior = How the Crew Acts  
 
-# Checkboxes  
+# Checkboxes
+verify-on-extract = Don't extract assets that don't match the checksum Roblox stored with them # TODO: Translate
+merge-duplicate-sources = List assets that are in both the database and the cache directory under different names once (slower, their contents are compared) # TODO: Translate
+normalize-audio = Normalise the volume of converted audio # TODO: Translate
+trim-silence = Trim silence at the start and end of converted audio # TODO: Translate
+search-regex = Regex # TODO: Translate
+auto-refresh = Refresh the list automatically when the cache changes # TODO: Translate
+convert-audio-to-wav = Convert OGG audio to WAV # TODO: Translate
+convert-images-to-png = Convert WebP and KTX textures to PNG # TODO: Translate
+export-manifest = Write a manifest.json listing which file came from which asset # TODO: Translate
+sort-into-subfolders = Sort extracted files into a folder for each type # TODO: Translate
+incremental-extract = Skip files that haven't changed since they were last extracted # TODO: Translate
+show-json-tab = Show a tab for JSON assets # TODO: Translate
+checkbox-read-only = 🔒 Read-only mode # TODO: Translate
+image-power-of-two = Snap image sizes down to a power of two # TODO: Translate
+export-playlist = Write a playlist of extracted audio # TODO: Translate
+playlist-sort-by-alias = Sort the playlist by alias instead of extraction order # TODO: Translate
+skip-previously-extracted = Skip assets that have been extracted before # TODO: Translate
+record-extraction-history = Record extraction history # TODO: Translate  
 check-for-updates = Keep an Eye on the Horizon  
 automatically-install-updates = Let the Crew Handle Updates  
 use-alias = Mark Ye Plunder with Custom Names  
@@ -85,6 +186,24 @@ download-development-build = Test New Gadgets (May Be Unstable Waters)
 checkbox-hide-user-logs = Hide Ye True Name from the Ship’s Log  
 
 # Descriptions
+statistics-description = How many assets the cache has of each category, how much space they take and when they were cached. # TODO: Translate
+force-rescan-description = Assets that haven't changed since the last scan are listed without being read again. Force a full rescan if the list looks out of date. # TODO: Translate
+temp-directory-description = Files opened from the list are written to the temporary directory. Large files, or any file when it's nearly full, are written to the fallback directory instead, as the temporary directory can be stored in RAM. # TODO: Translate
+contact-sheet-thumbnail-size = Thumbnail size # TODO: Translate
+contact-sheet-description = Contact sheets lay the listed images out on a grid with their aliases, images that can't be decoded are crossed out. # TODO: Translate
+contact-sheet-columns = Columns # TODO: Translate
+contact-sheet-rows = Rows per page # TODO: Translate
+notes-description = Notes can be added to an asset in its preview window, and are matched by the search box. Notes on assets that are no longer cached can be found and removed here. # TODO: Translate
+read-only-description = Read-only mode makes sure nothing in the cache or database is changed, for inspecting or archiving it. Extracting still works, but nothing is added to the extraction history. # TODO: Translate
+custom-sounds-dir-description = The Music tab lists the sounds folder, which is usually inside the cache directory. If your install keeps it somewhere else, choose it below. # TODO: Translate
+batch-alias-description = Aliases the selected assets in the order they were selected, select more with Ctrl+click or Shift+click. { $counter } counts up from 1, { $padded } pads it to 3 digits and { $id } is the first 8 characters of the asset id. # TODO: Translate
+image-resize-description = Images bigger than the maximum size are downscaled when extracting, keeping their aspect ratio. 0 extracts them as they are. # TODO: Translate
+health-check-description = Checks that RoExtract can find Roblox's cache and that it has assets in it. # TODO: Translate
+extract-template-description = Output path template, used instead of naming and layout for every category when set. Placeholders: id, alias, category, ext, hash, hash8, date, date:%Y-%m, counter, counter:4 in curly brackets. Leave empty to use the options above. # TODO: Translate
+extract-options-description = Options for each type of asset when extracting. Command line flags override these. # TODO: Translate
+modifications-description = Every asset RoExtract swaps or copies over is backed up first, so all of your changes can be reverted at once. # TODO: Translate
+import-swap-pack-description = Swap packs are lists of swaps shared by other users. Every asset in the pack is checked against your cache before anything is changed. # TODO: Translate
+extraction-history-description = Every asset you extract can be recorded so you can look up when and where you extracted it. The history is stored next to your config file. # TODO: Translate
 custom-sql-db-description = If ye be needin’ to access a different stash o’ booty, chart a new course fer yer SQL ledger below. Ye can always sail back to default waters. This be not the same as yer main installin’ cove.
 clear-cache-description = If it be takin’ too long to find treasure, ye can toss all cargo overboard. The ship will fetch new supplies next time ye sail.  
 extract-all-description = This button will haul all loot aboard and sort it into proper holds, like /cannon-blasts and /treasure-maps. Pick yer stash spot when ye begin.  
@@ -96,7 +215,61 @@ logs-description = The ship’s log keeps track o’ what’s happenin’. If tr
 copy-choose-file = Double-click a file to copy it  
 overwrite-with = Double-click a file to overwrite with "{ $asset }"  
 
-# Statuses  
+# Statuses
+extract-summary-corrupt = { $summary }, { $count } corrupt # TODO: Translate
+statistics-duplicates = { $count ->
+        [one] { $count } asset is in both the database and the cache directory, it was counted once.
+       *[other] { $count } assets are in both the database and the cache directory, each was counted once.
+    } # TODO: Translate
+merging-duplicates = Looking for assets in both the database and the cache directory # TODO: Translate
+opened-for-session = Opened { $path } until RoExtract is closed # TODO: Translate
+dropped-file-ignored = { $file } was ignored, only a database or a cache folder can be dropped # TODO: Translate
+statistics-stopped = Counting was stopped, not every asset is counted. # TODO: Translate
+statistics-no-database = No database was found, only the cache directory was counted. # TODO: Translate
+listed-total = { $count } files, { $size } # TODO: Translate
+auto-extracted-count = auto-extracted { $count } assets # TODO: Translate
+auto-refreshed-ago = auto-refreshed { $seconds }s ago # TODO: Translate
+no-files-to-extract = No files to extract. # TODO: Translate
+extract-summary-failed = Extracted { $written } of { $total }, { $failed } failed ({ $size }) in { $duration }s, { $skipped } skipped # TODO: Translate
+extract-summary-not-converted = { $summary }, { $count } couldn't be converted # TODO: Translate
+extract-summary-duplicates = { $summary }, { $unique } unique, { $duplicates } duplicates skipped # TODO: Translate
+reclassify-summary = Re-classified { $checked ->
+        [one] { $checked } asset
+       *[other] { $checked } assets
+    } in { $duration }s, { $moved } changed category, { $reformatted } changed format, { $errors ->
+        [one] { $errors } error
+       *[other] { $errors } errors
+    } # TODO: Translate
+reclassifying-files = Re-classifying files ({ $item }/{ $total }) # TODO: Translate
+contact-sheet-written = Wrote { $pages } contact sheet pages with { $images } images, { $failed } couldn't be decoded # TODO: Translate
+making-contact-sheet = Making contact sheets ({ $item }/{ $total }) # TODO: Translate
+cancelling-task = Cancelling, waiting for the current file to finish… # TODO: Translate
+batch-alias-applied = Aliased { $count ->
+        [one] { $count } asset
+       *[other] { $count } assets
+    } # TODO: Translate
+initializing-sources = Initialising sources… # TODO: Translate
+database-busy = The database is busy, try again in a moment # TODO: Translate
+cancelled-after = Cancelled after { $item } of { $total ->
+        [one] { $total } file
+       *[other] { $total } files
+    } # TODO: Translate
+skipped-duplicates = Skipped { $count ->
+        [one] { $count } duplicate
+       *[other] { $count } duplicates
+    } # TODO: Translate
+extract-summary = Extracted { $written ->
+        [one] { $written } file
+       *[other] { $written } files
+    } ({ $size }) in { $duration }s, { $skipped } skipped, { $failed } failed # TODO: Translate
+refresh-summary = Last refresh: { $listed } listed, { $skipped } skipped, { $errors ->
+        [one] { $errors } error
+       *[other] { $errors } errors
+    } in { $duration }s # TODO: Translate
+loading-category = Loading { $category }… # TODO: Translate
+copied-to-clipboard = Copied to clipboard # TODO: Translate
+modifications-reverted = Modifications reverted, { $failed } could not be restored # TODO: Translate
+checking-swap-pack = Checking swap pack # TODO: Translate  
 idling = Swayin’ in the Breeze  
 deleting-files = Tossin’ Files Overboard ({ $item }/{ $total })  
 reading-files = Readin’ the Treasure Maps ({ $item }/{ $total })  
@@ -107,7 +280,24 @@ stage = Stage { $stage }/{ $max }: { $status }
 swapped = Traded { $item_a } for { $item_b }  
 copied = Overwritten { $item_b } with { $item_a }  
 
-# Error Statuses  
+# Error Statuses
+error-asset-corrupt = ERROR: This asset doesn't match the checksum stored with it # TODO: Translate
+error-open-dropped = ERROR: Couldn't open { $path }: { $error } # TODO: Translate
+invalid-regex = Invalid regular expression: { $error } # TODO: Translate
+error-task-running = ERROR: Another task is running, try again when it has finished # TODO: Translate
+error-list-replaced = ERROR: The list was refreshed before it could be extracted, nothing was extracted # TODO: Translate
+error-no-database = ERROR: No database is open # TODO: Translate
+error-asset-not-found = ERROR: This asset isn't in the cache # TODO: Translate
+error-header-not-found = ERROR: No known file type was found in this asset # TODO: Translate
+error-with-code = ERROR: { $error } # TODO: Translate
+error-invalid-asset-id = ERROR: '{ $id }' isn't a valid asset ID # TODO: Translate
+error-temp-space-exhausted = ERROR: Not enough space in the temporary directory for { $size } # TODO: Translate
+error-read-only = Read-only mode is on, nothing was changed # TODO: Translate
+error-invalid-template = Invalid extraction template: { $error } # TODO: Translate
+error-asset-evicted = ERROR: This entry's data is no longer in the cache # TODO: Translate
+error-too-large-for-clipboard = ERROR: This asset is too big to copy to the clipboard, the limit is { $max_size } # TODO: Translate
+error-cache-directory-unreachable = ERROR: The cache directory { $directory } can't be reached, check that the drive is connected # TODO: Translate
+error-reading-swap-pack = ERROR: Failed to read swap pack: { $error } # TODO: Translate  
 failed-deleting-file = ERROR: Couldn’t Toss ({ $item }/{ $total }) Overboard!  
 failed-opening-file = ERROR: Couldn’t Crack Open the Chest: { $error }  
 failed-not-file = ERROR: '{ $file }' Ain’t Proper Booty!  
@@ -115,6 +305,126 @@ error-extracting-file = ERROR: Couldn’t Haul It Aboard: { $error }
 error-check-logs = ERROR: Check the Captain’s Log for More Clues.  
 
 # Misc
+thumbnail-headshot = Headshot # TODO: Translate
+filter-thumbnails = Only avatar and asset thumbnails # TODO: Translate
+naming-thumbnail = Thumbnail user or asset ID # TODO: Translate
+thumbnail-avatar = Avatar # TODO: Translate
+thumbnail-bust = Bust # TODO: Translate
+column-thumbnail = Thumbnail # TODO: Translate
+thumbnail-asset = Asset # TODO: Translate
+statistics-total = Total # TODO: Translate
+statistics-newest = Newest # TODO: Translate
+statistics-category = Category # TODO: Translate
+statistics-size = Size # TODO: Translate
+statistics-count = Assets # TODO: Translate
+statistics-oldest = Oldest # TODO: Translate
+filter-min-size = At least # TODO: Translate
+filter-newer-than = Modified in the last # TODO: Translate
+filter-max-size = At most # TODO: Translate
+sort-modified = Sort by date modified # TODO: Translate
+sort-size = Sort by size # TODO: Translate
+sort-category = Sort by category # TODO: Translate
+sort-name = Sort by name # TODO: Translate
+decimal-separator = . # TODO: Translate
+overwrite-rename = Add a number to the name # TODO: Translate
+overwrite-skip = Skip it # TODO: Translate
+overwrite-policy = If a file already exists # TODO: Translate
+overwrite-overwrite = Overwrite it # TODO: Translate
+preview-format-compressed = Format: { $format }, stored compressed with { $compression } # TODO: Translate
+preview-format = Format: { $format } # TODO: Translate
+batch-alias-invalid-control = contains control characters # TODO: Translate
+batch-alias-invalid-too-long = longer than { $length } characters # TODO: Translate
+temp-fallback-directory = Fallback directory: { $directory } # TODO: Translate
+temp-directory-usage = Temporary directory: { $directory } ({ $usage } used by RoExtract, { $available } free) # TODO: Translate
+column-format = Format # TODO: Translate
+format-unknown = unknown # TODO: Translate
+orphaned-notes = Notes on missing assets: { $count } # TODO: Translate
+notes-count = Notes: { $count } # TODO: Translate
+read-only-mode = read-only # TODO: Translate
+read-only-tooltip = Turn off read-only mode in settings to use this # TODO: Translate
+sounds-directory = Sounds directory: { $directory } # TODO: Translate
+music-tooltip = Audio files from the sounds folder, listed as they are. These are usually longer tracks such as music. # TODO: Translate
+sounds-directory-missing = No sounds directory detected at { $directory }, configure it in settings. # TODO: Translate
+health-sounds-directory-ok = Music is listed from { $directory }. # TODO: Translate
+health-action-choose-sounds-directory = Choose the sounds folder in settings if your install keeps it elsewhere. # TODO: Translate
+health-sounds-directory = Sounds directory # TODO: Translate
+sounds-tooltip = Audio found in the http cache and the database by its contents. These are usually short sound effects, but music can show up here too. # TODO: Translate
+health-sounds-directory-missing = No sounds directory detected at { $directory }, the Music tab will be empty. # TODO: Translate
+batch-alias-collision-existing = already used by { $asset } # TODO: Translate
+batch-alias-new = New alias # TODO: Translate
+batch-alias-current = Current alias # TODO: Translate
+batch-alias-collision-empty = empty # TODO: Translate
+batch-alias-collision-batch = used more than once # TODO: Translate
+batch-alias-list = List of names, one per line # TODO: Translate
+batch-alias-pattern = Pattern # TODO: Translate
+sql-database-read-only = Connected read-only # TODO: Translate
+sql-database-read-write = Connected # TODO: Translate
+sql-database-disconnected = Not connected # TODO: Translate
+sql-database-info = { $state }, { $rows ->
+        [one] { $rows } row
+       *[other] { $rows } rows
+    }, { $size } # TODO: Translate
+image-max-dimension = Maximum size (px) # TODO: Translate
+image-resize-filter = Filter # TODO: Translate
+resize-filter-gaussian = Gaussian # TODO: Translate
+resize-filter-nearest = Nearest # TODO: Translate
+resize-filter-triangle = Triangle # TODO: Translate
+resize-filter-lanczos3 = Lanczos3 # TODO: Translate
+resize-filter-catmullrom = Catmull-Rom # TODO: Translate
+group-today = Today # TODO: Translate
+group-this-week = This week # TODO: Translate
+group-unknown-date = Unknown date # TODO: Translate
+group-yesterday = Yesterday # TODO: Translate
+group-header = { $group } ({ $count }, { $size }) # TODO: Translate
+health-database-empty = The database at { $path } has no assets. # TODO: Translate
+health-cache-directory-empty = { $directory } has no cached files. # TODO: Translate
+health-database-ok = { $count ->
+        [one] { $count } asset
+       *[other] { $count } assets
+    } in { $path }. # TODO: Translate
+health-action-choose-database = Choose the Roblox or Sober database in settings. # TODO: Translate
+health-database = Database # TODO: Translate
+health-classification = Asset types # TODO: Translate
+health-cache-directory = Cache folder # TODO: Translate
+health-cache-directory-unreachable = { $directory } can't be reached. # TODO: Translate
+health-classification-skipped = There were no assets to sample. # TODO: Translate
+health-classification-none = None of the { $total } sampled assets have a known type. # TODO: Translate
+health-database-missing = No Roblox database was found. # TODO: Translate
+health-action-launch-roblox = Launch Roblox and join a game to populate the cache. # TODO: Translate
+health-cache-directory-missing-folders = { $directory } is missing these folders: { $folders }. # TODO: Translate
+health-classification-ok = { $known } of the { $total } sampled assets have a known type. # TODO: Translate
+health-cache-directory-ok = { $count ->
+        [one] { $count } cached file
+       *[other] { $count } cached files
+    } in { $directory }. # TODO: Translate
+health-action-choose-directory = Choose the Roblox or Sober cache folder in settings. # TODO: Translate
+evicted-badge = evicted # TODO: Translate
+option-dedupe = Skip duplicates # TODO: Translate
+naming-hash = Content hash # TODO: Translate
+layout-flat = One folder # TODO: Translate
+option-naming = File names # TODO: Translate
+naming-alias = Alias # TODO: Translate
+naming-id = Asset ID # TODO: Translate
+layout-category = Folder per type # TODO: Translate
+option-layout = Folders # TODO: Translate
+other-assets = Other # TODO: Translate
+hex-view-truncated = Showing the first { $shown } of { $total } # TODO: Translate
+modified-badge = modified # TODO: Translate
+modified-assets = Modified assets: { $count } # TODO: Translate
+warning-size-disparity = The asset being replaced is { $destination } but the new content is { $payload }. # TODO: Translate
+warning-category-mismatch = The asset being replaced is { $destination } but the new content is { $payload }. # TODO: Translate
+swap-pack-title = Swap pack # TODO: Translate
+swap-pack-nothing-to-apply = None of the swaps in this pack can be applied. { $missing ->
+        [one] { $missing } asset is
+       *[other] { $missing } assets are
+    } missing from your cache and { $mismatches ->
+        [one] { $mismatches } swap looks
+       *[other] { $mismatches } swaps look
+    } incompatible (different types or very different sizes). # TODO: Translate
+history-entries = Entries: { $count } # TODO: Translate
+column-size-on-disk = Size (on disk) # TODO: Translate
+column-size-actual = Size (actual) # TODO: Translate
+column-name = Name # TODO: Translate
 sql-database = Ledger o' SQL: { $path }
 no-function = (Not Workin’ Yet, Matey)  
 version = Ship’s Version: v{ $version } (Forged on { $date })  
@@ -7,6 +7,11 @@ sounds = Звуки
 images = Изображения
 rbxm-files = Файлы RBXM
 ktx-files = Файлы KTX
+mesh-files = Меши
+fonts = Шрифты
+json-files = JSON
+all = Все
+statistics = Статистика
 settings = Настройки
 about = О нас
 logs = Журналы
@@ -19,7 +24,7 @@ button-extract-all = Распаковать всё <F3>
 button-change-cache-dir = Сменить директорию кэша
 button-reset-cache-dir = Сбросить директорию кэша
 button-change-sql-db = Изменить базу данных SQL
-button-reset-sql-db = Сбросить базу данных SQL
+button-reset-sql-db = Найти базу данных SQL заново
 button-finish = Закончить
 button-yes = Да
 button-no = Нет
@@ -34,6 +39,47 @@ button-extract-file = Извлечь <Ctrl+E>
 button-display-image-preview = Показать предпросмотр изображений
 button-disable-display-image-preview = Не показывать предпросмотр изображений
 input-preview-size = Размер предпросмотра
+input-alias-display-length = Самый длинный псевдоним в списке
+button-clear-history = Очистить историю
+button-show-failures = ⚠ Ошибок: { $count }
+button-clear-failures = Очистить
+input-search-history = Поиск в истории по ID ресурса
+button-import-swap-pack = Импортировать набор замен
+button-revert-modifications = Отменить все мои изменения
+button-copy-base64 = Копировать как base64
+button-copy-data-uri = Копировать как data URI
+button-preview = Просмотр в новом окне
+button-run-health-check = Запустить проверку
+button-use-detected = Использовать { $path }
+button-group-by-day = Группировать по дням
+button-ungroup = Не группировать
+button-extract-group = Извлечь
+button-batch-alias = Пакетные псевдонимы…
+button-apply = Применить
+button-cancel = Отмена
+button-undo = Отменить
+button-change-sounds-dir = Изменить директорию звуков
+button-reset-sounds-dir = Сбросить директорию звуков
+button-cancel-and-exit = Отменить и выйти
+button-keep-running = Продолжить
+button-exit-now = Выйти сейчас
+button-find-orphaned-notes = Найти заметки удалённых ресурсов
+button-remove = Удалить
+button-remove-all = Удалить все
+input-note = Заметки, например где был найден этот ресурс
+button-contact-sheet = Создать контактные листы
+button-change-temp-fallback-dir = Изменить запасную временную директорию
+button-reset-temp-fallback-dir = Сбросить запасную временную директорию
+button-reclassify = Переклассифицировать
+button-export-archive = Экспортировать в ZIP…
+button-copy-hash = Копировать SHA-256
+button-force-rescan = Полное пересканирование
+button-auto-extract = Автоизвлечение новых…
+button-sort-ascending = По возрастанию
+button-sort-descending = По убыванию
+button-recount = Пересчитать
+button-open-as-database = Открыть как базу данных
+button-open-as-cache-directory = Открыть как папку кэша
 
 # Confirmations
 confirmation-custom-sql-title = Выбор базы данных SQL
@@ -49,6 +95,19 @@ confirmation-custom-directory-title = Выбрать другую директо
 confirmation-custom-directory-description = Вы хотите выбрать другую директорию кэша?
 confirmation-ban-warning-title = Предупреждение о возможном бане
 confirmation-ban-warning-description = Редактирование ресурсов в играх может привести к изменению поведения вашего клиента, что может привести к блокировке аккаунта! Используйте на свой страх и риск. Вы согласны?
+confirmation-swap-pack-description = Можно применить замен: { $resolvable } (по содержимому найдено: { $hash_matched }), ресурсов нет в вашем кэше: { $missing }, несовместимых замен (разные типы или сильно различающиеся размеры): { $mismatches }. Будут записаны только применимые замены ({ $resolvable }). Продолжить?
+confirmation-risky-write-title = Эти ресурсы выглядят несовместимыми
+confirmation-risky-write-description = { $warnings } После этого Roblox может вести себя странно. Всё равно продолжить?
+confirmation-revert-modifications-title = Отменить все изменения?
+confirmation-revert-modifications-description = { $count ->
+        [one] { $count } ресурс будет восстановлен
+        [few] { $count } ресурса будут восстановлены
+       *[other] { $count } ресурсов будут восстановлены
+    } из резервных копий. Продолжить?
+task-running-title = Идёт извлечение
+task-running-description = RoExtract всё ещё извлекает или удаляет ресурсы. Отмена остановит его после текущего файла, а немедленный выход может оставить файл записанным не до конца.
+confirmation-extract-filtered-title = Извлечение результатов поиска
+confirmation-extract-filtered-description = Будут извлечены только подходящие под поиск файлы ({ $count }), а не весь список. Продолжить?
 
 # Errors
 no-files = Нет файлов в списке. 
@@ -63,6 +122,10 @@ error-invalid-directory-description = Пожалуйста, убедитесь,
 error-invalid-database-title = Неверная база данных!
 error-invalid-database-description = Убедитесь, что указанный вами путь соответствует базе данных SQLite.
 generic-error-critical = Критическая ошибка
+error-sql-detection-details = Не удалось открыть ни одну базу данных:
+error-invalid-alias-pattern = Неверный шаблон: { $error }
+error-clear-refused-title = Кэш не очищен
+error-clear-refused-description = Ничего не удалено, так как очищать { $path } небезопасно: { $reason }. Проверьте папку кэша и базу данных в «Настройках».
 
 # Headings
 actions = Действия
@@ -72,6 +135,15 @@ new-updates = Доступны новые обновления
 contributors = Авторы
 dependencies = Зависимости
 behavior = Поведение
+extraction-history = История извлечения
+modifications = Изменённые ресурсы
+preview = Просмотр
+extract-options = Параметры извлечения
+health-check = Проверка кэша
+batch-alias = Пакетные псевдонимы
+notes = Заметки
+extract-failures = Не удалось извлечь
+open-dropped-title = Открыть до закрытия RoExtract?
 
 # Checkboxes
 check-for-updates = Проверить наличие обновлений
@@ -79,8 +151,26 @@ automatically-install-updates = Автоматически устанавлив
 use-alias = Экспортировать переименованные файлы
 use-topbar-buttons = Включить панель инструментов
 refresh-before-extract = Обновлять список файлов перед извлечением
+merge-duplicate-sources = Показывать один раз ресурсы, которые есть и в базе данных, и в папке кэша под разными именами (медленнее, сравнивается их содержимое)
 download-development-build = Использовать сборку для разработчиков, чтобы получать новейшие функции заранее (эти сборки могут быть нестабильными)
 checkbox-hide-user-logs = Скрыть имя пользователя из журналов
+record-extraction-history = Записывать историю извлечения
+skip-previously-extracted = Пропускать ресурсы, которые уже извлекались
+export-playlist = Создавать плейлист извлечённого аудио
+playlist-sort-by-alias = Сортировать плейлист по псевдониму, а не по порядку извлечения
+image-power-of-two = Округлять размеры изображений вниз до степени двойки
+convert-images-to-png = Преобразовывать текстуры WebP и KTX в PNG
+convert-audio-to-wav = Преобразовывать аудио OGG в WAV
+trim-silence = Обрезать тишину в начале и в конце преобразованного аудио
+normalize-audio = Нормализовать громкость преобразованного аудио
+checkbox-read-only = 🔒 Режим только для чтения
+show-json-tab = Показывать вкладку с JSON-ресурсами
+incremental-extract = Пропускать файлы, которые не изменились с прошлого извлечения
+sort-into-subfolders = Раскладывать извлечённые файлы по папкам для каждого типа
+export-manifest = Создавать manifest.json со списком, какой файл из какого ресурса
+verify-on-extract = Не извлекать ресурсы, которые не совпадают с контрольной суммой, сохранённой Roblox
+auto-refresh = Автоматически обновлять список при изменении кэша
+search-regex = Рег. выражение
 
 
 # Descriptions
@@ -94,6 +184,24 @@ swap-with = Нажмите дважды на файл для замены на "
 logs-description = Журнал показывает, как работает программа. Если возникнут ошибки, они отобразятся здесь.
 copy-choose-file = Нажмите дважды на файл чтобы скопировать
 overwrite-with = Нажмите дважды на файл чтобы заменить им "{ $asset }"
+extraction-history-description = Каждый извлечённый ресурс может быть записан, чтобы вы могли узнать, когда и куда вы его извлекли. История хранится рядом с файлом конфигурации.
+import-swap-pack-description = Наборы замен — это списки замен, которыми делятся другие пользователи. Перед любыми изменениями каждый ресурс из набора проверяется в вашем кэше.
+modifications-description = Перед каждой заменой или копированием RoExtract сохраняет резервную копию ресурса, поэтому все ваши изменения можно отменить разом.
+extract-options-description = Параметры для каждого типа ресурсов при извлечении. Флаги командной строки имеют приоритет.
+extract-template-description = Шаблон пути вывода; если задан, используется вместо именования и структуры для всех категорий. Подстановки: id, alias, category, ext, hash, hash8, date, date:%Y-%m, counter, counter:4 в фигурных скобках. Оставьте пустым, чтобы использовать параметры выше.
+health-check-description = Проверяет, что RoExtract может найти кэш Roblox и что в нём есть ресурсы.
+image-resize-description = Изображения больше максимального размера уменьшаются при извлечении с сохранением пропорций. 0 — извлекать как есть.
+batch-alias-description = Задаёт псевдонимы выбранным ресурсам в порядке выбора, выбрать несколько можно с помощью Ctrl+клик или Shift+клик. { $counter } считает с 1, { $padded } дополняет число нулями до 3 цифр, а { $id } — первые 8 символов id ресурса.
+custom-sounds-dir-description = Вкладка «Музыка» показывает папку sounds, которая обычно находится в директории кэша. Если в вашей установке она в другом месте, выберите её ниже.
+read-only-description = Режим только для чтения гарантирует, что ничего в кэше и базе данных не изменится, — для изучения или архивации. Извлечение работает, но ничего не добавляется в историю извлечений.
+notes-description = Заметки к ресурсу можно добавить в окне предпросмотра, по ним также работает поиск. Здесь можно найти и удалить заметки ресурсов, которых больше нет в кэше.
+contact-sheet-description = Контактные листы раскладывают изображения из списка по сетке с их псевдонимами, изображения, которые не удалось декодировать, перечёркиваются.
+contact-sheet-columns = Столбцы
+contact-sheet-rows = Строк на странице
+contact-sheet-thumbnail-size = Размер миниатюр
+temp-directory-description = Файлы, открытые из списка, записываются во временную директорию. Большие файлы, а также любые файлы, когда она почти заполнена, записываются в запасную директорию, так как временная директория может храниться в оперативной памяти.
+force-rescan-description = Ресурсы, которые не изменились с прошлого сканирования, выводятся без повторного чтения. Запустите полное пересканирование, если список кажется устаревшим.
+statistics-description = Сколько ресурсов каждой категории в кэше, сколько места они занимают и когда были кэшированы.
 
 
 # Statuses
@@ -102,10 +210,58 @@ deleting-files = Удаление файлов ({ $item }/{ $total })
 reading-files = Чтение файлов ({ $item }/{ $total })
 extracting-files = Извлечение файлов ({ $item }/{ $total })
 filtering-files = Фильтрация файлов ({ $item }/{ $total })
+merging-duplicates = Поиск ресурсов, которые есть и в базе данных, и в папке кэша
 all-extracted = Все файлы извлечены
 stage = Стадия { $stage }/{ $max }: { $status }
 swapped = { $item_a } заменён на { $item_b }
 copied = { $item_b } перезаписан файлом { $item_a }
+checking-swap-pack = Проверка набора замен
+modifications-reverted = Изменения отменены, не удалось восстановить: { $failed }
+copied-to-clipboard = Скопировано в буфер обмена
+loading-category = Загрузка: { $category }…
+cancelled-after = Отменено после { $item } из { $total ->
+        [one] { $total } файла
+       *[other] { $total } файлов
+    }
+skipped-duplicates = Пропущено { $count ->
+        [one] { $count } повторяющийся файл
+        [few] { $count } повторяющихся файла
+       *[other] { $count } повторяющихся файлов
+    }
+database-busy = База данных занята, попробуйте чуть позже
+batch-alias-applied = { $count ->
+        [one] Псевдоним задан { $count } ресурсу
+       *[other] Псевдонимы заданы { $count } ресурсам
+    }
+extract-summary = Извлечено { $written ->
+        [one] { $written } файл
+        [few] { $written } файла
+       *[other] { $written } файлов
+    } ({ $size }) за { $duration } с, пропущено: { $skipped }, ошибок: { $failed }
+extract-summary-failed = Извлечено { $written } из { $total }, ошибок: { $failed } ({ $size }) за { $duration } с, пропущено: { $skipped }
+extract-summary-duplicates = { $summary }, уникальных: { $unique }, пропущено дубликатов: { $duplicates }
+extract-summary-not-converted = { $summary }, не удалось преобразовать: { $count }
+extract-summary-corrupt = { $summary }, повреждено: { $count }
+refresh-summary = Последнее обновление: найдено { $listed }, пропущено { $skipped }, ошибок { $errors } за { $duration } с
+initializing-sources = Подготовка источников…
+cancelling-task = Отмена, ожидание завершения текущего файла…
+making-contact-sheet = Создание контактных листов ({ $item }/{ $total })
+contact-sheet-written = Записано страниц контактных листов: { $pages }, изображений: { $images }, не удалось декодировать: { $failed }
+reclassifying-files = Переклассификация файлов ({ $item }/{ $total })
+reclassify-summary = Переклассифицировано ресурсов: { $checked } за { $duration } с: сменили категорию: { $moved }, сменили формат: { $reformatted }, ошибок: { $errors }
+no-files-to-extract = Нет файлов для извлечения.
+auto-refreshed-ago = автообновлено { $seconds } с назад
+auto-extracted-count = автоизвлечено ресурсов: { $count }
+listed-total = Файлов: { $count }, { $size }
+statistics-stopped = Подсчёт был остановлен, учтены не все ресурсы.
+statistics-no-database = База данных не найдена, подсчитана только папка кэша.
+statistics-duplicates = { $count ->
+        [one] { $count } ресурс есть и в базе данных, и в папке кэша, он подсчитан один раз.
+        [few] { $count } ресурса есть и в базе данных, и в папке кэша, каждый подсчитан один раз.
+       *[other] { $count } ресурсов есть и в базе данных, и в папке кэша, каждый подсчитан один раз.
+    }
+opened-for-session = { $path } открыт до закрытия RoExtract
+dropped-file-ignored = { $file } пропущен, перетащить можно только базу данных или папку кэша
 
 # Error Statuses
 failed-deleting-file = ОШИБКА: Не удалось удалить ({ $item }/{ $total })
@@ -113,6 +269,23 @@ failed-opening-file = ОШИБКА: Не удалось открыть файл
 failed-not-file = ОШИБКА: '{ $file }' Не файл
 error-extracting-file = ОШИБКА: Не удалось извлечь: { $error }
 error-check-logs = ОШИБКА: Более подробную информацию смотрите в журнале.
+error-reading-swap-pack = ОШИБКА: Не удалось прочитать набор замен: { $error }
+error-cache-directory-unreachable = ОШИБКА: Каталог кэша { $directory } недоступен, проверьте, подключён ли диск
+error-too-large-for-clipboard = ОШИБКА: Ресурс слишком большой для копирования в буфер обмена, предел — { $max_size }
+error-asset-evicted = ОШИБКА: Данных этой записи больше нет в кэше
+error-asset-corrupt = ОШИБКА: Этот ресурс не совпадает с сохранённой с ним контрольной суммой
+error-invalid-template = Недопустимый шаблон извлечения: { $error }
+error-read-only = Включён режим только для чтения, ничего не изменено
+error-temp-space-exhausted = ОШИБКА: Недостаточно места во временной директории для { $size }
+error-header-not-found = ОШИБКА: В этом ассете не найден известный тип файла
+error-asset-not-found = ОШИБКА: Этого ассета нет в кэше
+error-invalid-asset-id = ОШИБКА: '{ $id }' не является допустимым ID ассета
+error-no-database = ОШИБКА: База данных не открыта
+error-with-code = ОШИБКА: { $error }
+error-task-running = ОШИБКА: Выполняется другая задача, попробуйте снова, когда она завершится
+error-list-replaced = ОШИБКА: Список обновился до начала извлечения, ничего не извлечено
+invalid-regex = Недопустимое регулярное выражение: { $error }
+error-open-dropped = ОШИБКА: Не удалось открыть { $path }: { $error }
 
 # Misc
 no-function = (Пока не функционирует)
@@ -124,4 +297,121 @@ download-update-question = Хотите загрузить обновление?
 update-changelog = Список изменений смотрите ниже
 support-sponsor = ♥ Спонсорство
 support-project-donate = ♥ Поддержать
-setting-below-restart-required = Примечание: для применения изменений настроек ниже потребуется перезапустить программу.
\ No newline at end of file
+setting-below-restart-required = Примечание: для применения изменений настроек ниже потребуется перезапустить программу.
+column-name = Имя
+column-format = Формат
+column-thumbnail = Миниатюра
+thumbnail-avatar = Аватар
+thumbnail-headshot = Портрет
+thumbnail-bust = Бюст
+thumbnail-asset = Ресурс
+column-size-on-disk = Размер (на диске)
+column-size-actual = Размер (фактический)
+history-entries = Записей: { $count }
+swap-pack-title = Набор замен
+swap-pack-nothing-to-apply = Ни одну замену из этого набора нельзя применить. Ресурсов нет в вашем кэше: { $missing }, несовместимых замен (разные типы или сильно различающиеся размеры): { $mismatches }.
+warning-category-mismatch = Заменяемый ресурс относится к категории { $destination }, а новое содержимое — к { $payload }.
+warning-size-disparity = Заменяемый ресурс занимает { $destination }, а новое содержимое — { $payload }.
+modified-assets = Изменено ресурсов: { $count }
+modified-badge = изменён
+hex-view-truncated = Показаны первые { $shown } из { $total }
+preview-format = Формат: { $format }
+preview-format-compressed = Формат: { $format }, хранится сжатым с { $compression }
+option-naming = Имена файлов
+option-dedupe = Пропускать дубликаты
+option-layout = Папки
+other-assets = Прочее
+naming-id = ID ресурса
+naming-alias = Псевдоним
+naming-hash = Хеш содержимого
+naming-thumbnail = ID пользователя или ресурса миниатюры
+layout-flat = Одна папка
+layout-category = Папка для каждого типа
+evicted-badge = удалён из кэша
+health-database = База данных
+health-cache-directory = Папка кэша
+health-classification = Типы ресурсов
+health-database-missing = База данных Roblox не найдена.
+health-database-empty = В базе данных { $path } нет ресурсов.
+health-database-ok = { $count ->
+        [one] { $count } ресурс
+        [few] { $count } ресурса
+       *[other] { $count } ресурсов
+    } в { $path }.
+health-cache-directory-unreachable = Не удаётся получить доступ к { $directory }.
+health-cache-directory-missing-folders = В { $directory } нет папок: { $folders }.
+health-cache-directory-empty = В { $directory } нет кэшированных файлов.
+health-cache-directory-ok = { $count ->
+        [one] { $count } кэшированный файл
+        [few] { $count } кэшированных файла
+       *[other] { $count } кэшированных файлов
+    } в { $directory }.
+health-classification-skipped = Нет ресурсов для проверки.
+health-classification-none = Ни один из { $total } проверенных ресурсов не имеет известного типа.
+health-classification-ok = Известный тип у { $known } из { $total } проверенных ресурсов.
+health-action-launch-roblox = Запустите Roblox и зайдите в игру, чтобы заполнить кэш.
+health-action-choose-directory = Выберите папку кэша Roblox или Sober в настройках.
+health-action-choose-database = Выберите базу данных Roblox или Sober в настройках.
+group-today = Сегодня
+group-yesterday = Вчера
+group-this-week = На этой неделе
+group-unknown-date = Дата неизвестна
+group-header = { $group } ({ $count }, { $size })
+image-max-dimension = Максимальный размер (пикс.)
+image-resize-filter = Фильтр
+resize-filter-nearest = Ближайший сосед
+resize-filter-triangle = Билинейный
+resize-filter-catmullrom = Catmull-Rom
+resize-filter-gaussian = Гауссов
+resize-filter-lanczos3 = Lanczos3
+sql-database-info = { $state }, { $rows ->
+        [one] { $rows } строка
+        [few] { $rows } строки
+       *[other] { $rows } строк
+    }, { $size }
+sql-database-read-write = Подключено
+sql-database-read-only = Подключено только для чтения
+sql-database-disconnected = Не подключено
+batch-alias-pattern = Шаблон
+batch-alias-list = Список имён, по одному на строку
+batch-alias-current = Текущий псевдоним
+batch-alias-new = Новый псевдоним
+batch-alias-collision-empty = пустой
+batch-alias-collision-existing = уже используется ресурсом { $asset }
+batch-alias-collision-batch = используется несколько раз
+batch-alias-invalid-control = содержит управляющие символы
+batch-alias-invalid-too-long = длиннее { $length } символов
+sounds-directory = Директория звуков: { $directory }
+sounds-directory-missing = Директория звуков не найдена в { $directory }, укажите её в настройках.
+music-tooltip = Аудиофайлы из папки sounds, показанные как есть. Обычно это длинные треки, например музыка.
+sounds-tooltip = Аудио, найденное по содержимому в кэше http и базе данных. Обычно это короткие звуковые эффекты, но музыка тоже может здесь оказаться.
+health-sounds-directory = Директория звуков
+health-sounds-directory-ok = Музыка берётся из { $directory }.
+health-sounds-directory-missing = Директория звуков не найдена в { $directory }, вкладка «Музыка» будет пустой.
+health-action-choose-sounds-directory = Если в вашей установке папка sounds находится в другом месте, выберите её в настройках.
+read-only-mode = только чтение
+read-only-tooltip = Чтобы использовать это, отключите режим только для чтения в настройках
+notes-count = Заметок: { $count }
+orphaned-notes = Заметок удалённых ресурсов: { $count }
+format-unknown = неизвестно
+temp-directory-usage = Временная директория: { $directory } (RoExtract использует { $usage }, свободно { $available })
+temp-fallback-directory = Запасная директория: { $directory }
+overwrite-policy = Если файл уже существует
+overwrite-overwrite = Перезаписать
+overwrite-skip = Пропустить
+overwrite-rename = Добавить номер к имени
+decimal-separator = ,
+sort-name = Сортировка по имени
+sort-size = Сортировка по размеру
+sort-modified = Сортировка по дате изменения
+sort-category = Сортировка по категории
+filter-min-size = Не меньше
+filter-max-size = Не больше
+filter-newer-than = Изменены за последние
+filter-thumbnails = Только миниатюры аватаров и ресурсов
+statistics-category = Категория
+statistics-count = Ресурсы
+statistics-size = Размер
+statistics-oldest = Самый старый
+statistics-newest = Самый новый
+statistics-total = Всего
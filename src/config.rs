@@ -1,18 +1,46 @@
+// The config is the state's, see logic::state. The default state reads the config file the first
+// time it's needed, other states start from an empty config. The system config is the install's.
 use serde_json::{json, Value};
 use std::sync::LazyLock;
-use std::{fs, path::PathBuf, sync::Mutex};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
 use crate::logic;
 
-static CONFIG: LazyLock<Mutex<Value>> = LazyLock::new(|| Mutex::new(read_config_file()));
 static SYSTEM_CONFIG: LazyLock<Mutex<Value>> = LazyLock::new(|| Mutex::new(read_system_config()));
-static CONFIG_FILE: LazyLock<Mutex<PathBuf>> = LazyLock::new(|| Mutex::new(detect_config_file()));
 
 const SYSTEM_CONFIG_FILE: &str = "RoExtract-system.json";
-const DEFAULT_CONFIG_FILE: &str = "RoExtract-config.json";
+pub const DEFAULT_CONFIG_FILE: &str = "RoExtract-config.json";
+
+// Aliases become file names when extracting, which are usually limited to 255 bytes. Longer ones are
+// cut short when stored or read, and control characters (e.g. newlines from a pasted list) are removed
+pub const MAX_ALIAS_LENGTH: usize = 200; // Characters
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AliasError {
+    ControlCharacter,
+    TooLong,
+}
+
+impl std::fmt::Display for AliasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AliasError::ControlCharacter => write!(f, "Aliases can't contain control characters"),
+            AliasError::TooLong => write!(
+                f,
+                "Aliases can't be longer than {MAX_ALIAS_LENGTH} characters"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for AliasError {}
 
 // Define local functions
-fn detect_config_file() -> PathBuf {
+pub fn detect_config_file() -> PathBuf {
     if let Some(config_path) = get_system_config_string("config-path") {
         PathBuf::from(logic::resolve_path(&config_path))
     } else {
@@ -20,8 +48,8 @@ fn detect_config_file() -> PathBuf {
     }
 }
 
-fn read_config_file() -> Value {
-    match fs::read(CONFIG_FILE.lock().unwrap().clone()) {
+pub fn read_config_file(path: &Path) -> Value {
+    match fs::read(path) {
         Ok(bytes) => {
             match serde_json::from_slice(&bytes) {
                 Ok(v) => v,
@@ -63,8 +91,17 @@ fn read_system_config() -> Value {
     }
 }
 
+// Directory the config file is stored in, other files RoExtract creates are stored next to it
+pub fn get_config_directory() -> PathBuf {
+    logic::state()
+        .config_file()
+        .parent()
+        .map(|parent| parent.to_path_buf())
+        .unwrap_or_default()
+}
+
 pub fn get_config() -> Value {
-    CONFIG.lock().unwrap().clone()
+    logic::state().config().lock().unwrap().clone()
 }
 
 pub fn get_config_string(key: &str) -> Option<String> {
@@ -91,10 +128,30 @@ pub fn get_config_u64(key: &str) -> Option<u64> {
     }
 }
 
+// For aliases typed or imported, before they're stored
+pub fn validate_alias(alias: &str) -> Result<(), AliasError> {
+    if alias.chars().any(char::is_control) {
+        Err(AliasError::ControlCharacter)
+    } else if alias.chars().count() > MAX_ALIAS_LENGTH {
+        Err(AliasError::TooLong)
+    } else {
+        Ok(())
+    }
+}
+
+// Config files edited by hand or written by older versions can have anything in them
+fn clean_alias(alias: &str) -> String {
+    alias
+        .chars()
+        .filter(|c| *c != '"' && !c.is_control())
+        .take(MAX_ALIAS_LENGTH)
+        .collect()
+}
+
 pub fn get_asset_alias(asset: &str) -> String {
     if let Some(aliases) = get_config().get("aliases") {
         if let Some(value) = aliases.get(asset) {
-            value.as_str().unwrap().to_owned().replace('"', "")
+            clean_alias(value.as_str().unwrap())
         } else {
             asset.to_string()
         }
@@ -103,8 +160,23 @@ pub fn get_asset_alias(asset: &str) -> String {
     }
 }
 
+// Every asset with an alias and its alias
+pub fn get_asset_aliases() -> Vec<(String, String)> {
+    match get_config()
+        .get("aliases")
+        .and_then(|aliases| aliases.as_object())
+    {
+        Some(aliases) => aliases
+            .iter()
+            .filter_map(|(asset, alias)| Some((asset.clone(), clean_alias(alias.as_str()?))))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
 pub fn set_config(value: Value) {
-    let mut config = CONFIG.lock().unwrap();
+    let state = logic::state();
+    let mut config = state.config().lock().unwrap();
     // Change only if it changes
     if *config != value {
         *config = value;
@@ -129,7 +201,7 @@ pub fn set_asset_alias(asset: &str, value: &str) {
         config["aliases"] = json!({});
     }
 
-    config["aliases"][asset] = value.replace('"', "").into();
+    config["aliases"][asset] = clean_alias(value).into();
     set_config(config);
 }
 
@@ -154,10 +226,11 @@ pub fn get_system_config_bool(key: &str) -> Option<bool> {
 }
 
 pub fn save_config_file() {
-    let config = CONFIG.lock().unwrap().clone();
+    let state = logic::state();
+    let config = state.config().lock().unwrap().clone();
     match serde_json::to_vec_pretty(&config) {
         Ok(data) => {
-            let result = fs::write(CONFIG_FILE.lock().unwrap().clone(), data);
+            let result = fs::write(state.config_file(), data);
             if result.is_err() {
                 log_critical!(
                     "Failed to write config file: {}",
@@ -1,24 +1,30 @@
 // Used for gui
 use eframe::egui;
 use egui_dock::{DockArea, DockState, NodeIndex, Style, SurfaceIndex};
-use fluent_bundle::{FluentBundle, FluentResource};
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use native_dialog::DialogBuilder;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::{LazyLock, Mutex};
-use std::time::Duration;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use std::collections::HashMap;
 // Used for input
 use crate::{config, locale, log, logic, updater}; // Used for functionality
 use eframe::egui::TextureHandle;
 
+mod batch_alias;
+mod dialog;
 mod file_list;
+mod preview;
 mod settings;
+mod statistics;
 mod welcome;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION"); // Get version for use in the title bar
 const COMPILE_DATE: &str = env!("COMPILE_DATE");
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(10); // Waiting for a cancelled task when closing
 const ICON: &[u8; 11400] = include_bytes!("../assets/icon.png");
 const CONTRIBUTORS: [&str; 6] = [
     "AeEn123",
@@ -28,7 +34,7 @@ const CONTRIBUTORS: [&str; 6] = [
     "IDDQD1337",
     "yuk1n0w",
 ];
-const DEPENDENCIES: [[&str; 2]; 14] = [
+const DEPENDENCIES: [[&str; 2]; 26] = [
     ["https://github.com/emilk/egui", ""],
     ["https://github.com/Adanos020/egui_dock", ""],
     ["https://github.com/lampsitter/egui_commonmark", ""],
@@ -43,10 +49,23 @@ const DEPENDENCIES: [[&str; 2]; 14] = [
     ["https://github.com/Peternator7/strum", ""],
     ["https://github.com/chronotope/chrono", ""],
     ["https://github.com/image-rs/image", ""],
+    ["https://github.com/gyscos/zstd-rs", ""],
+    ["https://github.com/RustCrypto/hashes", ""],
+    ["https://github.com/marshallpierce/rust-base64", ""],
+    ["https://github.com/Detegr/rust-ctrlc", ""],
+    ["https://github.com/alexheretic/ab-glyph", ""],
+    ["https://github.com/al8n/fs4-rs", ""],
+    ["https://github.com/BurntSushi/memchr", ""],
+    ["https://github.com/rust-lang/flate2-rs", ""],
+    ["https://github.com/zip-rs/zip2", ""],
+    ["https://github.com/RustAudio/lewton", ""],
+    ["https://github.com/rust-lang/regex", ""],
+    ["https://github.com/rust-random/getrandom", ""],
 ];
 
 pub static IMAGES: LazyLock<Mutex<HashMap<String, TextureHandle>>> =
     LazyLock::new(|| Mutex::new(HashMap::new()));
+static STARTED: LazyLock<Instant> = LazyLock::new(Instant::now); // When run_gui was called
 
 struct TabViewer<'a> {
     locale: &'a mut FluentBundle<Arc<FluentResource>>,
@@ -79,6 +98,27 @@ pub fn load_image(
     }
 }
 
+// Last position and size of an auxiliary window, stored as [x, y, width, height]
+pub fn get_window_rect(key: &str) -> Option<(egui::Pos2, egui::Vec2)> {
+    let values = config::get_config().get(key)?.as_array()?.clone();
+    let values: Vec<f32> = values
+        .iter()
+        .filter_map(|value| value.as_f64().map(|value| value as f32))
+        .collect();
+
+    match values.as_slice() {
+        [x, y, width, height] => Some((egui::pos2(*x, *y), egui::vec2(*width, *height))),
+        _ => None,
+    }
+}
+
+pub fn set_window_rect(key: &str, position: egui::Pos2, size: egui::Vec2) {
+    config::set_config_value(
+        key,
+        serde_json::json!([position.x, position.y, size.x, size.y]),
+    );
+}
+
 fn add_dependency_credit(dependency: [&str; 2], ui: &mut egui::Ui, sponsor_message: &str) {
     if !dependency[1].is_empty() {
         ui.horizontal(|ui| {
@@ -106,8 +146,20 @@ impl egui_dock::TabViewer for TabViewer<'_> {
         locale::get_message(self.locale, &*tab, None).into()
     }
 
+    // Music and Sounds are both audio, explain where each comes from
+    fn on_tab_button(&mut self, tab: &mut Self::Tab, response: &egui::Response) {
+        let tooltip = match logic::Category::from_tab_id(tab) {
+            Some(logic::Category::Music) => "music-tooltip",
+            Some(logic::Category::Sounds) => "sounds-tooltip",
+            _ => return,
+        };
+        response
+            .clone()
+            .on_hover_text(locale::get_message(self.locale, tooltip, None));
+    }
+
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
-        if tab != "settings" && tab != "about" && tab != "logs" {
+        if tab != "settings" && tab != "about" && tab != "logs" && tab != "statistics" {
             // This is only shown on tabs other than settings (Extracting assets)
             self.file_list_ui.ui(tab.to_string(), ui);
         } else if tab == "settings" {
@@ -115,8 +167,15 @@ impl egui_dock::TabViewer for TabViewer<'_> {
 
             settings::actions(ui, self.locale);
             settings::cache_dir_management(ui, self.locale);
+            settings::sounds_dir_management(ui, self.locale);
             settings::sql_db_management(ui, self.locale);
+            settings::temp_directory(ui, self.locale);
+            settings::health_check(ui, self.locale);
             settings::behavior(ui, self.locale);
+            settings::extract_options(ui, self.locale);
+            settings::history(ui, self.locale);
+            settings::modifications(ui, self.locale);
+            settings::notes(ui, self.locale);
             settings::updates(ui, self.locale);
 
             if settings::language(ui, self.locale) {
@@ -124,6 +183,8 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                 *self.locale = locale::get_locale(None);
                 self.file_list_ui.locale = locale::get_locale(None);
             }
+        } else if tab == "statistics" {
+            statistics::statistics(ui, self.locale);
         } else if tab == "logs" {
             ui.heading(locale::get_message(self.locale, "logs", None));
             ui.label(locale::get_message(self.locale, "logs-description", None));
@@ -152,14 +213,25 @@ impl egui_dock::TabViewer for TabViewer<'_> {
                     ui.ctx().copy_text(logs.clone());
                 }
                 if ui
-                    .button(locale::get_message(self.locale, "button-export-logs", None))
+                    .add_enabled(
+                        !dialog::is_pending(),
+                        egui::Button::new(locale::get_message(
+                            self.locale,
+                            "button-export-logs",
+                            None,
+                        )),
+                    )
                     .clicked()
                 {
-                    if let Some(path) = DialogBuilder::file().save_single_file().show().unwrap() {
-                        if let Err(e) = std::fs::write(path, logs.clone()) {
-                            log_critical!("Failed to save logs: {}", e);
+                    let logs = logs.clone();
+                    dialog::spawn("export-logs", move || {
+                        if let Some(path) = DialogBuilder::file().save_single_file().show().unwrap()
+                        {
+                            if let Err(e) = std::fs::write(path, logs) {
+                                log_critical!("Failed to save logs: {}", e);
+                            }
                         }
-                    }
+                    });
                 }
             });
 
@@ -247,20 +319,38 @@ struct MyApp {
     tab_map: HashMap<u32, (SurfaceIndex, NodeIndex, usize)>, // Tab map for keyboard navigation
     locale: FluentBundle<Arc<FluentResource>>,
     file_list_ui: file_list::FileListUi,
+    sources_started: bool,       // The database is opened after the first frame
+    close_prompt: bool,          // Asking what to do with the running task before closing
+    cancelling: Option<Instant>, // When the running task was asked to stop for closing
+    closing: bool,               // The close request is no longer intercepted
+    title: String,               // Updated when read-only mode or a session override changes
+    dropped: Option<PathBuf>,    // Asking how to open a file or folder dropped on the window
+    show_failures: bool,         // Listing the assets the last extraction couldn't write
 }
 
 impl Default for MyApp {
     fn default() -> Self {
-        let tree = DockState::new(vec![
-            "music".to_owned(),
-            "sounds".to_owned(),
-            "images".to_owned(),
-            "rbxm-files".to_owned(),
-            "ktx-files".to_owned(),
+        let mut tabs = vec![
+            logic::Category::Music.tab_id().to_owned(),
+            logic::Category::Sounds.tab_id().to_owned(),
+            logic::Category::Images.tab_id().to_owned(),
+            logic::Category::Rbxm.tab_id().to_owned(),
+            logic::Category::Ktx.tab_id().to_owned(),
+            logic::Category::Mesh.tab_id().to_owned(),
+            logic::Category::Fonts.tab_id().to_owned(),
+        ];
+        // Most of the cache's JSON is API responses, so it's hidden unless asked for
+        if config::get_config_bool("show_json_tab").unwrap_or(false) {
+            tabs.push(logic::Category::Json.tab_id().to_owned());
+        }
+        tabs.extend([
+            logic::Category::All.tab_id().to_owned(),
+            "statistics".to_owned(),
             "settings".to_owned(),
             "logs".to_owned(),
             "about".to_owned(),
         ]);
+        let tree = DockState::new(tabs);
 
         // Tab map for keyboard navigation
         let mut tab_map = HashMap::new();
@@ -276,10 +366,206 @@ impl Default for MyApp {
             tab_map,
             locale: locale::get_locale(None),
             file_list_ui: file_list::FileListUi::default(),
+            sources_started: false,
+            close_prompt: false,
+            cancelling: None,
+            closing: false,
+            title: String::new(), // Set on the first frame
+            dropped: None,
+            show_failures: false,
         }
     }
 }
 
+impl MyApp {
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        if let Some(path) = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .first()
+                .and_then(|file| file.path.clone())
+        }) {
+            if path.is_dir() || path.extension().is_some_and(|extension| extension == "db") {
+                self.dropped = Some(path);
+            } else {
+                let mut args = FluentArgs::new();
+                args.set("file", path.display().to_string());
+                logic::update_status(locale::get_message(
+                    &self.locale,
+                    "dropped-file-ignored",
+                    Some(&args),
+                ));
+            }
+        }
+
+        let Some(path) = self.dropped.clone() else {
+            return;
+        };
+
+        egui::Modal::new(egui::Id::new("dropped_prompt")).show(ctx, |ui| {
+            ui.heading(locale::get_message(
+                &self.locale,
+                "open-dropped-title",
+                None,
+            ));
+            ui.label(path.display().to_string());
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        path.is_file(),
+                        egui::Button::new(locale::get_message(
+                            &self.locale,
+                            "button-open-as-database",
+                            None,
+                        )),
+                    )
+                    .clicked()
+                {
+                    open_dropped(path.clone(), true);
+                    self.dropped = None;
+                }
+                if ui
+                    .add_enabled(
+                        path.is_dir(),
+                        egui::Button::new(locale::get_message(
+                            &self.locale,
+                            "button-open-as-cache-directory",
+                            None,
+                        )),
+                    )
+                    .clicked()
+                {
+                    open_dropped(path.clone(), false);
+                    self.dropped = None;
+                }
+                if ui
+                    .button(locale::get_message(&self.locale, "button-cancel", None))
+                    .clicked()
+                {
+                    self.dropped = None;
+                }
+            });
+        });
+    }
+
+    fn show_failures(&mut self, ctx: &egui::Context) {
+        if !self.show_failures {
+            return;
+        }
+        let failures = logic::summary::get_last_extract_failures();
+        let mut open = true;
+        egui::Window::new(locale::get_message(&self.locale, "extract-failures", None))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for (asset, reason) in &failures {
+                            ui.horizontal(|ui| {
+                                ui.strong(asset);
+                                ui.label(reason);
+                            });
+                        }
+                    });
+                if ui
+                    .button(locale::get_message(
+                        &self.locale,
+                        "button-clear-failures",
+                        None,
+                    ))
+                    .clicked()
+                {
+                    logic::summary::clear_last_extract_failures();
+                    self.show_failures = false;
+                }
+            });
+        if !open {
+            self.show_failures = false;
+        }
+    }
+
+    // Closing mid-extraction would leave half written files and race clean_up for the temp directory
+    fn handle_close(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.viewport().close_requested())
+            && !self.closing
+            && logic::get_task_running()
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.close_prompt = true;
+        }
+
+        if let Some(cancelled) = self.cancelling {
+            if !logic::get_task_running() || cancelled.elapsed() >= CLOSE_TIMEOUT {
+                self.close(ctx);
+            } else {
+                ctx.request_repaint_after(Duration::from_millis(100));
+            }
+        }
+
+        if !self.close_prompt {
+            return;
+        }
+
+        egui::Modal::new(egui::Id::new("close_prompt")).show(ctx, |ui| {
+            ui.heading(locale::get_message(
+                &self.locale,
+                "task-running-title",
+                None,
+            ));
+            if self.cancelling.is_some() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(locale::get_message(&self.locale, "cancelling-task", None));
+                });
+                return;
+            }
+
+            ui.label(locale::get_message(
+                &self.locale,
+                "task-running-description",
+                None,
+            ));
+            ui.horizontal(|ui| {
+                if ui
+                    .button(locale::get_message(
+                        &self.locale,
+                        "button-cancel-and-exit",
+                        None,
+                    ))
+                    .clicked()
+                {
+                    logic::cancel_task();
+                    self.cancelling = Some(Instant::now());
+                }
+                if ui
+                    .button(locale::get_message(
+                        &self.locale,
+                        "button-keep-running",
+                        None,
+                    ))
+                    .clicked()
+                {
+                    self.close_prompt = false;
+                }
+                if ui
+                    .button(locale::get_message(&self.locale, "button-exit-now", None))
+                    .clicked()
+                {
+                    self.close(ctx); // clean_up is skipped while the task runs
+                }
+            });
+        });
+    }
+
+    fn close(&mut self, ctx: &egui::Context) {
+        self.closing = true;
+        self.close_prompt = false;
+        self.cancelling = None;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+    }
+}
+
 fn detect_japanese_font() -> Option<std::path::PathBuf> {
     let font_dirs = [
         "C:\\Windows\\Fonts\\msgothic.ttc",
@@ -346,30 +632,190 @@ fn init_japanese_font(cc: &eframe::CreationContext<'_>) {
 pub fn gui_setup(cc: &eframe::CreationContext<'_>) {
     init_japanese_font(cc);
 
-    // Get theme from config
-    match config::get_config_string("theme")
+    apply_theme(&cc.egui_ctx);
+}
+
+// Apply the theme from config, called every frame so "system" follows the OS theme when it changes
+pub fn apply_theme(ctx: &egui::Context) {
+    let theme_preference = match config::get_config_string("theme")
         .unwrap_or("system".to_owned())
         .as_str()
     {
-        "dark" => cc.egui_ctx.set_theme(egui::Theme::Dark),
-        "light" => cc.egui_ctx.set_theme(egui::Theme::Light),
-        _ => (),
+        "dark" => egui::ThemePreference::Dark,
+        "light" => egui::ThemePreference::Light,
+        _ => egui::ThemePreference::System, // egui picks up the system theme from the viewport each frame
+    };
+
+    // Change only if it changes
+    if ctx.options(|opt| opt.theme_preference) != theme_preference {
+        ctx.set_theme(theme_preference);
     }
 }
 
+// So slow startups can be tracked down from the logs
+fn log_startup(phase: &str) {
+    log_info!(
+        "Startup: {} after {}ms",
+        phase,
+        STARTED.elapsed().as_millis()
+    );
+}
+
+// Opening the database can take a while and ask for a path, the window should show first
+fn init_sources() {
+    thread::spawn(|| {
+        let start = Instant::now();
+        let connected = logic::sql_database::connect();
+        log_info!(
+            "Startup: database {} in {}ms",
+            if connected { "opened" } else { "not found" },
+            start.elapsed().as_millis()
+        );
+
+        if !connected {
+            dialog::spawn("custom-sql-db", || {
+                if logic::sql_database::prompt_for_database() {
+                    settings::database_changed();
+                }
+            });
+        }
+
+        // Polls the database too, so only once it's open
+        logic::watcher::set_enabled(config::get_config_bool("auto_refresh").unwrap_or(false));
+
+        // A refresh may have already replaced the status
+        let locale = locale::get_locale(None);
+        if logic::get_status() == locale::get_message(&locale, "initializing-sources", None) {
+            logic::update_status(locale::get_message(&locale, "idling", None));
+        }
+    });
+}
+
+// From `roextract gui`, to open on a tab with a search already typed in
+#[derive(Default)]
+pub struct StartupOptions {
+    pub tab: Option<String>,
+    pub search: Option<String>,
+}
+
 impl MyApp {
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>, startup: StartupOptions) -> Self {
         gui_setup(cc);
+        log_startup("Fonts and theme set up");
 
-        Default::default()
+        let mut app = Self::default();
+        if let Some(tab) = startup.tab {
+            app.open_tab(&tab);
+        }
+        if let Some(search) = startup.search {
+            app.file_list_ui.set_search(search);
+        }
+        app
+    }
+
+    // Accepts tab ids and category names, e.g. "ktx" for the "ktx-files" tab
+    fn open_tab(&mut self, name: &str) {
+        let name = name.trim().to_lowercase();
+        let tab = match name.parse::<logic::Category>() {
+            Ok(category) => category.tab_id().to_owned(),
+            Err(_) => name,
+        };
+
+        match self.tree.find_tab(&tab) {
+            Some(location) => self.tree.set_active_tab(location),
+            None => log_warn!("No tab called '{}', opening the first tab", tab),
+        }
     }
 }
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        apply_theme(ctx);
+        dialog::update(ctx);
+
         // Display the status bar at the bottom
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
-            ui.add(egui::ProgressBar::new(logic::get_progress()).text(logic::get_status()));
+            ui.horizontal(|ui| {
+                // Extracting and clearing stop between files
+                if logic::get_task_running() {
+                    if logic::get_cancel_task() {
+                        ui.spinner().on_hover_text(locale::get_message(
+                            &self.locale,
+                            "cancelling-task",
+                            None,
+                        ));
+                    } else if ui
+                        .button(locale::get_message(&self.locale, "button-cancel", None))
+                        .clicked()
+                    {
+                        logic::cancel_task();
+                    }
+                } else {
+                    let failures = logic::summary::get_last_extract_failures().len();
+                    if failures > 0 {
+                        let mut args = FluentArgs::new();
+                        args.set("count", failures);
+                        if ui
+                            .button(locale::get_message(
+                                &self.locale,
+                                "button-show-failures",
+                                Some(&args),
+                            ))
+                            .clicked()
+                        {
+                            self.show_failures = true;
+                        }
+                    }
+                }
+                // What's listed on the tab, stays the same while the status changes
+                let listed = logic::get_listed_count();
+                if listed > 0 {
+                    let mut args = FluentArgs::new();
+                    args.set("count", listed);
+                    args.set("size", logic::format_size(logic::get_total_listed_size()));
+                    ui.label(locale::get_message(
+                        &self.locale,
+                        "listed-total",
+                        Some(&args),
+                    ));
+                    ui.separator();
+                }
+
+                // Shows the watcher is working, counting up every second
+                let mut notes = Vec::new();
+                if let Some(elapsed) = logic::watcher::since_last_refresh() {
+                    let mut args = FluentArgs::new();
+                    args.set("seconds", elapsed.as_secs());
+                    notes.push(locale::get_message(
+                        &self.locale,
+                        "auto-refreshed-ago",
+                        Some(&args),
+                    ));
+                }
+                if let Some(count) = logic::watcher::get_auto_extracted() {
+                    let mut args = FluentArgs::new();
+                    args.set("count", count);
+                    notes.push(locale::get_message(
+                        &self.locale,
+                        "auto-extracted-count",
+                        Some(&args),
+                    ));
+                }
+                let mut status = logic::get_status();
+                if !notes.is_empty() {
+                    status = format!("{} ({})", status, notes.join(", "));
+                    ctx.request_repaint_after(Duration::from_secs(1));
+                }
+                match logic::get_progress() {
+                    logic::Progress::Counting => {
+                        ui.spinner();
+                        ui.label(status);
+                    }
+                    progress => {
+                        ui.add(egui::ProgressBar::new(progress.fraction()).text(status));
+                    }
+                }
+            });
         });
 
         // Switch tabs with keyboard input (num keys)
@@ -386,6 +832,17 @@ impl eframe::App for MyApp {
             }
         }
 
+        preview::show(ctx);
+        self.handle_dropped_files(ctx);
+        self.show_failures(ctx);
+        self.handle_close(ctx);
+
+        let title = window_title(&self.locale);
+        if title != self.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+
         DockArea::new(&mut self.tree)
             .style(Style::from_egui(ctx.style().as_ref()))
             .show_close_buttons(false)
@@ -406,13 +863,81 @@ impl eframe::App for MyApp {
                 ctx.request_repaint_after(Duration::from_millis(250)); // Delay added here to prevent refreshes from stopping
             }
         }
+
+        if !self.sources_started {
+            self.sources_started = true;
+            log_startup("First frame");
+            init_sources();
+        }
     }
 }
 
-pub fn run_gui() {
+fn window_title(locale: &FluentBundle<Arc<FluentResource>>) -> String {
+    let mut title = format!("RoExtract v{VERSION}");
+    if logic::read_only::is_enabled() {
+        title.push_str(&format!(
+            " ({})",
+            locale::get_message(locale, "read-only-mode", None)
+        ));
+    }
+    // Sources opened by dropping them aren't saved, so it's shown which are in use
+    let overrides: Vec<String> = [
+        logic::sql_database::get_session_database(),
+        logic::cache_directory::get_session_directory(),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|path| path.display().to_string())
+    .collect();
+    if !overrides.is_empty() {
+        title.push_str(&format!(" - {}", overrides.join(", ")));
+    }
+    title
+}
+
+// Opening a database waits for the running task to let go of the connection
+fn open_dropped(path: PathBuf, database: bool) {
+    thread::spawn(move || {
+        let locale = locale::get_locale(None);
+        let path_string = path.to_string_lossy().to_string();
+        let result = if database {
+            logic::sql_database::open_for_session(&path_string)
+        } else {
+            logic::cache_directory::open_for_session(&path_string)
+        };
+        match result {
+            Ok(path) => {
+                let mut args = FluentArgs::new();
+                args.set("path", path.display().to_string());
+                logic::update_status(locale::get_message(
+                    &locale,
+                    "opened-for-session",
+                    Some(&args),
+                ));
+                logic::refresh(logic::get_list_category(), false, false);
+            }
+            Err(e) => {
+                log_error!("Couldn't open {path_string}: {e}");
+                let mut args = FluentArgs::new();
+                args.set("path", path_string);
+                args.set("error", e.to_string());
+                logic::update_status(locale::get_message(
+                    &locale,
+                    "error-open-dropped",
+                    Some(&args),
+                ));
+            }
+        }
+    });
+}
+
+pub fn run_gui(startup: StartupOptions) {
+    LazyLock::force(&STARTED);
+
     // If the user is not welcomed before, welcome them
     if !config::get_config_bool("welcomed").unwrap_or(false) {
         let _ = welcome::run_gui();
+        log_startup("Welcome closed");
     }
 
     // Only run GUI after user has been welcomed
@@ -423,8 +948,17 @@ pub fn run_gui() {
                 true,
                 config::get_config_bool("automatically_install_updates").unwrap_or(false),
             );
+            log_startup("Update check finished");
         }
 
+        // Shown until the database has been opened, detection failing is asked about in the window
+        logic::sql_database::set_prompt_for_database(false);
+        logic::update_status(locale::get_message(
+            &locale::get_locale(None),
+            "initializing-sources",
+            None,
+        ));
+
         let options = eframe::NativeOptions {
             viewport: egui::ViewportBuilder::default().with_icon(
                 eframe::icon_data::from_png_bytes(&ICON[..]).expect("Failed to load icon"),
@@ -433,11 +967,14 @@ pub fn run_gui() {
         };
 
         let result = eframe::run_native(
-            &format!("RoExtract v{VERSION}").to_owned(),
+            &window_title(&locale::get_locale(None)),
             options,
-            Box::new(|cc| Ok(Box::new(MyApp::new(cc)))),
+            Box::new(|cc| Ok(Box::new(MyApp::new(cc, startup)))),
         );
 
+        logic::watcher::set_enabled(false);
+        logic::stop_auto_extract();
+        logic::stats::stop();
         if result.is_err() {
             log_critical!("GUI failed: {}", result.unwrap_err());
         }
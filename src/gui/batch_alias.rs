@@ -0,0 +1,243 @@
+// Window for aliasing the selected assets at once, and the toast to undo it afterwards
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    config::AliasError,
+    locale,
+    logic::{
+        self,
+        batch_alias::{self, Collision, PlannedAlias},
+    },
+};
+
+const TOAST_DURATION: Duration = Duration::from_secs(10);
+
+pub struct BatchAlias {
+    assets: Vec<String>, // In the order they were selected
+    use_list: bool,
+    pattern: String,
+    list: String,
+}
+
+pub enum Outcome {
+    Cancelled,
+    Applied(Vec<(String, String)>), // Aliases from before, for undoing
+}
+
+pub struct UndoToast {
+    shown: Instant,
+    previous: Vec<(String, String)>,
+}
+
+fn collision_message(collision: &Collision, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+    match collision {
+        Collision::Empty => locale::get_message(locale, "batch-alias-collision-empty", None),
+        Collision::Invalid(AliasError::ControlCharacter) => {
+            locale::get_message(locale, "batch-alias-invalid-control", None)
+        }
+        Collision::Invalid(AliasError::TooLong) => {
+            let mut args = FluentArgs::new();
+            args.set("length", crate::config::MAX_ALIAS_LENGTH);
+            locale::get_message(locale, "batch-alias-invalid-too-long", Some(&args))
+        }
+        Collision::Existing(asset) => {
+            let mut args = FluentArgs::new();
+            args.set("asset", asset.clone());
+            locale::get_message(locale, "batch-alias-collision-existing", Some(&args))
+        }
+        Collision::Batch => locale::get_message(locale, "batch-alias-collision-batch", None),
+    }
+}
+
+fn preview_table(
+    ui: &mut egui::Ui,
+    plan: &[PlannedAlias],
+    locale: &FluentBundle<Arc<FluentResource>>,
+) {
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .show(ui, |ui| {
+            egui::Grid::new("batch_alias_preview")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.strong(locale::get_message(locale, "column-name", None));
+                    ui.strong(locale::get_message(locale, "batch-alias-current", None));
+                    ui.strong(locale::get_message(locale, "batch-alias-new", None));
+                    ui.end_row();
+
+                    // Imported lists can have anything in them, the full alias is in the tooltip
+                    for planned in plan {
+                        ui.label(&planned.asset);
+                        ui.label(logic::display_text(&planned.previous))
+                            .on_hover_text(&planned.previous);
+                        match &planned.collision {
+                            Some(collision) => ui.colored_label(
+                                ui.visuals().error_fg_color,
+                                format!(
+                                    "{} ({})",
+                                    logic::display_text(&planned.alias),
+                                    collision_message(collision, locale)
+                                ),
+                            ),
+                            None => ui.label(logic::display_text(&planned.alias)),
+                        }
+                        .on_hover_text(&planned.alias);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+impl BatchAlias {
+    pub fn new(assets: Vec<String>) -> Self {
+        Self {
+            assets,
+            use_list: false,
+            pattern: String::new(),
+            list: String::new(),
+        }
+    }
+
+    // None while the window is still open
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        locale: &FluentBundle<Arc<FluentResource>>,
+    ) -> Option<Outcome> {
+        let mut open = true;
+        let mut outcome = None;
+
+        egui::Window::new(locale::get_message(locale, "batch-alias", None))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let mut args = FluentArgs::new();
+                args.set("count", self.assets.len());
+                args.set("counter", "{n}");
+                args.set("padded", "{n:3}");
+                args.set("id", "{id8}");
+                ui.label(locale::get_message(
+                    locale,
+                    "batch-alias-description",
+                    Some(&args),
+                ));
+
+                ui.horizontal(|ui| {
+                    ui.radio_value(
+                        &mut self.use_list,
+                        false,
+                        locale::get_message(locale, "batch-alias-pattern", None),
+                    );
+                    ui.radio_value(
+                        &mut self.use_list,
+                        true,
+                        locale::get_message(locale, "batch-alias-list", None),
+                    );
+                });
+
+                let aliases = if self.use_list {
+                    ui.add(egui::TextEdit::multiline(&mut self.list).desired_rows(6));
+                    batch_alias::from_list(&self.list, &self.assets)
+                } else {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.pattern).hint_text("boss-theme-{n}"),
+                    );
+                    batch_alias::from_pattern(&self.pattern, &self.assets)
+                };
+
+                let plan = match aliases {
+                    Ok(aliases) => {
+                        let plan = batch_alias::plan(&self.assets, aliases);
+                        preview_table(ui, &plan, locale);
+                        Some(plan)
+                    }
+                    Err(e) => {
+                        let mut args = FluentArgs::new();
+                        args.set("error", e);
+                        ui.colored_label(
+                            ui.visuals().error_fg_color,
+                            locale::get_message(locale, "error-invalid-alias-pattern", Some(&args)),
+                        );
+                        None
+                    }
+                };
+
+                // Nothing is written while any alias collides
+                let can_apply = plan
+                    .as_ref()
+                    .is_some_and(|plan| plan.iter().all(|planned| planned.collision.is_none()));
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            can_apply,
+                            egui::Button::new(locale::get_message(locale, "button-apply", None)),
+                        )
+                        .clicked()
+                    {
+                        if let Some(plan) = &plan {
+                            outcome = Some(Outcome::Applied(batch_alias::apply(plan)));
+                        }
+                    }
+                    if ui
+                        .button(locale::get_message(locale, "button-cancel", None))
+                        .clicked()
+                    {
+                        outcome = Some(Outcome::Cancelled);
+                    }
+                });
+            });
+
+        if !open {
+            return Some(Outcome::Cancelled);
+        }
+        outcome
+    }
+}
+
+impl UndoToast {
+    pub fn new(previous: Vec<(String, String)>) -> Self {
+        Self {
+            shown: Instant::now(),
+            previous,
+        }
+    }
+
+    // False once the toast should be removed
+    pub fn show(&self, ctx: &egui::Context, locale: &FluentBundle<Arc<FluentResource>>) -> bool {
+        let elapsed = self.shown.elapsed();
+        if elapsed >= TOAST_DURATION {
+            return false;
+        }
+        ctx.request_repaint_after(TOAST_DURATION - elapsed); // To hide it without waiting for input
+
+        let mut keep = true;
+        egui::Area::new(egui::Id::new("batch_alias_toast"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-8.0, -40.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut args = FluentArgs::new();
+                        args.set("count", self.previous.len());
+                        ui.label(locale::get_message(
+                            locale,
+                            "batch-alias-applied",
+                            Some(&args),
+                        ));
+                        if ui
+                            .button(locale::get_message(locale, "button-undo", None))
+                            .clicked()
+                        {
+                            batch_alias::restore(&self.previous);
+                            keep = false;
+                        }
+                    });
+                });
+            });
+        keep
+    }
+}
@@ -0,0 +1,80 @@
+// Native dialogs block until they're closed, which freezes egui and can deadlock with the
+// compositor on Linux. They're shown on a helper thread instead and the result is picked up on
+// a later frame with take(). Only one dialog can be open at a time, spawning another is ignored.
+//
+//     if ui.add_enabled(!dialog::is_pending(), button).clicked() {
+//         dialog::spawn("extract-all", || DialogBuilder::file().open_single_dir().show().ok().flatten());
+//     }
+//     if let Some(Some(path)) = dialog::take::<Option<PathBuf>>("extract-all") { ... }
+use std::{
+    any::Any,
+    collections::HashMap,
+    sync::{mpsc, LazyLock, Mutex},
+    thread,
+};
+
+type DialogResult = Box<dyn Any + Send>;
+type PendingDialog = (&'static str, mpsc::Receiver<DialogResult>);
+
+static PENDING: LazyLock<Mutex<Option<PendingDialog>>> = LazyLock::new(|| Mutex::new(None));
+static READY: LazyLock<Mutex<HashMap<&'static str, DialogResult>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// f can show several dialogs in a row, e.g. a confirmation and then a folder picker
+pub fn spawn<T: Send + 'static>(id: &'static str, f: impl FnOnce() -> T + Send + 'static) {
+    let mut pending = PENDING.lock().unwrap();
+    if pending.is_some() {
+        return; // Stops a second click opening another dialog
+    }
+
+    // Results nobody picked up (e.g. the user switched tabs) shouldn't be handled later
+    READY.lock().unwrap().clear();
+
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(Box::new(f()) as DialogResult);
+    });
+    *pending = Some((id, receiver));
+}
+
+// Moves a finished dialog's result to READY
+fn poll() {
+    let mut pending = PENDING.lock().unwrap();
+    if let Some((id, receiver)) = &*pending {
+        match receiver.try_recv() {
+            Ok(result) => {
+                READY.lock().unwrap().insert(id, result);
+                *pending = None;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                log_error!("Dialog {} closed without a result", id);
+                *pending = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => (),
+        }
+    }
+}
+
+pub fn is_pending() -> bool {
+    poll();
+    PENDING.lock().unwrap().is_some()
+}
+
+pub fn take<T: 'static>(id: &str) -> Option<T> {
+    poll();
+    let result = READY.lock().unwrap().remove(id)?;
+    match result.downcast::<T>() {
+        Ok(result) => Some(*result),
+        Err(_) => {
+            log_error!("Dialog {} returned an unexpected type", id);
+            None
+        }
+    }
+}
+
+// Called every frame so results are handled without waiting for input
+pub fn update(ctx: &egui::Context) {
+    if is_pending() {
+        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+    }
+}
@@ -1,20 +1,75 @@
 use crate::{
-    config, gui, locale,
-    logic::{self, AssetInfo},
+    config,
+    gui::{
+        self,
+        batch_alias::{self, BatchAlias, UndoToast},
+        dialog,
+    },
+    locale,
+    logic::{self, error_code::ErrorCode, read_only::ReadOnlyMode, AssetInfo},
 };
 use egui::{Color32, TextureHandle};
 // Used for functionality
-use fluent_bundle::{FluentBundle, FluentResource};
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use native_dialog::{DialogBuilder, MessageLevel};
 use std::num::NonZero;
 use std::{
+    collections::HashSet,
     sync::{Arc, LazyLock, Mutex},
     thread,
     time::Duration,
 };
+use strum::IntoEnumIterator;
+
+const MAX_CLIPBOARD_SIZE: u64 = 4 * 1024 * 1024; // Bytes, anything bigger shouldn't be on a clipboard
 
 static ASSETS_LOADING: LazyLock<Mutex<Vec<String>>> = LazyLock::new(|| Mutex::new(Vec::new()));
 
+// Ask the user before going through with a write that doesn't look right, on_confirm does it
+fn confirm_write(
+    validation: Result<logic::WriteValidation, ReadOnlyMode>,
+    on_confirm: impl FnOnce() + Send + 'static,
+) {
+    match validation {
+        Err(_) => logic::update_status(locale::get_message(
+            &locale::get_locale(None),
+            "error-read-only",
+            None,
+        )),
+        Ok(logic::WriteValidation::Ok) => (), // Already written
+        Ok(logic::WriteValidation::NeedsConfirmation(warnings)) => {
+            dialog::spawn("risky-write", move || {
+                let locale = locale::get_locale(None);
+                let mut args = FluentArgs::new();
+                args.set(
+                    "warnings",
+                    logic::describe_write_warnings(&locale, &warnings),
+                );
+
+                let yes = DialogBuilder::message()
+                    .set_level(MessageLevel::Info)
+                    .set_title(locale::get_message(
+                        &locale,
+                        "confirmation-risky-write-title",
+                        None,
+                    ))
+                    .set_text(locale::get_message(
+                        &locale,
+                        "confirmation-risky-write-description",
+                        Some(&args),
+                    ))
+                    .confirm()
+                    .show()
+                    .unwrap();
+
+                if yes {
+                    on_confirm();
+                }
+            });
+        }
+    }
+}
+
 fn double_click(
     asset: logic::AssetInfo,
     swapping: &mut bool,
@@ -25,22 +80,48 @@ fn double_click(
         if swapping_asset.is_none() {
             *swapping_asset = Some(asset);
         } else {
-            logic::copy_assets(swapping_asset.clone().unwrap(), asset);
+            let asset_a = swapping_asset.clone().unwrap();
+            let validation = logic::copy_assets(asset_a.clone(), asset.clone(), false);
+            confirm_write(validation, move || {
+                let _ = logic::copy_assets(asset_a, asset, true);
+            });
         }
     } else if *swapping {
         if swapping_asset.is_none() {
             *swapping_asset = Some(asset);
         } else {
-            logic::swap_assets(swapping_asset.clone().unwrap(), asset);
+            let asset_a = swapping_asset.clone().unwrap();
+            let validation = logic::swap_assets(asset_a.clone(), asset.clone(), false);
+            confirm_write(validation, move || {
+                let _ = logic::swap_assets(asset_a, asset, true);
+            });
             *swapping_asset = None;
             *swapping = false
         }
     } else {
-        let temp_dir = logic::get_temp_dir();
-        let alias = config::get_asset_alias(&asset.name);
+        let size = asset.size.max(asset.actual_size);
+        let temp_dir = match logic::temp_space::directory_for(size) {
+            Ok(temp_dir) => temp_dir,
+            Err(e) => {
+                show_temp_space_exhausted(e.needed);
+                return;
+            }
+        };
+        // An alias like "../x" would otherwise be written outside the temp directory
+        let alias = logic::template::sanitise(&config::get_asset_alias(&asset.name));
         let destination = temp_dir.join(alias);
-        match logic::extract_to_file(asset, destination.clone(), true) {
-            Ok(new_destination) => match open::that(new_destination) {
+        // Only a copy to open, an older one can be replaced
+        match logic::extract_to_file(
+            asset,
+            destination.clone(),
+            true,
+            logic::extract_options::OverwritePolicy::Overwrite,
+            logic::extract_options::get_convert_to_png(&Default::default()),
+            logic::extract_options::get_convert_to_wav(&Default::default()),
+            logic::extract_options::get_verify_on_extract(&Default::default()),
+        ) {
+            Ok(None) => (),
+            Ok(Some(new_destination)) => match open::that(new_destination) {
                 Ok(()) => (),
                 Err(err) => {
                     logic::update_status(locale::get_message(
@@ -51,103 +132,216 @@ fn double_click(
                     log_error!("Failed opening file: {}", err)
                 }
             },
+            // The temp directory filling up mid-write is reported like running out before starting
+            Err(e) if e.code() == ErrorCode::StorageFull => {
+                log_error!("Failed opening file: {}", e.describe());
+                show_temp_space_exhausted(size);
+            }
             Err(e) => {
-                logic::update_status(locale::get_message(
-                    &locale::get_locale(None),
-                    "failed-opening-file",
-                    None,
-                ));
-                log_error!("Failed opening file: {}", e)
+                logic::update_status(e.message(&locale::get_locale(None)));
+                log_error!("Failed opening file: {}", e.describe())
             }
         }
     }
 }
 
-fn extract_all_of_type(category: logic::Category, locale: &FluentBundle<Arc<FluentResource>>) {
-    let mut no = logic::get_list_task_running();
+fn show_temp_space_exhausted(size: u64) {
+    let mut args = FluentArgs::new();
+    args.set("size", logic::format_size(size));
+    logic::update_status(locale::get_message(
+        &locale::get_locale(None),
+        "error-temp-space-exhausted",
+        Some(&args),
+    ));
+}
 
-    // Confirmation dialog, the program is still listing files
-    if no {
-        // NOT result, will become false if user clicks yes
-        no = !DialogBuilder::message()
-            .set_level(MessageLevel::Info)
-            .set_title(locale::get_message(
-                locale,
-                "confirmation-filter-confirmation-title",
-                None,
-            ))
-            .set_text(locale::get_message(
-                locale,
-                "confirmation-filter-confirmation-description",
-                None,
-            ))
-            .confirm()
-            .show()
-            .unwrap();
-    }
+// Only extracts the assets matching the search if there is one
+fn extract_all_of_type(category: logic::Category, filtered: Option<Vec<AssetInfo>>) {
+    dialog::spawn("extract-type", move || {
+        let locale = locale::get_locale(None);
+        let mut no = logic::get_list_task_running();
+
+        // Make sure the user knows it's not everything
+        if let Some(assets) = &filtered {
+            let mut args = FluentArgs::new();
+            args.set("count", assets.len());
+            let extract_filtered = DialogBuilder::message()
+                .set_level(MessageLevel::Info)
+                .set_title(locale::get_message(
+                    &locale,
+                    "confirmation-extract-filtered-title",
+                    None,
+                ))
+                .set_text(locale::get_message(
+                    &locale,
+                    "confirmation-extract-filtered-description",
+                    Some(&args),
+                ))
+                .confirm()
+                .show()
+                .unwrap();
+            if !extract_filtered {
+                return;
+            }
+        }
 
-    // The user either agreed or the program is not listing files
-    if !no {
-        let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+        // Confirmation dialog, the program is still listing files
+        if no {
+            // NOT result, will become false if user clicks yes
+            no = !DialogBuilder::message()
+                .set_level(MessageLevel::Info)
+                .set_title(locale::get_message(
+                    &locale,
+                    "confirmation-filter-confirmation-title",
+                    None,
+                ))
+                .set_text(locale::get_message(
+                    &locale,
+                    "confirmation-filter-confirmation-description",
+                    None,
+                ))
+                .confirm()
+                .show()
+                .unwrap();
+        }
+
+        // The user either agreed or the program is not listing files
+        if !no {
+            let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+
+            // If the user provides a directory, the program will extract the assets to that directory
+            if let Some(path) = option_path {
+                match filtered {
+                    Some(assets) => {
+                        logic::extract_assets(path, category, assets, false, Default::default());
+                    }
+                    None => {
+                        logic::extract_dir(path, category, false, Default::default());
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn export_archive_of_type(category: logic::Category) {
+    dialog::spawn("export-archive", move || {
+        if let Some(path) = DialogBuilder::file()
+            .set_filename(format!("{category}.zip"))
+            .add_filter("ZIP", ["zip"])
+            .save_single_file()
+            .show()
+            .unwrap()
+        {
+            logic::extract_dir_to_archive(path, category, false, Default::default());
+        }
+    });
+}
 
-        // If the user provides a directory, the program will extract the assets to that directory
-        if let Some(path) = option_path {
-            logic::extract_dir(
-                path,
+// New assets on the tab are extracted to the folder as they appear
+fn auto_extract_type(category: logic::Category) {
+    dialog::spawn("auto-extract", move || {
+        if let Some(destination) = DialogBuilder::file().open_single_dir().show().unwrap() {
+            logic::start_auto_extract(
                 category,
-                false,
+                destination,
                 config::get_config_bool("use_alias").unwrap_or(false),
             );
         }
+    });
+}
+
+// The list the last time it changed, see logic::get_file_list_generation()
+fn cached_list(
+    cache: &mut Option<(u64, Arc<Vec<AssetInfo>>)>,
+    get_list: fn() -> Vec<AssetInfo>,
+) -> Arc<Vec<AssetInfo>> {
+    let generation = logic::get_file_list_generation();
+    match cache {
+        Some((cached_generation, list)) if *cached_generation == generation => list.clone(),
+        _ => {
+            let list = Arc::new(get_list());
+            *cache = Some((generation, list.clone()));
+            list
+        }
     }
 }
-fn toggle_swap(
-    swapping: &mut bool,
-    swapping_asset: &mut Option<AssetInfo>,
-    locale: &FluentBundle<Arc<FluentResource>>,
-) {
-    let mut warning_acknowledged = config::get_config_bool("ban-warning-ack").unwrap_or(false);
 
-    if !warning_acknowledged {
-        warning_acknowledged = DialogBuilder::message()
+// Uses the filtered list so searching first narrows down what goes on the sheets
+fn make_contact_sheets() {
+    let assets = logic::get_filtered_file_list();
+    dialog::spawn("contact-sheet", move || {
+        if let Some(destination) = DialogBuilder::file().open_single_dir().show().unwrap() {
+            logic::contact_sheet::generate(assets, destination, Default::default(), false);
+        }
+    });
+}
+
+// The answer is picked up by handle_ban_warning() on a later frame
+fn show_ban_warning(id: &'static str) {
+    dialog::spawn(id, || {
+        let locale = locale::get_locale(None);
+        DialogBuilder::message()
             .set_level(MessageLevel::Info)
             .set_title(locale::get_message(
-                locale,
+                &locale,
                 "confirmation-ban-warning-title",
                 None,
             ))
             .set_text(locale::get_message(
-                locale,
+                &locale,
                 "confirmation-ban-warning-description",
                 None,
             ))
             .confirm()
             .show()
-            .unwrap();
+            .unwrap()
+    });
+}
+
+fn toggle_swap(
+    swapping: &mut bool,
+    swapping_asset: &mut Option<AssetInfo>,
+    warning_id: &'static str,
+) {
+    if !config::get_config_bool("ban-warning-ack").unwrap_or(false) {
+        show_ban_warning(warning_id);
+        return;
     }
 
-    if warning_acknowledged {
-        config::set_config_value("ban-warning-ack", warning_acknowledged.into());
-        if *swapping {
-            *swapping_asset = None;
-        }
-        *swapping = !*swapping;
+    if *swapping {
+        *swapping_asset = None;
     }
+    *swapping = !*swapping;
 }
 
 fn extract_file_button(asset: logic::AssetInfo) {
-    let alias = config::get_asset_alias(&asset.name);
-    if let Some(destination) = native_dialog::DialogBuilder::file()
-        .set_filename(&alias)
-        .save_single_file()
-        .show()
-        .unwrap()
-    {
-        match logic::extract_to_file(asset, destination, false) {
-            Ok(_) => (),
-            Err(e) => log_critical!("{}", e),
+    dialog::spawn("extract-file", move || {
+        let alias = config::get_asset_alias(&asset.name);
+        if let Some(destination) = native_dialog::DialogBuilder::file()
+            .set_filename(&alias)
+            .save_single_file()
+            .show()
+            .unwrap()
+        {
+            // The save dialog has already asked about replacing the file
+            match logic::extract_to_file(
+                asset,
+                destination,
+                false,
+                logic::extract_options::OverwritePolicy::Overwrite,
+                logic::extract_options::get_convert_to_png(&Default::default()),
+                logic::extract_options::get_convert_to_wav(&Default::default()),
+                logic::extract_options::get_verify_on_extract(&Default::default()),
+            ) {
+                Ok(_) => (),
+                Err(e @ logic::Error::Evicted) => {
+                    logic::update_status(e.message(&locale::get_locale(None)));
+                }
+                Err(e) => log_critical!("{}", e.describe()),
+            }
         }
-    }
+    });
 }
 
 fn load_asset_image(asset: AssetInfo, ctx: egui::Context) -> Option<TextureHandle> {
@@ -203,74 +397,260 @@ fn load_asset_image(asset: AssetInfo, ctx: egui::Context) -> Option<TextureHandl
     }
 }
 
-fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
-    // Confirmation dialog
-    let yes = DialogBuilder::message()
-        .set_level(MessageLevel::Info)
-        .set_title(locale::get_message(
-            locale,
-            "confirmation-clear-cache-title",
-            None,
-        ))
-        .set_text(locale::get_message(
-            locale,
-            "confirmation-clear-cache-description",
-            None,
-        ))
-        .confirm()
-        .show()
-        .unwrap();
-
-    if yes {
-        logic::clear_cache();
-    }
-}
-
-fn toggle_swap_or_copy(
-    swapping_or_copying: &mut bool,
-    swapping_asset: &mut Option<AssetInfo>,
-    locale: &FluentBundle<Arc<FluentResource>>,
-) {
-    let mut warning_acknowledged = config::get_config_bool("ban-warning-ack").unwrap_or(false);
-
-    if !warning_acknowledged {
-        warning_acknowledged = DialogBuilder::message()
+fn clear_cache() {
+    dialog::spawn("clear-cache", || {
+        let locale = locale::get_locale(None);
+        // Confirmation dialog
+        let yes = DialogBuilder::message()
             .set_level(MessageLevel::Info)
             .set_title(locale::get_message(
-                locale,
-                "confirmation-ban-warning-title",
+                &locale,
+                "confirmation-clear-cache-title",
                 None,
             ))
             .set_text(locale::get_message(
-                locale,
-                "confirmation-ban-warning-description",
+                &locale,
+                "confirmation-clear-cache-description",
                 None,
             ))
             .confirm()
             .show()
             .unwrap();
+
+        if yes {
+            if let Err(e) = logic::clear_cache() {
+                gui::settings::show_clear_error(e, &locale);
+            }
+        }
+    });
+}
+
+fn toggle_swap_or_copy(
+    swapping_or_copying: &mut bool,
+    swapping_asset: &mut Option<AssetInfo>,
+    warning_id: &'static str,
+) {
+    if !config::get_config_bool("ban-warning-ack").unwrap_or(false) {
+        show_ban_warning(warning_id);
+        return;
     }
 
-    if warning_acknowledged {
-        config::set_config_value("ban-warning-ack", warning_acknowledged.into());
-        if *swapping_or_copying {
-            *swapping_asset = None;
+    if *swapping_or_copying {
+        *swapping_asset = None;
+    }
+    *swapping_or_copying = !*swapping_or_copying;
+}
+
+fn copy_hash(ctx: &egui::Context, asset: &AssetInfo, locale: &FluentBundle<Arc<FluentResource>>) {
+    match logic::compute_hash(asset) {
+        Ok(hash) => {
+            ctx.copy_text(hash);
+            logic::update_status(locale::get_message(locale, "copied-to-clipboard", None));
+        }
+        Err(e) => {
+            logic::update_status(e.message(locale));
+            log_error!("Failed to hash {}: {}", asset.name, e.describe())
         }
-        *swapping_or_copying = !*swapping_or_copying;
     }
 }
 
-// fn format_size(bytes: u64) -> String {
-//     const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
-//     let mut size = bytes as f64 / 1024.0;
-//     let mut unit_idx = 0;
+// Put the asset on the clipboard for embedding in HTML/Markdown
+fn copy_base64(
+    ctx: &egui::Context,
+    asset: AssetInfo,
+    data_uri: bool,
+    locale: &FluentBundle<Arc<FluentResource>>,
+) {
+    let max_size = config::get_config_u64("max_clipboard_size").unwrap_or(MAX_CLIPBOARD_SIZE);
 
-//     while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-//         size /= 1024.0;
-//         unit_idx += 1;
-//     }
-//     format!("{:.1} {}", size, UNITS[unit_idx])
-// }
+    match logic::extract_asset_to_base64(asset, data_uri, Some(max_size)) {
+        Ok(encoded) => {
+            ctx.copy_text(encoded);
+            logic::update_status(locale::get_message(locale, "copied-to-clipboard", None));
+        }
+        Err(e) if e.code() == ErrorCode::TooLarge => {
+            let mut args = FluentArgs::new();
+            args.set("max_size", logic::format_size(max_size));
+            logic::update_status(locale::get_message(
+                locale,
+                "error-too-large-for-clipboard",
+                Some(&args),
+            ));
+        }
+        Err(e) => {
+            logic::update_status(e.message(locale));
+            log_error!("Failed copying to clipboard: {}", e.describe())
+        }
+    }
+}
+
+// Draw the name and size columns of a row
+// "Headshot 156", the kind alone if the URL doesn't have the id
+fn thumbnail_label(
+    thumbnail: logic::thumbnail::Thumbnail,
+    locale: &FluentBundle<Arc<FluentResource>>,
+) -> String {
+    let kind = locale::get_message(locale, &format!("thumbnail-{}", thumbnail.kind), None);
+    match thumbnail.id {
+        Some(id) => format!("{kind} {id}"),
+        None => kind,
+    }
+}
+
+fn paint_columns(ui: &egui::Ui, rect: egui::Rect, columns: [String; 4], text_colour: Color32) {
+    let [name, format, size, actual_size] = columns;
+
+    // Column positions (add padding)
+    let name_x = rect.min.x + 5.0;
+    let format_x = rect.min.x + rect.width() * 0.65;
+    let size_x = rect.min.x + rect.width() * 0.8;
+    let actual_size_x = rect.max.x - 5.0;
+
+    let font = egui::TextStyle::Body.resolve(ui.style());
+
+    // Stop the name from drawing over the other columns
+    let name_rect = egui::Rect::from_min_max(
+        rect.min,
+        egui::pos2(rect.min.x + rect.width() * 0.45, rect.max.y),
+    );
+    ui.painter().with_clip_rect(name_rect).text(
+        egui::pos2(name_x, rect.min.y),
+        egui::Align2::LEFT_TOP,
+        name,
+        font.clone(),
+        text_colour,
+    );
+    ui.painter().text(
+        egui::pos2(format_x, rect.min.y),
+        egui::Align2::RIGHT_TOP,
+        format,
+        font.clone(),
+        text_colour,
+    );
+    ui.painter().text(
+        egui::pos2(size_x, rect.min.y),
+        egui::Align2::RIGHT_TOP,
+        size,
+        font.clone(),
+        text_colour,
+    );
+    ui.painter().text(
+        egui::pos2(actual_size_x, rect.min.y),
+        egui::Align2::RIGHT_TOP,
+        actual_size,
+        font,
+        text_colour,
+    );
+}
+
+fn category_colour(category: logic::Category) -> Color32 {
+    match category {
+        logic::Category::Music => Color32::from_rgb(186, 104, 200),
+        logic::Category::Sounds => Color32::from_rgb(79, 160, 230),
+        logic::Category::Images => Color32::from_rgb(102, 187, 106),
+        logic::Category::Ktx => Color32::from_rgb(255, 167, 38),
+        logic::Category::Rbxm => Color32::from_rgb(239, 83, 80),
+        logic::Category::Mesh => Color32::from_rgb(38, 166, 154),
+        logic::Category::Fonts => Color32::from_rgb(141, 110, 99),
+        logic::Category::Json => Color32::from_rgb(255, 213, 79),
+        logic::Category::All => Color32::GRAY,
+    }
+}
+
+// Small coloured label with the category name, drawn after the name column
+fn paint_category_chip(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    category: logic::Category,
+    locale: &FluentBundle<Arc<FluentResource>>,
+) {
+    let colour = category_colour(category);
+    let font = egui::TextStyle::Small.resolve(ui.style());
+    let galley = ui.painter().layout_no_wrap(
+        locale::get_message(locale, category.locale_key(), None),
+        font,
+        colour,
+    );
+
+    let chip_rect = egui::Rect::from_min_size(
+        egui::pos2(
+            rect.min.x + rect.width() * 0.45 + 4.0,
+            rect.center().y - galley.size().y / 2.0 - 1.0,
+        ),
+        galley.size() + egui::vec2(8.0, 2.0),
+    );
+    ui.painter()
+        .rect_filled(chip_rect, 4.0, colour.gamma_multiply(0.2));
+    ui.painter()
+        .galley(chip_rect.min + egui::vec2(4.0, 1.0), galley, colour);
+}
+
+// A row in the list, either a day group's header or an index into the file list
+enum ListRow {
+    Header(usize),
+    Asset(usize),
+}
+
+// The rows shown for a list, grouping by day sorts every asset so it's only redone when the list,
+// the grouping, the collapsed groups or the day change
+struct ListRows {
+    source: Arc<Vec<AssetInfo>>,
+    group_by_day: bool,
+    today: chrono::NaiveDate, // Groups are relative to it
+    collapsed_groups: HashSet<logic::DayGroup>,
+    file_list: Arc<Vec<AssetInfo>>, // Reordered to match the groups
+    rows: Vec<ListRow>,
+    groups: Vec<(logic::DayGroup, Vec<AssetInfo>)>,
+}
+
+impl ListRows {
+    fn new(
+        source: Arc<Vec<AssetInfo>>,
+        group_by_day: bool,
+        collapsed_groups: &HashSet<logic::DayGroup>,
+    ) -> Self {
+        let (file_list, rows, groups) = if group_by_day {
+            let groups = logic::group_by_day(source.to_vec());
+            let mut grouped = Vec::new();
+            let mut rows = Vec::new();
+            for (group_index, (group, assets)) in groups.iter().enumerate() {
+                rows.push(ListRow::Header(group_index));
+                if !collapsed_groups.contains(group) {
+                    for asset in assets {
+                        rows.push(ListRow::Asset(grouped.len()));
+                        grouped.push(asset.clone());
+                    }
+                }
+            }
+            (Arc::new(grouped), rows, groups)
+        } else {
+            let rows = (0..source.len()).map(ListRow::Asset).collect();
+            (source.clone(), rows, Vec::new())
+        };
+        ListRows {
+            source,
+            group_by_day,
+            today: chrono::Local::now().date_naive(),
+            collapsed_groups: collapsed_groups.clone(),
+            file_list,
+            rows,
+            groups,
+        }
+    }
+
+    fn is_for(
+        &self,
+        source: &Arc<Vec<AssetInfo>>,
+        group_by_day: bool,
+        collapsed_groups: &HashSet<logic::DayGroup>,
+    ) -> bool {
+        Arc::ptr_eq(&self.source, source)
+            && self.group_by_day == group_by_day
+            && (!group_by_day
+                || (&self.collapsed_groups == collapsed_groups
+                    && self.today == chrono::Local::now().date_naive()))
+    }
+}
 
 // fn format_modified(time: std::time::SystemTime) -> String {
 //     let datetime: chrono::DateTime<chrono::Local> = time.into();
@@ -278,15 +658,35 @@ fn toggle_swap_or_copy(
 // }
 
 pub struct FileListUi {
-    selected: Option<usize>, // Used for storing selected state to retain keyboard navigation as seen in the tkinter version
+    selected: Option<String>, // Asset id rather than the row, so the selection survives refreshes
+    batch_selection: Vec<String>, // Ctrl/Shift+clicked assets in the order they were selected
+    select_range: Option<(String, String)>, // Shift+click, resolved once the rows are known
+    batch_alias: Option<BatchAlias>,
+    batch_undo: Option<UndoToast>,
+    top_asset: Option<String>, // Topmost visible asset, to scroll back to it after a refresh
+    restore_scroll: Option<String>,
+    list_generation: u64,        // Used to tell when a refresh has started
     current_tab: Option<String>, // Allows for detecting when the user changes tabs to refresh automatically
     renaming: bool,
     searching: bool,
     search_query: String,
+    search_regex: bool,
     swapping: bool,
     swapping_asset: Option<logic::AssetInfo>,
     asset_context_menu_open: Option<usize>,
     copying: bool,
+    list_loading: bool, // Whether a refresh was running last frame
+    category_filter: HashSet<logic::Category>, // Categories shown in the All tab
+    thumbnails_only: bool, // Avatar and asset thumbnails on the Images tab
+    last_filter: Option<logic::FilterQuery>,
+    min_size_kib: u64, // Size and date filters shown while searching, 0 doesn't filter
+    max_size_kib: u64,
+    newer_than_minutes: u64,
+    collapsed_groups: HashSet<logic::DayGroup>,
+    // Copied from the logic only when they've changed, not every frame
+    file_list_cache: Option<(u64, Arc<Vec<AssetInfo>>)>,
+    filtered_file_list_cache: Option<(u64, Arc<Vec<AssetInfo>>)>,
+    list_rows_cache: Option<Arc<ListRows>>,
     pub locale: FluentBundle<Arc<FluentResource>>,
 }
 // selected: Option<usize>, // Used for storing selected state to retain keyboard navigation as seen in the tkinter version
@@ -311,12 +711,102 @@ pub struct FileListUi {
 // copying: &'a mut bool,
 
 impl FileListUi {
+    // Turns swapping/copying on once the ban warning is accepted
+    fn handle_ban_warning(&mut self) {
+        for (id, toggle) in [
+            ("ban-warning-swap", &mut self.swapping),
+            ("ban-warning-copy", &mut self.copying),
+        ] {
+            if dialog::take::<bool>(id) == Some(true) {
+                config::set_config_value("ban-warning-ack", true.into());
+                *toggle = true;
+            }
+        }
+    }
+
+    // What's shown while searching or filtering by category, None if it's the whole list
+    fn filtered_assets(&self) -> Option<Vec<AssetInfo>> {
+        self.last_filter
+            .as_ref()
+            .filter(|filter| !filter.is_empty())
+            .map(|_| logic::get_filtered_file_list())
+    }
+
+    // Collapsible header for a day group, with its count, size and an extract button
+    fn group_header(
+        &mut self,
+        ui: &mut egui::Ui,
+        row_height: f32,
+        group: logic::DayGroup,
+        assets: &[AssetInfo],
+        category: logic::Category,
+    ) {
+        let full_width = ui.available_width();
+        let (rect, response) =
+            ui.allocate_exact_size(egui::vec2(full_width, row_height), egui::Sense::click());
+
+        let collapsed = self.collapsed_groups.contains(&group);
+        let size: u64 = assets.iter().map(|asset| asset.size).sum();
+        let mut args = FluentArgs::new();
+        args.set("group", group.label(&self.locale));
+        args.set("count", assets.len());
+        args.set("size", logic::format_size(size));
+
+        ui.painter()
+            .rect_filled(rect, 0.0, ui.visuals().faint_bg_color);
+        ui.painter().text(
+            egui::pos2(rect.min.x + 5.0, rect.min.y),
+            egui::Align2::LEFT_TOP,
+            format!(
+                "{} {}",
+                if collapsed { "▶" } else { "▼" },
+                locale::get_message(&self.locale, "group-header", Some(&args))
+            ),
+            egui::TextStyle::Body.resolve(ui.style()),
+            ui.visuals().strong_text_color(),
+        );
+
+        let button_rect = egui::Rect::from_min_max(
+            egui::pos2(rect.max.x - rect.width() * 0.2, rect.min.y),
+            rect.max,
+        );
+        let extract_clicked = ui
+            .put(
+                button_rect,
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-extract-group",
+                    None,
+                ))
+                .small(),
+            )
+            .clicked();
+
+        if extract_clicked {
+            let assets = assets.to_vec();
+            dialog::spawn("extract-group", move || {
+                let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+                if let Some(path) = option_path {
+                    logic::extract_assets(path, category, assets, false, Default::default());
+                }
+            });
+        } else if response.clicked() {
+            if collapsed {
+                self.collapsed_groups.remove(&group);
+            } else {
+                self.collapsed_groups.insert(group);
+            }
+        }
+    }
+
     fn handle_text_edit(&mut self, ui: &mut egui::Ui, alias: &str, file_name: &str) {
         let mut mutable_name = alias.to_string();
         let response = egui::TextEdit::singleline(&mut mutable_name)
             .hint_text(file_name)
+            .char_limit(config::MAX_ALIAS_LENGTH)
             .show(ui)
             .response;
+        mutable_name.retain(|c| !c.is_control()); // Pasted tabs and the like
 
         if mutable_name != alias {
             config::set_asset_alias(file_name, &mutable_name);
@@ -339,6 +829,7 @@ impl FileListUi {
         focus_search_box: &mut bool,
         asset: Option<AssetInfo>,
     ) {
+        let dialog_open = dialog::is_pending();
         if let Some(asset) = asset.clone() {
             if ui
                 .button(locale::get_message(&self.locale, "button-open", None))
@@ -352,15 +843,54 @@ impl FileListUi {
                 );
                 self.asset_context_menu_open = None;
             }
+            if ui
+                .add_enabled(
+                    !dialog_open,
+                    egui::Button::new(locale::get_message(
+                        &self.locale,
+                        "button-extract-file",
+                        None,
+                    )),
+                )
+                .clicked()
+            {
+                extract_file_button(asset.clone());
+                self.asset_context_menu_open = None;
+            }
+            if ui
+                .button(locale::get_message(&self.locale, "button-preview", None))
+                .clicked()
+            {
+                gui::preview::open(asset.clone());
+                self.asset_context_menu_open = None;
+            }
             if ui
                 .button(locale::get_message(
                     &self.locale,
-                    "button-extract-file",
+                    "button-copy-base64",
                     None,
                 ))
                 .clicked()
             {
-                extract_file_button(asset);
+                copy_base64(ui.ctx(), asset.clone(), false, &self.locale);
+                self.asset_context_menu_open = None;
+            }
+            if ui
+                .button(locale::get_message(
+                    &self.locale,
+                    "button-copy-data-uri",
+                    None,
+                ))
+                .clicked()
+            {
+                copy_base64(ui.ctx(), asset.clone(), true, &self.locale);
+                self.asset_context_menu_open = None;
+            }
+            if ui
+                .button(locale::get_message(&self.locale, "button-copy-hash", None))
+                .clicked()
+            {
+                copy_hash(ui.ctx(), &asset, &self.locale);
                 self.asset_context_menu_open = None;
             }
         }
@@ -382,42 +912,129 @@ impl FileListUi {
             self.asset_context_menu_open = None;
         }
 
+        // The selected asset alone if nothing has been Ctrl/Shift+clicked
+        let batch = if self.batch_selection.is_empty() {
+            self.selected.iter().cloned().collect()
+        } else {
+            self.batch_selection.clone()
+        };
         if ui
-            .button(locale::get_message(
-                &self.locale,
-                "button-clear-cache",
-                None,
-            ))
+            .add_enabled(
+                !batch.is_empty(),
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-batch-alias",
+                    None,
+                )),
+            )
             .clicked()
-            || ui.input(|i| i.key_pressed(egui::Key::Delete))
         {
-            clear_cache(&self.locale);
+            self.batch_alias = Some(BatchAlias::new(batch));
             self.asset_context_menu_open = None;
         }
 
+        let read_only = logic::read_only::is_enabled();
+        let read_only_tooltip = locale::get_message(&self.locale, "read-only-tooltip", None);
+
         if ui
-            .button(locale::get_message(
-                &self.locale,
-                "button-extract-type",
-                None,
-            ))
+            .add_enabled(
+                !dialog_open && !read_only,
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-clear-cache",
+                    None,
+                )),
+            )
+            .on_disabled_hover_text(&read_only_tooltip)
             .clicked()
+            || ui.input(|i| i.key_pressed(egui::Key::Delete)) && !read_only
         {
-            extract_all_of_type(category, &self.locale);
+            clear_cache();
             self.asset_context_menu_open = None;
         }
+
         if ui
-            .button(locale::get_message(&self.locale, "button-refresh", None))
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-extract-type",
+                    None,
+                )),
+            )
             .clicked()
         {
+            extract_all_of_type(category, self.filtered_assets());
+            self.asset_context_menu_open = None;
+        }
+        if ui
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-export-archive",
+                    None,
+                )),
+            )
+            .clicked()
+        {
+            export_archive_of_type(category);
+            self.asset_context_menu_open = None;
+        }
+        // Keeps running on other tabs until it's turned off here
+        let auto_extracting = logic::watcher::get_auto_extracted().is_some();
+        if ui
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(
+                    &self.locale,
+                    "button-auto-extract",
+                    None,
+                ))
+                .selected(auto_extracting),
+            )
+            .clicked()
+        {
+            if auto_extracting {
+                thread::spawn(logic::stop_auto_extract); // Waits for the current extraction
+            } else {
+                auto_extract_type(category);
+            }
+            self.asset_context_menu_open = None;
+        }
+        let mut refresh_button =
+            ui.button(locale::get_message(&self.locale, "button-refresh", None));
+        // Shows whether files were skipped or failed, not just how many were listed
+        if let Some(summary) = logic::summary::get_last_refresh() {
+            refresh_button = refresh_button.on_hover_text(summary.message(&self.locale));
+        }
+        if refresh_button.clicked() {
             logic::refresh(category, false, false);
             self.asset_context_menu_open = None;
         }
         if ui
-            .button(locale::get_message(&self.locale, "button-swap", None))
+            .add_enabled(
+                !logic::get_list_task_running(),
+                egui::Button::new(locale::get_message(&self.locale, "button-reclassify", None)),
+            )
             .clicked()
         {
-            toggle_swap(&mut self.swapping, &mut self.swapping_asset, &self.locale);
+            logic::reclassify(false);
+            self.asset_context_menu_open = None;
+        }
+        if ui
+            .add_enabled(
+                !dialog_open && !read_only,
+                egui::Button::new(locale::get_message(&self.locale, "button-swap", None)),
+            )
+            .on_disabled_hover_text(&read_only_tooltip)
+            .clicked()
+        {
+            toggle_swap(
+                &mut self.swapping,
+                &mut self.swapping_asset,
+                "ban-warning-swap",
+            );
             self.asset_context_menu_open = None;
 
             if let Some(n) = asset.clone() {
@@ -427,10 +1044,18 @@ impl FileListUi {
             }
         }
         if ui
-            .button(locale::get_message(&self.locale, "button-copy", None))
+            .add_enabled(
+                !dialog_open && !read_only,
+                egui::Button::new(locale::get_message(&self.locale, "button-copy", None)),
+            )
+            .on_disabled_hover_text(&read_only_tooltip)
             .clicked()
         {
-            toggle_swap(&mut self.copying, &mut self.swapping_asset, &self.locale);
+            toggle_swap(
+                &mut self.copying,
+                &mut self.swapping_asset,
+                "ban-warning-copy",
+            );
             self.asset_context_menu_open = None;
 
             if let Some(n) = asset.clone() {
@@ -440,6 +1065,48 @@ impl FileListUi {
             }
         }
 
+        let message = if config::get_config_bool("group_by_day").unwrap_or(false) {
+            locale::get_message(&self.locale, "button-ungroup", None)
+        } else {
+            locale::get_message(&self.locale, "button-group-by-day", None)
+        };
+        if ui.button(message).clicked() {
+            config::set_config_value(
+                "group_by_day",
+                (!config::get_config_bool("group_by_day").unwrap_or(false)).into(),
+            );
+            self.asset_context_menu_open = None;
+        }
+
+        let (mut sort_key, ascending) = logic::get_sort();
+        let old_sort_key = sort_key;
+        egui::ComboBox::from_id_salt("Sort key")
+            .selected_text(locale::get_message(
+                &self.locale,
+                &format!("sort-{sort_key}"),
+                None,
+            ))
+            .show_ui(ui, |ui| {
+                for option in logic::SortKey::iter() {
+                    ui.selectable_value(
+                        &mut sort_key,
+                        option,
+                        locale::get_message(&self.locale, &format!("sort-{option}"), None),
+                    );
+                }
+            });
+        let message = if ascending {
+            locale::get_message(&self.locale, "button-sort-ascending", None)
+        } else {
+            locale::get_message(&self.locale, "button-sort-descending", None)
+        };
+        if ui.button(message).clicked() {
+            logic::set_sort(sort_key, !ascending);
+            self.asset_context_menu_open = None;
+        } else if sort_key != old_sort_key {
+            logic::set_sort(sort_key, ascending);
+        }
+
         if category == logic::Category::Images {
             let message = if config::get_config_bool("display_image_preview").unwrap_or(false) {
                 locale::get_message(&self.locale, "button-disable-display-image-preview", None)
@@ -454,6 +1121,21 @@ impl FileListUi {
                 );
                 self.asset_context_menu_open = None;
             }
+
+            if ui
+                .add_enabled(
+                    !dialog_open,
+                    egui::Button::new(locale::get_message(
+                        &self.locale,
+                        "button-contact-sheet",
+                        None,
+                    )),
+                )
+                .clicked()
+            {
+                make_contact_sheets();
+                self.asset_context_menu_open = None;
+            }
         }
     }
 
@@ -485,11 +1167,35 @@ impl FileListUi {
 
         // Handle the click/double click
         if response.clicked() && !self.renaming {
-            self.selected = Some(i);
+            let modifiers = response.ctx.input(|i| i.modifiers);
+            if modifiers.command {
+                // Ctrl+click adds to or removes from the selection
+                if self.batch_selection.is_empty() {
+                    self.batch_selection.extend(self.selected.clone());
+                }
+                match self
+                    .batch_selection
+                    .iter()
+                    .position(|name| name == &asset.name)
+                {
+                    Some(position) => {
+                        self.batch_selection.remove(position);
+                    }
+                    None => self.batch_selection.push(asset.name.clone()),
+                }
+            } else if modifiers.shift {
+                self.select_range = self
+                    .selected
+                    .clone()
+                    .map(|start| (start, asset.name.clone()));
+            } else {
+                self.batch_selection.clear();
+            }
+            self.selected = Some(asset.name.clone());
         }
 
         if response.secondary_clicked() {
-            self.selected = Some(i);
+            self.selected = Some(asset.name.clone());
             self.asset_context_menu_open = Some(i);
         }
 
@@ -519,20 +1225,20 @@ impl FileListUi {
         (background_colour, text_colour)
     }
 
+    // Opens the search bar with the query already typed in
+    pub fn set_search(&mut self, query: String) {
+        self.searching = true;
+        self.search_query = query;
+    }
+
     pub fn ui(&mut self, tab: String, ui: &mut egui::Ui) {
-        let category = match tab.as_str() {
-            "music" => logic::Category::Music,
-            "sounds" => logic::Category::Sounds,
-            "images" => logic::Category::Images,
-            "ktx-files" => logic::Category::Ktx,
-            "rbxm-files" => logic::Category::Rbxm,
-            _ => logic::Category::All,
-        };
+        let category = logic::Category::from_tab_id(&tab).unwrap_or(logic::Category::All);
 
         // Detect if tab changed and do a refresh if so
         if let Some(current_tab) = &self.current_tab {
             if current_tab != &tab {
                 self.current_tab = Some(tab.to_owned());
+                self.top_asset = None; // Another tab's assets won't be there
                 logic::refresh(category, false, false);
             }
         } else {
@@ -540,10 +1246,37 @@ impl FileListUi {
             logic::refresh(category, false, false);
         }
 
-        let file_list = logic::get_file_list();
+        // A refresh has replaced the list, scroll back to where the user was once it's finished
+        let list_generation = logic::get_list_generation();
+        if list_generation != self.list_generation {
+            self.list_generation = list_generation;
+            if let Some(top_asset) = self.top_asset.take() {
+                self.restore_scroll = Some(top_asset);
+            }
+        }
+
+        let file_list = cached_list(&mut self.file_list_cache, logic::get_file_list);
 
         let mut focus_search_box = false; // Focus the search box toggle for this frame
 
+        self.handle_ban_warning();
+
+        if let Some(window) = &mut self.batch_alias {
+            match window.show(ui.ctx(), &self.locale) {
+                Some(batch_alias::Outcome::Applied(previous)) => {
+                    self.batch_alias = None;
+                    self.batch_undo = Some(UndoToast::new(previous));
+                }
+                Some(batch_alias::Outcome::Cancelled) => self.batch_alias = None,
+                None => (),
+            }
+        }
+        if let Some(toast) = &self.batch_undo {
+            if !toast.show(ui.ctx(), &self.locale) {
+                self.batch_undo = None;
+            }
+        }
+
         // Handle key shortcuts here
         if ui.input(|i| i.key_pressed(egui::Key::F2)) {
             // Rename hotkey
@@ -554,37 +1287,48 @@ impl FileListUi {
             self.searching = !self.searching;
             focus_search_box = true;
         }
-        if ui.input(|i| i.key_pressed(egui::Key::Delete)) && !self.renaming {
+        let read_only = logic::read_only::is_enabled();
+        if ui.input(|i| i.key_pressed(egui::Key::Delete)) && !self.renaming && !read_only {
             // del key used for editing, don't allow during editing
-            clear_cache(&self.locale);
+            clear_cache();
         }
         if ui.input(|i| i.key_pressed(egui::Key::F3)) {
-            extract_all_of_type(category, &self.locale);
+            extract_all_of_type(category, self.filtered_assets());
         }
         if ui.input(|i| i.key_pressed(egui::Key::F5)) {
             logic::refresh(category, false, false);
         }
-        if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::D)) {
+        if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::D)) && !read_only {
             // Ctrl+D (Swap)
-            toggle_swap_or_copy(&mut self.swapping, &mut self.swapping_asset, &self.locale);
-            if let Some(i) = self.selected {
-                self.swapping_asset = file_list.get(i).cloned();
-            } else {
-                self.swapping_asset = None;
-            }
+            toggle_swap_or_copy(
+                &mut self.swapping,
+                &mut self.swapping_asset,
+                "ban-warning-swap",
+            );
+            self.swapping_asset = self
+                .selected
+                .as_ref()
+                .and_then(|name| file_list.iter().find(|asset| &asset.name == name))
+                .cloned();
         }
-        if ui.input(|inp| inp.events.iter().any(|ev| matches!(ev, egui::Event::Copy))) {
+        if ui.input(|inp| inp.events.iter().any(|ev| matches!(ev, egui::Event::Copy))) && !read_only
+        {
             // https://github.com/emilk/egui/issues/4065#issuecomment-2071047410
             // Ctrl+C (Copy)
-            toggle_swap_or_copy(&mut self.copying, &mut self.swapping_asset, &self.locale);
-            if let Some(i) = self.selected {
-                self.swapping_asset = file_list.get(i).cloned();
-            } else {
-                self.swapping_asset = None;
-            }
+            toggle_swap_or_copy(
+                &mut self.copying,
+                &mut self.swapping_asset,
+                "ban-warning-copy",
+            );
+            self.swapping_asset = self
+                .selected
+                .as_ref()
+                .and_then(|name| file_list.iter().find(|asset| &asset.name == name))
+                .cloned();
         }
         if ui.input(|i| i.key_pressed(egui::Key::Escape)) && !self.searching {
             // Esc (Cancel actions)
+            self.batch_selection.clear();
             self.swapping_asset = None;
             self.copying = false;
             self.swapping = false;
@@ -603,63 +1347,6 @@ impl FileListUi {
             });
         }
 
-        let mut scroll_to: Option<usize> = None; // This is reset every frame, so it doesn't constantly scroll to the same label
-        let mut none_selected: bool = false; // Used to scroll to the first value shown when none is selected
-
-        // Only allow navigation of the user is not renaming
-        if !self.renaming {
-            // If the user presses up, decrement the selected value
-            if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
-                if let Some(selected) = self.selected {
-                    if selected > 0 {
-                        // Check if it is larger than 0 otherwise it'll attempt to select non-existant labels
-                        self.selected = Some(selected - 1);
-                        scroll_to = Some(selected - 1); // This is also set to the same number, allowing for auto scrolling
-                    }
-                } else {
-                    none_selected = true // Select the first visible entry
-                }
-            }
-
-            // If the user presses down, increment the selected value
-            if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
-                if let Some(selected) = self.selected {
-                    if selected < file_list.len() - 1 {
-                        // Stop it from overflowing otherwise it'll attempt to select non-existant labels
-                        self.selected = Some(selected + 1);
-                        scroll_to = Some(selected + 1); // This is also set to the same number, allowing for auto scrolling
-                    }
-                } else {
-                    none_selected = true // Select the first visible entry
-                }
-            }
-
-            // Allow the user to confirm with enter
-            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                if let Some(selected) = self.selected {
-                    // Get file name after getting the selected value
-                    if let Some(asset) = file_list.get(selected) {
-                        double_click(
-                            asset.clone(),
-                            &mut self.swapping,
-                            &mut self.copying,
-                            &mut self.swapping_asset,
-                        );
-                    }
-                }
-            }
-
-            if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::E)) {
-                // Ctrl+E (Extract)
-                if let Some(selected) = self.selected {
-                    // Get file name after getting the selected value
-                    if let Some(asset) = file_list.get(selected) {
-                        extract_file_button(asset.clone());
-                    }
-                }
-            }
-        }
-
         let mut navigation_accepted: bool = false; // Used to check if the selected label is available to accept the keyboard navigation
 
         if self.swapping {
@@ -669,7 +1356,9 @@ impl FileListUi {
                 let mut args = fluent_bundle::FluentArgs::new();
                 args.set(
                     "asset",
-                    config::get_asset_alias(&self.swapping_asset.as_ref().unwrap().name),
+                    logic::display_text(&config::get_asset_alias(
+                        &self.swapping_asset.as_ref().unwrap().name,
+                    )),
                 );
                 ui.heading(locale::get_message(&self.locale, "swap-with", Some(&args)));
             }
@@ -682,7 +1371,9 @@ impl FileListUi {
                 let mut args = fluent_bundle::FluentArgs::new();
                 args.set(
                     "asset",
-                    config::get_asset_alias(&self.swapping_asset.as_ref().unwrap().name),
+                    logic::display_text(&config::get_asset_alias(
+                        &self.swapping_asset.as_ref().unwrap().name,
+                    )),
                 );
                 ui.heading(locale::get_message(
                     &self.locale,
@@ -692,10 +1383,91 @@ impl FileListUi {
             }
         }
 
-        let file_list = if self.searching {
-            let old_search_query = self.search_query.clone();
+        // Rows stream in while the new category is being listed
+        let list_loading = logic::get_list_task_running() && logic::get_list_category() == category;
+        if list_loading {
+            let mut args = fluent_bundle::FluentArgs::new();
+            args.set("category", locale::get_message(&self.locale, &tab, None));
+            ui.label(locale::get_message(
+                &self.locale,
+                "loading-category",
+                Some(&args),
+            ));
+        } else if logic::get_list_state() == logic::ListState::Empty {
+            // Makes it easy to tell the program is working and there's just nothing to list
+            ui.label(locale::get_message(&self.locale, "no-files", None));
+        }
+        // Filter once more after loading finishes to include the last rows
+        let list_changed = list_loading || self.list_loading;
+        self.list_loading = list_loading;
+
+        // Music is listed from a folder that not every install has
+        if category == logic::Category::Music
+            && logic::cache_directory::get_sounds_directory().is_none()
+        {
+            let mut args = fluent_bundle::FluentArgs::new();
+            args.set(
+                "directory",
+                logic::cache_directory::expected_sounds_directory()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                locale::get_message(&self.locale, "sounds-directory-missing", Some(&args)),
+            );
+        }
+
+        // Category chips for the All tab
+        if tab == "all" {
+            ui.horizontal_wrapped(|ui| {
+                for category in logic::Category::iter() {
+                    let enabled = self.category_filter.contains(&category);
+                    let text = egui::RichText::new(locale::get_message(
+                        &self.locale,
+                        category.locale_key(),
+                        None,
+                    ))
+                    .color(category_colour(category));
+
+                    if ui.selectable_label(enabled, text).clicked() {
+                        if enabled {
+                            self.category_filter.remove(&category);
+                        } else {
+                            self.category_filter.insert(category);
+                        }
+                    }
+                }
+            });
+        }
+        let categories = (tab == "all"
+            && self.category_filter.len() != logic::Category::iter().count())
+        .then(|| self.category_filter.clone());
+
+        // Told apart by the URL they were downloaded from, their ids are shown instead of the format
+        if tab == "images" {
+            ui.checkbox(
+                &mut self.thumbnails_only,
+                locale::get_message(&self.locale, "filter-thumbnails", None),
+            );
+        }
+        let show_thumbnails = tab == "images" && self.thumbnails_only;
 
-            let response = ui.text_edit_singleline(&mut self.search_query);
+        let mut filter = logic::FilterQuery {
+            categories,
+            thumbnails: show_thumbnails,
+            ..Default::default()
+        };
+        if self.searching {
+            let response = ui
+                .horizontal(|ui| {
+                    ui.checkbox(
+                        &mut self.search_regex,
+                        locale::get_message(&self.locale, "search-regex", None),
+                    );
+                    ui.text_edit_singleline(&mut self.search_query)
+                })
+                .inner;
 
             if focus_search_box {
                 response.request_focus();
@@ -705,16 +1477,47 @@ impl FileListUi {
                 self.searching = false; // Remove the search bar when the use presses escape
             }
 
-            if self.search_query != old_search_query {
-                logic::filter_file_list(self.search_query.clone());
+            ui.horizontal_wrapped(|ui| {
+                for (key, value, suffix) in [
+                    ("filter-min-size", &mut self.min_size_kib, " KiB"),
+                    ("filter-max-size", &mut self.max_size_kib, " KiB"),
+                    ("filter-newer-than", &mut self.newer_than_minutes, " min"),
+                ] {
+                    ui.label(locale::get_message(&self.locale, key, None));
+                    ui.add(egui::DragValue::new(value).suffix(suffix));
+                }
+            });
+
+            filter.text = self.search_query.clone();
+            filter.regex = self.search_regex;
+            filter.min_size = (self.min_size_kib > 0).then_some(self.min_size_kib * 1024);
+            filter.max_size = (self.max_size_kib > 0).then_some(self.max_size_kib * 1024);
+            filter.newer_than = (self.newer_than_minutes > 0)
+                .then(|| Duration::from_secs(self.newer_than_minutes * 60));
+        }
+
+        let file_list = if self.searching || filter.categories.is_some() || filter.thumbnails {
+            // Only filter again when something has changed
+            if self.last_filter.is_none() {
+                // What's left over from the last search shouldn't show while it filters
+                logic::filter_file_list(&filter);
+                self.last_filter = Some(filter);
+            } else if list_changed || self.last_filter.as_ref() != Some(&filter) {
+                logic::filter_file_list_in_background(filter.clone());
+                self.last_filter = Some(filter);
             }
-            logic::get_filtered_file_list()
+            cached_list(
+                &mut self.filtered_file_list_cache,
+                logic::get_filtered_file_list,
+            )
         } else {
+            self.last_filter = None;
             file_list
         };
 
-        let display_image_preview =
-            config::get_config_bool("display_image_preview").unwrap_or(false) && tab == "images";
+        let display_image_preview = config::get_config_bool("display_image_preview")
+            .unwrap_or(false)
+            && category == logic::Category::Images;
 
         let row_height = if display_image_preview {
             config::get_config_u64("image_preview_size").unwrap_or(128) as f32
@@ -728,73 +1531,169 @@ impl FileListUi {
             1
         };
 
+        // Grouping only applies to the list, file_list is reordered to match the groups
+        let group_by_day =
+            config::get_config_bool("group_by_day").unwrap_or(false) && !display_image_preview;
+        let list_rows = match &self.list_rows_cache {
+            Some(list_rows)
+                if list_rows.is_for(&file_list, group_by_day, &self.collapsed_groups) =>
+            {
+                list_rows.clone()
+            }
+            _ => {
+                let list_rows = Arc::new(ListRows::new(
+                    file_list,
+                    group_by_day,
+                    &self.collapsed_groups,
+                ));
+                self.list_rows_cache = Some(list_rows.clone());
+                list_rows
+            }
+        };
+        let file_list = list_rows.file_list.clone();
+        let rows = &list_rows.rows;
+        let groups = &list_rows.groups;
+
+        // Rows are matched by asset id, the same asset can be on a different row after a refresh
+        let selected_index = self
+            .selected
+            .as_ref()
+            .and_then(|name| file_list.iter().position(|asset| &asset.name == name));
+        if selected_index.is_none() && !list_loading {
+            self.selected = None; // The asset is gone or hidden, select something on-screen instead
+        }
+
+        let mut scroll_to: Option<usize> = None; // This is reset every frame, so it doesn't constantly scroll to the same label
+        let mut none_selected: bool = false; // Used to scroll to the first value shown when none is selected
+
+        // Only allow navigation of the user is not renaming
+        if !self.renaming {
+            // If the user presses up, decrement the selected value
+            if ui.input(|i| i.key_pressed(egui::Key::ArrowUp)) {
+                if let Some(selected) = selected_index {
+                    if selected > 0 {
+                        // Check if it is larger than 0 otherwise it'll attempt to select non-existant labels
+                        self.selected = Some(file_list[selected - 1].name.clone());
+                        scroll_to = Some(selected - 1); // This is also set to the same number, allowing for auto scrolling
+                    }
+                } else {
+                    none_selected = true // Select the first visible entry
+                }
+            }
+
+            // If the user presses down, increment the selected value
+            if ui.input(|i| i.key_pressed(egui::Key::ArrowDown)) {
+                if let Some(selected) = selected_index {
+                    if selected < file_list.len() - 1 {
+                        // Stop it from overflowing otherwise it'll attempt to select non-existant labels
+                        self.selected = Some(file_list[selected + 1].name.clone());
+                        scroll_to = Some(selected + 1); // This is also set to the same number, allowing for auto scrolling
+                    }
+                } else {
+                    none_selected = true // Select the first visible entry
+                }
+            }
+
+            // Allow the user to confirm with enter
+            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                if let Some(selected) = selected_index {
+                    // Get file name after getting the selected value
+                    if let Some(asset) = file_list.get(selected) {
+                        double_click(
+                            asset.clone(),
+                            &mut self.swapping,
+                            &mut self.copying,
+                            &mut self.swapping_asset,
+                        );
+                    }
+                }
+            }
+
+            if ui.input(|i| i.modifiers.ctrl && i.key_pressed(egui::Key::E)) {
+                // Ctrl+E (Extract)
+                if let Some(selected) = selected_index {
+                    // Get file name after getting the selected value
+                    if let Some(asset) = file_list.get(selected) {
+                        extract_file_button(asset.clone());
+                    }
+                }
+            }
+        }
+
         let total_rows = if display_image_preview {
             f32::ceil(file_list.len() as f32 / amount_per_row as f32) as usize
         // Show even unfilled rows
         } else {
-            file_list.len()
+            rows.len()
         };
 
-        // let mut table_properties = Vec::new();
-        // table_properties.push(("name", 0.0));
-        // table_properties.push(("size", 0.5));
-        // table_properties.push(("modified", 0.75));
-
-        // if !display_image_preview {
-        //     // Display table headers
-        //     let full_width = ui.available_width();
-        //     let desired_size = egui::vec2(full_width, row_height);
-        //     let rect = ui.allocate_exact_size(desired_size, egui::Sense::hover()).0;
-
-        //     for property in table_properties {
-        //         let size = rect.size();
-        //         println!("{}", property.1size.x);
-        //         let property_rect = egui::Rect::from_min_size(
-        //             rect.min + egui::vec2(property.1size.x, 0.0),
-        //             egui::vec2((1.0-property.1)size.x, size.y)
-        //         );
-
-        //         ui.put(property_rect,
-        //         egui::Label::new(property.0).truncate().selectable(false));
-        //     }
-
-        //     // // Column positions
-        //     // let alias_x = rect.min.x + 5.0;
-        //     // let size_x = rect.min.x + rect.width() * 0.7;
-        //     // let modified_x = rect.min.x + rect.width() * 1.0 - 5.0; // adjust for padding
-
-        //     // // Draw all columns
-        //     // ui.painter().text(
-        //     //     egui::pos2(alias_x+5, rect.min.y),
-        //     //     egui::Align2::LEFT_TOP,
-        //     //     "Name",
-        //     //     egui::TextStyle::Body.resolve(ui.style()),
-        //     //     visuals.text_color(),
-        //     // );
-
-        //     // ui.painter().text(
-        //     //     egui::pos2(size_x+5, rect.min.y),
-        //     //     egui::Align2::LEFT_TOP,
-        //     //     "Size",
-        //     //     egui::TextStyle::Body.resolve(ui.style()),
-        //     //     visuals.text_color(),
-        //     // );
-
-        //     // ui.painter().text(
-        //     //     egui::pos2(modified_x+5, rect.min.y),
-        //     //     egui::Align2::LEFT_TOP,
-        //     //     "Modified",
-        //     //     egui::TextStyle::Body.resolve(ui.style()),
-        //     //     visuals.text_color(),
-        //     // );
-        // }
+        if !display_image_preview {
+            // Display table headers
+            let full_width = ui.available_width();
+            let desired_size = egui::vec2(full_width, row_height);
+            let rect = ui.allocate_exact_size(desired_size, egui::Sense::hover()).0;
+
+            paint_columns(
+                ui,
+                rect,
+                [
+                    locale::get_message(&self.locale, "column-name", None),
+                    locale::get_message(
+                        &self.locale,
+                        if show_thumbnails {
+                            "column-thumbnail"
+                        } else {
+                            "column-format"
+                        },
+                        None,
+                    ),
+                    locale::get_message(&self.locale, "column-size-on-disk", None),
+                    locale::get_message(&self.locale, "column-size-actual", None),
+                ],
+                ui.visuals().strong_text_color(),
+            );
+            ui.separator();
+        }
+
+        let mut file_list_scroll_area = egui::ScrollArea::vertical();
+        if !list_loading {
+            if let Some(name) = self.restore_scroll.take() {
+                let row = if display_image_preview {
+                    file_list
+                        .iter()
+                        .position(|asset| asset.name == name)
+                        .map(|i| i / amount_per_row)
+                } else {
+                    rows.iter().position(
+                        |row| matches!(row, ListRow::Asset(i) if file_list[*i].name == name),
+                    )
+                };
+                // Stays at the top if the asset has gone
+                if let Some(row) = row {
+                    file_list_scroll_area = file_list_scroll_area.vertical_scroll_offset(
+                        row as f32 * (row_height + ui.spacing().item_spacing.y),
+                    );
+                }
+            }
+        }
 
         // File list for assets
-        egui::ScrollArea::vertical().auto_shrink(false).show_rows(
+        let mut top_asset = None;
+        file_list_scroll_area.auto_shrink(false).show_rows(
             ui,
             row_height,
             total_rows,
             |ui, row_range| {
+                top_asset = if display_image_preview {
+                    file_list.get(row_range.start * amount_per_row)
+                } else {
+                    rows[row_range.clone()].iter().find_map(|row| match row {
+                        ListRow::Asset(i) => file_list.get(*i),
+                        ListRow::Header(_) => None,
+                    })
+                }
+                .map(|asset| asset.name.clone());
+
                 if display_image_preview {
                     for row_idx in row_range {
                         ui.horizontal(|ui| {
@@ -806,11 +1705,12 @@ impl FileListUi {
 
                                     let is_selected = if none_selected && i != 0 {
                                         // Selecting the very first causes some issues
-                                        self.selected = Some(i); // If there is none selected, Set selected and return true
+                                        self.selected = Some(file_name.clone()); // If there is none selected, Set selected and return true
                                         none_selected = false; // Will select everything if this is not set to false immediately
                                         true
                                     } else {
-                                        self.selected == Some(i) // Check if this current one is selected
+                                        // Check if this current one is selected
+                                        self.selected.as_ref() == Some(file_name)
                                     };
 
                                     // Draw the text
@@ -823,6 +1723,7 @@ impl FileListUi {
                                             desired_size,
                                             egui::Sense::click(),
                                         );
+                                        let response = response.on_hover_text(&alias);
 
                                         // Only attempt to load if it's a real asset
                                         if asset.from_file | asset.from_sql {
@@ -839,10 +1740,12 @@ impl FileListUi {
                                         let visuals = ui.visuals();
 
                                         // Get colours and handle response
+                                        let highlighted =
+                                            is_selected || self.batch_selection.contains(file_name);
                                         let colours = self.handle_asset_response(
                                             response,
                                             visuals,
-                                            is_selected,
+                                            highlighted,
                                             i,
                                             scroll_to,
                                             &mut navigation_accepted,
@@ -863,7 +1766,7 @@ impl FileListUi {
 
                                         // Draw text ontop of image
                                         let text = egui::Label::new(
-                                            egui::RichText::new(alias)
+                                            egui::RichText::new(logic::display_text(&alias))
                                                 .text_style(egui::TextStyle::Body)
                                                 .color(text_colour),
                                         )
@@ -887,9 +1790,10 @@ impl FileListUi {
                                             egui::Color32::from_rgba_unmultiplied(27, 27, 27, 160)
                                         // Dark mode
                                         } else {
+                                            // Light mode
                                             egui::Color32::from_rgba_unmultiplied(
                                                 248, 248, 248, 160,
-                                            ) // Light mode
+                                            )
                                         };
                                         ui.painter().rect_filled(text_rect, 0.0, background_colour);
 
@@ -900,30 +1804,48 @@ impl FileListUi {
                         });
                     }
                 } else {
-                    for i in row_range {
+                    for row in row_range {
+                        let i = match rows.get(row) {
+                            Some(ListRow::Header(group_index)) => {
+                                let (group, assets) = &groups[*group_index];
+                                self.group_header(ui, row_height, *group, assets, category);
+                                continue;
+                            }
+                            Some(ListRow::Asset(i)) => *i,
+                            None => continue,
+                        };
                         if let Some(asset) = file_list.get(i) {
-                            let alias = config::get_asset_alias(&asset.name);
+                            let full_alias = config::get_asset_alias(&asset.name);
+                            let mut alias = logic::display_text(&full_alias);
                             let is_selected = if none_selected && i != 0 {
-                                self.selected = Some(i);
+                                self.selected = Some(asset.name.clone());
                                 none_selected = false;
                                 true
                             } else {
-                                self.selected == Some(i)
+                                self.selected.as_ref() == Some(&asset.name)
                             };
 
                             if is_selected && self.renaming {
-                                self.handle_text_edit(ui, &alias, &asset.name);
+                                self.handle_text_edit(ui, &full_alias, &asset.name);
                             } else {
                                 let full_width = ui.available_width();
                                 let desired_size = egui::vec2(full_width, row_height);
                                 let (rect, response) =
                                     ui.allocate_exact_size(desired_size, egui::Sense::click());
+                                // Only cut short aliases need a tooltip to show all of it
+                                let response = if alias != full_alias {
+                                    response.on_hover_text(&full_alias)
+                                } else {
+                                    response
+                                };
 
                                 let visuals = ui.visuals();
+                                let highlighted =
+                                    is_selected || self.batch_selection.contains(&asset.name);
                                 let colours = self.handle_asset_response(
                                     response,
                                     visuals,
-                                    is_selected,
+                                    highlighted,
                                     i,
                                     scroll_to,
                                     &mut navigation_accepted,
@@ -936,44 +1858,56 @@ impl FileListUi {
 
                                 ui.painter().rect_filled(rect, 0.0, background_colour);
 
-                                // // Format metadata
-                                // let size = format_size(asset.size);
-                                // let modified = if asset.last_modified.is_some() {
-                                //     format_modified(asset.last_modified.unwrap())
-                                // } else {
-                                //     "".to_string()
-                                // };
-
-                                // Column positions (add padding)
-                                let alias_x = rect.min.x + 5.0;
-                                // let size_x = rect.min.x + rect.width() * 0.7;
-                                // let modified_x = rect.min.x + rect.width() * 1.0 - 5.0; // adjust for padding
-
-                                // Draw all columns
-                                ui.painter().text(
-                                    egui::pos2(alias_x, rect.min.y),
-                                    egui::Align2::LEFT_TOP,
-                                    alias,
-                                    egui::TextStyle::Body.resolve(ui.style()),
+                                // Only show sizes and formats for real assets
+                                let (format, size, actual_size) =
+                                    if asset.from_file | asset.from_sql {
+                                        // The zstd frame didn't declare a size if approximate
+                                        let actual_size = if asset.actual_size_approximate {
+                                            format!("~{}", logic::format_size(asset.actual_size))
+                                        } else {
+                                            logic::format_size(asset.actual_size)
+                                        };
+                                        let format = match (&asset.header, asset.thumbnail()) {
+                                            (_, Some(thumbnail)) if show_thumbnails => {
+                                                thumbnail_label(thumbnail, &self.locale)
+                                            }
+                                            (Some(header), _) => logic::format_header(header),
+                                            (None, _) => locale::get_message(
+                                                &self.locale,
+                                                "format-unknown",
+                                                None,
+                                            ),
+                                        };
+                                        (format, logic::format_size(asset.size), actual_size)
+                                    } else {
+                                        (String::new(), String::new(), String::new())
+                                    };
+
+                                // Badge for assets whose content is gone
+                                if asset.evicted {
+                                    alias = format!(
+                                        "{alias}  [{}]",
+                                        locale::get_message(&self.locale, "evicted-badge", None)
+                                    );
+                                }
+
+                                // Badge for assets RoExtract has written to
+                                if logic::modifications::is_modified(&asset.name) {
+                                    alias = format!(
+                                        "{alias}  [{}]",
+                                        locale::get_message(&self.locale, "modified-badge", None)
+                                    );
+                                }
+
+                                paint_columns(
+                                    ui,
+                                    rect,
+                                    [alias, format, size, actual_size],
                                     text_colour,
                                 );
-
-                                // These are for later, broken rn
-                                //     ui.painter().text(
-                                //         egui::pos2(size_x, rect.min.y),
-                                //         egui::Align2::RIGHT_TOP,
-                                //         size,
-                                //         egui::TextStyle::Body.resolve(ui.style()),
-                                //         text_colour,
-                                //     );
-
-                                //     ui.painter().text(
-                                //         egui::pos2(modified_x, rect.min.y),
-                                //         egui::Align2::RIGHT_TOP,
-                                //         modified,
-                                //         egui::TextStyle::Body.resolve(ui.style()),
-                                //         text_colour,
-                                //     );
+                                if tab == "all" {
+                                    paint_category_chip(ui, rect, asset.category, &self.locale);
+                                }
                             }
                         }
                     }
@@ -981,6 +1915,33 @@ impl FileListUi {
             },
         );
 
+        // Rows that are still streaming in aren't where the user was
+        if !list_loading && self.restore_scroll.is_none() {
+            self.top_asset = top_asset;
+        }
+
+        // Select everything shown between the selected asset and the Shift+clicked one
+        if let Some((start, end)) = self.select_range.take() {
+            let shown: Vec<&AssetInfo> = if display_image_preview {
+                file_list.iter().collect()
+            } else {
+                rows.iter()
+                    .filter_map(|row| match row {
+                        ListRow::Asset(i) => file_list.get(*i),
+                        ListRow::Header(_) => None,
+                    })
+                    .collect()
+            };
+            let start = shown.iter().position(|asset| asset.name == start);
+            let end = shown.iter().position(|asset| asset.name == end);
+            if let (Some(start), Some(end)) = (start, end) {
+                self.batch_selection = shown[start.min(end)..=start.max(end)]
+                    .iter()
+                    .map(|asset| asset.name.clone())
+                    .collect();
+            }
+        }
+
         if !navigation_accepted && scroll_to.is_some() {
             // If the keyboard navigation wasn't accepted and there is keyboard navigation then...
             self.selected = None; // Set the selected to none, so it selects something on-screen
@@ -992,15 +1953,34 @@ impl Default for FileListUi {
     fn default() -> Self {
         Self {
             selected: None,
+            batch_selection: Vec::new(),
+            select_range: None,
+            batch_alias: None,
+            batch_undo: None,
+            top_asset: None,
+            restore_scroll: None,
+            list_generation: 0,
             current_tab: None,
             renaming: false,
             searching: false,
             search_query: "".to_owned(),
+            search_regex: false,
             swapping: false,
             swapping_asset: None,
             locale: locale::get_locale(None),
             asset_context_menu_open: None,
             copying: false,
+            list_loading: false,
+            category_filter: logic::Category::iter().collect(),
+            thumbnails_only: false,
+            last_filter: None,
+            min_size_kib: 0,
+            max_size_kib: 0,
+            newer_than_minutes: 0,
+            collapsed_groups: HashSet::new(),
+            file_list_cache: None,
+            filtered_file_list_cache: None,
+            list_rows_cache: None,
         }
     }
 }
@@ -0,0 +1,199 @@
+use crate::{
+    config, gui, locale,
+    logic::{self, AssetInfo, ExtractedAsset},
+};
+use std::{
+    sync::{LazyLock, Mutex},
+    thread,
+};
+
+const HEX_VIEW_LIMIT: usize = 64 * 1024; // Bytes, the rest of the asset isn't shown
+const BYTES_PER_ROW: usize = 16;
+const WINDOW_KEY: &str = "preview_window"; // Config key for the last size/position
+
+// The preview window is a deferred viewport, it can only reach state through statics
+static PREVIEW_ASSET: LazyLock<Mutex<Option<AssetInfo>>> = LazyLock::new(|| Mutex::new(None));
+static PREVIEW_BYTES: LazyLock<Mutex<Option<ExtractedAsset>>> = LazyLock::new(|| Mutex::new(None));
+// Read from config once when opening, changing the builder every frame would move the window
+static WINDOW_BUILDER: LazyLock<Mutex<egui::ViewportBuilder>> =
+    LazyLock::new(|| Mutex::new(egui::ViewportBuilder::default()));
+
+pub fn open(asset: AssetInfo) {
+    if PREVIEW_ASSET.lock().unwrap().is_none() {
+        let mut builder = egui::ViewportBuilder::default().with_title(locale::get_message(
+            &locale::get_locale(None),
+            "preview",
+            None,
+        ));
+        if let Some((position, size)) = gui::get_window_rect(WINDOW_KEY) {
+            builder = builder.with_position(position).with_inner_size(size);
+        } else {
+            builder = builder.with_inner_size([600.0, 500.0]);
+        }
+        *WINDOW_BUILDER.lock().unwrap() = builder;
+    }
+
+    {
+        *PREVIEW_ASSET.lock().unwrap() = Some(asset.clone());
+        *PREVIEW_BYTES.lock().unwrap() = None;
+    }
+
+    // Reading may be slow on network drives, don't block the UI
+    thread::spawn(move || {
+        let extracted = match logic::extract_asset(&asset) {
+            Ok(extracted) => extracted,
+            Err(e) => {
+                log_error!("Failed to read {} for preview: {}", asset.name, e);
+                ExtractedAsset::default()
+            }
+        };
+
+        // Only show the bytes if the asset is still the one being previewed
+        let current = PREVIEW_ASSET.lock().unwrap().clone();
+        if current.is_some_and(|current| current.name == asset.name) {
+            *PREVIEW_BYTES.lock().unwrap() = Some(extracted);
+        }
+    });
+}
+
+fn close() {
+    *PREVIEW_ASSET.lock().unwrap() = None;
+    *PREVIEW_BYTES.lock().unwrap() = None;
+}
+
+fn hex_row(bytes: &[u8], offset: usize) -> String {
+    let hex: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    let ascii: String = bytes
+        .iter()
+        .map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        })
+        .collect();
+    format!(
+        "{offset:08x}  {:<width$}  {ascii}",
+        hex.join(" "),
+        width = BYTES_PER_ROW * 3 - 1
+    )
+}
+
+fn hex_view(ui: &mut egui::Ui, bytes: &[u8]) {
+    let shown = &bytes[..bytes.len().min(HEX_VIEW_LIMIT)];
+    let rows = shown.len().div_ceil(BYTES_PER_ROW);
+    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+    egui::ScrollArea::both()
+        .id_salt("Hex view")
+        .auto_shrink(false)
+        .show_rows(ui, row_height, rows, |ui, row_range| {
+            for row in row_range {
+                let start = row * BYTES_PER_ROW;
+                let end = (start + BYTES_PER_ROW).min(shown.len());
+                ui.label(egui::RichText::new(hex_row(&shown[start..end], start)).monospace());
+            }
+        });
+}
+
+fn preview_ui(ctx: &egui::Context) {
+    let Some(asset) = PREVIEW_ASSET.lock().unwrap().clone() else {
+        return;
+    };
+    let extracted = PREVIEW_BYTES.lock().unwrap().clone();
+    let locale = locale::get_locale(None);
+
+    egui::CentralPanel::default().show(ctx, |ui| {
+        let alias = config::get_asset_alias(&asset.name);
+        ui.heading(logic::display_text(&alias)).on_hover_text(alias);
+
+        let mut note = logic::notes::get_note(&asset.name);
+        if ui
+            .add(
+                egui::TextEdit::multiline(&mut note)
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY)
+                    .char_limit(logic::notes::MAX_NOTE_LENGTH)
+                    .hint_text(locale::get_message(&locale, "input-note", None)),
+            )
+            .changed()
+        {
+            logic::notes::set_note(&asset.name, &note);
+        }
+
+        let Some(extracted) = extracted else {
+            ui.spinner();
+            ctx.request_repaint(); // Keep checking until the bytes are loaded
+            return;
+        };
+        let bytes = extracted.bytes;
+
+        let mut args = fluent_bundle::FluentArgs::new();
+        args.set(
+            "format",
+            match &extracted.header {
+                Some(header) => logic::format_header(header),
+                None => locale::get_message(&locale, "format-unknown", None),
+            },
+        );
+        let key = match extracted.compression {
+            Some(compression) => {
+                args.set("compression", compression.name());
+                "preview-format-compressed"
+            }
+            None => "preview-format",
+        };
+        ui.label(locale::get_message(&locale, key, Some(&args)));
+
+        if logic::determine_category(&bytes) == logic::Category::Images {
+            // Same context as the main window, so the texture cache is shared
+            match gui::load_image(&asset.name, &bytes, ctx.clone()) {
+                Ok(texture) => {
+                    ui.add(egui::Image::new(&texture).max_height(ui.available_height() / 2.0));
+                }
+                Err(e) => {
+                    ui.label(e.to_string());
+                }
+            }
+            ui.separator();
+        }
+
+        if bytes.len() > HEX_VIEW_LIMIT {
+            let mut args = fluent_bundle::FluentArgs::new();
+            args.set("shown", logic::format_size(HEX_VIEW_LIMIT as u64));
+            args.set("total", logic::format_size(bytes.len() as u64));
+            ui.label(locale::get_message(
+                &locale,
+                "hex-view-truncated",
+                Some(&args),
+            ));
+        }
+        hex_view(ui, &bytes);
+    });
+
+    // Remember where the window was for next time
+    let (outer_rect, inner_rect) =
+        ctx.input(|i| (i.viewport().outer_rect, i.viewport().inner_rect));
+    if let (Some(outer_rect), Some(inner_rect)) = (outer_rect, inner_rect) {
+        gui::set_window_rect(WINDOW_KEY, outer_rect.min, inner_rect.size());
+    }
+
+    if ctx.input(|i| i.viewport().close_requested()) {
+        close();
+    }
+}
+
+// Called every frame by the main window, closing the main window closes this too
+pub fn show(ctx: &egui::Context) {
+    if PREVIEW_ASSET.lock().unwrap().is_none() {
+        return;
+    }
+
+    let builder = WINDOW_BUILDER.lock().unwrap().clone();
+    ctx.show_viewport_deferred(
+        egui::ViewportId::from_hash_of(WINDOW_KEY),
+        builder,
+        |ctx, _class| preview_ui(ctx),
+    );
+}
@@ -1,87 +1,313 @@
-use crate::{config, locale, logic};
+use crate::{config, gui::dialog, locale, logic};
 use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use native_dialog::{DialogBuilder, MessageLevel};
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex},
+    time::{Duration, Instant},
+};
 
-pub fn actions(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
-    ui.separator();
-    ui.heading(locale::get_message(locale, "actions", None));
+static HISTORY_QUERY: LazyLock<Mutex<String>> = LazyLock::new(|| Mutex::new(String::new()));
+// Searched again only when the query or the history changes, with the history's generation
+type HistoryResults = (String, u64, Vec<logic::history::HistoryEntry>);
+static HISTORY_RESULTS: LazyLock<Mutex<Option<HistoryResults>>> =
+    LazyLock::new(|| Mutex::new(None));
+static ORPHANED_NOTES: LazyLock<Mutex<Option<Vec<String>>>> = LazyLock::new(|| Mutex::new(None)); // None until searched for
+static DATABASE_INFO: LazyLock<Mutex<Option<(Instant, logic::sql_database::DatabaseInfo)>>> =
+    LazyLock::new(|| Mutex::new(None));
+type TempSpace = (Instant, u64, Option<u64>); // When it was measured, usage and available space
+static TEMP_SPACE: LazyLock<Mutex<Option<TempSpace>>> = LazyLock::new(|| Mutex::new(None));
 
-    // Clear cache description
-    ui.label(locale::get_message(locale, "clear-cache-description", None));
-
-    // Clear cache button
-    if ui
-        .button(locale::get_message(locale, "button-clear-cache", None))
-        .clicked()
-        || ui.input(|i| i.key_pressed(egui::Key::Delete))
-    {
-        // Confirmation dialog
-        let yes = DialogBuilder::message()
-            .set_level(MessageLevel::Info)
+// Call from a dialog thread, the button is disabled in read-only mode so only refusals are shown
+pub fn show_clear_error(
+    error: logic::clear::ClearError,
+    locale: &FluentBundle<Arc<FluentResource>>,
+) {
+    log_error!("{}", error);
+    if let logic::clear::ClearError::UnsafePath(path, reason) = error {
+        let mut args = FluentArgs::new();
+        args.set("path", path.display().to_string());
+        args.set("reason", reason);
+        DialogBuilder::message()
+            .set_level(MessageLevel::Error)
             .set_title(locale::get_message(
                 locale,
-                "confirmation-clear-cache-title",
+                "error-clear-refused-title",
                 None,
             ))
             .set_text(locale::get_message(
                 locale,
-                "confirmation-clear-cache-description",
-                None,
+                "error-clear-refused-description",
+                Some(&args),
             ))
-            .confirm()
+            .alert()
             .show()
             .unwrap();
+    }
+}
 
-        if yes {
-            logic::clear_cache();
-        }
+pub fn actions(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.heading(locale::get_message(locale, "actions", None));
+
+    let dialog_open = dialog::is_pending();
+
+    // Nothing in the cache or database can be changed while this is on
+    let mut read_only = logic::read_only::is_enabled();
+    ui.label(locale::get_message(locale, "read-only-description", None));
+    if ui
+        .checkbox(
+            &mut read_only,
+            locale::get_message(locale, "checkbox-read-only", None),
+        )
+        .changed()
+    {
+        config::set_config_value("read_only", read_only.into());
+        logic::read_only::set_enabled(read_only);
+        log_info!("Read-only mode {}", if read_only { "on" } else { "off" });
+        database_changed(); // Opened again with the right flags
     }
+    let read_only_tooltip = locale::get_message(locale, "read-only-tooltip", None);
 
-    // Extract all description
-    ui.label(locale::get_message(locale, "extract-all-description", None));
+    // Clear cache description
+    ui.label(locale::get_message(locale, "clear-cache-description", None));
 
-    // Extract all button
+    // Clear cache button
     if ui
-        .button(locale::get_message(locale, "button-extract-all", None))
+        .add_enabled(
+            !dialog_open && !read_only,
+            egui::Button::new(locale::get_message(locale, "button-clear-cache", None)),
+        )
+        .on_disabled_hover_text(&read_only_tooltip)
         .clicked()
-        || ui.input(|i| i.key_pressed(egui::Key::F3))
+        || ui.input(|i| i.key_pressed(egui::Key::Delete)) && !read_only
     {
-        let mut no = logic::get_list_task_running();
-
-        // Confirmation dialog, the program is still listing files
-        if no {
-            // NOT result, will become false if user clicks yes
-            no = !DialogBuilder::message()
+        dialog::spawn("clear-cache", || {
+            let locale = locale::get_locale(None);
+            // Confirmation dialog
+            let yes = DialogBuilder::message()
                 .set_level(MessageLevel::Info)
                 .set_title(locale::get_message(
-                    locale,
-                    "confirmation-filter-confirmation-title",
+                    &locale,
+                    "confirmation-clear-cache-title",
                     None,
                 ))
                 .set_text(locale::get_message(
-                    locale,
-                    "confirmation-filter-confirmation-description",
+                    &locale,
+                    "confirmation-clear-cache-description",
                     None,
                 ))
                 .confirm()
                 .show()
                 .unwrap();
+
+            if yes {
+                if let Err(e) = logic::clear_cache() {
+                    show_clear_error(e, &locale);
+                }
+            }
+        });
+    }
+
+    // Extract all description
+    ui.label(locale::get_message(locale, "extract-all-description", None));
+
+    // Extract all button
+    if ui
+        .add_enabled(
+            !dialog_open,
+            egui::Button::new(locale::get_message(locale, "button-extract-all", None)),
+        )
+        .clicked()
+        || ui.input(|i| i.key_pressed(egui::Key::F3))
+    {
+        dialog::spawn("extract-all", || {
+            let locale = locale::get_locale(None);
+            let mut no = logic::get_list_task_running();
+
+            // Confirmation dialog, the program is still listing files
+            if no {
+                // NOT result, will become false if user clicks yes
+                no = !DialogBuilder::message()
+                    .set_level(MessageLevel::Info)
+                    .set_title(locale::get_message(
+                        &locale,
+                        "confirmation-filter-confirmation-title",
+                        None,
+                    ))
+                    .set_text(locale::get_message(
+                        &locale,
+                        "confirmation-filter-confirmation-description",
+                        None,
+                    ))
+                    .confirm()
+                    .show()
+                    .unwrap();
+            }
+
+            // The user either agreed or the program is not listing files
+            if !no {
+                let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+
+                // If the user provides a directory, the program will extract the assets to that directory
+                if let Some(path) = option_path {
+                    // The tab that's open is already listed, unless it's to be refreshed first
+                    let refresh =
+                        config::get_config_bool("refresh_before_extract").unwrap_or(false);
+                    let overrides = logic::extract_options::ExtractOverrides {
+                        refresh: Some(refresh),
+                        ..Default::default()
+                    };
+                    logic::extract_all(path, false, overrides);
+                }
+            }
+        });
+    }
+
+    // Force rescan description
+    ui.label(locale::get_message(
+        locale,
+        "force-rescan-description",
+        None,
+    ));
+
+    // Force rescan button, listing reads every asset again instead of using the scan index
+    if ui
+        .add_enabled(
+            !read_only,
+            egui::Button::new(locale::get_message(locale, "button-force-rescan", None)),
+        )
+        .on_disabled_hover_text(&read_only_tooltip)
+        .clicked()
+    {
+        if let Err(e) = logic::scan_index::clear() {
+            log_error!("Failed to clear the scan index: {}", e);
         }
+        logic::refresh(logic::get_list_category(), false, false);
+    }
 
-        // The user either agreed or the program is not listing files
-        if !no {
-            let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+    // Import swap pack description
+    ui.label(locale::get_message(
+        locale,
+        "import-swap-pack-description",
+        None,
+    ));
 
-            // If the user provides a directory, the program will extract the assets to that directory
-            if let Some(path) = option_path {
-                logic::extract_all(
-                    path,
-                    false,
-                    config::get_config_bool("use_alias").unwrap_or(false),
-                )
+    // Import swap pack button
+    if ui
+        .add_enabled(
+            !dialog_open && !read_only,
+            egui::Button::new(locale::get_message(locale, "button-import-swap-pack", None)),
+        )
+        .on_disabled_hover_text(&read_only_tooltip)
+        .clicked()
+    {
+        dialog::spawn("import-swap-pack", || {
+            let locale = locale::get_locale(None);
+            let mut warning_acknowledged =
+                config::get_config_bool("ban-warning-ack").unwrap_or(false);
+
+            if !warning_acknowledged {
+                warning_acknowledged = DialogBuilder::message()
+                    .set_level(MessageLevel::Info)
+                    .set_title(locale::get_message(
+                        &locale,
+                        "confirmation-ban-warning-title",
+                        None,
+                    ))
+                    .set_text(locale::get_message(
+                        &locale,
+                        "confirmation-ban-warning-description",
+                        None,
+                    ))
+                    .confirm()
+                    .show()
+                    .unwrap();
+            }
+
+            if warning_acknowledged {
+                config::set_config_value("ban-warning-ack", warning_acknowledged.into());
+
+                let option_path = DialogBuilder::file()
+                    .add_filter("JSON", ["json"])
+                    .open_single_file()
+                    .show()
+                    .unwrap();
+
+                if let Some(path) = option_path {
+                    import_swap_pack(path, &locale);
+                }
             }
+        });
+    }
+}
+
+fn import_swap_pack(path: PathBuf, locale: &FluentBundle<Arc<FluentResource>>) {
+    let pack = match logic::swap_pack::read_swap_pack(&path) {
+        Ok(pack) => pack,
+        Err(e) => {
+            log_error!("Failed to read swap pack: {}", e);
+            let mut args = FluentArgs::new();
+            args.set("error", e);
+            logic::update_status(locale::get_message(
+                locale,
+                "error-reading-swap-pack",
+                Some(&args),
+            ));
+            return;
         }
+    };
+
+    // Runs on the dialog thread, so checking the pack (which may hash the whole cache) doesn't freeze the UI
+    logic::update_status(locale::get_message(locale, "checking-swap-pack", None));
+
+    let report = logic::swap_pack::preflight(&pack);
+
+    let mut args = FluentArgs::new();
+    args.set("resolvable", report.resolved.len());
+    args.set("missing", report.missing.len());
+    args.set("mismatches", report.incompatible.len());
+    args.set("hash_matched", report.matched_by_hash);
+
+    for id in &report.missing {
+        log_warn!("Swap pack asset not found: {}", id);
+    }
+    for swap in &report.incompatible {
+        log_warn!("Swap pack swap looks incompatible: {}", swap);
+    }
+
+    logic::update_status(locale::get_message(locale, "idling", None));
+
+    if report.resolved.is_empty() {
+        DialogBuilder::message()
+            .set_level(MessageLevel::Info)
+            .set_title(locale::get_message(locale, "swap-pack-title", None))
+            .set_text(locale::get_message(
+                locale,
+                "swap-pack-nothing-to-apply",
+                Some(&args),
+            ))
+            .alert()
+            .show()
+            .unwrap();
+        return;
+    }
+
+    // Nothing is written until the user confirms
+    let yes = DialogBuilder::message()
+        .set_level(MessageLevel::Info)
+        .set_title(locale::get_message(locale, "swap-pack-title", None))
+        .set_text(locale::get_message(
+            locale,
+            "confirmation-swap-pack-description",
+            Some(&args),
+        ))
+        .confirm()
+        .show()
+        .unwrap();
+
+    if yes && logic::swap_pack::apply(report.resolved).is_err() {
+        logic::update_status(locale::get_message(locale, "error-read-only", None));
     }
 }
 
@@ -103,42 +329,51 @@ pub fn cache_dir_management(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentR
 
     ui.label(locale::get_message(locale, "cache-directory", Some(&args)));
 
+    let dialog_open = dialog::is_pending();
     ui.horizontal(|ui| {
         if ui
-            .button(locale::get_message(locale, "button-change-cache-dir", None))
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(locale, "button-change-cache-dir", None)),
+            )
             .clicked()
         {
-            let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
-
-            // If the user provides a directory, the program will change the cache directory to the new one
-            if let Some(path) = option_path {
-                // Validation checks
-                match logic::cache_directory::validate_directory(path.to_string_lossy().as_ref()) {
-                    Ok(directory) => {
-                        config::set_config_value("cache_directory", directory.into());
-                        logic::cache_directory::set_cache_directory(
-                            logic::cache_directory::detect_directory(),
-                        ); // Set directory to new one
-                    }
-                    Err(_) => {
-                        DialogBuilder::message()
-                            .set_level(MessageLevel::Info)
-                            .set_title(locale::get_message(
-                                locale,
-                                "error-invalid-directory-title",
-                                None,
-                            ))
-                            .set_text(locale::get_message(
-                                locale,
-                                "error-invalid-directory-description",
-                                None,
-                            ))
-                            .alert()
-                            .show()
-                            .unwrap();
+            dialog::spawn("change-cache-dir", || {
+                let locale = locale::get_locale(None);
+                let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+
+                // If the user provides a directory, the program will change the cache directory to the new one
+                if let Some(path) = option_path {
+                    // Validation checks
+                    match logic::cache_directory::validate_directory(
+                        path.to_string_lossy().as_ref(),
+                    ) {
+                        Ok(directory) => {
+                            config::set_config_value("cache_directory", directory.into());
+                            logic::cache_directory::set_cache_directory(
+                                logic::cache_directory::detect_directory(),
+                            ); // Set directory to new one
+                        }
+                        Err(_) => {
+                            DialogBuilder::message()
+                                .set_level(MessageLevel::Info)
+                                .set_title(locale::get_message(
+                                    &locale,
+                                    "error-invalid-directory-title",
+                                    None,
+                                ))
+                                .set_text(locale::get_message(
+                                    &locale,
+                                    "error-invalid-directory-description",
+                                    None,
+                                ))
+                                .alert()
+                                .show()
+                                .unwrap();
+                        }
                     }
                 }
-            }
+            });
         }
         if ui
             .button(locale::get_message(locale, "button-reset-cache-dir", None))
@@ -151,7 +386,87 @@ pub fn cache_dir_management(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentR
     });
 }
 
+// Where the Music tab lists from, usually /sounds inside the cache directory
+pub fn sounds_dir_management(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.label(locale::get_message(
+        locale,
+        "custom-sounds-dir-description",
+        None,
+    ));
+
+    let mut args = FluentArgs::new();
+    match logic::cache_directory::get_sounds_directory() {
+        Some(directory) => {
+            args.set("directory", directory.to_string_lossy().to_string());
+            ui.label(locale::get_message(locale, "sounds-directory", Some(&args)));
+        }
+        None => {
+            args.set(
+                "directory",
+                logic::cache_directory::expected_sounds_directory()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            ui.colored_label(
+                ui.visuals().warn_fg_color,
+                locale::get_message(locale, "sounds-directory-missing", Some(&args)),
+            );
+        }
+    }
+
+    let dialog_open = dialog::is_pending();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(
+                    locale,
+                    "button-change-sounds-dir",
+                    None,
+                )),
+            )
+            .clicked()
+        {
+            dialog::spawn("change-sounds-dir", || {
+                let option_path = DialogBuilder::file().open_single_dir().show().unwrap();
+                if let Some(path) = option_path {
+                    config::set_config_value(
+                        "sounds_directory",
+                        path.to_string_lossy().to_string().into(),
+                    );
+                    sounds_directory_changed();
+                }
+            });
+        }
+        if ui
+            .button(locale::get_message(locale, "button-reset-sounds-dir", None))
+            .clicked()
+        {
+            config::remove_config_value("sounds_directory");
+            sounds_directory_changed();
+        }
+    });
+}
+
+fn sounds_directory_changed() {
+    logic::cache_directory::reset_sounds_directory();
+    if logic::get_list_category() == logic::Category::Music {
+        logic::refresh(logic::Category::Music, false, false);
+    }
+}
+
+// Changing the database switches what every tab lists, so the current one is listed again
+pub fn database_changed() {
+    config::save_config_file(); // Keep the new path even if RoExtract doesn't close cleanly
+    let _ = logic::sql_database::reset_database();
+    *DATABASE_INFO.lock().unwrap() = None;
+    logic::refresh(logic::get_list_category(), false, false);
+}
+
 pub fn sql_db_management(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    use logic::sql_database::ConnectionState;
+
     ui.separator();
     ui.label(locale::get_message(
         locale,
@@ -159,61 +474,783 @@ pub fn sql_db_management(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentReso
         None,
     ));
 
+    // Counting rows every frame would be slow, and the database is locked while it's listed
+    let info = {
+        let mut cached = DATABASE_INFO.lock().unwrap();
+        if cached
+            .as_ref()
+            .is_none_or(|(updated, _)| updated.elapsed() > Duration::from_secs(5))
+        {
+            if let Some(info) = logic::sql_database::info() {
+                *cached = Some((Instant::now(), info));
+            }
+        }
+        cached.as_ref().map(|(_, info)| info.clone())
+    };
+
+    if let Some(info) = &info {
+        let mut args = FluentArgs::new();
+        args.set(
+            "path",
+            info.path.clone().unwrap_or("No database".to_string()),
+        );
+
+        ui.label(locale::get_message(locale, "sql-database", Some(&args)));
+
+        let state = match info.state {
+            ConnectionState::ReadWrite => "sql-database-read-write",
+            ConnectionState::ReadOnly => "sql-database-read-only",
+            ConnectionState::Disconnected => "sql-database-disconnected",
+        };
+        let mut args = FluentArgs::new();
+        args.set("state", locale::get_message(locale, state, None));
+        match info.rows {
+            Some(rows) => args.set("rows", rows),
+            None => args.set("rows", "?"),
+        }
+        args.set(
+            "size",
+            info.size.map(logic::format_size).unwrap_or("?".to_string()),
+        );
+        ui.label(locale::get_message(
+            locale,
+            "sql-database-info",
+            Some(&args),
+        ));
+
+        // Say why no database could be opened
+        if info.state == ConnectionState::Disconnected {
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                locale::get_message(locale, "error-sql-detection-details", None),
+            );
+            for error in logic::sql_database::get_detection_errors() {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        }
+    } else {
+        // Listed before it could be read once
+        ui.label(locale::get_message(locale, "database-busy", None));
+    }
+
+    let dialog_open = dialog::is_pending();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(locale, "button-change-sql-db", None)),
+            )
+            .clicked()
+        {
+            dialog::spawn("change-sql-db", || {
+                let locale = locale::get_locale(None);
+                let option_path = DialogBuilder::file()
+                    .add_filter("SQLite", ["db"])
+                    .open_single_file()
+                    .show()
+                    .unwrap();
+
+                // If the user provides a path, the program will change the SQL database to the new one
+                if let Some(path) = option_path {
+                    // Validation checks
+                    match logic::sql_database::validate_file(path.to_string_lossy().as_ref()) {
+                        Ok(directory) => {
+                            config::set_config_value("sql_database", directory.into());
+
+                            // Close current db and open new one
+                            database_changed();
+                        }
+                        Err(_) => {
+                            DialogBuilder::message()
+                                .set_level(MessageLevel::Info)
+                                .set_title(locale::get_message(
+                                    &locale,
+                                    "error-invalid-database-title",
+                                    None,
+                                ))
+                                .set_text(locale::get_message(
+                                    &locale,
+                                    "error-invalid-database-description",
+                                    None,
+                                ))
+                                .alert()
+                                .show()
+                                .unwrap();
+                        }
+                    }
+                }
+            });
+        }
+        if ui
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(locale, "button-reset-sql-db", None)),
+            )
+            .clicked()
+        {
+            // Detection shows dialogs if no default database is found
+            dialog::spawn("reset-sql-db", || {
+                config::remove_config_value("sql_database"); // Clear db in config
+
+                // Close current db and open new one
+                database_changed();
+            });
+        }
+    });
+}
+
+// Shows how much RoExtract has in the temp directory, large files go to the fallback directory instead
+pub fn temp_directory(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.label(locale::get_message(
+        locale,
+        "temp-directory-description",
+        None,
+    ));
+
+    let temp_dir = logic::get_temp_dir();
+    // Walking the temp directory every frame would be slow
+    let (usage, available) = {
+        let mut cached = TEMP_SPACE.lock().unwrap();
+        match *cached {
+            Some((updated, usage, available)) if updated.elapsed() <= Duration::from_secs(5) => {
+                (usage, available)
+            }
+            _ => {
+                let usage = logic::temp_space::usage();
+                let available = logic::temp_space::available_space(&temp_dir);
+                *cached = Some((Instant::now(), usage, available));
+                (usage, available)
+            }
+        }
+    };
     let mut args = FluentArgs::new();
+    args.set("directory", temp_dir.to_string_lossy().to_string());
+    args.set("usage", logic::format_size(usage));
     args.set(
-        "path",
-        logic::sql_database::get_db_path().unwrap_or("No database".to_string()),
+        "available",
+        available
+            .map(logic::format_size)
+            .unwrap_or_else(|| locale::get_message(locale, "format-unknown", None)),
     );
+    ui.label(locale::get_message(
+        locale,
+        "temp-directory-usage",
+        Some(&args),
+    ));
 
-    ui.label(locale::get_message(locale, "sql-database", Some(&args)));
+    let mut args = FluentArgs::new();
+    args.set(
+        "directory",
+        logic::temp_space::get_fallback_directory()
+            .map(|directory| directory.to_string_lossy().to_string())
+            .unwrap_or_else(|| locale::get_message(locale, "format-unknown", None)),
+    );
+    ui.label(locale::get_message(
+        locale,
+        "temp-fallback-directory",
+        Some(&args),
+    ));
 
+    let dialog_open = dialog::is_pending();
     ui.horizontal(|ui| {
         if ui
-            .button(locale::get_message(locale, "button-change-sql-db", None))
+            .add_enabled(
+                !dialog_open,
+                egui::Button::new(locale::get_message(
+                    locale,
+                    "button-change-temp-fallback-dir",
+                    None,
+                )),
+            )
             .clicked()
         {
-            let option_path = DialogBuilder::file().open_single_file().show().unwrap();
+            dialog::spawn("change-temp-fallback-dir", || {
+                if let Some(path) = DialogBuilder::file().open_single_dir().show().unwrap() {
+                    config::set_config_value(
+                        "temp_fallback_directory",
+                        path.to_string_lossy().to_string().into(),
+                    );
+                }
+            });
+        }
+        if ui
+            .button(locale::get_message(
+                locale,
+                "button-reset-temp-fallback-dir",
+                None,
+            ))
+            .clicked()
+        {
+            config::remove_config_value("temp_fallback_directory");
+        }
+    });
+}
 
-            // If the user provides a path, the program will change the SQL database to the new one
-            if let Some(path) = option_path {
-                // Validation checks
-                match logic::sql_database::validate_file(path.to_string_lossy().as_ref()) {
-                    Ok(directory) => {
-                        config::set_config_value("sql_database", directory.into());
+pub fn health_check(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    use logic::health_check::{self, CheckState};
 
-                        // Close current db and open new one
-                        let _ = logic::sql_database::reset_database();
-                    }
-                    Err(_) => {
-                        DialogBuilder::message()
-                            .set_level(MessageLevel::Info)
-                            .set_title(locale::get_message(
-                                locale,
-                                "error-invalid-database-title",
-                                None,
-                            ))
-                            .set_text(locale::get_message(
-                                locale,
-                                "error-invalid-database-description",
-                                None,
-                            ))
-                            .alert()
-                            .show()
-                            .unwrap();
-                    }
+    ui.separator();
+    ui.heading(locale::get_message(locale, "health-check", None));
+    ui.label(locale::get_message(
+        locale,
+        "health-check-description",
+        None,
+    ));
+
+    let running = health_check::is_running();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                !running,
+                egui::Button::new(locale::get_message(locale, "button-run-health-check", None)),
+            )
+            .clicked()
+        {
+            health_check::run();
+        }
+        if running {
+            ui.spinner();
+            ui.ctx().request_repaint(); // Show results as they come in
+        }
+    });
+
+    for result in health_check::get_results() {
+        let (icon, colour) = match result.state {
+            CheckState::Pass => ("✔", egui::Color32::from_rgb(102, 187, 106)),
+            CheckState::Warn => ("⚠", ui.visuals().warn_fg_color),
+            CheckState::Fail => ("✖", ui.visuals().error_fg_color),
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.colored_label(colour, icon);
+            ui.strong(&result.title);
+            ui.label(&result.message);
+        });
+        if let Some(action) = &result.action {
+            ui.label(action);
+        }
+        if let Some(fix) = &result.fix {
+            let path = match fix {
+                health_check::Fix::CacheDirectory(path) | health_check::Fix::Database(path) => {
+                    path.clone()
+                }
+            };
+            let mut args = FluentArgs::new();
+            args.set("path", path);
+            if ui
+                .add_enabled(
+                    !running,
+                    egui::Button::new(locale::get_message(
+                        locale,
+                        "button-use-detected",
+                        Some(&args),
+                    )),
+                )
+                .clicked()
+            {
+                fix.apply();
+            }
+        }
+    }
+}
+
+pub fn extract_options(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    use logic::extract_options::{self, Layout, Naming, OverwritePolicy, ResizeFilter};
+    use strum::IntoEnumIterator;
+
+    ui.separator();
+    ui.heading(locale::get_message(locale, "extract-options", None));
+    ui.label(locale::get_message(
+        locale,
+        "extract-options-description",
+        None,
+    ));
+
+    egui::Grid::new("Extract options")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("");
+            ui.strong(locale::get_message(locale, "option-naming", None));
+            ui.strong(locale::get_message(locale, "option-dedupe", None));
+            ui.strong(locale::get_message(locale, "option-layout", None));
+            ui.end_row();
+
+            for category in logic::Category::iter() {
+                let mut options = extract_options::get_options(category);
+                let old_options = options;
+
+                ui.label(locale::get_message(locale, category.locale_key(), None));
+
+                egui::ComboBox::from_id_salt(format!("Naming {category}"))
+                    .selected_text(locale::get_message(
+                        locale,
+                        &format!("naming-{}", options.naming),
+                        None,
+                    ))
+                    .show_ui(ui, |ui| {
+                        for naming in Naming::iter() {
+                            ui.selectable_value(
+                                &mut options.naming,
+                                naming,
+                                locale::get_message(locale, &format!("naming-{naming}"), None),
+                            );
+                        }
+                    });
+
+                ui.checkbox(&mut options.dedupe, "");
+
+                egui::ComboBox::from_id_salt(format!("Layout {category}"))
+                    .selected_text(locale::get_message(
+                        locale,
+                        &format!("layout-{}", options.layout),
+                        None,
+                    ))
+                    .show_ui(ui, |ui| {
+                        for layout in Layout::iter() {
+                            ui.selectable_value(
+                                &mut options.layout,
+                                layout,
+                                locale::get_message(locale, &format!("layout-{layout}"), None),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                // Only store categories the user has changed, the rest follow the global settings
+                if options != old_options {
+                    extract_options::set_options(category, options);
                 }
             }
+        });
+
+    ui.label(locale::get_message(
+        locale,
+        "extract-template-description",
+        None,
+    ));
+    let mut template = extract_options::get_template();
+    if ui
+        .add(
+            egui::TextEdit::singleline(&mut template)
+                .hint_text("{category}/{date:%Y-%m}/{alias}-{hash8}.{ext}"),
+        )
+        .changed()
+    {
+        extract_options::set_template(&template);
+    }
+
+    // Extraction refuses to start with an invalid template, show why straight away
+    if !template.is_empty() {
+        if let Err(e) = logic::template::Template::parse(&template) {
+            let mut args = FluentArgs::new();
+            args.set("error", e);
+            ui.colored_label(
+                ui.visuals().error_fg_color,
+                locale::get_message(locale, "error-invalid-template", Some(&args)),
+            );
+        }
+    }
+
+    ui.horizontal(|ui| {
+        let mut overwrite = extract_options::get_overwrite_policy(&Default::default());
+        let old_overwrite = overwrite;
+        ui.label(locale::get_message(locale, "overwrite-policy", None));
+        egui::ComboBox::from_id_salt("Overwrite policy")
+            .selected_text(locale::get_message(
+                locale,
+                &format!("overwrite-{overwrite}"),
+                None,
+            ))
+            .show_ui(ui, |ui| {
+                for option in OverwritePolicy::iter() {
+                    ui.selectable_value(
+                        &mut overwrite,
+                        option,
+                        locale::get_message(locale, &format!("overwrite-{option}"), None),
+                    );
+                }
+            });
+        if overwrite != old_overwrite {
+            config::set_config_value("overwrite_policy", serde_json::json!(overwrite));
         }
+    });
+
+    let mut incremental_extract = config::get_config_bool("incremental_extract").unwrap_or(false);
+    ui.checkbox(
+        &mut incremental_extract,
+        locale::get_message(locale, "incremental-extract", None),
+    );
+    config::set_config_value("incremental_extract", incremental_extract.into());
+
+    let mut export_manifest = config::get_config_bool("export_manifest").unwrap_or(false);
+    ui.checkbox(
+        &mut export_manifest,
+        locale::get_message(locale, "export-manifest", None),
+    );
+    config::set_config_value("export_manifest", export_manifest.into());
+
+    let mut verify_on_extract = config::get_config_bool("verify_on_extract").unwrap_or(true);
+    ui.checkbox(
+        &mut verify_on_extract,
+        locale::get_message(locale, "verify-on-extract", None),
+    );
+    config::set_config_value("verify_on_extract", verify_on_extract.into());
+
+    let mut export_playlist = config::get_config_bool("export_playlist").unwrap_or(false);
+    ui.checkbox(
+        &mut export_playlist,
+        locale::get_message(locale, "export-playlist", None),
+    );
+    config::set_config_value("export_playlist", export_playlist.into());
+
+    if export_playlist {
+        let mut playlist_sort_by_alias =
+            config::get_config_bool("playlist_sort_by_alias").unwrap_or(false);
+        ui.checkbox(
+            &mut playlist_sort_by_alias,
+            locale::get_message(locale, "playlist-sort-by-alias", None),
+        );
+        config::set_config_value("playlist_sort_by_alias", playlist_sort_by_alias.into());
+    }
+
+    ui.label(locale::get_message(
+        locale,
+        "image-resize-description",
+        None,
+    ));
+    ui.horizontal(|ui| {
+        let mut max_dimension = config::get_config_u64("image_max_dimension").unwrap_or(0);
+        ui.label(locale::get_message(locale, "image-max-dimension", None));
         if ui
-            .button(locale::get_message(locale, "button-reset-sql-db", None))
+            .add(egui::DragValue::new(&mut max_dimension).range(0..=16384))
+            .changed()
+        {
+            config::set_config_value("image_max_dimension", max_dimension.into());
+        }
+
+        let mut filter = extract_options::get_resize_filter();
+        let old_filter = filter;
+        ui.label(locale::get_message(locale, "image-resize-filter", None));
+        egui::ComboBox::from_id_salt("Resize filter")
+            .selected_text(locale::get_message(
+                locale,
+                &format!("resize-filter-{filter}"),
+                None,
+            ))
+            .show_ui(ui, |ui| {
+                for option in ResizeFilter::iter() {
+                    ui.selectable_value(
+                        &mut filter,
+                        option,
+                        locale::get_message(locale, &format!("resize-filter-{option}"), None),
+                    );
+                }
+            });
+        if filter != old_filter {
+            config::set_config_value("image_resize_filter", serde_json::json!(filter));
+        }
+    });
+
+    let mut power_of_two = config::get_config_bool("image_power_of_two").unwrap_or(false);
+    ui.checkbox(
+        &mut power_of_two,
+        locale::get_message(locale, "image-power-of-two", None),
+    );
+    config::set_config_value("image_power_of_two", power_of_two.into());
+
+    let mut convert_images_to_png =
+        config::get_config_bool("convert_images_to_png").unwrap_or(false);
+    ui.checkbox(
+        &mut convert_images_to_png,
+        locale::get_message(locale, "convert-images-to-png", None),
+    );
+    config::set_config_value("convert_images_to_png", convert_images_to_png.into());
+
+    let mut convert_audio_to_wav = config::get_config_bool("convert_audio_to_wav").unwrap_or(false);
+    ui.checkbox(
+        &mut convert_audio_to_wav,
+        locale::get_message(locale, "convert-audio-to-wav", None),
+    );
+    config::set_config_value("convert_audio_to_wav", convert_audio_to_wav.into());
+
+    // Only done to the samples while converting
+    if convert_audio_to_wav {
+        let mut trim_silence = config::get_config_bool("trim_silence").unwrap_or(false);
+        ui.checkbox(
+            &mut trim_silence,
+            locale::get_message(locale, "trim-silence", None),
+        );
+        config::set_config_value("trim_silence", trim_silence.into());
+
+        let mut normalize_audio = config::get_config_bool("normalize_audio").unwrap_or(false);
+        ui.checkbox(
+            &mut normalize_audio,
+            locale::get_message(locale, "normalize-audio", None),
+        );
+        config::set_config_value("normalize_audio", normalize_audio.into());
+    }
+
+    ui.label(locale::get_message(
+        locale,
+        "contact-sheet-description",
+        None,
+    ));
+    ui.horizontal(|ui| {
+        for (key, setting, default, range) in [
+            ("contact-sheet-columns", "contact_sheet_columns", 8, 1..=64),
+            ("contact-sheet-rows", "contact_sheet_rows", 6, 1..=64),
+            (
+                "contact-sheet-thumbnail-size",
+                "contact_sheet_thumbnail_size",
+                128,
+                16..=1024,
+            ),
+        ] {
+            let mut value = config::get_config_u64(setting).unwrap_or(default);
+            ui.label(locale::get_message(locale, key, None));
+            if ui
+                .add(egui::DragValue::new(&mut value).range(range))
+                .changed()
+            {
+                config::set_config_value(setting, value.into());
+            }
+        }
+    });
+}
+
+pub fn history(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.heading(locale::get_message(locale, "extraction-history", None));
+    ui.label(locale::get_message(
+        locale,
+        "extraction-history-description",
+        None,
+    ));
+
+    let mut extraction_history = config::get_config_bool("extraction_history").unwrap_or(false);
+    ui.checkbox(
+        &mut extraction_history,
+        locale::get_message(locale, "record-extraction-history", None),
+    );
+    config::set_config_value("extraction_history", extraction_history.into());
+
+    let mut skip_previously_extracted =
+        config::get_config_bool("skip_previously_extracted").unwrap_or(false);
+    ui.checkbox(
+        &mut skip_previously_extracted,
+        locale::get_message(locale, "skip-previously-extracted", None),
+    );
+    config::set_config_value(
+        "skip_previously_extracted",
+        skip_previously_extracted.into(),
+    );
+
+    let mut args = FluentArgs::new();
+    args.set("count", logic::history::count());
+
+    ui.horizontal(|ui| {
+        ui.label(locale::get_message(locale, "history-entries", Some(&args)));
+        if ui
+            .add_enabled(
+                !logic::read_only::is_enabled(),
+                egui::Button::new(locale::get_message(locale, "button-clear-history", None)),
+            )
+            .on_disabled_hover_text(locale::get_message(locale, "read-only-tooltip", None))
             .clicked()
         {
-            config::remove_config_value("sql_database"); // Clear db in config
+            if let Err(e) = logic::history::clear() {
+                log_error!("Failed to clear extraction history: {}", e);
+            }
+        }
+    });
+
+    let mut query = HISTORY_QUERY.lock().unwrap();
+    ui.add(
+        egui::TextEdit::singleline(&mut *query).hint_text(locale::get_message(
+            locale,
+            "input-search-history",
+            None,
+        )),
+    );
 
-            // Close current db and open new one
-            let _ = logic::sql_database::reset_database();
+    if !query.is_empty() {
+        let generation = logic::history::get_generation();
+        let mut results = HISTORY_RESULTS.lock().unwrap();
+        if !matches!(&*results, Some((cached_query, cached_generation, _))
+            if *cached_query == *query && *cached_generation == generation)
+        {
+            *results = Some((
+                query.clone(),
+                generation,
+                logic::history::search(&query, 50),
+            ));
+        }
+        let entries = results.as_ref().map(|(_, _, entries)| entries.as_slice());
+
+        egui::ScrollArea::vertical()
+            .id_salt("History results")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for entry in entries.unwrap_or_default() {
+                    let timestamp: chrono::DateTime<chrono::Local> = entry.timestamp.into();
+                    ui.label(format!(
+                        "{}  {}  →  {}",
+                        timestamp.format("%Y-%m-%d %H:%M"),
+                        entry.asset,
+                        entry.destination
+                    ))
+                    .on_hover_text(format!("SHA-256: {}\nv{}", entry.hash, entry.version));
+                }
+            });
+    }
+}
+
+pub fn modifications(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.heading(locale::get_message(locale, "modifications", None));
+    ui.label(locale::get_message(
+        locale,
+        "modifications-description",
+        None,
+    ));
+
+    let mut args = FluentArgs::new();
+    args.set("count", logic::modifications::count());
+
+    ui.horizontal(|ui| {
+        ui.label(locale::get_message(locale, "modified-assets", Some(&args)));
+        if ui
+            .add_enabled(
+                !dialog::is_pending() && !logic::read_only::is_enabled(),
+                egui::Button::new(locale::get_message(
+                    locale,
+                    "button-revert-modifications",
+                    None,
+                )),
+            )
+            .on_disabled_hover_text(locale::get_message(locale, "read-only-tooltip", None))
+            .clicked()
+        {
+            dialog::spawn("revert-modifications", move || {
+                let locale = locale::get_locale(None);
+                let yes = DialogBuilder::message()
+                    .set_level(MessageLevel::Info)
+                    .set_title(locale::get_message(
+                        &locale,
+                        "confirmation-revert-modifications-title",
+                        None,
+                    ))
+                    .set_text(locale::get_message(
+                        &locale,
+                        "confirmation-revert-modifications-description",
+                        Some(&args),
+                    ))
+                    .confirm()
+                    .show()
+                    .unwrap();
+
+                if yes {
+                    match logic::modifications::revert_all() {
+                        Ok(failed) => {
+                            let mut args = FluentArgs::new();
+                            args.set("failed", failed);
+                            logic::update_status(locale::get_message(
+                                &locale,
+                                "modifications-reverted",
+                                Some(&args),
+                            ));
+                        }
+                        Err(_) => logic::update_status(locale::get_message(
+                            &locale,
+                            "error-read-only",
+                            None,
+                        )),
+                    }
+                }
+            });
         }
     });
+
+    egui::ScrollArea::vertical()
+        .id_salt("Modifications")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            for modification in logic::modifications::get_modifications() {
+                let timestamp: chrono::DateTime<chrono::Local> = modification.timestamp.into();
+                ui.label(format!(
+                    "{}  {}  ({})",
+                    timestamp.format("%Y-%m-%d %H:%M"),
+                    modification.asset,
+                    modification.operation
+                ));
+            }
+        });
+}
+
+pub fn notes(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    ui.separator();
+    ui.heading(locale::get_message(locale, "notes", None));
+    ui.label(locale::get_message(locale, "notes-description", None));
+
+    let mut args = FluentArgs::new();
+    args.set("count", logic::notes::count());
+
+    ui.horizontal(|ui| {
+        ui.label(locale::get_message(locale, "notes-count", Some(&args)));
+        if ui
+            .button(locale::get_message(
+                locale,
+                "button-find-orphaned-notes",
+                None,
+            ))
+            .clicked()
+        {
+            // Checking every asset can be slow on network drives
+            std::thread::spawn(|| {
+                *ORPHANED_NOTES.lock().unwrap() = Some(logic::notes::find_orphaned());
+            });
+        }
+    });
+
+    let mut orphaned_notes = ORPHANED_NOTES.lock().unwrap();
+    let Some(orphaned) = orphaned_notes.as_mut() else {
+        return;
+    };
+
+    let mut args = FluentArgs::new();
+    args.set("count", orphaned.len());
+    ui.horizontal(|ui| {
+        ui.label(locale::get_message(locale, "orphaned-notes", Some(&args)));
+        if !orphaned.is_empty()
+            && ui
+                .button(locale::get_message(locale, "button-remove-all", None))
+                .clicked()
+        {
+            for asset in orphaned.drain(..) {
+                logic::notes::set_note(&asset, "");
+            }
+        }
+    });
+
+    egui::ScrollArea::vertical()
+        .id_salt("Orphaned notes")
+        .max_height(200.0)
+        .show(ui, |ui| {
+            orphaned.retain(|asset| {
+                ui.horizontal(|ui| {
+                    let remove = ui
+                        .button(locale::get_message(locale, "button-remove", None))
+                        .clicked();
+                    ui.label(format!("{asset}: {}", logic::notes::get_note(asset)));
+                    if remove {
+                        logic::notes::set_note(asset, "");
+                    }
+                    !remove
+                })
+                .inner
+            });
+        });
 }
 
 pub fn updates(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
@@ -277,6 +1314,13 @@ pub fn behavior(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
     );
     config::set_config_value("use_alias", use_alias.into());
 
+    let mut sort_into_subfolders = config::get_config_bool("sort_into_subfolders").unwrap_or(false);
+    ui.checkbox(
+        &mut sort_into_subfolders,
+        locale::get_message(locale, "sort-into-subfolders", None),
+    );
+    config::set_config_value("sort_into_subfolders", sort_into_subfolders.into());
+
     let mut use_alias = config::get_config_bool("refresh_before_extract").unwrap_or(false);
     ui.checkbox(
         &mut use_alias,
@@ -284,6 +1328,27 @@ pub fn behavior(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
     );
     config::set_config_value("refresh_before_extract", use_alias.into());
 
+    let mut merge_duplicate_sources =
+        config::get_config_bool("merge_duplicate_sources").unwrap_or(false);
+    ui.checkbox(
+        &mut merge_duplicate_sources,
+        locale::get_message(locale, "merge-duplicate-sources", None),
+    );
+    config::set_config_value("merge_duplicate_sources", merge_duplicate_sources.into());
+
+    // Started and stopped straight away, not on the next launch
+    let mut auto_refresh = logic::watcher::is_enabled();
+    if ui
+        .checkbox(
+            &mut auto_refresh,
+            locale::get_message(locale, "auto-refresh", None),
+        )
+        .changed()
+    {
+        config::set_config_value("auto_refresh", auto_refresh.into());
+        logic::watcher::set_enabled(auto_refresh);
+    }
+
     let mut use_topbar_buttons = config::get_config_bool("use_topbar_buttons").unwrap_or(true);
     ui.checkbox(
         &mut use_topbar_buttons,
@@ -305,6 +1370,26 @@ pub fn behavior(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
             .text(locale::get_message(locale, "input-preview-size", None)),
     );
     config::set_config_value("image_preview_size", image_preview_size.into());
+
+    let mut alias_display_length = config::get_config_u64("alias_display_length").unwrap_or(64);
+    ui.add(
+        egui::widgets::Slider::new(&mut alias_display_length, 16_u64..=200_u64).text(
+            locale::get_message(locale, "input-alias-display-length", None),
+        ),
+    );
+    config::set_config_value("alias_display_length", alias_display_length.into());
+
+    ui.label(locale::get_message(
+        locale,
+        "setting-below-restart-required",
+        None,
+    )); // The tabs are only set up when starting
+    let mut show_json_tab = config::get_config_bool("show_json_tab").unwrap_or(false);
+    ui.checkbox(
+        &mut show_json_tab,
+        locale::get_message(locale, "show-json-tab", None),
+    );
+    config::set_config_value("show_json_tab", show_json_tab.into());
 }
 
 pub fn language(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) -> bool {
@@ -0,0 +1,118 @@
+use crate::{
+    locale,
+    logic::{self, stats},
+};
+use eframe::egui;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use std::{sync::Arc, time::SystemTime};
+
+// Empty when unknown
+fn format_time(time: Option<SystemTime>) -> String {
+    time.map(|time| {
+        let time: chrono::DateTime<chrono::Local> = time.into();
+        time.format("%Y-%m-%d %H:%M").to_string()
+    })
+    .unwrap_or_default()
+}
+
+fn group_row(ui: &mut egui::Ui, name: egui::RichText, group: &stats::GroupStats) {
+    ui.label(name);
+    ui.label(group.count.to_string());
+    ui.label(logic::format_size(group.bytes));
+    ui.label(format_time(group.oldest));
+    ui.label(format_time(group.newest));
+    ui.end_row();
+}
+
+pub fn statistics(ui: &mut egui::Ui, locale: &FluentBundle<Arc<FluentResource>>) {
+    let running = stats::is_running();
+    let statistics = stats::get_statistics();
+    // Counted the first time the tab is opened
+    if statistics.is_none() && !running {
+        stats::run();
+    }
+
+    ui.heading(locale::get_message(locale, "statistics", None));
+    ui.label(locale::get_message(locale, "statistics-description", None));
+
+    ui.horizontal(|ui| {
+        if running {
+            if ui
+                .button(locale::get_message(locale, "button-cancel", None))
+                .clicked()
+            {
+                stats::stop();
+            }
+            ui.spinner();
+            ui.ctx().request_repaint();
+        } else if ui
+            .button(locale::get_message(locale, "button-recount", None))
+            .clicked()
+        {
+            stats::run();
+        }
+    });
+
+    let Some(statistics) = statistics else {
+        return;
+    };
+    if !statistics.database {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            locale::get_message(locale, "statistics-no-database", None),
+        );
+    }
+    if statistics.duplicates > 0 {
+        let mut args = FluentArgs::new();
+        args.set("count", statistics.duplicates);
+        ui.label(locale::get_message(
+            locale,
+            "statistics-duplicates",
+            Some(&args),
+        ));
+    }
+    if statistics.stopped {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            locale::get_message(locale, "statistics-stopped", None),
+        );
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink(false)
+        .show(ui, |ui| {
+            egui::Grid::new("Statistics")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for key in [
+                        "statistics-category",
+                        "statistics-count",
+                        "statistics-size",
+                        "statistics-oldest",
+                        "statistics-newest",
+                    ] {
+                        ui.strong(locale::get_message(locale, key, None));
+                    }
+                    ui.end_row();
+
+                    for (category, group) in &statistics.categories {
+                        group_row(
+                            ui,
+                            egui::RichText::new(locale::get_message(
+                                locale,
+                                category.locale_key(),
+                                None,
+                            )),
+                            group,
+                        );
+                    }
+                    group_row(
+                        ui,
+                        egui::RichText::new(locale::get_message(locale, "statistics-total", None))
+                            .strong(),
+                        &statistics.total,
+                    );
+                });
+        });
+}
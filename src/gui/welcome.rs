@@ -20,6 +20,8 @@ impl MyApp {
 
 impl eframe::App for MyApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        gui::apply_theme(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::both().show(ui, |ui| {
                 ui.heading(locale::get_message(&self.locale, "welcome", None));
@@ -28,6 +30,7 @@ impl eframe::App for MyApp {
                     // This returns true if the locales need to be refreshed
                     self.locale = locale::get_locale(None);
                 }
+                settings::health_check(ui, &self.locale);
                 settings::behavior(ui, &self.locale);
                 settings::updates(ui, &self.locale);
                 if self.first_frame {
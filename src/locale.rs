@@ -1,6 +1,6 @@
 use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     sync::{Arc, LazyLock, Mutex},
 };
 use unic_langid::LanguageIdentifier;
@@ -11,6 +11,12 @@ include!(concat!(env!("OUT_DIR"), "/locale_data.rs")); // defines get_locale_res
 
 static LANGUAGE_LIST: LazyLock<Mutex<Vec<(String, String)>>> =
     LazyLock::new(|| Mutex::new(init_language_list()));
+static REPORTED_MESSAGES: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new())); // Messages already logged as formatted with errors
+static REPORTED_LOCALES: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new())); // Locales already logged as failing to parse
+static DECIMAL_SEPARATOR: LazyLock<Mutex<Option<(String, String)>>> =
+    LazyLock::new(|| Mutex::new(None)); // With the language setting it was read for
 
 fn init_language_list() -> Vec<(String, String)> {
     let mut languages = LOCALES.to_vec();
@@ -41,10 +47,21 @@ pub fn get_message(
     if let Some(message) = locale.get_message(id) {
         if let Some(value) = message.value() {
             let mut err = vec![];
-            locale
+            let message = locale
                 .format_pattern(value, args, &mut err)
                 .to_string()
-                .replace(" # TODO: Translate", "") // Remove the TODO from strings
+                .replace(" # TODO: Translate", ""); // Remove the TODO from strings
+
+            // e.g. a missing argument, which is shown as its name. Only logged once as messages are formatted every frame
+            if !err.is_empty() && REPORTED_MESSAGES.lock().unwrap().insert(id.to_owned()) {
+                let errors: Vec<String> = err.iter().map(|e| e.to_string()).collect();
+                log_warn!(
+                    "Message {} formatted with errors: {}",
+                    id,
+                    errors.join(", ")
+                );
+            }
+            message
         } else {
             id.to_owned() // Return id if it is not available
         }
@@ -53,6 +70,28 @@ pub fn get_message(
     }
 }
 
+// Entries that fail to parse are left out rather than losing the whole file
+fn parse_resource(locale: &str, data: String) -> FluentResource {
+    match FluentResource::try_new(data) {
+        Ok(resource) => resource,
+        Err((resource, errors)) => {
+            // get_locale is called often, only log each file once
+            if REPORTED_LOCALES.lock().unwrap().insert(locale.to_owned()) {
+                for error in errors {
+                    let line = resource.source()[..error.pos.start].matches('\n').count() + 1;
+                    log_error!(
+                        "Failed to parse locales/{}.ftl at line {}: {:?}",
+                        locale,
+                        line,
+                        error.kind
+                    );
+                }
+            }
+            resource
+        }
+    }
+}
+
 pub fn get_locale(lang: Option<&str>) -> FluentBundle<Arc<FluentResource>> {
     let locale = if let Some(locale) = lang {
         locale
@@ -66,21 +105,82 @@ pub fn get_locale(lang: Option<&str>) -> FluentBundle<Arc<FluentResource>> {
         }
     };
 
-    let resource_data = if let Some(resources) = get_locale_resources(locale) {
-        resources
-    } else {
-        get_locale_resources("en-GB").unwrap() // Use English if the locale is not supported
-    };
-
-    let resource = FluentResource::try_new(resource_data).expect("Failed to parse FTL string.");
+    // Otherwise only English is used as the locale is not supported
+    let translation = (locale != "en-GB")
+        .then(|| get_locale_resources(locale))
+        .flatten();
+    build_bundle(locale, get_locale_resources("en-GB"), translation)
+}
 
+// English goes underneath so anything missing or broken in the locale is still shown
+fn build_bundle(
+    locale: &str,
+    english: Option<String>,
+    translation: Option<String>,
+) -> FluentBundle<Arc<FluentResource>> {
     let lang_id: LanguageIdentifier = locale.parse().unwrap_or_else(|_| "en-GB".parse().unwrap());
     let mut bundle = FluentBundle::new(vec![lang_id]);
 
-    bundle.add_resource_overriding(resource.into());
+    if let Some(english) = english {
+        bundle.add_resource_overriding(parse_resource("en-GB", english).into());
+    }
+    if let Some(translation) = translation {
+        bundle.add_resource_overriding(parse_resource(locale, translation).into());
+    }
     bundle
 }
 
+// Sizes are formatted for every row, loading the locale each time would be too slow
+pub fn get_decimal_separator() -> String {
+    let language = config::get_config_string("language").unwrap_or_default(); // Empty for the system language
+    let mut cached = DECIMAL_SEPARATOR.lock().unwrap();
+    match &*cached {
+        Some((cached_language, separator)) if *cached_language == language => separator.clone(),
+        _ => {
+            let separator = get_message(&get_locale(None), "decimal-separator", None);
+            *cached = Some((language, separator.clone()));
+            separator
+        }
+    }
+}
+
 pub fn get_language_list() -> Vec<(String, String)> {
     LANGUAGE_LIST.lock().unwrap().clone()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broken_locale_falls_back() {
+        // An unclosed placeable, then an entry with no value
+        let broken = "language-name = Broken\n\
+            idling = { $\n\
+            no-value =\n\
+            good = Still here\n"
+            .to_owned();
+        let bundle = build_bundle("xx-BROKEN", get_locale_resources("en-GB"), Some(broken));
+
+        // What parsed is used, the rest is English
+        assert_eq!(get_message(&bundle, "language-name", None), "Broken");
+        assert_eq!(get_message(&bundle, "good", None), "Still here");
+        let english = build_bundle("en-GB", get_locale_resources("en-GB"), None);
+        assert_eq!(
+            get_message(&bundle, "idling", None),
+            get_message(&english, "idling", None)
+        );
+        assert_eq!(get_message(&bundle, "no-value", None), "no-value");
+        // Reported so the broken line gets logged
+        assert!(REPORTED_LOCALES.lock().unwrap().contains("xx-BROKEN"));
+    }
+
+    #[test]
+    fn nothing_usable_still_gives_a_bundle() {
+        let bundle = build_bundle("not a locale!", None, Some("{{{".to_owned()));
+        assert_eq!(get_message(&bundle, "idling", None), "idling");
+
+        let english = build_bundle("not a locale!", get_locale_resources("en-GB"), None);
+        assert_ne!(get_message(&english, "idling", None), "idling");
+    }
+}
@@ -1,12 +1,48 @@
-use std::sync::{LazyLock, Mutex};
+use std::sync::{
+    atomic::{AtomicU8, Ordering},
+    LazyLock, Mutex,
+};
+
+// Levels, only messages at or below the level are printed. The log tab gets them regardless
+pub const ERROR: u8 = 0;
+pub const WARN: u8 = 1;
+pub const INFO: u8 = 2;
+pub const DEBUG: u8 = 3;
 
 static LOG: LazyLock<Mutex<String>> = LazyLock::new(|| Mutex::new(String::new()));
+static USE_STDERR: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
+static LEVEL: AtomicU8 = AtomicU8::new(WARN);
+
+// Set by --quiet and --verbose
+pub fn set_level(level: u8) {
+    LEVEL.store(level.min(DEBUG), Ordering::Relaxed);
+}
+
+pub fn level() -> u8 {
+    LEVEL.load(Ordering::Relaxed)
+}
 
-pub fn log(log_type: &str, message: &str, file: &str, line: u32, column: u32) {
+// Only errors and what was asked for should be printed
+pub fn is_quiet() -> bool {
+    level() == ERROR
+}
+
+// Keep stdout clean when the CLI writes asset bytes to it
+pub fn use_stderr() {
+    *USE_STDERR.lock().unwrap() = true;
+}
+
+pub fn log(level: u8, log_type: &str, message: &str, file: &str, line: u32, column: u32) {
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
     let log_message = format!("{now}  {log_type}{message} [{file}:{line}:{column}]");
 
-    println!("{log_message}");
+    if level <= self::level() {
+        if *USE_STDERR.lock().unwrap() {
+            eprintln!("{log_message}");
+        } else {
+            println!("{log_message}");
+        }
+    }
 
     let mut log = LOG.lock().unwrap();
     log.push_str(&format!("{log_message}\n"));
@@ -15,29 +51,58 @@ pub fn log(log_type: &str, message: &str, file: &str, line: u32, column: u32) {
 #[macro_export]
 macro_rules! log_info {
     ($($arg:tt)*) => {
-        $crate::log::log("INFO:  ", &format!($($arg)*), file!(), line!(), column!())
+        $crate::log::log(
+            $crate::log::INFO,
+            "INFO:  ",
+            &format!($($arg)*),
+            file!(),
+            line!(),
+            column!(),
+        )
     };
 }
 
 #[macro_export]
 macro_rules! log_warn {
     ($($arg:tt)*) => {
-        $crate::log::log("WARN:  ", &format!($($arg)*), file!(), line!(), column!())
+        $crate::log::log(
+            $crate::log::WARN,
+            "WARN:  ",
+            &format!($($arg)*),
+            file!(),
+            line!(),
+            column!(),
+        )
     };
 }
 
 #[macro_export]
 macro_rules! log_error {
     ($($arg:tt)*) => {
-        $crate::log::log("ERROR: ", &format!($($arg)*), file!(), line!(), column!())
+        $crate::log::log(
+            $crate::log::ERROR,
+            "ERROR: ",
+            &format!($($arg)*),
+            file!(),
+            line!(),
+            column!(),
+        )
     };
 }
 
 #[macro_export]
 macro_rules! log_debug {
     ($($arg:tt)*) => {
-        if cfg!(debug_assertions) {
-            $crate::log::log("DEBUG: ", &format!($($arg)*), file!(), line!(), column!())
+        // Always kept in debug builds, release builds only format them for -vv
+        if cfg!(debug_assertions) || $crate::log::level() >= $crate::log::DEBUG {
+            $crate::log::log(
+                $crate::log::DEBUG,
+                "DEBUG: ",
+                &format!($($arg)*),
+                file!(),
+                line!(),
+                column!(),
+            )
         }
     };
 }
@@ -46,7 +111,14 @@ macro_rules! log_debug {
 macro_rules! log_critical {
     ($($arg:tt)*) => {{
         let formatted = format!($($arg)*);
-        $crate::log::log("CRITICAL: ", &formatted, file!(), line!(), column!());
+        $crate::log::log(
+            $crate::log::ERROR,
+            "CRITICAL: ",
+            &formatted,
+            file!(),
+            line!(),
+            column!(),
+        );
 
         let _ = native_dialog::DialogBuilder::message()
             .set_level(native_dialog::MessageLevel::Error)
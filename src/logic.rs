@@ -1,151 +1,1017 @@
 use std::{
+    collections::{HashMap, HashSet},
     env, fs,
-    path::PathBuf,
-    sync::{Arc, LazyLock, Mutex},
+    io::{Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     thread,
-    time::SystemTime,
+    time::{Duration, SystemTime},
 };
 
+use base64::{prelude::BASE64_STANDARD, Engine};
 use clap::ValueEnum;
 use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use serde::{Deserialize, Serialize};
 
-use strum::IntoEnumIterator;
-use strum_macros::{Display, EnumIter};
+use strum::{IntoEnumIterator, VariantArray};
+use strum_macros::{EnumIter, VariantArray};
 
 use crate::{config, locale};
-
+use archive::ArchiveWriter;
+pub use error::Error;
+use error_code::ErrorCode;
+use extract_options::{ExtractOptions, ExtractOverrides, OverwritePolicy};
+use manifest::ManifestEntry;
+pub use state::state;
+use state::ExtractorState;
+pub use stats::compute_statistics;
+use summary::{ExtractFailure, ExtractSummary, ReclassifySummary, RefreshSummary, ResizedImage};
+use template::{Template, TemplateValues};
+use verify::Verification;
+pub use watcher::{start_auto_extract, stop_auto_extract};
+
+pub mod archive;
+pub mod asset_end;
+pub mod batch_alias;
 pub mod cache_directory;
+pub mod clear;
+pub mod contact_sheet;
+pub mod error;
+pub mod error_code;
+pub mod extract_options;
+#[cfg(test)]
+pub mod fixtures;
+pub mod health_check;
+pub mod history;
+pub mod image_resize;
+pub mod manifest;
+pub mod modifications;
+pub mod notes;
+pub mod png_convert;
+pub mod read_only;
+pub mod scan_index;
 pub mod sql_database;
-
-static TEMP_DIRECTORY: LazyLock<Mutex<PathBuf>> = LazyLock::new(|| Mutex::new(create_temp_dir()));
-
-// Define global values
-static STATUS: LazyLock<Mutex<String>> = LazyLock::new(|| {
-    Mutex::new(locale::get_message(
-        &locale::get_locale(None),
-        "idling",
-        None,
-    ))
-});
-static FILE_LIST: LazyLock<Mutex<Vec<AssetInfo>>> = LazyLock::new(|| Mutex::new(Vec::new()));
-static REQUEST_REPAINT: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
-static PROGRESS: LazyLock<Mutex<f32>> = LazyLock::new(|| Mutex::new(1.0));
-static LIST_TASK_RUNNING: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
-static STOP_LIST_RUNNING: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
-static FILTERED_FILE_LIST: LazyLock<Mutex<Vec<AssetInfo>>> =
-    LazyLock::new(|| Mutex::new(Vec::new()));
-static TASK_RUNNING: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false)); // Delete/extract
+pub mod state;
+pub mod stats;
+pub mod summary;
+pub mod swap_pack;
+pub mod task_lock;
+pub mod temp_space;
+pub mod template;
+pub mod thumbnail;
+pub mod verify;
+pub mod watcher;
+pub mod wav_convert;
+
+const SIZE_DISPARITY_RATIO: u64 = 4; // Warn if one asset is this many times bigger than the other
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+// A corrupt or crafted zstd frame can decompress to far more than anything Roblox caches, so decoding
+// stops here. max_decompressed_size in the config (in MiB) changes it
+const DEFAULT_DECOMPRESSED_LIMIT: u64 = 512; // MiB
+const MAX_EXTRACT_THREADS: usize = 64;
+
+// Headers are only looked for this many bytes into the payload, an rbxm embedding an OGG further in is
+// still an rbxm. header_scan_window in the config changes it, 0 looks through everything read.
+pub(crate) const DEFAULT_SCAN_WINDOW: usize = 512;
+const PREAMBLE_ALLOWANCE: usize = 2048; // Read on top of the window for anything in front of the payload
+const ROBLOX_HTTP_MAGIC: &[u8] = b"RBXH"; // Cache files with the URL and response headers first
+
+// PNG's signature starts with a byte that isn't text, "PNG" alone shows up in plenty of other assets
+pub const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+// KTX textures start with a 12 byte identifier that isn't text, matched whole so version 2 isn't
+// taken for version 1
+pub const KTX1_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'1', b'1', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+pub const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+pub const KTX2_HEADER: &str = "KTX2";
+
+// Meshes start with their version, text for 1.xx and binary after. "version" shows up inside plenty of
+// other assets, so these only count at the very start of the payload
+const MESH_HEADERS: [&str; 8] = [
+    "version 1.00",
+    "version 1.01",
+    "version 2.00",
+    "version 3.00",
+    "version 3.01",
+    "version 4.00",
+    "version 4.01",
+    "version 5.00",
+];
+
+// Fonts start with a magic at the very start of the payload too. TrueType's is 00 01 00 00, which isn't
+// text and is common in binary data, so it gets a name and its table directory has to make sense
+const TRUETYPE_HEADER: &str = "TrueType";
+const FONT_HEADERS: [&str; 3] = [TRUETYPE_HEADER, "OTTO", "ttcf"];
+const SFNT_VERSION: &[u8] = &[0x00, 0x01, 0x00, 0x00];
+const MAX_FONT_TABLES: usize = 64; // Real fonts have around 10 to 30
+const MAX_COLLECTION_FONTS: u32 = 256;
+
+// JSON has no magic, so it's text starting with { or [ at the start of the payload
+const JSON_HEADER: &str = "JSON";
+
+// MP3s without an ID3 tag start straight away with a frame, found by find_mpeg_frame() as it isn't text
+const MPEG_HEADER: &str = "MPEG";
+const MPEG1_BITRATES: [usize; 15] = [
+    0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+]; // kbit/s, layer III
+const MPEG2_BITRATES: [usize; 15] = [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+const MPEG1_SAMPLE_RATES: [usize; 3] = [44100, 48000, 32000];
+const MPEG2_SAMPLE_RATES: [usize; 3] = [22050, 24000, 16000];
 
 // CLI stuff
-#[derive(ValueEnum, Clone, Debug, Eq, PartialEq, Hash, Copy, EnumIter, Display)]
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Copy, EnumIter, VariantArray)]
 pub enum Category {
     Music,
     Sounds,
     Images,
     Ktx,
     Rbxm,
+    Mesh,
+    Fonts,
+    Json, // Last so binary formats are checked first
     All,
 }
 
+// Every name comes from an exhaustive match, so a new category won't compile until it has all of them
+impl Category {
+    // The canonical name, used for display, config keys, CLI arguments and templates
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Music => "music",
+            Category::Sounds => "sounds",
+            Category::Images => "images",
+            Category::Ktx => "ktx",
+            Category::Rbxm => "rbxm",
+            Category::Mesh => "mesh",
+            Category::Fonts => "fonts",
+            Category::Json => "json",
+            Category::All => "all",
+        }
+    }
+
+    // Id of the tab listing this category
+    pub fn tab_id(self) -> &'static str {
+        match self {
+            Category::Ktx => "ktx-files",
+            Category::Rbxm => "rbxm-files",
+            Category::Mesh => "mesh-files",
+            Category::Json => "json-files",
+            category => category.name(),
+        }
+    }
+
+    // Locale key for the category's name, All is the "Other" of the other categories
+    pub fn locale_key(self) -> &'static str {
+        match self {
+            Category::All => "other-assets",
+            category => category.tab_id(),
+        }
+    }
+
+    pub fn from_tab_id(tab: &str) -> Option<Category> {
+        Category::iter().find(|category| category.tab_id() == tab)
+    }
+
+    // Older names that are still accepted when parsing
+    fn legacy_names(self) -> &'static [&'static str] {
+        match self {
+            Category::Music | Category::Sounds | Category::Images | Category::Fonts => &[],
+            Category::Ktx => &["ktx-files"],
+            Category::Rbxm => &["rbxm-files"],
+            Category::Mesh => &["mesh-files"],
+            Category::Json => &["json-files"],
+            Category::All => &["other-assets", "other"],
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+// Case insensitive so CamelCase names written by older versions still parse
+impl std::str::FromStr for Category {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.trim().to_lowercase();
+        Category::iter()
+            .find(|category| {
+                category.name() == name || category.legacy_names().contains(&name.as_str())
+            })
+            .ok_or_else(|| {
+                let names: Vec<&str> = Category::iter().map(|category| category.name()).collect();
+                format!(
+                    "Unknown category '{name}', expected one of: {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl ValueEnum for Category {
+    fn value_variants<'a>() -> &'a [Self] {
+        Category::VARIANTS
+    }
+
+    fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
+        Some(clap::builder::PossibleValue::new(self.name()).aliases(self.legacy_names()))
+    }
+}
+
+impl Serialize for Category {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Category {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AssetInfo {
     pub name: String,
-    pub _size: u64,
+    pub size: u64,
+    pub actual_size: u64, // Decompressed size, same as size for uncompressed assets
+    pub actual_size_approximate: bool, // The zstd frame didn't declare its size
     pub last_modified: Option<SystemTime>,
     pub from_file: bool,
     pub from_sql: bool,
+    pub prefer_sql: bool, // In both and the database's copy is newer, so it's read first
+    pub other_name: Option<String>, // Its name in the copy that isn't preferred, when that's different
+    pub evicted: bool,              // The row exists but Roblox has removed its content
+    pub header: Option<String>, // Header that matched when listing, None if nothing matched or it wasn't read
+    pub hash: Option<String>, // SHA-256 of the extracted content, None until compute_hash() is called
+    pub origin_url: Option<String>, // Where Roblox downloaded it from, None without a preamble or if it wasn't read
     pub category: Category,
 }
 
-// Define local functions
-fn update_file_list(value: AssetInfo, cli_list_mode: bool) {
-    // cli_list_mode will print out to console
-    // It is done this way so it can read files and print to console in the same stage
-    if cli_list_mode {
-        println!("{}", value.name);
+// Where an asset's bytes are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    File,
+    Sql,
+}
+
+impl AssetInfo {
+    // Listed from both the cache directory and the database
+    pub fn in_both_sources(&self) -> bool {
+        self.from_file && self.from_sql
+    }
+
+    // Avatar or asset thumbnail, from the URL it was downloaded from
+    pub fn thumbnail(&self) -> Option<thumbnail::Thumbnail> {
+        self.origin_url.as_deref().and_then(thumbnail::parse)
+    }
+
+    // The preferred one first
+    fn sources(&self) -> Vec<Source> {
+        let mut sources = Vec::new();
+        if self.from_file {
+            sources.push(Source::File);
+        }
+        if self.from_sql {
+            sources.push(Source::Sql);
+        }
+        if self.prefer_sql {
+            sources.reverse();
+        }
+        sources
+    }
+
+    // Named as the source has it, see merge_duplicates()
+    fn in_source(&self, source: Source) -> std::borrow::Cow<'_, AssetInfo> {
+        match &self.other_name {
+            Some(other_name) if (source == Source::Sql) != self.prefer_sql => {
+                std::borrow::Cow::Owned(AssetInfo {
+                    name: other_name.clone(),
+                    ..self.clone()
+                })
+            }
+            _ => std::borrow::Cow::Borrowed(self),
+        }
+    }
+}
+
+// Tries each source the asset is in until one can be read
+fn read_from_sources<T>(
+    asset: &AssetInfo,
+    read: impl Fn(Source) -> Result<T, Error>,
+) -> Result<T, Error> {
+    let mut result = Err(Error::NotFound);
+    for (i, source) in asset.sources().into_iter().enumerate() {
+        if let (Err(e), 1..) = (&result, i) {
+            log_warn!(
+                "Failed to read {}, trying its other copy: {}",
+                asset.name,
+                e
+            );
+        }
+        result = read(source);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+// One entry for an asset listed from both sources, shown as the newer copy. An evicted row has
+// nothing to read, so the file is preferred then
+fn merge_sources(file: AssetInfo, sql: AssetInfo) -> AssetInfo {
+    let prefer_sql = !sql.evicted && sql.last_modified > file.last_modified;
+    AssetInfo {
+        from_file: true,
+        from_sql: true,
+        prefer_sql,
+        evicted: false,
+        ..if prefer_sql { sql } else { file }
+    }
+}
+
+// Anything drawing the lists compares this to tell when to get them again
+fn file_list_changed(state: &ExtractorState) {
+    *state.file_list_generation.lock().unwrap() += 1;
+}
+
+pub fn get_file_list_generation() -> u64 {
+    *state().file_list_generation.lock().unwrap()
+}
+
+// Assets found while listing, added to the file list a few at a time so it isn't locked for each one
+struct ListBatch {
+    assets: Vec<AssetInfo>,
+    cli_list_mode: bool,
+    last_flush: std::time::Instant,
+}
+
+impl ListBatch {
+    fn new(cli_list_mode: bool) -> ListBatch {
+        ListBatch {
+            assets: Vec::new(),
+            cli_list_mode,
+            last_flush: std::time::Instant::now(),
+        }
+    }
+
+    fn push(&mut self, value: AssetInfo) {
+        // cli_list_mode will print out to console
+        // It is done this way so it can read files and print to console in the same stage
+        if self.cli_list_mode
+            && !get_stable_order()
+            && !get_stop_list_running()
+            && state().printed.lock().unwrap().insert(value.name.clone())
+        {
+            println!("{}", list_line(&value));
+        }
+        self.assets.push(value);
+        // Still shown as it streams in
+        if self.assets.len() >= LIST_BATCH_SIZE || self.last_flush.elapsed() >= LIST_BATCH_INTERVAL
+        {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        self.last_flush = std::time::Instant::now();
+        if self.assets.is_empty() {
+            return;
+        }
+        let state = state();
+        let mut file_list = state.file_list.lock().unwrap();
+        // A newer refresh has taken over, checked while holding the list so nothing stale is added after it clears
+        if get_stop_list_running() {
+            self.assets.clear();
+            return;
+        }
+        file_list.append(&mut self.assets);
+        file_list_changed(&state);
+    }
+}
+
+// An asset in both the cache directory and the database is listed once, see merge_sources()
+fn merge_file_list() {
+    let state = state();
+    let mut file_list = state.file_list.lock().unwrap();
+    let mut merged: Vec<AssetInfo> = Vec::with_capacity(file_list.len());
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for asset in file_list.drain(..) {
+        match positions.get(&asset.name) {
+            Some(&i) if merged[i].from_sql != asset.from_sql => {
+                let listed = merged[i].clone();
+                merged[i] = if asset.from_sql {
+                    merge_sources(listed, asset)
+                } else {
+                    merge_sources(asset, listed)
+                };
+            }
+            _ => {
+                positions.insert(asset.name.clone(), merged.len());
+                merged.push(asset);
+            }
+        }
+    }
+    *file_list = merged;
+    file_list_changed(&state);
+}
+
+// The ID a cache directory file is named after, ignoring case and any extension
+fn embedded_id(name: &str) -> Option<String> {
+    let id = name.split('.').next()?.to_ascii_lowercase();
+    (!id.is_empty() && id.bytes().all(|byte| byte.is_ascii_hexdigit())).then_some(id)
+}
+
+// Assets that are in both sources under different names are listed once, the same as
+// merge_file_list(). They're matched by the ID in the file's name, then by hashing their content,
+// which is only done for categories with assets in both. Returns how many were merged
+fn merge_duplicates() -> usize {
+    let mut assets = get_file_list();
+    let mut pairs = Vec::new(); // Positions of the file and the database row
+    let mut paired = HashSet::new();
+
+    let mut files_by_id = HashMap::new();
+    for (i, asset) in assets.iter().enumerate() {
+        if asset.from_file && !asset.from_sql {
+            if let Some(id) = embedded_id(&asset.name) {
+                files_by_id.insert(id, i);
+            }
+        }
+    }
+    for (i, asset) in assets.iter().enumerate() {
+        if asset.from_sql && !asset.from_file {
+            if let Some(&file) = files_by_id.get(&asset.name.to_ascii_lowercase()) {
+                if paired.insert(file) && paired.insert(i) {
+                    pairs.push((file, i));
+                }
+            }
+        }
+    }
+
+    // Stored sizes can't be compared, files have the response's headers before the content
+    let mut by_category: HashMap<Category, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    for (i, asset) in assets.iter().enumerate() {
+        if paired.contains(&i) || asset.in_both_sources() || asset.evicted {
+            continue;
+        }
+        let (files, rows) = by_category.entry(asset.category).or_default();
+        if asset.from_sql {
+            rows.push(i);
+        } else if asset.from_file {
+            files.push(i);
+        }
+    }
+    for (files, rows) in by_category.into_values() {
+        if files.is_empty() || rows.is_empty() {
+            continue;
+        }
+        let mut files_by_hash = HashMap::new();
+        for file in files {
+            if get_stop_list_running() {
+                return 0;
+            }
+            if let Ok(hash) = compute_hash(&assets[file]) {
+                assets[file].hash = Some(hash.clone());
+                files_by_hash.insert(hash, file);
+            }
+        }
+        for row in rows {
+            if get_stop_list_running() {
+                return 0;
+            }
+            let Ok(hash) = compute_hash(&assets[row]) else {
+                continue;
+            };
+            assets[row].hash = Some(hash.clone());
+            if let Some(file) = files_by_hash.remove(&hash) {
+                pairs.push((file, row));
+            }
+        }
+    }
+
+    if pairs.is_empty() {
+        return 0;
+    }
+    let mut removed = HashSet::new();
+    for &(file, row) in &pairs {
+        let (file_asset, sql_asset) = (assets[file].clone(), assets[row].clone());
+        let (file_name, sql_name) = (file_asset.name.clone(), sql_asset.name.clone());
+        let mut merged = merge_sources(file_asset, sql_asset);
+        merged.other_name = Some(if merged.prefer_sql {
+            file_name
+        } else {
+            sql_name
+        });
+        assets[row] = merged;
+        removed.insert(file);
     }
-    let mut file_list = FILE_LIST.lock().unwrap();
-    file_list.push(value)
+    let merged = pairs.len();
+
+    let state = state();
+    let mut file_list = state.file_list.lock().unwrap();
+    *file_list = assets
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !removed.contains(i))
+        .map(|(_, asset)| asset)
+        .collect();
+    file_list_changed(&state);
+    merged
+}
+
+// Refreshes end up in the same order every time no matter which files are read first:
+// assets from the database, then ones from the cache directory, each sorted by name
+fn sort_by_source() {
+    let state = state();
+    let mut file_list = state.file_list.lock().unwrap();
+    file_list.sort_by(|a, b| {
+        b.from_sql
+            .cmp(&a.from_sql)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    file_list_changed(&state);
 }
 
 fn clear_file_list() {
-    let mut file_list = FILE_LIST.lock().unwrap();
-    *file_list = Vec::new()
+    let state = state();
+    let mut file_list = state.file_list.lock().unwrap();
+    *file_list = Vec::new();
+    file_list_changed(&state);
+    state.printed.lock().unwrap().clear();
 }
 
+// memmem is much faster than comparing every window when searching multi-megabyte assets
 fn bytes_search(haystack: &[u8], needle: &[u8]) -> Option<usize> {
-    let len = needle.len();
-    if len > 0 {
-        haystack.windows(len).position(|window| window == needle)
-    } else {
+    if needle.is_empty() {
         None
+    } else {
+        memchr::memmem::find(haystack, needle)
     }
 }
 
 fn bytes_contains(haystack: &[u8], needle: &[u8]) -> bool {
-    let len = needle.len();
-    if len > 0 {
-        haystack.windows(len).any(|window| window == needle)
+    bytes_search(haystack, needle).is_some()
+}
+
+// Length of the MP3 frame starting at bytes[0], None if it doesn't start with a valid layer III frame header
+fn mpeg_frame_length(bytes: &[u8]) -> Option<usize> {
+    let [0xFF, version, details, ..] = *bytes else {
+        return None;
+    };
+    let (bitrates, sample_rates, samples) = match version {
+        0xFB => (MPEG1_BITRATES, MPEG1_SAMPLE_RATES, 144), // MPEG-1
+        0xF3 | 0xF2 => (MPEG2_BITRATES, MPEG2_SAMPLE_RATES, 72), // MPEG-2, with and without CRC
+        _ => return None,
+    };
+
+    let bitrate = *bitrates.get((details >> 4) as usize)?; // 15 is invalid
+    let sample_rate = *sample_rates.get(((details >> 2) & 0b11) as usize)?; // 3 is reserved
+    if bitrate == 0 {
+        return None; // Free format, the length can't be known
+    }
+    let padding = ((details >> 1) & 1) as usize;
+    Some(samples * bitrate * 1000 / sample_rate + padding)
+}
+
+// A TrueType or OpenType font starting at bytes[0], the whole table directory has to be there
+fn is_sfnt(bytes: &[u8], version: &[u8]) -> bool {
+    if !bytes.starts_with(version) {
+        return false;
+    }
+    let Some(&[high, low]) = bytes.get(4..6) else {
+        return false;
+    };
+    let tables = u16::from_be_bytes([high, low]) as usize;
+    if tables == 0 || tables > MAX_FONT_TABLES {
+        return false;
+    }
+    // Each record is 16 bytes after the 12 byte header, starting with a tag like "glyf"
+    bytes.get(12..12 + tables * 16).is_some_and(|records| {
+        records
+            .chunks_exact(16)
+            .all(|record| record[..4].iter().all(|c| (0x20..=0x7E).contains(c)))
+    })
+}
+
+// Whitespace first is fine, a prefix may end part way through a character
+fn is_json(bytes: &[u8]) -> bool {
+    let starts_like_json = bytes
+        .iter()
+        .find(|c| !c.is_ascii_whitespace())
+        .is_some_and(|c| *c == b'{' || *c == b'[');
+    starts_like_json
+        && match std::str::from_utf8(bytes) {
+            Ok(_) => true,
+            Err(e) => e.error_len().is_none(),
+        }
+}
+
+// A TrueType collection starting at bytes[0]
+fn is_font_collection(bytes: &[u8]) -> bool {
+    let Some(&[b't', b't', b'c', b'f', 0x00, major, _, _, f0, f1, f2, f3]) = bytes.get(..12) else {
+        return false;
+    };
+    let fonts = u32::from_be_bytes([f0, f1, f2, f3]);
+    (major == 1 || major == 2) && fonts > 0 && fonts <= MAX_COLLECTION_FONTS
+}
+
+// Random binary data often has a frame sync in it, so the frame after it has to be valid too
+fn find_mpeg_frame(bytes: &[u8], range: Range<usize>) -> Option<usize> {
+    range.into_iter().find(|&i| {
+        mpeg_frame_length(&bytes[i..]).is_some_and(|length| {
+            bytes
+                .get(i + length..)
+                .and_then(mpeg_frame_length)
+                .is_some()
+        })
+    })
+}
+
+pub fn get_scan_window() -> usize {
+    state().scan_window()
+}
+
+// How much of each asset is read while listing, enough for a preamble and the window after it
+pub fn get_prefix_length() -> usize {
+    PREAMBLE_ALLOWANCE + get_scan_window()
+}
+
+// Where the payload starts after Roblox's HTTP preamble, its response headers end with a blank line.
+// None if the end of the preamble couldn't be found
+fn payload_start(bytes: &[u8]) -> Option<usize> {
+    if bytes.starts_with(ROBLOX_HTTP_MAGIC) {
+        bytes_search(bytes, b"\r\n\r\n").map(|position| position + 4)
+    } else {
+        Some(0)
+    }
+}
+
+// The URL Roblox downloaded the asset from, on the preamble's first line after the magic. None for
+// assets without a preamble
+pub fn origin_url(bytes: &[u8]) -> Option<String> {
+    let rest = bytes.strip_prefix(ROBLOX_HTTP_MAGIC)?;
+    let line = &rest[..bytes_search(rest, b"\r\n")?];
+    let scheme_end = bytes_search(line, b"://")?;
+    // Length fields come before the scheme
+    let start = line[..scheme_end]
+        .iter()
+        .rposition(|byte| !byte.is_ascii_alphabetic())
+        .map_or(0, |position| position + 1);
+    if start == scheme_end {
+        return None;
+    }
+    let url = &line[start..];
+    let end = url
+        .iter()
+        .position(|byte| byte.is_ascii_control() || *byte == b' ')
+        .unwrap_or(url.len());
+    std::str::from_utf8(&url[..end]).ok().map(str::to_owned)
+}
+
+// Positions a header is allowed to start at
+fn scan_range(bytes: &[u8]) -> Range<usize> {
+    let window = get_scan_window();
+    match payload_start(bytes) {
+        Some(start) if window > 0 => start.min(bytes.len())..(start + window).min(bytes.len()),
+        _ => 0..bytes.len(), // Look through everything rather than miss the header
+    }
+}
+
+// What's looked for in the bytes, the header's name unless it has a magic that isn't text
+fn header_bytes(header: &str) -> &[u8] {
+    match header {
+        "PNG" => PNG_SIGNATURE,
+        "KTX" => &KTX1_IDENTIFIER,
+        KTX2_HEADER => &KTX2_IDENTIFIER,
+        _ => header.as_bytes(),
+    }
+}
+
+// Where the header starts in the bytes
+fn find_header_position(bytes: &[u8], header: &str) -> Option<usize> {
+    if MESH_HEADERS.contains(&header) || FONT_HEADERS.contains(&header) || header == JSON_HEADER {
+        let start = payload_start(bytes)?;
+        let payload = bytes.get(start..)?;
+        let found = match header {
+            TRUETYPE_HEADER => is_sfnt(payload, SFNT_VERSION),
+            "OTTO" => is_sfnt(payload, b"OTTO"),
+            "ttcf" => is_font_collection(payload),
+            JSON_HEADER => is_json(payload),
+            _ => payload.starts_with(header.as_bytes()),
+        };
+        return found.then_some(start);
+    }
+
+    let range = scan_range(bytes);
+    if header == MPEG_HEADER {
+        find_mpeg_frame(bytes, range)
     } else {
-        false
+        // Starting inside the window is enough, it can end after it
+        let needle = header_bytes(header);
+        let end = (range.end + needle.len()).min(bytes.len());
+        bytes_search(&bytes[range.start..end], needle).map(|position| position + range.start)
     }
 }
 
-fn find_header(category: Category, bytes: &[u8]) -> Result<String, String> {
+// The header that starts first, as whatever comes later is usually embedded in it.
+// Frame syncs are only used when no other header is found, a real header is more reliable
+fn find_earliest_header<'a>(bytes: &[u8], headers: &'a [String]) -> Option<&'a String> {
+    let earliest = headers
+        .iter()
+        .filter(|header| !header.is_empty() && *header != MPEG_HEADER)
+        .filter_map(|header| Some((find_header_position(bytes, header)?, header)))
+        .min_by_key(|(position, _)| *position) // The first one listed if they start at the same place
+        .map(|(_, header)| header);
+
+    earliest.or_else(|| {
+        headers
+            .iter()
+            .find(|header| *header == MPEG_HEADER && find_header_position(bytes, header).is_some())
+    })
+}
+
+// The header to list an asset under out of a tab's headers. None if another category's header starts
+// first, so an rbxm embedding an OGG isn't listed as a sound as well
+pub fn find_listing_header<'a>(bytes: &[u8], headers: &'a [String]) -> Option<&'a String> {
+    let all_headers = get_headers(&Category::All);
+    let earliest = find_earliest_header(bytes, &all_headers)?;
+    headers.iter().find(|header| *header == earliest)
+}
+
+fn find_header(category: Category, bytes: &[u8]) -> Result<String, Error> {
     // Get the header for the current category
     let headers = get_headers(&category);
 
-    // iterate through headers to find the correct one for this file.
-    for header in headers {
-        if bytes_contains(bytes, header.as_bytes()) {
-            return Ok(header.to_owned());
-        }
+    match find_earliest_header(bytes, &headers) {
+        Some(header) => Ok(header.to_owned()),
+        None => Err(Error::HeaderNotFound),
     }
-    Err("Headers not found in bytes".to_owned())
 }
 
 fn extract_bytes(header: &str, bytes: Vec<u8>) -> Vec<u8> {
     // Set offset depending on header
     let offset: usize = match header {
-        "PNG" => 1,
-        "KTX" => 1,
         "WEBP" => 8,
         _ => 0,
     };
 
     // Find the header in the file
-    if let Some(mut index) = bytes_search(&bytes, header.as_bytes()) {
+    if let Some(index) = find_header_position(&bytes, header) {
         // Found the header, extract from the bytes
-        index -= offset; // Apply offset
-                         // Return all the bytes after the found header index
-        return bytes[index..].to_vec();
+        let start = match index.checked_sub(offset) {
+            Some(start) => start, // Apply offset
+            None => {
+                // Nothing before the header (e.g. no leading metadata), so the offset doesn't fit
+                log_warn!(
+                    "{} header found at {}, before its offset of {}, extracting from the header",
+                    header,
+                    index,
+                    offset
+                );
+                index
+            }
+        };
+        // Up to where the asset ends, anything after it is Roblox's
+        let end =
+            asset_end::find_end(header, &bytes[start..]).map_or(bytes.len(), |end| start + end);
+        return bytes[start..end].to_vec();
     }
-    log_warn!("Failed to extract a file!");
+    log_warn!(
+        "{}: Failed to extract a file, {} not found",
+        ErrorCode::HeaderNotFound,
+        header
+    );
     // Return bytes instead if this fails
     bytes
 }
 
-fn create_no_files(locale: &FluentBundle<Arc<FluentResource>>) -> AssetInfo {
-    AssetInfo {
-        name: locale::get_message(locale, "no-files", None),
-        _size: 0,
-        last_modified: None,
-        from_file: false,
-        from_sql: false,
-        category: Category::All,
+// The decompressed start of an asset, enough for detecting its headers
+fn read_asset_prefix(asset: &AssetInfo) -> Result<Vec<u8>, Error> {
+    let prefix = read_from_sources(asset, |source| match source {
+        Source::File => {
+            cache_directory::read_asset_prefix(&asset.in_source(source), get_prefix_length())
+        }
+        Source::Sql => {
+            sql_database::read_asset_prefix(&asset.in_source(source), get_prefix_length())
+        }
+    })?;
+
+    match decompress_prefix(&prefix, get_prefix_length()) {
+        Some(prefix) if detect_format(&prefix).is_none() => {
+            Ok(inflate_prefix(&prefix, get_prefix_length()).unwrap_or(prefix))
+        }
+        Some(prefix) => Ok(prefix),
+        None => {
+            // Too little of the frame to decode anything, the same as listing does
+            let mut bytes = read_asset(asset)?;
+            bytes.truncate(get_prefix_length());
+            Ok(bytes)
+        }
+    }
+}
+
+// The category and header listing would give these bytes, None if they don't belong on the tab
+fn classify(prefix: &[u8], category: Category) -> Option<(Category, Option<String>)> {
+    let headers = get_headers(&category);
+    let header = find_listing_header(prefix, &headers)?;
+
+    if category == Category::All {
+        // Determine category if all, the header shown is the one that decided it
+        Some(match detect_format(prefix) {
+            Some((category, header)) => (category, Some(header)),
+            None => (Category::All, None),
+        })
+    } else {
+        Some((category, Some(header.clone())))
+    }
+}
+
+// The asset as it's stored, which may be compressed
+fn read_stored_asset(asset: &AssetInfo) -> Result<Vec<u8>, Error> {
+    read_from_sources(asset, |source| match source {
+        Source::File => cache_directory::read_asset(&asset.in_source(source)),
+        Source::Sql => sql_database::read_asset(&asset.in_source(source)),
+    })
+}
+
+fn read_asset(asset: &AssetInfo) -> Result<Vec<u8>, Error> {
+    Ok(decompress(read_stored_asset(asset)?)?)
+}
+
+pub fn get_decompressed_limit() -> u64 {
+    config::get_config_u64("max_decompressed_size")
+        .unwrap_or(DEFAULT_DECOMPRESSED_LIMIT)
+        .saturating_mul(1024 * 1024)
+}
+
+// How a payload is stored compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Gzip,
+    Deflate, // Raw, without a magic, so it's only tried when no header is found
+}
+
+impl Compression {
+    // From the magic the payload starts with
+    fn detect(bytes: &[u8]) -> Option<Compression> {
+        if bytes.starts_with(&ZSTD_MAGIC) {
+            Some(Compression::Zstd)
+        } else if bytes.starts_with(&GZIP_MAGIC) {
+            Some(Compression::Gzip)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Compression::Zstd => "zstd",
+            Compression::Gzip => "gzip",
+            Compression::Deflate => "deflate",
+        }
+    }
+}
+
+// Streams the payload so no more than limit bytes are ever held. The bytes decoded before an error
+// are left in the buffer
+fn decode(
+    compression: Compression,
+    bytes: &[u8],
+    limit: u64,
+    buffer: &mut Vec<u8>,
+) -> Result<(), std::io::Error> {
+    match compression {
+        Compression::Zstd => zstd::stream::Decoder::new(bytes)?
+            .take(limit)
+            .read_to_end(buffer),
+        Compression::Gzip => flate2::read::MultiGzDecoder::new(bytes)
+            .take(limit)
+            .read_to_end(buffer),
+        Compression::Deflate => flate2::read::DeflateDecoder::new(bytes)
+            .take(limit)
+            .read_to_end(buffer),
+    }
+    .map(|_| ())
+}
+
+// Bytes without a header that inflate to something with one, None otherwise. A cut off stream is
+// kept up to where it ends, the same as a prefix
+fn inflate(bytes: &[u8], limit: u64) -> Option<Vec<u8>> {
+    let mut buffer = Vec::new();
+    let _ = decode(Compression::Deflate, bytes, limit, &mut buffer);
+    detect_format(&buffer).is_some().then_some(buffer)
+}
+
+// The start of an asset where no header was found, inflated if it's raw deflate
+pub fn inflate_prefix(bytes: &[u8], length: usize) -> Option<Vec<u8>> {
+    inflate(bytes, length as u64)
+}
+
+fn decompressed_too_large(limit: u64) -> std::io::Error {
+    error_code::with_code(
+        ErrorCode::TooLarge,
+        std::io::ErrorKind::FileTooLarge,
+        format!(
+            "Asset decompresses to more than the limit of {}",
+            format_size(limit)
+        ),
+    )
+}
+
+// Decompress zstd, gzip and raw deflate payloads, other payloads are returned as they are
+fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, std::io::Error> {
+    decompress_with(bytes).map(|(bytes, _)| bytes)
+}
+
+// The same as decompress(), along with what it was compressed with
+fn decompress_with(bytes: Vec<u8>) -> Result<(Vec<u8>, Option<Compression>), std::io::Error> {
+    decompress_within(bytes, get_decompressed_limit())
+}
+
+// Errors if it decompresses to more than limit bytes
+fn decompress_within(
+    bytes: Vec<u8>,
+    limit: u64,
+) -> Result<(Vec<u8>, Option<Compression>), std::io::Error> {
+    // One byte over the limit to tell a payload of exactly the limit apart from a bigger one
+    let too_large = |buffer: &Vec<u8>| buffer.len() as u64 > limit;
+
+    let Some(compression) = Compression::detect(&bytes) else {
+        if detect_format(&bytes).is_some() {
+            return Ok((bytes, None));
+        }
+        return match inflate(&bytes, limit.saturating_add(1)) {
+            Some(buffer) if too_large(&buffer) => Err(decompressed_too_large(limit)),
+            Some(buffer) => Ok((buffer, Some(Compression::Deflate))),
+            None => Ok((bytes, None)),
+        };
+    };
+
+    // Don't start if the frame already says it's too big
+    if compression == Compression::Zstd {
+        if let Ok(Some(size)) = zstd::zstd_safe::get_frame_content_size(&bytes) {
+            if size > limit {
+                return Err(decompressed_too_large(limit));
+            }
+        }
+    }
+
+    let mut buffer = Vec::new();
+    match decode(compression, &bytes, limit.saturating_add(1), &mut buffer) {
+        Ok(()) if too_large(&buffer) => Err(decompressed_too_large(limit)),
+        Ok(()) => Ok((buffer, Some(compression))),
+        Err(e) => {
+            log_warn!("Failed to decompress asset: {}", e);
+            Ok((bytes, None)) // Return bytes instead if this fails
+        }
     }
 }
 
-fn read_asset(asset: &AssetInfo) -> Result<Vec<u8>, std::io::Error> {
-    if asset.from_file {
-        cache_directory::read_asset(asset)
-    } else if asset.from_sql {
-        sql_database::read_asset(asset)
+// Decompress the start of a payload for header detection while listing.
+// Returns None if the payload is compressed but the prefix is too short to decode anything, the caller should read more of it.
+fn decompress_prefix(bytes: &[u8], length: usize) -> Option<Vec<u8>> {
+    let Some(compression) = Compression::detect(bytes) else {
+        return Some(bytes.to_vec()); // Raw deflate is tried by whatever finds no header, see inflate_prefix()
+    };
+
+    let mut buffer = Vec::new();
+    // The prefix is usually a cut off frame, so this errors once the input runs out. The bytes decoded so far are kept.
+    let _ = decode(compression, bytes, length as u64, &mut buffer);
+
+    if buffer.is_empty() {
+        None
+    } else {
+        Some(buffer)
+    }
+}
+
+// Get the size of the payload once decompressed, zstd frames usually declare it in the frame header.
+// Returns the size and whether it is approximate (falls back to the stored size)
+fn get_actual_size(bytes: &[u8], stored_size: u64) -> (u64, bool) {
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        match zstd::zstd_safe::get_frame_content_size(bytes) {
+            Ok(Some(size)) => (size, false),
+            _ => (stored_size, true), // Size not declared in the frame
+        }
+    } else if bytes.starts_with(&GZIP_MAGIC) {
+        (stored_size, true) // Only the end of the stream says how big it is
     } else {
-        Err(std::io::Error::new(
-            std::io::ErrorKind::InvalidInput,
-            "Not from_file or from_sql",
-        ))
+        (stored_size, false)
     }
 }
 
@@ -187,264 +1053,1568 @@ pub fn resolve_path(directory: &str) -> String {
 
 // Function to get temp directory, create it if it doesn't exist
 pub fn get_temp_dir() -> PathBuf {
-    return TEMP_DIRECTORY.lock().unwrap().clone();
+    state().temp_directory().clone()
 }
 
-pub fn clear_cache() {
-    let running = {
-        let task = TASK_RUNNING.lock().unwrap();
-        *task
-    };
+pub fn clear_cache() -> Result<(), clear::ClearError> {
+    read_only::check()?;
+    clear::check_paths()?; // Refused before the task starts so the GUI can say why
+
     // Stop multiple threads from running
-    if !running {
-        thread::spawn(move || {
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = true; // Stop other threads from running
-            }
+    if start_task() {
+        state::spawn(move || {
             // Get locale for localised status messages
             let locale = locale::get_locale(None);
 
-            sql_database::clear_cache(&locale);
-            cache_directory::clear_cache(&locale);
+            let _ = clear::clear_everything(&locale); // Already checked above
+
+            if get_cancel_task() {
+                // Some of it is still there, the status already says how far it got
+                finish_task();
+                refresh(get_list_category(), false, false);
+                return;
+            }
 
             // Clear the file list for visual feedback to the user that the files are actually deleted
             clear_file_list();
+            set_list_state(ListState::Empty);
 
-            update_file_list(create_no_files(&locale), false);
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = false; // Allow other threads to run again
-            }
+            finish_task(); // Allow other threads to run again
             update_status(locale::get_message(&locale, "idling", None)); // Set the status back
         });
     }
+    Ok(())
+}
+
+fn stop_listing(state: &ExtractorState) {
+    *state.stop_list_running.lock().unwrap() = true;
+    sql_database::interrupt_listing();
 }
 
-pub fn refresh(category: Category, cli_list_mode: bool, yield_for_thread: bool) {
-    // Get headers for use later
-    let handle = thread::spawn(move || {
+// Returns what was listed if yield_for_thread is set, the GUI gets it from summary::get_last_refresh()
+pub fn refresh(
+    category: Category,
+    cli_list_mode: bool,
+    yield_for_thread: bool,
+) -> Option<RefreshSummary> {
+    let state = state();
+    // Stop the running refresh straight away and drop its rows, so the previous category is never shown
+    if get_list_task_running() {
+        stop_listing(&state);
+    }
+    let generation = {
+        let mut list_generation = state.list_generation.lock().unwrap();
+        clear_file_list();
+        *state.list_state.lock().unwrap() = ListState::Listing;
+        *state.list_category.lock().unwrap() = category;
+        *list_generation += 1;
+        *list_generation
+    };
+
+    let handle = state::spawn(move || {
         // Get locale for localised status messages
         let locale = locale::get_locale(None);
         // This loop here is to make it wait until it is not running, and to set the STOP_LIST_RUNNING to true if it is running to make the other thread
         loop {
-            let running = {
-                let task = LIST_TASK_RUNNING.lock().unwrap();
-                *task
-            };
-            if !running {
-                break; // Break if not running
-            } else {
-                let mut stop = STOP_LIST_RUNNING.lock().unwrap(); // Tell the other thread to stop
-                *stop = true;
+            // Switching tabs quickly queues several refreshes, only the newest needs to run
+            if *state.list_generation.lock().unwrap() != generation {
+                return None;
+            }
+
+            {
+                let mut task = state.list_task_running.lock().unwrap();
+                if !*task {
+                    *task = true; // Tell other threads that a task is running
+                    let mut stop = state.stop_list_running.lock().unwrap();
+                    *stop = false; // Disable the stop, otherwise this thread will stop!
+                    break;
+                }
             }
+            stop_listing(&state); // Tell the other thread to stop
             thread::sleep(std::time::Duration::from_millis(10)); // Sleep for a bit to not be CPU intensive
         }
-        {
-            let mut task = LIST_TASK_RUNNING.lock().unwrap();
-            *task = true; // Tell other threads that a task is running
-            let mut stop = STOP_LIST_RUNNING.lock().unwrap();
-            *stop = false; // Disable the stop, otherwise this thread will stop!
-        }
 
         clear_file_list(); // Only list the files on the current tab
+        reset_progress();
+        let start = std::time::Instant::now();
+
+        let database = sql_database::refresh(category, cli_list_mode, &locale);
+        // Reading the cache directory can take a while to even start, don't if the category has changed
+        let cache_directory = if !get_stop_list_running() {
+            cache_directory::refresh(category, cli_list_mode, &locale)
+        } else {
+            Default::default()
+        };
+
+        let stopped = get_stop_list_running();
+        if stopped {
+            // Nothing is added after the stop, the next refresh would clear it but there may not be one
+            clear_file_list();
+        } else {
+            merge_file_list();
+            // Hashing takes a while, so it's only done when turned on
+            if config::get_config_bool("merge_duplicate_sources").unwrap_or(false) {
+                update_status(locale::get_message(&locale, "merging-duplicates", None));
+                let merged = merge_duplicates();
+                if merged > 0 {
+                    log_info!(
+                        "Merged {} assets listed under other names in both sources",
+                        merged
+                    );
+                }
+            }
+            sort_by_source();
+            let (sort_key, ascending) = get_sort();
+            sort_file_list(sort_key, ascending);
+            update_list_state();
+            // With --stable-order nothing was printed while listing
+            if cli_list_mode && get_stable_order() {
+                for asset in get_file_list() {
+                    println!("{}", list_line(&asset));
+                }
+            }
+        }
+        let summary = RefreshSummary {
+            category,
+            database,
+            cache_directory,
+            stopped,
+            duration_ms: start.elapsed().as_millis() as u64,
+        };
+        log_info!("{}", summary);
+        {
+            let mut task = state.list_task_running.lock().unwrap();
+            *task = false; // Allow other threads to run again
+        }
+        // The refresh that stopped this one has its own status
+        if !stopped {
+            summary::set_last_refresh(summary.clone());
+            update_status(locale::get_message(&locale, "idling", None)); // Set the status back
+        }
+        Some(summary)
+    });
+
+    if yield_for_thread {
+        // Will wait for the thread instead of quitting immediately
+        handle.join().ok().flatten()
+    } else {
+        None
+    }
+}
+
+// Detect the category and format of everything already listed again, e.g. after the headers have
+// changed, without listing again. Returns what changed if yield_for_thread is set
+pub fn reclassify(yield_for_thread: bool) -> Option<ReclassifySummary> {
+    let state = state();
+    {
+        let mut task = state.list_task_running.lock().unwrap();
+        if *task {
+            return None; // Still listing, which detects everything anyway
+        }
+        *task = true;
+        *state.stop_list_running.lock().unwrap() = false;
+    }
+    reset_progress();
+
+    let handle = state::spawn(move || {
+        let locale = locale::get_locale(None);
+        let start = std::time::Instant::now();
+        let category = get_list_category();
+        let assets = get_file_list();
+        let total = assets.len();
+
+        let mut summary = ReclassifySummary {
+            category,
+            checked: 0,
+            moved: 0,
+            removed: 0,
+            reformatted: 0,
+            errors: 0,
+            stopped: false,
+            duration_ms: 0,
+        };
+        let mut args = FluentArgs::new();
+        let mut reclassified = Vec::with_capacity(total);
+
+        for (i, asset) in assets.into_iter().enumerate() {
+            // Switching tabs or refreshing stops this like it stops listing
+            if get_stop_list_running() {
+                summary.stopped = true;
+                break;
+            }
 
-        sql_database::refresh(category, cli_list_mode, &locale);
-        cache_directory::refresh(category, cli_list_mode, &locale);
+            args.set("item", i);
+            args.set("total", total);
+            update_progress(i as f32 / total as f32);
+            update_status(locale::get_message(
+                &locale,
+                "reclassifying-files",
+                Some(&args),
+            ));
+
+            // Nothing to read, evicted rows stay as they are
+            if asset.evicted {
+                reclassified.push(asset);
+                continue;
+            }
+            summary.checked += 1;
+
+            let prefix = match read_asset_prefix(&asset) {
+                Ok(prefix) => prefix,
+                Err(e) => {
+                    log_warn!("Failed to re-classify {}: {}", asset.name, e);
+                    summary.errors += 1;
+                    reclassified.push(asset);
+                    continue;
+                }
+            };
 
+            match classify(&prefix, category) {
+                Some((new_category, header)) => {
+                    if new_category != asset.category {
+                        summary.moved += 1;
+                    } else if header != asset.header {
+                        summary.reformatted += 1;
+                    }
+                    reclassified.push(AssetInfo {
+                        category: new_category,
+                        header,
+                        ..asset
+                    });
+                }
+                None => {
+                    // Belongs on another tab now
+                    summary.moved += 1;
+                    summary.removed += 1;
+                }
+            }
+        }
+
+        {
+            // Checked while holding the list so a refresh that took over isn't overwritten
+            let mut file_list = state.file_list.lock().unwrap();
+            if get_stop_list_running() {
+                summary.stopped = true;
+            } else if !summary.stopped {
+                *file_list = reclassified;
+                file_list_changed(&state);
+                *state.list_state.lock().unwrap() = if file_list.is_empty() {
+                    ListState::Empty
+                } else {
+                    ListState::Listed
+                };
+            }
+        }
+
+        summary.duration_ms = start.elapsed().as_millis() as u64;
+        log_info!("{}", summary);
         {
-            let mut task = LIST_TASK_RUNNING.lock().unwrap();
+            let mut task = state.list_task_running.lock().unwrap();
             *task = false; // Allow other threads to run again
         }
-        update_status(locale::get_message(&locale, "idling", None)); // Set the status back
+        if !summary.stopped {
+            update_status(summary.message(&locale));
+        }
+        summary
     });
 
     if yield_for_thread {
         // Will wait for the thread instead of quitting immediately
-        let _ = handle.join();
+        handle.join().ok()
+    } else {
+        None
     }
 }
 
+// An asset with anything before its header removed, and what was found out about it on the way
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedAsset {
+    pub bytes: Vec<u8>,
+    pub header: Option<String>, // None if no header was found, the bytes are as they were read
+    pub suggested_extension: Option<&'static str>,
+    pub compression: Option<Compression>, // How it was stored
+    pub verification: Verification, // Against the digest in the preamble, checked before anything is cut off
+}
+
+impl ExtractedAsset {
+    fn from_bytes(category: Category, bytes: Vec<u8>, compression: Option<Compression>) -> Self {
+        let verification = verify::check(&bytes);
+        match find_header(category, &bytes) {
+            Ok(header) => ExtractedAsset {
+                bytes: extract_bytes(&header, bytes), // Extract between the header to the end of the file.
+                suggested_extension: Some(get_extension(&header)),
+                header: Some(header),
+                compression,
+                verification,
+            },
+            Err(_) => ExtractedAsset {
+                bytes, // No header was found.
+                header: None,
+                suggested_extension: None,
+                compression,
+                verification,
+            },
+        }
+    }
+
+    pub fn mime_type(&self) -> &'static str {
+        get_mime_type(self.header.as_deref().unwrap_or_default())
+    }
+}
+
+pub fn extract_asset(asset: &AssetInfo) -> Result<ExtractedAsset, Error> {
+    let (bytes, compression) = decompress_with(read_stored_asset(asset)?)?;
+    Ok(ExtractedAsset::from_bytes(
+        asset.category,
+        bytes,
+        compression,
+    ))
+}
+
+// The path written to, which is different to destination if it was renamed. None if it was skipped
 pub fn extract_to_file(
     asset: AssetInfo,
     destination: PathBuf,
     add_extension: bool,
-) -> Result<PathBuf, std::io::Error> {
-    let mut destination = destination.clone(); // Get own mutable destination
+    overwrite: OverwritePolicy,
+    convert_to_png: bool,
+    convert_to_wav: bool,
+    verify: bool,
+) -> Result<Option<PathBuf>, Error> {
+    let mut extracted = extract_asset(&asset)?;
+    if verify && extracted.verification == Verification::Mismatch {
+        return Err(Error::Corrupt);
+    }
+    if convert_to_png {
+        png_convert::convert(&asset.name, &mut extracted);
+    }
+    if convert_to_wav {
+        let processing = extract_options::get_audio_processing();
+        wav_convert::convert(&asset.name, &mut extracted, processing, &mut None);
+    }
+    write_extracted(&asset, extracted, destination, add_extension, overwrite)
+}
 
-    let bytes = read_asset(&asset)?;
-
-    let header = find_header(asset.category, &bytes);
-    let extracted_bytes = match header {
-        Ok(header) => {
-            // Add the extension if needed
-            if add_extension {
-                let extension = match header.as_str() {
-                    "OggS" => "ogg",
-                    "ID3" => "mp3",
-                    "PNG" => "png",
-                    "WEBP" => "webp",
-                    "KTX" => "ktx",
-                    "<roblox!" => "rbxm",
-                    _ => "ogg",
-                };
+// "name (1).ogg" for "name.ogg"
+fn numbered_path(path: &Path, number: usize) -> PathBuf {
+    let mut file_name = path.file_stem().unwrap_or_default().to_owned();
+    file_name.push(format!(" ({number})"));
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name)
+}
+
+// Files are only created if nothing is there, so two workers can't both take the same free name
+fn create_new(path: &Path) -> Result<Option<fs::File>, std::io::Error> {
+    match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+    {
+        Ok(file) => Ok(Some(file)),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+        Err(e) => Err(e),
+    }
+}
 
-                destination.set_extension(extension);
+// The file to write to and its path, None if it should be skipped
+fn create_destination(
+    destination: &Path,
+    overwrite: OverwritePolicy,
+) -> Result<Option<(fs::File, PathBuf)>, std::io::Error> {
+    match overwrite {
+        OverwritePolicy::Overwrite => Ok(Some((
+            fs::File::create(destination)?,
+            destination.to_path_buf(),
+        ))),
+        OverwritePolicy::Skip => {
+            Ok(create_new(destination)?.map(|file| (file, destination.to_path_buf())))
+        }
+        OverwritePolicy::Rename => {
+            if let Some(file) = create_new(destination)? {
+                return Ok(Some((file, destination.to_path_buf())));
+            }
+            for number in 1.. {
+                let path = numbered_path(destination, number);
+                if let Some(file) = create_new(&path)? {
+                    return Ok(Some((file, path)));
+                }
             }
+            unreachable!()
+        }
+    }
+}
+
+// Write an extracted asset to a file, None if it was skipped
+fn write_extracted(
+    asset: &AssetInfo,
+    extracted: ExtractedAsset,
+    destination: PathBuf,
+    add_extension: bool,
+    overwrite: OverwritePolicy,
+) -> Result<Option<PathBuf>, Error> {
+    let mut destination = destination.clone(); // Get own mutable destination
 
-            extract_bytes(&header, bytes.clone()) // Extract between the header to the end of the file.
+    // Add the extension if needed
+    if add_extension {
+        if let Some(extension) = extracted.suggested_extension {
+            destination = template::append_extension(destination, extension);
         }
-        Err(_) => bytes.clone(), // No header was found.
-    };
+    }
 
-    match fs::write(destination.clone(), extracted_bytes) {
-        Ok(_) => (),
-        Err(e) => log_error!("Error writing file: {}", e),
+    // The kind is kept, so a full disk is still StorageFull
+    let Some((mut file, destination)) = create_destination(&destination, overwrite)
+        .map_err(|e| error_code::with_code(ErrorCode::Write, e.kind(), e))?
+    else {
+        log_info!(
+            "Skipped {}, something is already there",
+            destination.display()
+        );
+        return Ok(None);
     };
+    file.write_all(&extracted.bytes)
+        .map_err(|e| error_code::with_code(ErrorCode::Write, e.kind(), e))?;
+    drop(file); // Closed before setting the modification time, writing would change it
+    history::record(&asset.name, &extracted.bytes, &destination);
 
     if let Some(sys_modified_time) = asset.last_modified {
         let modified_time = filetime::FileTime::from_system_time(sys_modified_time);
         match filetime::set_file_times(&destination, modified_time, modified_time) {
             Ok(_) => (),
-            Err(e) => log_error!("Failed to write file modification time {}", e),
+            // The file itself was written, so it still counts as extracted
+            Err(e) => log_warn!(
+                "Failed to set the modification time of {}: {}",
+                destination.display(),
+                e
+            ),
         };
     }
 
-    Ok(destination)
+    Ok(Some(destination))
+}
+
+// For names and aliases shown in a single line, e.g. a list row. Control characters are removed and
+// anything past alias_display_length characters is replaced with an ellipsis, the full text should be
+// shown somewhere else such as a tooltip
+pub fn display_text(text: &str) -> String {
+    let max_length = config::get_config_u64("alias_display_length").unwrap_or(64) as usize;
+    let mut chars = text.chars().filter(|c| !c.is_control());
+    let mut shown: String = chars.by_ref().take(max_length.max(1)).collect();
+    if chars.next().is_some() {
+        shown.push('…');
+    }
+    shown
+}
+
+// Binary units, with the decimal separator of the language in use
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit_idx = 0;
+
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+    let number = format!("{size:.1}").replace('.', &locale::get_decimal_separator());
+    format!("{} {}", number, UNITS[unit_idx])
+}
+
+// e.g. "500", "100K" or "1.5M", in binary units like format_size
+pub fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (amount, unit) = size.split_at(split);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("{size} doesn't start with a number"))?;
+
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Unknown unit {}, use B, K, M or G", unit.trim())),
+    };
+    Ok((amount * multiplier as f64) as u64)
+}
+
+pub fn get_extension(header: &str) -> &'static str {
+    match header {
+        "OggS" => "ogg",
+        "ID3" => "mp3",
+        MPEG_HEADER => "mp3",
+        "RIFF" => "wav", // Only ever converted to, it's not looked for
+        "PNG" => "png",
+        "WEBP" => "webp",
+        "KTX" => "ktx",
+        KTX2_HEADER => "ktx2",
+        "<roblox!" => "rbxm",
+        header if MESH_HEADERS.contains(&header) => "mesh",
+        TRUETYPE_HEADER => "ttf",
+        "OTTO" => "otf",
+        "ttcf" => "ttc",
+        JSON_HEADER => "json",
+        _ => "ogg",
+    }
+}
+
+// e.g. "PNG (.png)", with the extension extracting would add
+pub fn format_header(header: &str) -> String {
+    format!("{header} (.{})", get_extension(header))
+}
+
+pub fn get_mime_type(header: &str) -> &'static str {
+    match header {
+        "OggS" => "audio/ogg",
+        "ID3" | MPEG_HEADER => "audio/mpeg",
+        "RIFF" => "audio/wav",
+        "PNG" => "image/png",
+        "WEBP" => "image/webp",
+        "KTX" => "image/ktx",
+        KTX2_HEADER => "image/ktx2",
+        TRUETYPE_HEADER => "font/ttf",
+        "OTTO" => "font/otf",
+        "ttcf" => "font/collection",
+        JSON_HEADER => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+// Base64 of the extracted asset, optionally as a data URI. Refuses assets bigger than max_size.
+pub fn extract_asset_to_base64(
+    asset: AssetInfo,
+    data_uri: bool,
+    max_size: Option<u64>,
+) -> Result<String, Error> {
+    let extracted = extract_asset(&asset)?;
+    let mime_type = extracted.mime_type();
+    let extracted_bytes = extracted.bytes;
+
+    if let Some(max_size) = max_size {
+        if extracted_bytes.len() as u64 > max_size {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::FileTooLarge,
+                format!(
+                    "{} bytes is over the {max_size} byte limit",
+                    extracted_bytes.len()
+                ),
+            )
+            .into());
+        }
+    }
+
+    let encoded = BASE64_STANDARD.encode(extracted_bytes);
+    if data_uri {
+        Ok(format!("data:{mime_type};base64,{encoded}"))
+    } else {
+        Ok(encoded)
+    }
+}
+
+// SHA-256 of what extracting the asset gives, the same as {hash} in templates and the extraction
+// history. Hashing means reading the whole asset, so it's only done when asked for and then kept
+// in the file list until the next refresh
+pub fn compute_hash(asset: &AssetInfo) -> Result<String, Error> {
+    if let Some(hash) = &asset.hash {
+        return Ok(hash.clone());
+    }
+    let hash = history::hash_bytes(&extract_asset(asset)?.bytes);
+
+    let state = state();
+    for list in [&state.file_list, &state.filtered_file_list] {
+        for listed in list.lock().unwrap().iter_mut() {
+            if listed.name == asset.name && listed.category == asset.category {
+                listed.hash = Some(hash.clone());
+            }
+        }
+    }
+    file_list_changed(&state);
+    Ok(hash)
+}
+
+// For when only the bytes are needed
+pub fn extract_asset_to_bytes(asset: AssetInfo) -> Result<Vec<u8>, Error> {
+    extract_asset(&asset).map(|extracted| extracted.bytes)
+}
+
+// Where extracted files are written
+enum ExtractTarget {
+    Folder(PathBuf),
+    Archive(PathBuf), // A ZIP file
+}
+
+// Returns what was extracted if yield_for_thread is set
+pub fn extract_dir(
+    destination: PathBuf,
+    category: Category,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    extract_list(
+        ExtractTarget::Folder(destination),
+        category,
+        None,
+        yield_for_thread,
+        overrides,
+    )
+}
+
+// The same as extract_dir, into a ZIP file
+pub fn extract_dir_to_archive(
+    archive: PathBuf,
+    category: Category,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    extract_list(
+        ExtractTarget::Archive(archive),
+        category,
+        None,
+        yield_for_thread,
+        overrides,
+    )
+}
+
+// Extract some of the listed assets, e.g. a group in the file list
+pub fn extract_assets(
+    destination: PathBuf,
+    category: Category,
+    assets: Vec<AssetInfo>,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    extract_list(
+        ExtractTarget::Folder(destination),
+        category,
+        Some(assets),
+        yield_for_thread,
+        overrides,
+    )
+}
+
+// Options and templates for each category, as they depend on each asset's category
+type ExtractSettings = (
+    HashMap<Category, ExtractOptions>,
+    HashMap<Category, Template>,
+);
+
+// None if the template is invalid, nothing should be written then
+fn prepare_extraction(overrides: &ExtractOverrides) -> Option<ExtractSettings> {
+    let options: HashMap<Category, ExtractOptions> = Category::iter()
+        .map(|category| {
+            (
+                category,
+                extract_options::get_options(category).with_overrides(overrides),
+            )
+        })
+        .collect();
+
+    let mut templates: HashMap<Category, Template> = HashMap::new();
+    for (category, options) in &options {
+        match extract_options::resolve_template(options, overrides) {
+            Ok(template) => {
+                templates.insert(*category, template);
+            }
+            Err(e) => {
+                log_error!("Invalid extraction template: {}", e);
+                let mut args = FluentArgs::new();
+                args.set("error", e);
+                update_status(locale::get_message(
+                    &locale::get_locale(None),
+                    "error-invalid-template",
+                    Some(&args),
+                ));
+                return None;
+            }
+        }
+    }
+
+    Some((options, templates))
+}
+
+// Set the status to confirm to the user that everything has finished
+fn update_extracted_status(summary: &ExtractSummary) {
+    summary::set_last_extract_failures(summary);
+    let locale = locale::get_locale(None);
+    if summary.unreachable {
+        let mut args = FluentArgs::new();
+        args.set(
+            "directory",
+            cache_directory::get_cache_directory()
+                .to_string_lossy()
+                .to_string(),
+        );
+        update_status(locale::get_message(
+            &locale,
+            "error-cache-directory-unreachable",
+            Some(&args),
+        ));
+    } else {
+        update_status(summary.message(&locale));
+    }
+}
+
+// Extracts the whole file list if assets is None
+fn extract_list(
+    target: ExtractTarget,
+    category: Category,
+    assets: Option<Vec<AssetInfo>>,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    // Reject an invalid template before anything is written
+    let settings = prepare_extraction(&overrides)?;
+
+    // Stop multiple threads from running
+    if !start_task() {
+        return None;
+    }
+
+    let handle = state::spawn(move || {
+        let file_list = match assets {
+            Some(assets) => assets,
+            None => {
+                // User has configured it to refresh before extracting, callers that have just listed skip it
+                let refresh_first = overrides.refresh.unwrap_or_else(|| {
+                    config::get_config_bool("refresh_before_extract").unwrap_or(false)
+                });
+                if refresh_first {
+                    match list_for_task(category) {
+                        Some(file_list) => file_list,
+                        None => return list_replaced(category),
+                    }
+                } else {
+                    get_file_list()
+                }
+            }
+        };
+
+        let summary = extract_files(&target, category, file_list, &settings, &overrides);
+
+        finish_task(); // Allow other threads to run again
+        update_extracted_status(&summary);
+        summary
+    });
+
+    if yield_for_thread {
+        // Will wait for the thread instead of quitting immediately
+        handle.join().ok()
+    } else {
+        None
+    }
+}
+
+// Lists category for a task and returns the list, None if a refresh started by the user replaced it
+// first. Listing again would take the list away from the user's refresh, e.g. for another tab.
+fn list_for_task(category: Category) -> Option<Vec<AssetInfo>> {
+    refresh(category, false, true).filter(|summary| !summary.stopped)?;
+    // Another refresh may start between listing and taking the list
+    let generation = get_list_generation();
+    let file_list = get_file_list();
+    (get_list_generation() == generation).then_some(file_list)
+}
+
+// Puts back a list that was taken before listing another category, unless the user has refreshed since
+fn restore_file_list(category: Category, file_list: Vec<AssetInfo>, generation: u64) {
+    let state = state();
+    // Held so a refresh can't start while it's put back
+    let list_generation = state.list_generation.lock().unwrap();
+    if *list_generation != generation {
+        return;
+    }
+    *state.list_category.lock().unwrap() = category;
+    *state.file_list.lock().unwrap() = file_list;
+    file_list_changed(&state);
+    update_list_state();
+}
+
+// Ends an extraction that didn't start because list_for_task() returned None
+fn list_replaced(category: Category) -> ExtractSummary {
+    log_warn!("The list was refreshed before extracting it, nothing was extracted");
+    finish_task();
+    update_status(locale::get_message(
+        &locale::get_locale(None),
+        "error-list-replaced",
+        None,
+    ));
+    ExtractSummary {
+        cancelled: true,
+        ..ExtractSummary::new(category)
+    }
+}
+
+// Does the extracting for extract_list and extract_all, on their thread
+fn extract_files(
+    target: &ExtractTarget,
+    category: Category,
+    file_list: Vec<AssetInfo>,
+    settings: &ExtractSettings,
+    overrides: &ExtractOverrides,
+) -> ExtractSummary {
+    let (options, templates) = settings;
+    let start = std::time::Instant::now();
+    let mut summary = ExtractSummary::new(category);
+
+    // Paths are relative to the archive's root when writing to one
+    let (destination, archive) = match target {
+        ExtractTarget::Folder(destination) => {
+            // Create directory if it doesn't exist
+            match fs::create_dir_all(destination) {
+                Ok(_) => (),
+                Err(e) => log_error!("Error creating directory: {}", e),
+            };
+            (destination.as_path(), None)
+        }
+        ExtractTarget::Archive(path) => match ArchiveWriter::create(path.clone()) {
+            Ok(archive) => (Path::new(""), Some(Mutex::new(archive))),
+            Err(e) => {
+                log_error!(
+                    "Failed to create archive {}: {}",
+                    path.display(),
+                    error_code::describe(&e)
+                );
+                summary.failed.push(ExtractFailure {
+                    asset: path.display().to_string(),
+                    code: ErrorCode::of(&e),
+                    reason: e.to_string(),
+                });
+                return summary;
+            }
+        },
+    };
+
+    let skip_previously_extracted =
+        config::get_config_bool("skip_previously_extracted").unwrap_or(false);
+
+    let playlist = extract_options::get_playlist(overrides);
+    let overwrite = extract_options::get_overwrite_policy(overrides);
+    let incremental = extract_options::get_incremental(overrides);
+    let manifest = extract_options::get_manifest(overrides);
+    let image_resize = extract_options::get_image_resize(overrides);
+    let convert_to_png = extract_options::get_convert_to_png(overrides);
+    let convert_to_wav = extract_options::get_convert_to_wav(overrides);
+    let verify_on_extract = extract_options::get_verify_on_extract(overrides);
+    let audio_processing = extract_options::get_audio_processing();
+
+    // Get amount and initialise counter for progress
+    let total = file_list.len();
+    summary.total = total;
+    if total == 0 {
+        update_progress(1.0); // No worker will
+    }
+
+    // Shared between the workers, each takes the next asset in the list until there are none left
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0); // For progress, in whatever order they finish
+    let extracted_count = AtomicUsize::new(0);
+    let unreachable = AtomicBool::new(false);
+    let extracted_hashes: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new()); // Each hash and the asset it came from first
+    let playlist_entries = Mutex::new(Vec::new()); // With their place in the list, to keep its order
+    let manifest_entries = Mutex::new(Vec::new()); // The same
+    let summary = Mutex::new(summary);
+
+    let threads = get_extract_threads().min(total).max(1);
+    log_info!("Extracting {} assets with {} threads", total, threads);
+
+    let state = state(); // Workers read assets from the same place
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                state.run(|| {
+                    // Get locale for localised status messages, bundles can't be shared between threads
+                    let locale = locale::get_locale(None);
+
+                    loop {
+                        // Stopping between files means nothing is left half written
+                        if get_cancel_task() {
+                            summary.lock().unwrap().cancelled = true;
+                            break;
+                        }
+                        if unreachable.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(entry) = file_list.get(index) else {
+                            break;
+                        };
+
+                        // User has configured it to skip anything extracted before, evicted assets have nothing to extract
+                        if entry.evicted
+                            || skip_previously_extracted && history::contains(&entry.name)
+                        {
+                            summary.lock().unwrap().skipped += 1;
+                            update_extract_progress(&done, total, &locale);
+                            continue;
+                        }
+
+                        let options = options[&entry.category];
+                        let template = &templates[&entry.category];
+
+                        // The number of bytes written, None if it was skipped
+                        let mut resized = None;
+                        let mut duplicate = false;
+                        let mut not_converted = false; // Written in its own format because converting failed
+                        let mut corrupt = false; // Not written, it doesn't match its digest
+                        let mut audio = None; // What trimming and normalising changed
+                        let result = (|| -> Result<Option<u64>, Error> {
+                            // Going by the history, nothing is read or converted for an asset that's
+                            // already there. Without a history entry it's checked once it's converted
+                            if incremental
+                                && archive.is_none()
+                                && !options.dedupe
+                                && !template.uses_hash()
+                                && !template.uses_counter()
+                            {
+                                if let Some((path, size)) =
+                                    extracted_before(entry, destination, template)
+                                {
+                                    if manifest {
+                                        manifest_entries.lock().unwrap().push((
+                                            index,
+                                            ManifestEntry::new(entry, destination, &path, size),
+                                        ));
+                                    }
+                                    return Ok(None);
+                                }
+                            }
+
+                            let extracted = extract_asset(entry)?;
+
+                            if verify_on_extract
+                                && extracted.verification == Verification::Mismatch
+                            {
+                                corrupt = true;
+                                if manifest {
+                                    manifest_entries.lock().unwrap().push((
+                                        index,
+                                        ManifestEntry::corrupt(entry, extracted.bytes.len() as u64),
+                                    ));
+                                }
+                                return Ok(None);
+                            }
+
+                            let hash = if template.uses_hash() || options.dedupe {
+                                // Already known if it was hashed since the last refresh
+                                entry
+                                    .hash
+                                    .clone()
+                                    .or_else(|| Some(history::hash_bytes(&extracted.bytes)))
+                            } else {
+                                None
+                            };
+
+                            // Same content as an asset already extracted in this run
+                            if options.dedupe {
+                                if let Some(hash) = &hash {
+                                    let mut extracted_hashes = extracted_hashes.lock().unwrap();
+                                    if let Some(original) = extracted_hashes.get(hash) {
+                                        duplicate = true;
+                                        if manifest {
+                                            manifest_entries.lock().unwrap().push((
+                                                index,
+                                                ManifestEntry::duplicate(
+                                                    entry,
+                                                    original,
+                                                    extracted.bytes.len() as u64,
+                                                ),
+                                            ));
+                                        }
+                                        return Ok(None);
+                                    }
+                                    extracted_hashes.insert(hash.clone(), entry.name.clone());
+                                }
+                            }
+
+                            // Hashed before converting and resizing so duplicates are still found. Resizing keeps the
+                            // format, so only the bytes change
+                            let mut extracted = extracted;
+                            if convert_to_png && !png_convert::convert(&entry.name, &mut extracted)
+                            {
+                                not_converted = true;
+                            }
+                            if convert_to_wav
+                                && !wav_convert::convert(
+                                    &entry.name,
+                                    &mut extracted,
+                                    audio_processing,
+                                    &mut audio,
+                                )
+                            {
+                                not_converted = true;
+                            }
+                            // Textures converted to PNG are resized as well
+                            if let Some(settings) = image_resize.filter(|_| {
+                                entry.category == Category::Images
+                                    || extracted.header.as_deref() == Some("PNG")
+                            }) {
+                                match image_resize::resize(&extracted.bytes, settings) {
+                                    Ok(Some(image)) => {
+                                        resized = Some(ResizedImage {
+                                            asset: entry.name.clone(),
+                                            original: image.original,
+                                            output: image.output,
+                                        });
+                                        extracted.bytes = image.bytes;
+                                    }
+                                    Ok(None) => (), // Already within bounds
+                                    Err(e) => {
+                                        log_warn!(
+                                            "Failed to resize {}, extracting as is: {}",
+                                            entry.name,
+                                            e
+                                        )
+                                    }
+                                }
+                            }
+
+                            let counter = extracted_count.fetch_add(1, Ordering::Relaxed) + 1;
+                            let thumbnail = entry.thumbnail().and_then(|thumbnail| thumbnail.name());
+                            let extension = extracted.suggested_extension.unwrap_or_default();
+                            let path = destination.join(template.render(&TemplateValues {
+                                id: &entry.name,
+                                alias: &config::get_asset_alias(&entry.name),
+                                category: entry.category.name(),
+                                extension,
+                                hash: hash.as_deref(),
+                                thumbnail: thumbnail.as_deref(),
+                                last_modified: entry.last_modified,
+                                counter,
+                            }));
+                            let size = extracted.bytes.len() as u64;
+                            if let Some(archive) = &archive {
+                                let mut archive = archive.lock().unwrap();
+                                let Some(name) = archive.add(
+                                    &path,
+                                    &extracted.bytes,
+                                    entry.last_modified,
+                                    overwrite,
+                                )?
+                                else {
+                                    return Ok(None);
+                                };
+                                history::record(
+                                    &entry.name,
+                                    &extracted.bytes,
+                                    &archive.entry_path(&name),
+                                );
+                                let path = PathBuf::from(name);
+                                if playlist
+                                    && matches!(entry.category, Category::Music | Category::Sounds)
+                                {
+                                    playlist_entries.lock().unwrap().push((
+                                        index,
+                                        path.clone(),
+                                        config::get_asset_alias(&entry.name),
+                                    ));
+                                }
+                                if manifest {
+                                    manifest_entries.lock().unwrap().push((
+                                        index,
+                                        ManifestEntry::new(entry, destination, &path, size)
+                                            .with_audio(audio),
+                                    ));
+                                }
+                                return Ok(Some(size));
+                            }
+
+                            // Compared after resizing, as that's the size that was written last time
+                            if incremental && is_unchanged(&path, entry, size) {
+                                // Still there, so it's still listed
+                                if manifest {
+                                    manifest_entries.lock().unwrap().push((
+                                        index,
+                                        ManifestEntry::new(entry, destination, &path, size)
+                                            .with_audio(audio),
+                                    ));
+                                }
+                                return Ok(None);
+                            }
+                            if let Some(directory) = path.parent() {
+                                fs::create_dir_all(directory)?;
+                            }
+
+                            let Some(path) =
+                                write_extracted(entry, extracted, path, false, overwrite)?
+                            else {
+                                return Ok(None);
+                            };
+                            let written = fs::metadata(&path)?.len();
+                            if manifest {
+                                manifest_entries.lock().unwrap().push((
+                                    index,
+                                    ManifestEntry::new(entry, destination, &path, written)
+                                        .with_audio(audio),
+                                ));
+                            }
+                            if playlist
+                                && matches!(entry.category, Category::Music | Category::Sounds)
+                            {
+                                playlist_entries.lock().unwrap().push((
+                                    index,
+                                    path,
+                                    config::get_asset_alias(&entry.name),
+                                ));
+                            }
+                            Ok(Some(written))
+                        })();
+
+                        let mut summary = summary.lock().unwrap();
+                        match result {
+                            Ok(Some(written)) => {
+                                summary.written += 1;
+                                summary.bytes += written;
+                                summary.resized.extend(resized);
+                                if not_converted {
+                                    summary.not_converted += 1;
+                                }
+                            }
+                            Ok(None) if corrupt => {
+                                log_error!(
+                                    "{} doesn't match the checksum stored with it, not extracting it",
+                                    entry.name
+                                );
+                                summary.corrupt.push(entry.name.clone());
+                            }
+                            Ok(None) => {
+                                summary.skipped += 1;
+                                if duplicate {
+                                    summary.duplicates += 1;
+                                }
+                            }
+                            Err(e) => {
+                                log_error!(
+                                    "Error extracting file ({}/{}): {}",
+                                    index + 1,
+                                    total,
+                                    e.describe()
+                                );
+                                summary.failed.push(ExtractFailure {
+                                    asset: entry.name.clone(),
+                                    code: e.code(),
+                                    reason: e.to_string(),
+                                });
+
+                                // Don't wait on every remaining file if the drive has disconnected
+                                if e.code() == ErrorCode::TimedOut
+                                    && !cache_directory::is_reachable()
+                                {
+                                    summary.unreachable = true;
+                                    unreachable.store(true, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                        drop(summary);
+                        update_extract_progress(&done, total, &locale);
+                    }
+                })
+            });
+        }
+    }); // Every worker has finished here
+
+    let mut summary = summary.into_inner().unwrap();
+    // Workers finish in any order, keep the list's order for reading
+    summary.failed.sort_by(|a, b| a.asset.cmp(&b.asset));
+    summary.resized.sort_by(|a, b| a.asset.cmp(&b.asset));
+    summary.corrupt.sort();
+
+    history::prune_to_limit();
+
+    let retries = cache_directory::take_retry_count();
+    if retries > 0 {
+        log_info!("Retried {} file reads while extracting", retries);
+    }
+
+    let mut playlist_entries = playlist_entries.into_inner().unwrap();
+    if !playlist_entries.is_empty() {
+        playlist_entries.sort_by_key(|(index, _, _)| *index);
+        let entries = playlist_entries
+            .into_iter()
+            .map(|(_, path, alias)| (path, alias))
+            .collect();
+        let playlist = playlist_contents(destination, entries);
+        let file_name = format!("{category}.m3u8");
+        match &archive {
+            Some(archive) => {
+                if let Err(e) = archive
+                    .lock()
+                    .unwrap()
+                    .add_file(&file_name, playlist.as_bytes())
+                {
+                    log_error!("Failed to add playlist to the archive: {}", e);
+                }
+            }
+            None => {
+                let path = destination.join(file_name);
+                if let Err(e) = fs::write(&path, playlist) {
+                    log_error!("Failed to write playlist {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+
+    if manifest {
+        let mut manifest_entries = manifest_entries.into_inner().unwrap();
+        manifest_entries.sort_by_key(|(index, _)| *index);
+        let mut entries: Vec<ManifestEntry> = manifest_entries
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        manifest::link_duplicates(&mut entries);
+        match &archive {
+            Some(archive) => {
+                if let Some(data) = manifest::serialise(&entries) {
+                    if let Err(e) = archive.lock().unwrap().add_file(manifest::FILE_NAME, &data) {
+                        log_error!("Failed to add manifest to the archive: {}", e);
+                    }
+                }
+            }
+            None => manifest::write(destination, &entries),
+        }
+    }
+
+    if let Some(archive) = archive {
+        match archive.into_inner().unwrap().finish() {
+            Ok(path) => log_info!("Wrote archive {}", path.display()),
+            Err(e) => {
+                log_error!("Failed to write archive: {}", error_code::describe(&e));
+                if let ExtractTarget::Archive(path) = target {
+                    summary.failed.push(ExtractFailure {
+                        asset: path.display().to_string(),
+                        code: ErrorCode::of(&e),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    summary.duration_ms = start.elapsed().as_millis() as u64;
+    log_info!("{}", summary);
+    summary
+}
+
+// Whether the file still has the modification time that was given to it when the asset was written.
+// Some file systems round timestamps, FAT to 2 seconds
+fn has_modified_time(metadata: &fs::Metadata, asset: &AssetInfo) -> bool {
+    let (Some(last_modified), Ok(modified)) = (asset.last_modified, metadata.modified()) else {
+        return false;
+    };
+    let difference = modified
+        .duration_since(last_modified)
+        .or_else(|_| last_modified.duration_since(modified))
+        .unwrap_or_default();
+    difference.as_secs() < 2
+}
+
+// Whether the file at path is what extracting the asset would write, going by the modification time
+// that was given to it and its size. Assets without a timestamp are always extracted again
+fn is_unchanged(path: &Path, asset: &AssetInfo, size: u64) -> bool {
+    fs::metadata(path)
+        .is_ok_and(|metadata| metadata.len() == size && has_modified_time(&metadata, asset))
+}
+
+// The file the asset was last extracted to and its size, if the template would still put it there
+// and it hasn't changed since: it has the asset's modification time and the hash that was recorded.
+// Only the history and the file are read, not the asset. The template can't use the hash or counter
+fn extracted_before(
+    asset: &AssetInfo,
+    destination: &Path,
+    template: &Template,
+) -> Option<(PathBuf, u64)> {
+    let latest = history::latest(&asset.name)?;
+    let path = PathBuf::from(latest.destination);
+    // Converting can change the extension, so it's the one it was written with
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+    let thumbnail = asset.thumbnail().and_then(|thumbnail| thumbnail.name());
+    let rendered = destination.join(template.render(&TemplateValues {
+        id: &asset.name,
+        alias: &config::get_asset_alias(&asset.name),
+        category: asset.category.name(),
+        extension,
+        hash: None,
+        thumbnail: thumbnail.as_deref(),
+        last_modified: asset.last_modified,
+        counter: 0,
+    }));
+    if rendered != path {
+        return None; // Extracted somewhere else, or with another template
+    }
+
+    let metadata = fs::metadata(&path).ok()?;
+    if !has_modified_time(&metadata, asset) {
+        return None;
+    }
+    let bytes = fs::read(&path).ok()?;
+    (history::hash_bytes(&bytes) == latest.hash).then_some((path, metadata.len()))
+}
+
+// extract_threads in the config, every core by default as decompressing and searching for headers is
+// what takes the time
+fn get_extract_threads() -> usize {
+    match config::get_config_u64("extract_threads") {
+        Some(threads) if threads > 0 => (threads as usize).min(MAX_EXTRACT_THREADS),
+        _ => thread::available_parallelism()
+            .map_or(1, |threads| threads.get())
+            .min(MAX_EXTRACT_THREADS),
+    }
+}
+
+fn update_extract_progress(
+    done: &AtomicUsize,
+    total: usize,
+    locale: &FluentBundle<Arc<FluentResource>>,
+) {
+    let count = done.fetch_add(1, Ordering::Relaxed) + 1;
+    update_progress(count as f32 / total as f32); // Convert to f32 to allow floating point output
+
+    // Args for formatting
+    let mut args = FluentArgs::new();
+    args.set("item", count);
+    args.set("total", total);
+    update_status(locale::get_message(locale, "extracting-files", Some(&args)));
+}
+
+// Paths are relative to the destination so the folder can be moved
+fn playlist_contents(destination: &Path, mut entries: Vec<(PathBuf, String)>) -> String {
+    if config::get_config_bool("playlist_sort_by_alias").unwrap_or(false) {
+        entries.sort_by_key(|(_, alias)| alias.to_lowercase());
+    }
+
+    let mut playlist = String::from("#EXTM3U\n");
+    for (path, alias) in entries {
+        let relative = path.strip_prefix(destination).unwrap_or(&path);
+        let relative: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        // The duration isn't known without decoding the audio, -1 means unknown
+        playlist.push_str(&format!("#EXTINF:-1,{alias}\n{}\n", relative.join("/")));
+    }
+    playlist
+}
+
+// Returns what was extracted if yield_for_thread is set
+pub fn extract_all(
+    destination: PathBuf,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    extract_everything(
+        ExtractTarget::Folder(destination),
+        yield_for_thread,
+        overrides,
+    )
+}
+
+// The same as extract_all, into a ZIP file
+pub fn extract_all_to_archive(
+    archive: PathBuf,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    extract_everything(ExtractTarget::Archive(archive), yield_for_thread, overrides)
+}
+
+fn extract_everything(
+    target: ExtractTarget,
+    yield_for_thread: bool,
+    overrides: ExtractOverrides,
+) -> Option<ExtractSummary> {
+    let settings = prepare_extraction(&overrides)?;
+
+    // Stop multiple threads from running
+    if !start_task() {
+        return None;
+    }
+
+    let handle = state::spawn(move || {
+        // The music directory and then the http directory are listed into one list, so there's one
+        // extraction with one progress bar. Both are listed unless the caller says the list is
+        // current, then the one already listed is used as it is and only the other one is listed
+        let shown = (overrides.refresh == Some(false) && get_list_state() != ListState::Listing)
+            .then(|| (get_list_category(), get_file_list()))
+            .filter(|(category, _)| matches!(category, Category::Music | Category::All));
+        let mut file_list = Vec::new();
+        let mut names = HashSet::new();
+        let mut generation = get_list_generation();
+        for category in [Category::Music, Category::All] {
+            let listed = match &shown {
+                Some((shown_category, shown_list)) if *shown_category == category => {
+                    shown_list.clone()
+                }
+                _ => match list_for_task(category) {
+                    Some(listed) => {
+                        generation = get_list_generation();
+                        listed
+                    }
+                    None => return list_replaced(Category::All),
+                },
+            };
+            // The same asset can be in both, it would be extracted twice
+            file_list.extend(
+                listed
+                    .into_iter()
+                    .filter(|asset| names.insert(asset.name.clone())),
+            );
+        }
+        // Listing the other category replaced the one on screen
+        if let Some((category, shown_list)) = shown {
+            restore_file_list(category, shown_list, generation);
+        }
+
+        let summary = extract_files(&target, Category::All, file_list, &settings, &overrides);
+
+        finish_task(); // Allow other threads to run again
+        update_extracted_status(&summary);
+        summary
+    });
+
+    if yield_for_thread {
+        // Will wait for the thread instead of quitting immediately
+        handle.join().ok()
+    } else {
+        None
+    }
+}
+
+// What is about to be written over an asset
+#[derive(Debug, Clone, Copy)]
+pub struct PayloadInfo {
+    pub category: Category,
+    pub size: u64,
 }
 
-pub fn extract_asset_to_bytes(asset: AssetInfo) -> Result<Vec<u8>, std::io::Error> {
-    let bytes = read_asset(&asset)?;
+impl PayloadInfo {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        PayloadInfo {
+            category: determine_category(bytes),
+            size: bytes.len() as u64,
+        }
+    }
 
-    match find_header(asset.category, &bytes) {
-        Ok(header) => Ok(extract_bytes(&header, bytes.clone())), // Extract between the header to the end of the file.
-        Err(_) => Ok(bytes.clone()),                             // No header was found.
+    pub fn from_asset(asset: &AssetInfo) -> Self {
+        Self::from_bytes(&read_asset(asset).unwrap_or_default())
     }
 }
 
-pub fn extract_dir(
-    destination: PathBuf,
-    category: Category,
-    yield_for_thread: bool,
-    use_alias: bool,
-) {
-    // Create directory if it doesn't exist
-    match fs::create_dir_all(destination.clone()) {
-        Ok(_) => (),
-        Err(e) => log_error!("Error creating directory: {}", e),
-    };
-    let running = {
-        let task = TASK_RUNNING.lock().unwrap();
-        *task
-    };
-    // Stop multiple threads from running
-    if !running {
-        let handle = thread::spawn(move || {
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = true; // Stop other threads from running
-            }
-
-            // User has configured it to refresh before extracting
-            if config::get_config_bool("refresh_before_extract").unwrap_or(false) {
-                refresh(category, false, true); // true because it'll run both and have unfinished file list
-            }
-
-            let file_list = get_file_list();
-
-            // Get locale for localised status messages
-            let locale = locale::get_locale(None);
-
-            // Get amount and initialise counter for progress
-            let total = file_list.len();
-            let mut count = 0;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteWarning {
+    CategoryMismatch {
+        destination: Category,
+        payload: Category,
+    },
+    SizeDisparity {
+        destination: u64,
+        payload: u64,
+    },
+}
 
-            for entry in file_list {
-                count += 1; // Increase counter for progress
-                update_progress(count as f32 / total as f32); // Convert to f32 to allow floating point output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WriteValidation {
+    Ok,
+    NeedsConfirmation(Vec<WriteWarning>),
+}
 
-                let alias = if use_alias {
-                    config::get_asset_alias(&entry.name)
-                } else {
-                    entry.name.clone()
-                };
+// Check that the payload looks like it belongs in the destination asset, Roblox behaves oddly otherwise
+pub fn validate_write(asset_dst: &AssetInfo, payload_info: &PayloadInfo) -> WriteValidation {
+    let destination = PayloadInfo::from_asset(asset_dst);
+    let mut warnings = Vec::new();
 
-                let dest = destination.join(alias); // Local variable destination
+    // All means the category couldn't be detected, don't warn about that
+    if destination.category != Category::All
+        && payload_info.category != Category::All
+        && destination.category != payload_info.category
+    {
+        warnings.push(WriteWarning::CategoryMismatch {
+            destination: destination.category,
+            payload: payload_info.category,
+        });
+    }
 
-                // Args for formatting
-                let mut args = FluentArgs::new();
-                args.set("item", count);
-                args.set("total", total);
-
-                match extract_to_file(entry, dest, true) {
-                    Ok(_) => {
-                        update_status(locale::get_message(
-                            &locale,
-                            "extracting-files",
-                            Some(&args),
-                        ));
-                    }
-                    Err(e) => {
-                        update_status(locale::get_message(
-                            &locale,
-                            "extracting-files",
-                            Some(&args),
-                        ));
-                        log_error!("Error extracting file ({}/{}): {}", count, total, e);
-                    }
-                }
-            }
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = false; // Allow other threads to run again
-            }
-            update_status(locale::get_message(&locale, "all-extracted", None)); // Set the status to confirm to the user that all has finished
+    let smaller = destination.size.min(payload_info.size);
+    let larger = destination.size.max(payload_info.size);
+    if smaller > 0 && larger / smaller >= SIZE_DISPARITY_RATIO {
+        warnings.push(WriteWarning::SizeDisparity {
+            destination: destination.size,
+            payload: payload_info.size,
         });
+    }
 
-        if yield_for_thread {
-            // Will wait for the thread instead of quitting immediately
-            let _ = handle.join();
-        }
+    if warnings.is_empty() {
+        WriteValidation::Ok
+    } else {
+        WriteValidation::NeedsConfirmation(warnings)
     }
 }
 
-pub fn extract_all(destination: PathBuf, yield_for_thread: bool, use_alias: bool) {
-    let running = {
-        let task = TASK_RUNNING.lock().unwrap();
-        *task
-    };
-    // Stop multiple threads from running
-    if !running {
-        let handle = thread::spawn(move || {
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = true; // Stop other threads from running
+// Human readable version of the warnings for confirmation dialogs and the CLI
+pub fn describe_write_warnings(
+    locale: &FluentBundle<Arc<FluentResource>>,
+    warnings: &[WriteWarning],
+) -> String {
+    let mut messages = Vec::new();
+    for warning in warnings {
+        let mut args = FluentArgs::new();
+        match warning {
+            WriteWarning::CategoryMismatch {
+                destination,
+                payload,
+            } => {
+                args.set("destination", destination.to_string());
+                args.set("payload", payload.to_string());
+                messages.push(locale::get_message(
+                    locale,
+                    "warning-category-mismatch",
+                    Some(&args),
+                ));
             }
-
-            // Get locale for localised status messages
-            let locale = locale::get_locale(None);
-
-            // Extract music directory
-            extract_dir(destination.clone(), Category::Music, true, use_alias);
-
-            // Extract http directory
-            extract_dir(destination.clone(), Category::All, true, use_alias);
-
-            {
-                let mut task = TASK_RUNNING.lock().unwrap();
-                *task = false; // Allow other threads to run again
+            WriteWarning::SizeDisparity {
+                destination,
+                payload,
+            } => {
+                args.set("destination", format_size(*destination));
+                args.set("payload", format_size(*payload));
+                messages.push(locale::get_message(
+                    locale,
+                    "warning-size-disparity",
+                    Some(&args),
+                ));
             }
-            update_status(locale::get_message(&locale, "all-extracted", None)); // Set the status to confirm to the user that all has finished
-        });
-
-        if yield_for_thread {
-            // Will wait for the thread instead of quitting immediately
-            let _ = handle.join();
         }
     }
+    messages.join("\n")
 }
 
-pub fn swap_assets(asset_a: AssetInfo, asset_b: AssetInfo) {
+// Nothing is written unless the validation passes or force is true
+pub fn swap_assets(
+    asset_a: AssetInfo,
+    asset_b: AssetInfo,
+    force: bool,
+) -> Result<WriteValidation, read_only::ReadOnlyMode> {
+    read_only::check()?;
+
+    if !force {
+        // The check is symmetric, so one direction covers both
+        let validation = validate_write(&asset_b, &PayloadInfo::from_asset(&asset_a));
+        if validation != WriteValidation::Ok {
+            return Ok(validation);
+        }
+    }
+
+    let backup_a = modifications::backup(&asset_a);
+    let backup_b = modifications::backup(&asset_b);
+
     let cache_directory_result = cache_directory::swap_assets(&asset_a, &asset_b);
     let sql_database_result = sql_database::swap_assets(&asset_a, &asset_b);
 
@@ -480,13 +2650,34 @@ pub fn swap_assets(asset_a: AssetInfo, asset_b: AssetInfo) {
         ));
         log_error!("Error opening file '{}'", sql_database_result.unwrap_err());
     } else {
+        modifications::record(&asset_a, "swap", backup_a);
+        modifications::record(&asset_b, "swap", backup_b);
+
         args.set("item_a", asset_a.name);
         args.set("item_b", asset_b.name);
         update_status(locale::get_message(&locale, "swapped", Some(&args)));
     }
+
+    Ok(WriteValidation::Ok)
 }
 
-pub fn copy_assets(asset_a: AssetInfo, asset_b: AssetInfo) {
+// Nothing is written unless the validation passes or force is true
+pub fn copy_assets(
+    asset_a: AssetInfo,
+    asset_b: AssetInfo,
+    force: bool,
+) -> Result<WriteValidation, read_only::ReadOnlyMode> {
+    read_only::check()?;
+
+    if !force {
+        let validation = validate_write(&asset_b, &PayloadInfo::from_asset(&asset_a));
+        if validation != WriteValidation::Ok {
+            return Ok(validation);
+        }
+    }
+
+    let backup = modifications::backup(&asset_b);
+
     let cache_directory_result = cache_directory::copy_assets(&asset_a, &asset_b);
     let sql_database_result = sql_database::copy_assets(&asset_a, &asset_b);
 
@@ -522,90 +2713,275 @@ pub fn copy_assets(asset_a: AssetInfo, asset_b: AssetInfo) {
         ));
         log_error!("Error opening file '{}'", sql_database_result.unwrap_err());
     } else {
+        modifications::record(&asset_b, "copy", backup);
+
         args.set("item_a", asset_a.name);
         args.set("item_b", asset_b.name);
         update_status(locale::get_message(&locale, "copied", Some(&args)));
     }
+
+    Ok(WriteValidation::Ok)
 }
 
-pub fn filter_file_list(query: String) {
-    let query_lower = query.to_lowercase();
-    // Clear file list before
-    {
-        let mut filtered_file_list = FILTERED_FILE_LIST.lock().unwrap();
-        *filtered_file_list = Vec::new();
-    }
-    let file_list = get_file_list(); // Clone file list
-    for file in file_list {
-        if file.name.contains(&query_lower)
-            || config::get_asset_alias(&file.name)
-                .to_lowercase()
-                .contains(&query_lower)
+const LIST_BATCH_SIZE: usize = 256;
+const LIST_BATCH_INTERVAL: Duration = Duration::from_millis(100);
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(150); // Typing pause before filtering
+
+// What the file list is narrowed down to, anything left as None doesn't filter
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FilterQuery {
+    pub text: String, // Found in the ID, alias or note, ignoring case
+    pub regex: bool,  // text is a regular expression matched against the ID and alias instead
+    pub categories: Option<HashSet<Category>>,
+    pub min_size: Option<u64>, // Stored size in bytes, inclusive
+    pub max_size: Option<u64>,
+    pub newer_than: Option<Duration>, // Last modified less than this long ago
+    pub older_than: Option<Duration>, // Last modified at least this long ago
+    pub thumbnails: bool,             // Only avatar and asset thumbnails, see thumbnail::parse()
+}
+
+impl FilterQuery {
+    pub fn is_empty(&self) -> bool {
+        *self == FilterQuery::default()
+    }
+
+    // None when text isn't a regular expression
+    fn compile(&self) -> Result<Option<regex::Regex>, regex::Error> {
+        if !self.regex {
+            return Ok(None);
+        }
+        regex::RegexBuilder::new(&self.text)
+            .case_insensitive(true)
+            .build()
+            .map(Some)
+    }
+
+    pub fn validate(&self) -> Result<(), regex::Error> {
+        self.compile().map(|_| ())
+    }
+
+    fn matches(&self, asset: &AssetInfo, now: SystemTime, regex: Option<&regex::Regex>) -> bool {
+        if self
+            .categories
+            .as_ref()
+            .is_some_and(|categories| !categories.contains(&asset.category))
+            || self.min_size.is_some_and(|min_size| asset.size < min_size)
+            || self.max_size.is_some_and(|max_size| asset.size > max_size)
+            || self.thumbnails && asset.thumbnail().is_none()
         {
+            return false;
+        }
+
+        if self.newer_than.is_some() || self.older_than.is_some() {
+            // Without a time it can't be known to be in the range
+            let Some(age) = asset
+                .last_modified
+                .map(|last_modified| now.duration_since(last_modified).unwrap_or_default())
+            else {
+                return false;
+            };
+            if self.newer_than.is_some_and(|newer_than| age >= newer_than)
+                || self.older_than.is_some_and(|older_than| age < older_than)
             {
-                let mut filtered_file_list = FILTERED_FILE_LIST.lock().unwrap();
-                filtered_file_list.push(file);
+                return false;
             }
         }
+
+        let alias = config::get_asset_alias(&asset.name);
+        if let Some(regex) = regex {
+            return regex.is_match(&asset.name) || regex.is_match(&alias);
+        }
+        let text = self.text.to_lowercase();
+        asset.name.to_lowercase().contains(&text)
+            || alias.to_lowercase().contains(&text)
+            || notes::get_note(&asset.name).to_lowercase().contains(&text)
     }
 }
 
-pub fn create_asset_info(asset: &str, category: Category) -> AssetInfo {
-    if let Some(info) = sql_database::create_asset_info(asset, category) {
-        return info;
+// The assets in the file list that match, nothing matches an invalid regular expression
+fn filtered(filter: &FilterQuery) -> Vec<AssetInfo> {
+    let regex = match filter.compile() {
+        Ok(regex) => regex,
+        Err(e) => {
+            let mut args = FluentArgs::new();
+            // The full error points at the mistake over several lines, the status only has room for what it is
+            args.set(
+                "error",
+                e.to_string().lines().last().unwrap_or_default().to_owned(),
+            );
+            update_status(locale::get_message(
+                &locale::get_locale(None),
+                "invalid-regex",
+                Some(&args),
+            ));
+            state().filter_error_shown.store(true, Ordering::Relaxed);
+            return Vec::new();
+        }
+    };
+    if state().filter_error_shown.swap(false, Ordering::Relaxed) {
+        update_status(locale::get_message(
+            &locale::get_locale(None),
+            "idling",
+            None,
+        ));
     }
 
-    if let Some(info) = cache_directory::create_asset_info(asset, category) {
-        return info;
+    let now = SystemTime::now();
+    get_file_list()
+        .into_iter()
+        .filter(|asset| filter.matches(asset, now, regex.as_ref()))
+        .collect()
+}
+
+pub fn filter_file_list(filter: &FilterQuery) {
+    let filtered = filtered(filter);
+    let state = state();
+    *state.filter_generation.lock().unwrap() += 1; // Filtering in the background is out of date now
+    *state.filtered_file_list.lock().unwrap() = filtered;
+    file_list_changed(&state);
+}
+
+// For the search box: waits for a pause in typing, filters on another thread and swaps the result in,
+// so the list keeps being drawn. Only the latest filter's result is kept
+pub fn filter_file_list_in_background(filter: FilterQuery) {
+    let generation = {
+        let state = state();
+        let mut generation = state.filter_generation.lock().unwrap();
+        *generation += 1;
+        *generation
+    };
+
+    state::spawn(move || {
+        let is_latest = || *state().filter_generation.lock().unwrap() == generation;
+        thread::sleep(FILTER_DEBOUNCE);
+        if !is_latest() {
+            return;
+        }
+
+        let filtered = filtered(&filter);
+        let state = state();
+        let latest = state.filter_generation.lock().unwrap(); // Held so a newer filter can't swap in first
+        if *latest == generation {
+            *state.filtered_file_list.lock().unwrap() = filtered;
+            file_list_changed(&state);
+            *state.request_repaint.lock().unwrap() = true;
+        }
+    });
+}
+
+// Only by ID, alias or note, like the search box. None keeps every category
+pub fn search_file_list(query: String, categories: Option<&HashSet<Category>>) {
+    filter_file_list(&FilterQuery {
+        text: query,
+        categories: categories.cloned(),
+        ..Default::default()
+    });
+}
+
+pub fn create_asset_info(asset: &str, category: Category) -> AssetInfo {
+    match (
+        cache_directory::create_asset_info(asset, category),
+        sql_database::create_asset_info(asset, category),
+    ) {
+        (Ok(file), Ok(sql)) => return merge_sources(file, sql),
+        (Ok(info), Err(_)) | (Err(_), Ok(info)) => return info,
+        (Err(file_error), Err(sql_error)) => log_debug!(
+            "{asset} isn't in either source: {}, {}",
+            file_error.describe(),
+            sql_error.describe()
+        ),
     }
 
     // Asset doesn't exist, but info is needed anyways
     AssetInfo {
         name: asset.to_string(),
-        _size: 0,
+        size: 0,
+        actual_size: 0,
+        actual_size_approximate: false,
         last_modified: None,
         from_file: false,
         from_sql: false,
+        prefer_sql: false,
+        other_name: None,
+        evicted: false,
+        header: None,
+        hash: None,
+        origin_url: None,
         category,
     }
 }
 
-pub fn determine_category(bytes: &[u8]) -> Category {
+// The category and the header that decided it, e.g. (Images, "PNG")
+pub fn detect_format(bytes: &[u8]) -> Option<(Category, String)> {
+    let mut earliest: Option<(usize, Category, String)> = None;
     for category in Category::iter().filter(|&cat| cat != Category::All && cat != Category::Music) {
         // Ignore music and all
         for header in get_headers(&category) {
+            if header == MPEG_HEADER {
+                continue; // Checked last, a real header elsewhere in the bytes is more reliable
+            }
             // Since MP3 gets an unusual amount of false-positives, we make an extra check
-            if header == "ID3" {
-                if bytes_contains(bytes, header.as_bytes()) && bytes_contains(bytes, b"binary/") {
-                    return category;
-                }
-            } else {
-                if bytes_contains(bytes, header.as_bytes()) {
-                    return category;
+            if header == "ID3" && !bytes_contains(bytes, b"binary/") {
+                continue;
+            }
+            if let Some(position) = find_header_position(bytes, &header) {
+                // The header that starts first wins, the first one checked if they start at the same place
+                if earliest
+                    .as_ref()
+                    .is_none_or(|(earliest, _, _)| position < *earliest)
+                {
+                    earliest = Some((position, category, header));
                 }
             }
         }
     }
+    if let Some((_, category, header)) = earliest {
+        return Some((category, header));
+    }
 
+    if find_mpeg_frame(bytes, scan_range(bytes)).is_some() {
+        return Some((Category::Sounds, MPEG_HEADER.to_owned()));
+    }
+    None
+}
+
+pub fn determine_category(bytes: &[u8]) -> Category {
     // No category found, return All
-    Category::All
+    detect_format(bytes).map_or(Category::All, |(category, _)| category)
 }
 
 // File headers for each category
 pub fn get_headers(category: &Category) -> Vec<String> {
     match category {
         Category::Music => {
-            vec!["OggS".to_string(), "ID3".to_string()]
+            vec![
+                "OggS".to_string(),
+                "ID3".to_string(),
+                MPEG_HEADER.to_string(),
+            ]
         }
         Category::Sounds => {
-            vec!["OggS".to_string(), "ID3".to_string()]
+            vec![
+                "OggS".to_string(),
+                "ID3".to_string(),
+                MPEG_HEADER.to_string(),
+            ]
         }
         Category::Ktx => {
-            vec!["KTX".to_string()]
+            vec!["KTX".to_string(), KTX2_HEADER.to_string()]
         }
         Category::Rbxm => {
             vec!["<roblox!".to_string()]
         }
+        Category::Mesh => MESH_HEADERS
+            .iter()
+            .map(|header| header.to_string())
+            .collect(),
+        Category::Fonts => FONT_HEADERS
+            .iter()
+            .map(|header| header.to_string())
+            .collect(),
+        Category::Json => vec![JSON_HEADER.to_string()],
         Category::Images => {
             vec!["PNG".to_string(), "WEBP".to_string()]
         }
@@ -621,45 +2997,419 @@ pub fn get_headers(category: &Category) -> Vec<String> {
 }
 
 pub fn update_status(value: String) {
-    let mut status = STATUS.lock().unwrap();
+    let state = state();
+    let mut status = state.status().lock().unwrap();
     *status = value;
-    let mut request = REQUEST_REPAINT.lock().unwrap();
+    let mut request = state.request_repaint.lock().unwrap();
     *request = true;
 }
 
+// Dividing by an empty total gives NaN, which is nothing left to do
 pub fn update_progress(value: f32) {
-    let mut progress = PROGRESS.lock().unwrap();
-    *progress = value;
-    let mut request = REQUEST_REPAINT.lock().unwrap();
+    let value = if value.is_nan() {
+        1.0
+    } else {
+        value.clamp(0.0, 1.0)
+    };
+    let state = state();
+    let mut progress = state.progress.lock().unwrap();
+    *progress = Some(value);
+    let mut request = state.request_repaint.lock().unwrap();
+    *request = true;
+}
+
+// Whether there's a list to show, the GUI says so when it's empty
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListState {
+    Listing, // Or waiting to, the list may have some of the assets
+    Empty,
+    Listed,
+}
+
+fn set_list_state(list_state: ListState) {
+    *state().list_state.lock().unwrap() = list_state;
+}
+
+// Once listing has finished
+fn update_list_state() {
+    set_list_state(if state().file_list.lock().unwrap().is_empty() {
+        ListState::Empty
+    } else {
+        ListState::Listed
+    });
+}
+
+pub fn get_list_state() -> ListState {
+    *state().list_state.lock().unwrap()
+}
+
+// How far the running task or listing is
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Progress {
+    Idle,
+    Counting, // Running, but how much there is to do isn't known yet
+    Running(f32),
+}
+
+impl Progress {
+    // For progress bars, idle is shown as finished
+    pub fn fraction(self) -> f32 {
+        match self {
+            Progress::Idle => 1.0,
+            Progress::Counting => 0.0,
+            Progress::Running(fraction) => fraction,
+        }
+    }
+}
+
+// What the file list is sorted by, ties keep the order they were listed in
+#[derive(
+    ValueEnum,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    EnumIter,
+    strum_macros::Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum SortKey {
+    Name,
+    Size,
+    Modified,
+    Category,
+}
+
+impl SortKey {
+    fn compare(self, a: &AssetInfo, b: &AssetInfo) -> std::cmp::Ordering {
+        match self {
+            SortKey::Name => a.name.cmp(&b.name),
+            SortKey::Size => a.size.cmp(&b.size),
+            SortKey::Modified => a.last_modified.cmp(&b.last_modified),
+            SortKey::Category => a.category.name().cmp(b.category.name()),
+        }
+    }
+}
+
+pub fn sort_file_list(key: SortKey, ascending: bool) {
+    let state = state();
+    for list in [&state.file_list, &state.filtered_file_list] {
+        // Stable, so reversing the comparison rather than the list keeps ties in order
+        list.lock().unwrap().sort_by(|a, b| {
+            if ascending {
+                key.compare(a, b)
+            } else {
+                key.compare(b, a)
+            }
+        });
+    }
+    file_list_changed(&state);
+    let mut request = state.request_repaint.lock().unwrap();
     *request = true;
 }
 
+// The sort chosen in the GUI, by name by default
+pub fn get_sort() -> (SortKey, bool) {
+    let key = config::get_config()
+        .get("sort_key")
+        .and_then(|key| serde_json::from_value(key.clone()).ok())
+        .unwrap_or(SortKey::Name);
+    (
+        key,
+        config::get_config_bool("sort_ascending").unwrap_or(true),
+    )
+}
+
+pub fn set_sort(key: SortKey, ascending: bool) {
+    config::set_config_value("sort_key", serde_json::json!(key));
+    config::set_config_value("sort_ascending", ascending.into());
+    sort_file_list(key, ascending);
+}
+
+// When an asset was cached, for grouping the list by day
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DayGroup {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Date(chrono::NaiveDate),
+    Unknown, // No timestamp
+}
+
+impl DayGroup {
+    pub fn from_time(last_modified: Option<SystemTime>) -> DayGroup {
+        let Some(last_modified) = last_modified else {
+            return DayGroup::Unknown;
+        };
+        let date = chrono::DateTime::<chrono::Local>::from(last_modified).date_naive();
+        match (chrono::Local::now().date_naive() - date).num_days() {
+            ..=0 => DayGroup::Today,
+            1 => DayGroup::Yesterday,
+            2..=6 => DayGroup::ThisWeek,
+            _ => DayGroup::Date(date),
+        }
+    }
+
+    pub fn label(&self, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+        match self {
+            DayGroup::Today => locale::get_message(locale, "group-today", None),
+            DayGroup::Yesterday => locale::get_message(locale, "group-yesterday", None),
+            DayGroup::ThisWeek => locale::get_message(locale, "group-this-week", None),
+            DayGroup::Date(date) => date.format("%Y-%m-%d").to_string(),
+            DayGroup::Unknown => locale::get_message(locale, "group-unknown-date", None),
+        }
+    }
+}
+
+// Newest group first and unknown dates last, newest asset first within each group
+pub fn group_by_day(mut assets: Vec<AssetInfo>) -> Vec<(DayGroup, Vec<AssetInfo>)> {
+    assets.sort_by_key(|asset| std::cmp::Reverse(asset.last_modified));
+
+    let mut groups: Vec<(DayGroup, Vec<AssetInfo>)> = Vec::new();
+    for asset in assets {
+        let group = DayGroup::from_time(asset.last_modified);
+        match groups.iter_mut().find(|(existing, _)| *existing == group) {
+            Some((_, group_assets)) => group_assets.push(asset),
+            None => groups.push((group, vec![asset])),
+        }
+    }
+    groups
+}
+
+// CLI listings are printed as assets are found unless this is set, which waits for the sorted list
+pub fn set_stable_order(value: bool) {
+    *state().stable_order.lock().unwrap() = value;
+}
+
+pub fn get_stable_order() -> bool {
+    *state().stable_order.lock().unwrap()
+}
+
+// --list --long prints each asset's category and size after its id
+pub fn set_long_listing(value: bool) {
+    *state().long_listing.lock().unwrap() = value;
+}
+
+pub fn get_long_listing() -> bool {
+    *state().long_listing.lock().unwrap()
+}
+
+// A line of --list output
+pub fn list_line(asset: &AssetInfo) -> String {
+    if get_long_listing() {
+        format!(
+            "{}\t{}\t{}",
+            asset.name,
+            asset.category,
+            format_size(asset.size)
+        )
+    } else {
+        asset.name.clone()
+    }
+}
+
+// How --list prints what it found, JSON and CSV are printed once everything has been listed
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListOutput {
+    Text, // One asset per line, see list_line()
+    Json,
+    Csv,
+}
+
+#[derive(Serialize)]
+struct ListEntry {
+    name: String,
+    alias: Option<String>, // Null if it has none
+    category: String,
+    size: u64,
+    last_modified: Option<String>, // RFC 3339 in UTC, null if unknown
+    from_sql: bool,
+    from_file: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    other_name: Option<String>, // Its name in the other source, if it's listed once for both
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumbnail: Option<thumbnail::Thumbnail>,
+}
+
+impl ListEntry {
+    fn new(asset: &AssetInfo) -> ListEntry {
+        let alias = config::get_asset_alias(&asset.name);
+        ListEntry {
+            alias: (alias != asset.name).then_some(alias),
+            name: asset.name.clone(),
+            category: asset.category.to_string(),
+            size: asset.size,
+            last_modified: asset.last_modified.map(|last_modified| {
+                chrono::DateTime::<chrono::Utc>::from(last_modified)
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+            }),
+            from_sql: asset.from_sql,
+            from_file: asset.from_file,
+            other_name: asset.other_name.clone(),
+            origin_url: asset.origin_url.clone(),
+            thumbnail: asset.thumbnail(),
+        }
+    }
+}
+
+// Quoted only when it has to be
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+// The whole listing as JSON or CSV, text is printed line by line instead
+pub fn format_listing(assets: &[AssetInfo], output: ListOutput) -> String {
+    let entries = || assets.iter().map(ListEntry::new).collect::<Vec<_>>();
+    match output {
+        ListOutput::Text => assets.iter().map(list_line).collect::<Vec<_>>().join("\n"),
+        ListOutput::Json => serde_json::to_string_pretty(&entries()).unwrap_or_default(),
+        ListOutput::Csv => {
+            let mut lines = vec![
+                "name,alias,category,size,last_modified,from_sql,from_file,other_name,origin_url,thumbnail,thumbnail_id".to_owned(),
+            ];
+            lines.extend(entries().iter().map(|entry| {
+                [
+                    csv_field(&entry.name),
+                    csv_field(entry.alias.as_deref().unwrap_or_default()),
+                    csv_field(&entry.category),
+                    entry.size.to_string(),
+                    entry.last_modified.clone().unwrap_or_default(),
+                    entry.from_sql.to_string(),
+                    entry.from_file.to_string(),
+                    csv_field(entry.other_name.as_deref().unwrap_or_default()),
+                    csv_field(entry.origin_url.as_deref().unwrap_or_default()),
+                    entry
+                        .thumbnail
+                        .map(|thumbnail| thumbnail.kind.to_string())
+                        .unwrap_or_default(),
+                    entry
+                        .thumbnail
+                        .and_then(|thumbnail| thumbnail.id)
+                        .map(|id| id.to_string())
+                        .unwrap_or_default(),
+                ]
+                .join(",")
+            }));
+            lines.join("\n")
+        }
+    }
+}
+
+pub fn get_listed_count() -> usize {
+    state().file_list.lock().unwrap().len()
+}
+
+// Stored size of everything listed, before decompressing
+pub fn get_total_listed_size() -> u64 {
+    state()
+        .file_list
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|asset| asset.size)
+        .sum()
+}
+
 pub fn get_file_list() -> Vec<AssetInfo> {
-    FILE_LIST.lock().unwrap().clone()
+    state().file_list.lock().unwrap().clone()
 }
 
 pub fn get_filtered_file_list() -> Vec<AssetInfo> {
-    FILTERED_FILE_LIST.lock().unwrap().clone()
+    state().filtered_file_list.lock().unwrap().clone()
 }
 
 pub fn get_status() -> String {
-    STATUS.lock().unwrap().clone()
+    state().status().lock().unwrap().clone()
+}
+
+pub fn get_progress() -> Progress {
+    if !get_task_running() && !get_list_task_running() {
+        return Progress::Idle;
+    }
+    match *state().progress.lock().unwrap() {
+        Some(fraction) => Progress::Running(fraction),
+        None => Progress::Counting,
+    }
+}
+
+// Called when a task or listing starts, until it knows its total
+fn reset_progress() {
+    *state().progress.lock().unwrap() = None;
+}
+
+pub fn get_task_running() -> bool {
+    *state().task_running.lock().unwrap()
+}
+
+// Marks a delete/extract task as running, false if one already is in this or another process. Checked
+// and set under one lock, so two tasks started at once can't both run
+pub fn start_task() -> bool {
+    let state = state();
+    let mut task = state.task_running.lock().unwrap();
+    // A separate state has nothing to do with other processes
+    if *task || (state.process_lock && !task_lock::acquire()) {
+        drop(task); // update_status() locks the state's status
+        log_warn!("Another task is running, not starting another");
+        update_status(locale::get_message(
+            &locale::get_locale(None),
+            "error-task-running",
+            None,
+        ));
+        return false;
+    }
+    *task = true;
+    *state.cancel_task.lock().unwrap() = false;
+    drop(task);
+    reset_progress();
+    true
+}
+
+pub fn finish_task() {
+    let state = state();
+    *state.task_running.lock().unwrap() = false;
+    if state.process_lock {
+        task_lock::release();
+    }
+}
+
+// Stays set after the task stops so the caller can tell it was cancelled
+pub fn cancel_task() {
+    *state().cancel_task.lock().unwrap() = true;
 }
 
-pub fn get_progress() -> f32 {
-    *PROGRESS.lock().unwrap()
+pub fn get_cancel_task() -> bool {
+    *state().cancel_task.lock().unwrap()
 }
 
 pub fn get_list_task_running() -> bool {
-    *LIST_TASK_RUNNING.lock().unwrap()
+    *state().list_task_running.lock().unwrap()
 }
 
 pub fn get_stop_list_running() -> bool {
-    *STOP_LIST_RUNNING.lock().unwrap()
+    *state().stop_list_running.lock().unwrap()
+}
+
+pub fn get_list_category() -> Category {
+    *state().list_category.lock().unwrap()
+}
+
+pub fn get_list_generation() -> u64 {
+    *state().list_generation.lock().unwrap()
 }
 
 pub fn get_request_repaint() -> bool {
-    let mut request_repaint = REQUEST_REPAINT.lock().unwrap();
+    let state = state();
+    let mut request_repaint = state.request_repaint.lock().unwrap();
     let old_request_repaint = *request_repaint;
     *request_repaint = false; // Set to false when this function is called to acknowledge
     old_request_repaint
@@ -667,6 +3417,12 @@ pub fn get_request_repaint() -> bool {
 
 // Delete the temp directory
 pub fn clean_up() {
+    // Files may still be written to the temp directory, it's left for next time
+    if get_task_running() {
+        log_warn!("Not cleaning up, a task is still running");
+        return;
+    }
+
     let temp_dir = get_temp_dir();
     // Just in case if it somehow resolves to "/"
     if temp_dir != PathBuf::new() && temp_dir != PathBuf::from("/") {
@@ -676,9 +3432,241 @@ pub fn clean_up() {
             Err(e) => log_error!("Failed to clean up directory: {}", e),
         }
     }
+    temp_space::clean_up();
 
     match sql_database::clean_up() {
         Ok(_) => (),
         Err(e) => log_error!("Failed to clean up SQL database: {:?}", e),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // How bytes_search used to look through every window
+    fn search_windows(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        let len = needle.len();
+        if len > 0 {
+            haystack.windows(len).position(|window| window == needle)
+        } else {
+            None
+        }
+    }
+
+    #[test]
+    fn bytes_search_matches_comparing_windows() {
+        let haystack = b"aaOggSaaOggSOggS\x00PNGab";
+        for needle in [
+            &b"OggS"[..],
+            b"gSOg",                       // Overlaps two matches
+            b"aa",                         // Overlapping occurrences, the first is found
+            b"PNGab",                      // At the very end
+            b"b",                          // Last byte
+            b"aaOggS",                     // At the very start
+            b"OggSx",                      // Not there
+            haystack,                      // The whole haystack
+            b"aaOggSaaOggSOggS\x00PNGabc", // Longer than the haystack
+            b"",                           // Empty needles are never found
+        ] {
+            assert_eq!(
+                bytes_search(haystack, needle),
+                search_windows(haystack, needle),
+                "{:?}",
+                String::from_utf8_lossy(needle)
+            );
+            assert_eq!(
+                bytes_contains(haystack, needle),
+                search_windows(haystack, needle).is_some()
+            );
+        }
+        assert_eq!(bytes_search(b"", b"a"), None);
+        assert_eq!(bytes_search(b"", b""), None);
+    }
+
+    // A mesh compresses well, so no header is left showing in the compressed bytes
+    fn compressed_mesh(compression: fixtures::Compression) -> (Vec<u8>, Vec<u8>) {
+        let mut mesh = fixtures::mesh();
+        for i in 0..300 {
+            mesh.extend(format!("[{i},{},{}]", i * 7 % 13, i * 3 % 11).bytes());
+        }
+        (fixtures::compress(compression, &mesh), mesh)
+    }
+
+    #[test]
+    fn decompress_round_trip() {
+        for (compression, expected) in [
+            (fixtures::Compression::Zstd, Compression::Zstd),
+            (fixtures::Compression::Gzip, Compression::Gzip),
+            (fixtures::Compression::Deflate, Compression::Deflate),
+        ] {
+            let (compressed, mesh) = compressed_mesh(compression);
+            let (bytes, found) = decompress_within(compressed, 1024 * 1024).unwrap();
+            assert_eq!(found, Some(expected));
+            assert_eq!(bytes, mesh, "{}", expected.name());
+        }
+
+        // Gzip streams can have several members one after another
+        let png = fixtures::png();
+        let (first, second) = png.split_at(30);
+        let mut members = fixtures::compress(fixtures::Compression::Gzip, first);
+        members.extend(fixtures::compress(fixtures::Compression::Gzip, second));
+        assert_eq!(decompress_within(members, 1024).unwrap().0, png);
+
+        // Anything with a header is left as it is. Deflate stores a PNG, which doesn't compress, as it
+        // is, so the header shows and it's extracted from there instead of inflated
+        let png = fixtures::png();
+        assert_eq!(
+            decompress_within(png.clone(), 1024).unwrap(),
+            (png.clone(), None)
+        );
+        let stored = fixtures::compress(fixtures::Compression::Deflate, &png);
+        assert_eq!(
+            decompress_within(stored.clone(), 1024).unwrap(),
+            (stored, None)
+        );
+        let text = b"neither compressed nor any known format".to_vec();
+        assert_eq!(decompress_within(text.clone(), 1024).unwrap(), (text, None));
+    }
+
+    #[test]
+    fn decompress_stops_at_the_limit() {
+        let mut payload = fixtures::png();
+        payload.resize(4096, 0);
+        for compression in [
+            fixtures::Compression::Zstd,
+            fixtures::Compression::Gzip,
+            fixtures::Compression::Deflate,
+        ] {
+            let compressed = fixtures::compress(compression, &payload);
+            let error = decompress_within(compressed.clone(), 4095).unwrap_err();
+            assert_eq!(error.kind(), std::io::ErrorKind::FileTooLarge);
+            // Exactly the limit is fine
+            assert_eq!(decompress_within(compressed, 4096).unwrap().0, payload);
+        }
+
+        // A zstd frame that doesn't say how big it is is still stopped while decoding
+        let mut encoder = zstd::stream::Encoder::new(Vec::new(), 0).unwrap();
+        encoder.include_contentsize(false).unwrap();
+        encoder.write_all(&payload).unwrap();
+        let compressed = encoder.finish().unwrap();
+        assert!(matches!(
+            zstd::zstd_safe::get_frame_content_size(&compressed),
+            Ok(None)
+        ));
+        let error = decompress_within(compressed, 4095).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::FileTooLarge);
+    }
+
+    #[test]
+    fn decompress_truncated_streams() {
+        // Zstd and gzip say when they're cut short, the stored bytes are kept then
+        for compression in [fixtures::Compression::Zstd, fixtures::Compression::Gzip] {
+            let (compressed, _) = compressed_mesh(compression);
+            let truncated = compressed[..compressed.len() / 2].to_vec();
+            assert_eq!(
+                decompress_within(truncated.clone(), 1024 * 1024).unwrap(),
+                (truncated, None)
+            );
+        }
+
+        // Raw deflate has no end marker to miss, what inflates is kept as long as it has a header
+        let (compressed, mesh) = compressed_mesh(fixtures::Compression::Deflate);
+        let truncated = compressed[..compressed.len() / 2].to_vec();
+        let (bytes, found) = decompress_within(truncated, 1024 * 1024).unwrap();
+        assert_eq!(found, Some(Compression::Deflate));
+        assert!(mesh.starts_with(&bytes) && bytes.len() < mesh.len());
+
+        // Too little left to inflate to anything with a header
+        let truncated = compressed[..4].to_vec();
+        assert_eq!(
+            decompress_within(truncated.clone(), 1024 * 1024).unwrap(),
+            (truncated, None)
+        );
+    }
+
+    #[test]
+    fn ktx2_behind_a_preamble() {
+        let ktx2 = fixtures::ktx2();
+        let preamble = fixtures::http_preamble("https://c0.rbxcdn.com/ktx2");
+        let bytes = [&preamble[..], &ktx2, b"\x00trailing metadata"].concat();
+
+        let (category, header) = detect_format(&bytes).unwrap();
+        assert_eq!((category, header.as_str()), (Category::Ktx, KTX2_HEADER));
+        assert_eq!(
+            find_header_position(&bytes, &header),
+            Some(preamble.len()),
+            "the identifier starts right after the preamble"
+        );
+        assert_eq!(get_extension(&header), "ktx2");
+        // All 12 bytes of the identifier are kept, and nothing after the last level
+        assert_eq!(extract_bytes(&header, bytes), ktx2);
+
+        // Version 1 isn't taken for version 2, or the other way around
+        let ktx1 = [&preamble[..], &fixtures::ktx1()].concat();
+        assert_eq!(
+            detect_format(&ktx1),
+            Some((Category::Ktx, "KTX".to_owned()))
+        );
+    }
+
+    #[test]
+    fn webp_at_the_start() {
+        // No RIFF header or leading metadata, so the header is found before its offset
+        let bytes = [&b"WEBP"[..], b"VP8L\x05\x00\x00\x00\x2f\x00\x00\x00\x00"].concat();
+
+        let (category, header) = detect_format(&bytes).unwrap();
+        assert_eq!((category, header.as_str()), (Category::Images, "WEBP"));
+        assert_eq!(find_header_position(&bytes, &header), Some(0));
+        assert_eq!(get_extension(&header), "webp");
+        // Extracted from the header, without panicking
+        assert_eq!(extract_bytes(&header, bytes.clone()), bytes);
+    }
+
+    #[test]
+    fn categories_round_trip() {
+        // Both lists have to be updated for a new variant
+        assert!(Category::iter().eq(Category::VARIANTS.iter().copied()));
+
+        let mut names = std::collections::HashSet::new();
+        for &category in Category::value_variants() {
+            let name = category.to_string();
+            assert!(names.insert(name.clone()), "{name} is used twice");
+            assert_eq!(name, name.to_lowercase());
+            assert_eq!(name.parse::<Category>(), Ok(category));
+            // What older versions wrote, and clap's own parsing
+            assert_eq!(format!("{category:?}").parse::<Category>(), Ok(category));
+            assert_eq!(
+                <Category as ValueEnum>::from_str(&name, false),
+                Ok(category)
+            );
+            for legacy in category.legacy_names() {
+                assert_eq!(legacy.parse::<Category>(), Ok(category), "{legacy}");
+                assert_eq!(
+                    <Category as ValueEnum>::from_str(legacy, false),
+                    Ok(category)
+                );
+            }
+
+            let json = serde_json::to_string(&category).unwrap();
+            assert_eq!(json, format!("\"{name}\""));
+            assert_eq!(serde_json::from_str::<Category>(&json).unwrap(), category);
+
+            assert_eq!(Category::from_tab_id(category.tab_id()), Some(category));
+        }
+        assert!("sound-effects".parse::<Category>().is_err());
+    }
+
+    #[test]
+    fn categories_have_names_in_english() {
+        let english = include_str!("../locales/en-GB.ftl");
+        for category in Category::iter() {
+            let key = format!("{} =", category.locale_key());
+            assert!(
+                english.lines().any(|line| line.starts_with(&key)),
+                "{category} has no name in en-GB.ftl"
+            );
+        }
+    }
+}
@@ -0,0 +1,151 @@
+// Extracting into a ZIP file instead of a folder. Entries are written as each asset is extracted,
+// to a .part file that's renamed once the archive is finished, so a failed run doesn't leave a
+// broken archive where the last one was.
+use chrono::{DateTime, Datelike, Local, Timelike};
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::logic::extract_options::OverwritePolicy;
+
+// Already compressed, deflating them again takes time for nothing
+const STORED_EXTENSIONS: [&str; 8] = ["png", "webp", "jpg", "jpeg", "gif", "ogg", "mp3", "mp4"];
+
+pub struct ArchiveWriter {
+    zip: ZipWriter<fs::File>,
+    path: PathBuf,
+    part_path: PathBuf,
+    names: HashSet<String>, // A ZIP can have the same name twice, but most tools can't open it then
+}
+
+// Entry names always use /, whatever the OS uses
+fn entry_name(path: &Path) -> String {
+    let components: Vec<String> = path
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    components.join("/")
+}
+
+// "name (1).ogg" for "name.ogg"
+fn numbered_name(name: &str, number: usize) -> String {
+    let (folder, file_name) = match name.rsplit_once('/') {
+        Some((folder, file_name)) => (format!("{folder}/"), file_name),
+        None => (String::new(), name),
+    };
+    match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{folder}{stem} ({number}).{extension}")
+        }
+        _ => format!("{folder}{file_name} ({number})"),
+    }
+}
+
+// ZIP times are local time to the second, from 1980 to 2107
+fn zip_time(time: SystemTime) -> Option<zip::DateTime> {
+    let time: DateTime<Local> = time.into();
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+impl ArchiveWriter {
+    pub fn create(path: PathBuf) -> io::Result<Self> {
+        if let Some(directory) = path
+            .parent()
+            .filter(|directory| !directory.as_os_str().is_empty())
+        {
+            fs::create_dir_all(directory)?;
+        }
+        let mut part_name = path.file_name().unwrap_or_default().to_owned();
+        part_name.push(".part");
+        let part_path = path.with_file_name(part_name);
+
+        Ok(ArchiveWriter {
+            zip: ZipWriter::new(fs::File::create(&part_path)?),
+            path,
+            part_path,
+            names: HashSet::new(),
+        })
+    }
+
+    // The archive's path followed by the entry, for the extraction history
+    pub fn entry_path(&self, name: &str) -> PathBuf {
+        self.path.join(name)
+    }
+
+    // The name it was added as, None if it was skipped. An entry can't be replaced once it's written,
+    // so a name that's already in the archive is numbered unless overwrite is Skip
+    pub fn add(
+        &mut self,
+        path: &Path,
+        bytes: &[u8],
+        last_modified: Option<SystemTime>,
+        overwrite: OverwritePolicy,
+    ) -> io::Result<Option<String>> {
+        let mut name = entry_name(path);
+        if self.names.contains(&name) {
+            if overwrite == OverwritePolicy::Skip {
+                return Ok(None);
+            }
+            name = (1..)
+                .map(|number| numbered_name(&name, number))
+                .find(|numbered| !self.names.contains(numbered))
+                .unwrap();
+        }
+
+        let stored = path.extension().is_some_and(|extension| {
+            STORED_EXTENSIONS
+                .iter()
+                .any(|stored| extension.eq_ignore_ascii_case(stored))
+        });
+        let mut options = SimpleFileOptions::default().compression_method(if stored {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        });
+        if let Some(time) = last_modified.and_then(zip_time) {
+            options = options.last_modified_time(time);
+        }
+
+        self.zip.start_file(name.as_str(), options)?;
+        self.zip.write_all(bytes)?;
+        self.names.insert(name.clone());
+        Ok(Some(name))
+    }
+
+    // For the playlist and manifest, always written as they are
+    pub fn add_file(&mut self, name: &str, bytes: &[u8]) -> io::Result<()> {
+        let mut options =
+            SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        if let Some(time) = zip_time(SystemTime::now()) {
+            options = options.last_modified_time(time);
+        }
+        self.zip.start_file(name, options)?;
+        self.zip.write_all(bytes)
+    }
+
+    // Also after cancelling, the archive has everything extracted until then
+    pub fn finish(self) -> io::Result<PathBuf> {
+        let result = self
+            .zip
+            .finish()
+            .map_err(io::Error::from)
+            .and_then(|_| fs::rename(&self.part_path, &self.path));
+        if result.is_err() {
+            fs::remove_file(&self.part_path).ok(); // Nothing is left behind, whatever went wrong
+        }
+        result.map(|()| self.path)
+    }
+}
@@ -0,0 +1,236 @@
+// Where an asset really ends. Roblox keeps metadata after some payloads, which strict decoders refuse,
+// so formats that say how long they are or mark their end are cut there. The bytes start with the
+// header, None means the end couldn't be told and everything is kept.
+use crate::logic::{KTX1_IDENTIFIER, KTX2_HEADER, KTX2_IDENTIFIER, PNG_SIGNATURE};
+
+const OGG_EOS: u8 = 0x04; // Header type flag of a stream's last page
+const KTX1_HEADER_SIZE: usize = 64;
+const KTX2_LEVEL_INDEX: usize = 80;
+
+fn u32_at(bytes: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let word: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if big_endian {
+        u32::from_be_bytes(word)
+    } else {
+        u32::from_le_bytes(word)
+    })
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> Option<u64> {
+    let word: [u8; 8] = bytes.get(offset..offset + 8)?.try_into().ok()?;
+    Some(u64::from_le_bytes(word))
+}
+
+// Only an end inside the bytes counts, a length past them means it's cut short or not what it seems
+fn within(bytes: &[u8], end: usize) -> Option<usize> {
+    (end <= bytes.len()).then_some(end)
+}
+
+// After the IEND chunk's CRC, following the chunks rather than searching so pixel data can't match
+fn png_end(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(PNG_SIGNATURE) {
+        return None;
+    }
+    let mut position = PNG_SIGNATURE.len();
+    loop {
+        let length = u32_at(bytes, position, true)? as usize;
+        let chunk_type = bytes.get(position + 4..position + 8)?;
+        // Length, type, data and CRC
+        let end = position.checked_add(12)?.checked_add(length)?;
+        if chunk_type == b"IEND" {
+            return within(bytes, end);
+        }
+        position = end;
+    }
+}
+
+// RIFF says how long the rest is, chunks are padded to an even size
+fn webp_end(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(b"RIFF") {
+        return None;
+    }
+    let size = u32_at(bytes, 4, false)? as usize;
+    within(bytes, size.checked_add(8 + size % 2)?)
+}
+
+// After the last page that ends a stream
+fn ogg_end(bytes: &[u8]) -> Option<usize> {
+    let mut position = 0;
+    let mut end = None;
+    while bytes.get(position..position + 4) == Some(b"OggS") {
+        let segments = *bytes.get(position + 26)? as usize;
+        let table = bytes.get(position + 27..position + 27 + segments)?;
+        let page_end =
+            position + 27 + segments + table.iter().map(|size| *size as usize).sum::<usize>();
+        if page_end > bytes.len() {
+            break;
+        }
+        if bytes[position + 5] & OGG_EOS != 0 {
+            end = Some(page_end);
+        }
+        position = page_end;
+    }
+    end
+}
+
+// Version 1 stores each mipmap level with its size in front, padded to 4 bytes
+fn ktx1_end(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(&KTX1_IDENTIFIER) {
+        return None;
+    }
+    let big_endian = match bytes.get(12..16)? {
+        [0x01, 0x02, 0x03, 0x04] => false,
+        [0x04, 0x03, 0x02, 0x01] => true,
+        _ => return None,
+    };
+    let field =
+        |index: usize| u32_at(bytes, 16 + index * 4, big_endian).map(|value| value as usize);
+    let array_elements = field(8)?;
+    let faces = field(9)?;
+    let levels = field(10)?.max(1);
+    let key_value_size = field(11)?;
+    let padded = |size: usize| size.checked_add(3).map(|size| size & !3);
+
+    let mut position = KTX1_HEADER_SIZE.checked_add(key_value_size)?;
+    for _ in 0..levels {
+        let image_size = u32_at(bytes, position, big_endian)? as usize;
+        // The size given for a cubemap that isn't an array is of one face
+        let size = if array_elements == 0 && faces == 6 {
+            padded(image_size)?.checked_mul(6)?
+        } else {
+            padded(image_size)?
+        };
+        position = position.checked_add(4)?.checked_add(size)?;
+        if position > bytes.len() {
+            return None;
+        }
+    }
+    Some(position)
+}
+
+// Version 2 has an index of where each mipmap level is
+fn ktx2_end(bytes: &[u8]) -> Option<usize> {
+    if !bytes.starts_with(&KTX2_IDENTIFIER) {
+        return None;
+    }
+    let levels = u32_at(bytes, 40, false)?.max(1) as usize;
+    let mut end = [(48, 52), (56, 60)]
+        .iter()
+        .map(|&(offset, length)| {
+            Some(u32_at(bytes, offset, false)? as u64 + u32_at(bytes, length, false)? as u64)
+        })
+        .chain(std::iter::once(
+            u64_at(bytes, 64)
+                .zip(u64_at(bytes, 72))
+                .and_then(|(offset, length)| offset.checked_add(length)),
+        ))
+        .collect::<Option<Vec<u64>>>()?
+        .into_iter()
+        .max()?;
+    for level in 0..levels {
+        let entry = KTX2_LEVEL_INDEX + level * 24;
+        end = end.max(u64_at(bytes, entry)?.checked_add(u64_at(bytes, entry + 8)?)?);
+    }
+    within(bytes, usize::try_from(end).ok()?)
+}
+
+pub fn find_end(header: &str, bytes: &[u8]) -> Option<usize> {
+    match header {
+        "PNG" => png_end(bytes),
+        "WEBP" => webp_end(bytes),
+        "OggS" => ogg_end(bytes),
+        "KTX" => ktx1_end(bytes),
+        KTX2_HEADER => ktx2_end(bytes),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{extract_bytes, fixtures};
+
+    const JUNK: &[u8] = b"\x00\x00roblox cache metadata\xFF\xFF";
+
+    fn with_junk(payload: &[u8]) -> Vec<u8> {
+        [payload, JUNK].concat()
+    }
+
+    #[test]
+    fn finds_the_end_of_each_format() {
+        for (header, payload) in [
+            ("PNG", fixtures::png()),
+            ("WEBP", fixtures::webp()),
+            ("OggS", fixtures::ogg()),
+            ("KTX", fixtures::ktx1()),
+            (KTX2_HEADER, fixtures::ktx2()),
+        ] {
+            assert_eq!(find_end(header, &payload), Some(payload.len()), "{header}");
+            assert_eq!(
+                find_end(header, &with_junk(&payload)),
+                Some(payload.len()),
+                "{header} with junk after it"
+            );
+        }
+    }
+
+    #[test]
+    fn ogg_ends_at_the_last_page_ending_a_stream() {
+        let mut bytes = fixtures::ogg();
+        let end = bytes.len();
+        bytes.extend(fixtures::ogg_page(0x00, 3, b"not ended"));
+        assert_eq!(find_end("OggS", &bytes), Some(end));
+
+        // No page ends the stream
+        let bytes = fixtures::ogg_page(0x02, 0, b"\x01vorbis");
+        assert_eq!(find_end("OggS", &bytes), None);
+    }
+
+    #[test]
+    fn falls_back_when_the_end_cant_be_told() {
+        for (header, payload) in [
+            ("PNG", fixtures::png()),
+            ("WEBP", fixtures::webp()),
+            ("KTX", fixtures::ktx1()),
+            (KTX2_HEADER, fixtures::ktx2()),
+        ] {
+            let cut = &payload[..payload.len() - 4];
+            assert_eq!(find_end(header, cut), None, "{header} cut short");
+        }
+        assert_eq!(find_end("PNG", b"not a png at all"), None);
+        assert_eq!(find_end("OggS", &[]), None);
+        assert_eq!(find_end("JSON", &fixtures::json()), None); // No end to look for
+
+        // Nothing to cut, so everything after the header is kept
+        let bytes = with_junk(&fixtures::png()[..40]);
+        assert_eq!(extract_bytes("PNG", bytes.clone()), bytes);
+    }
+
+    #[test]
+    fn crafted_lengths_dont_overflow() {
+        let mut bytes = fixtures::ktx2();
+        bytes[64..72].copy_from_slice(&u64::MAX.to_le_bytes());
+        bytes[72..80].copy_from_slice(&16u64.to_le_bytes());
+        assert_eq!(find_end(KTX2_HEADER, &bytes), None);
+
+        let mut bytes = fixtures::ktx2();
+        bytes[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(find_end(KTX2_HEADER, &bytes), None);
+
+        let mut bytes = fixtures::webp();
+        bytes[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(find_end("WEBP", &bytes), None);
+
+        let mut bytes = fixtures::png();
+        bytes[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(find_end("PNG", &bytes), None);
+    }
+
+    #[test]
+    fn png_with_junk_is_extracted_byte_exact() {
+        let png = fixtures::png();
+        let mut bytes = fixtures::http_preamble("https://c0.rbxcdn.com/png");
+        bytes.extend(with_junk(&png));
+        assert_eq!(extract_bytes("PNG", bytes), png);
+    }
+}
@@ -0,0 +1,206 @@
+// Aliasing several assets at once, either from a pattern like "boss-theme-{n}" or a list of names
+// with one per line. Assets are named in the order they're given.
+//
+// {n}              Counts up from 1, {n:3} pads it to 3 digits
+// {id}             Asset id, {id8} for the first 8 characters
+use std::collections::HashMap;
+
+use crate::config;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Counter(usize),    // Padding
+    Id(Option<usize>), // Number of characters
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Collision {
+    Empty,
+    Invalid(config::AliasError),
+    Existing(String), // Asset that already has the alias
+    Batch,            // Given to more than one asset in this batch
+}
+
+#[derive(Debug, Clone)]
+pub struct PlannedAlias {
+    pub asset: String,
+    pub previous: String,
+    pub alias: String,
+    pub collision: Option<Collision>,
+}
+
+fn parse_placeholder(placeholder: &str, position: usize) -> Result<Segment, String> {
+    let (name, argument) = match placeholder.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (placeholder, None),
+    };
+
+    match (name, argument) {
+        ("n", None) => Ok(Segment::Counter(0)),
+        ("n", Some(padding)) => match padding.parse::<usize>() {
+            Ok(padding) if padding <= 20 => Ok(Segment::Counter(padding)),
+            _ => Err(format!(
+                "Invalid counter padding '{padding}' at position {position}"
+            )),
+        },
+        ("id", None) => Ok(Segment::Id(None)),
+        _ => {
+            // {id8} etc.
+            if let Some(length) = name.strip_prefix("id").filter(|_| argument.is_none()) {
+                if let Ok(length) = length.parse::<usize>() {
+                    if length > 0 {
+                        return Ok(Segment::Id(Some(length)));
+                    }
+                }
+            }
+            Err(format!(
+                "Unknown placeholder '{{{placeholder}}}' at position {position}"
+            ))
+        }
+    }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut chars = pattern.char_indices();
+
+    while let Some((position, c)) = chars.next() {
+        match c {
+            '{' => {
+                if !text.is_empty() {
+                    segments.push(Segment::Text(std::mem::take(&mut text)));
+                }
+
+                let mut placeholder = String::new();
+                let mut closed = false;
+                for (_, c) in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    placeholder.push(c);
+                }
+
+                if !closed {
+                    return Err(format!("Unclosed '{{' at position {position}"));
+                }
+                segments.push(parse_placeholder(&placeholder, position)?);
+            }
+            '}' => return Err(format!("Unexpected '}}' at position {position}")),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+
+    Ok(segments)
+}
+
+fn render(segments: &[Segment], counter: usize, asset: &str) -> String {
+    let mut rendered = String::new();
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => rendered.push_str(text),
+            Segment::Counter(padding) => {
+                rendered.push_str(&format!("{counter:0padding$}"));
+            }
+            Segment::Id(length) => {
+                rendered.extend(asset.chars().take(length.unwrap_or(usize::MAX)));
+            }
+        }
+    }
+    rendered
+}
+
+pub fn from_pattern(pattern: &str, assets: &[String]) -> Result<Vec<String>, String> {
+    let segments = parse_pattern(pattern)?;
+    Ok(assets
+        .iter()
+        .enumerate()
+        .map(|(i, asset)| render(&segments, i + 1, asset))
+        .collect())
+}
+
+// Blank lines are ignored so a trailing newline doesn't matter
+pub fn from_list(list: &str, assets: &[String]) -> Result<Vec<String>, String> {
+    let names: Vec<String> = list
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect();
+
+    if names.len() != assets.len() {
+        return Err(format!(
+            "{} names were given for {} assets",
+            names.len(),
+            assets.len()
+        ));
+    }
+    Ok(names)
+}
+
+// Aliases are compared ignoring case as they become file names when extracting
+pub fn plan(assets: &[String], aliases: Vec<String>) -> Vec<PlannedAlias> {
+    // Aliases the assets outside the batch already have
+    let existing: HashMap<String, String> = config::get_asset_aliases()
+        .into_iter()
+        .filter(|(asset, alias)| asset != alias && !assets.contains(asset))
+        .map(|(asset, alias)| (alias.to_lowercase(), asset))
+        .collect();
+
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for alias in &aliases {
+        *counts.entry(alias.to_lowercase()).or_default() += 1;
+    }
+
+    assets
+        .iter()
+        .zip(aliases)
+        .map(|(asset, alias)| {
+            let key = alias.to_lowercase();
+            let collision = if alias.trim().is_empty() {
+                Some(Collision::Empty)
+            } else if let Err(e) = config::validate_alias(&alias) {
+                Some(Collision::Invalid(e))
+            } else if let Some(other) = existing.get(&key) {
+                Some(Collision::Existing(other.clone()))
+            } else if counts.get(&key).is_some_and(|count| *count > 1) {
+                Some(Collision::Batch)
+            } else {
+                None
+            };
+
+            PlannedAlias {
+                asset: asset.clone(),
+                previous: config::get_asset_alias(asset),
+                alias,
+                collision,
+            }
+        })
+        .collect()
+}
+
+// Returns what the aliases were before, to undo with restore()
+pub fn apply(plan: &[PlannedAlias]) -> Vec<(String, String)> {
+    let mut previous = Vec::new();
+    for planned in plan {
+        previous.push((
+            planned.asset.clone(),
+            config::get_asset_alias(&planned.asset),
+        ));
+        config::set_asset_alias(&planned.asset, &planned.alias);
+    }
+    log_info!("Aliased {} assets", plan.len());
+    previous
+}
+
+pub fn restore(previous: &[(String, String)]) {
+    for (asset, alias) in previous {
+        config::set_asset_alias(asset, alias);
+    }
+    log_info!("Restored the aliases of {} assets", previous.len());
+}
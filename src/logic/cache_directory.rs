@@ -1,19 +1,41 @@
 use std::io::Read;
-use std::{fs, path::PathBuf, sync::Mutex};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::PathBuf,
+    sync::mpsc,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
 
 use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use std::sync::{Arc, LazyLock};
 
 use crate::config;
 use crate::locale;
-use crate::logic::{self, determine_category};
+use crate::logic::{
+    self, clear, read_only,
+    scan_index::{self, ScanIndex},
+    summary::SourceSummary,
+};
 
 const DEFAULT_DIRECTORIES: [&str; 2] = [
     "%Temp%\\Roblox",
     "~/.var/app/org.vinegarhq.Sober/cache/sober",
 ]; // For windows and linux (sober)
 
-static CACHE_DIRECTORY: LazyLock<Mutex<PathBuf>> = LazyLock::new(|| Mutex::new(detect_directory()));
+// Tried when the cache directory has no /sounds, some Sober installs keep it with the app data
+const DEFAULT_SOUNDS_DIRECTORIES: [&str; 1] = ["~/.var/app/org.vinegarhq.Sober/data/sober/sounds"];
+
+const DEFAULT_TIMEOUT: u64 = 5; // Seconds before a network drive is considered disconnected
+const RETRY_DELAYS: [u64; 3] = [50, 150, 400]; // Milliseconds to wait before each retry
+const MAX_LIST_THREADS: usize = 8; // Past this they mostly wait on the drive
+const LIST_QUEUE_LENGTH: usize = 256; // Entries waiting for a worker while listing
+
+static RETRY_COUNT: LazyLock<Mutex<usize>> = LazyLock::new(|| Mutex::new(0)); // Since last taken
+static LAST_OPENED: LazyLock<Mutex<Option<Instant>>> = LazyLock::new(|| Mutex::new(None)); // An asset, for knowing the drive answers
+static SESSION_DIRECTORY: LazyLock<Mutex<Option<PathBuf>>> = LazyLock::new(|| Mutex::new(None)); // Not saved to the config
 
 fn create_asset_info_unchecked(path: &PathBuf, category: logic::Category) -> logic::AssetInfo {
     match path.file_name() {
@@ -24,10 +46,18 @@ fn create_asset_info_unchecked(path: &PathBuf, category: logic::Category) -> log
 
                 logic::AssetInfo {
                     name: file_name.to_string_lossy().to_string(),
-                    _size: size,
+                    size,
+                    actual_size: size,
+                    actual_size_approximate: false,
                     last_modified,
                     from_file: true,
                     from_sql: false,
+                    prefer_sql: false,
+                    other_name: None,
+                    evicted: false,
+                    header: None,
+                    hash: None,
+                    origin_url: None,
                     category,
                 }
             }
@@ -35,10 +65,18 @@ fn create_asset_info_unchecked(path: &PathBuf, category: logic::Category) -> log
                 log_warn!("Failed to get asset info: {}", e);
                 logic::AssetInfo {
                     name: file_name.to_string_lossy().to_string(),
-                    _size: 0,
+                    size: 0,
+                    actual_size: 0,
+                    actual_size_approximate: false,
                     last_modified: None,
                     from_file: true,
                     from_sql: false,
+                    prefer_sql: false,
+                    other_name: None,
+                    evicted: false,
+                    header: None,
+                    hash: None,
+                    origin_url: None,
                     category,
                 }
             }
@@ -47,20 +85,125 @@ fn create_asset_info_unchecked(path: &PathBuf, category: logic::Category) -> log
             log_warn!("Failed to get asset info: No filename");
             logic::AssetInfo {
                 name: path.to_string_lossy().to_string(),
-                _size: 0,
+                size: 0,
+                actual_size: 0,
+                actual_size_approximate: false,
                 last_modified: None,
                 from_file: true,
                 from_sql: false,
+                prefer_sql: false,
+                other_name: None,
+                evicted: false,
+                header: None,
+                hash: None,
+                origin_url: None,
                 category,
             }
         }
     }
 }
 
-fn get_category_cache_directory(category: logic::Category) -> PathBuf {
+// Read the first bytes of a file, used for detecting headers without reading the whole file
+fn read_prefix(path: &PathBuf, length: usize) -> std::io::Result<Vec<u8>> {
+    read_file_prefix(fs::File::open(path)?, length)
+}
+
+fn read_file_prefix(mut file: fs::File, length: usize) -> std::io::Result<Vec<u8>> {
+    let mut buffer = vec![0; length];
+    let bytes_read = file.read(&mut buffer)?;
+    buffer.truncate(bytes_read);
+    Ok(buffer)
+}
+
+// Blocking fs calls on a disconnected network drive can hang for minutes, so run them on a
+// helper thread and give up after the timeout. The helper thread is left to finish on its own.
+fn with_timeout<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(f());
+    });
+
+    receiver.recv_timeout(get_timeout()).ok()
+}
+
+fn get_timeout() -> Duration {
+    Duration::from_secs(
+        config::get_config_u64("cache_directory_timeout").unwrap_or(DEFAULT_TIMEOUT),
+    )
+}
+
+// Opening is where a disconnected drive hangs, once the file is open reading it isn't timed out
+// however large it is. While the drive keeps answering files are opened on this thread, so
+// extracting doesn't start a thread for every asset.
+fn open_asset(path: &PathBuf) -> std::io::Result<fs::File> {
+    let answering = LAST_OPENED
+        .lock()
+        .unwrap()
+        .is_some_and(|opened| opened.elapsed() < get_timeout());
+    let file = if answering {
+        fs::File::open(path)?
+    } else {
+        let path = path.clone();
+        with_timeout(move || fs::File::open(path))
+            .unwrap_or_else(|| Err(std::io::ErrorKind::TimedOut.into()))?
+    };
+    *LAST_OPENED.lock().unwrap() = Some(Instant::now());
+    Ok(file)
+}
+
+// Roblox may be writing the file being read, which only fails until it's done
+fn is_transient(error: &std::io::Error) -> bool {
+    match error.kind() {
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::Interrupted => true,
+        // Sharing and lock violations on Windows, permission errors elsewhere are permanent
+        std::io::ErrorKind::PermissionDenied => cfg!(windows),
+        _ => cfg!(windows) && matches!(error.raw_os_error(), Some(32) | Some(33)),
+    }
+}
+
+fn with_retries<T>(mut f: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        match f() {
+            Err(e) if is_transient(&e) => match delays.next() {
+                Some(delay) => {
+                    log_debug!("Retrying read in {}ms: {}", delay, e);
+                    *RETRY_COUNT.lock().unwrap() += 1;
+                    thread::sleep(Duration::from_millis(*delay));
+                }
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+// Number of retried reads since this was last called, for logging after a refresh or extraction
+pub fn take_retry_count() -> usize {
+    std::mem::take(&mut *RETRY_COUNT.lock().unwrap())
+}
+
+// Checked before every refresh, so a reconnected drive is picked up again
+pub fn is_reachable() -> bool {
+    let dir = get_cache_directory();
+    match with_timeout(move || fs::metadata(dir)) {
+        Some(Ok(metadata)) => metadata.is_dir(),
+        Some(Err(e)) => {
+            log_warn!("Cache directory unreachable: {}", e);
+            false
+        }
+        None => {
+            log_warn!("Cache directory unreachable: Timed out");
+            false
+        }
+    }
+}
+
+pub fn get_category_cache_directory(category: logic::Category) -> PathBuf {
     let cache_dir = get_cache_directory();
     if category == logic::Category::Music {
-        cache_dir.join("sounds") // Music located in /sounds
+        get_sounds_directory().unwrap_or_else(expected_sounds_directory) // Music located in /sounds
     } else {
         cache_dir.join("http") // Other stuff located in /http
     }
@@ -141,7 +284,110 @@ pub fn detect_directory() -> PathBuf {
     PathBuf::new()
 }
 
-pub fn validate_directory(directory: &str) -> Result<String, String> {
+// Music is listed from here: the configured folder, /sounds in the cache directory or another default
+pub fn detect_sounds_directory() -> Option<PathBuf> {
+    if let Some(directory) = config::get_config_string("sounds_directory") {
+        match validate_directory(&directory) {
+            Ok(resolved_directory) => return Some(PathBuf::from(resolved_directory)),
+            Err(e) => log_warn!("Detecting user-specified sounds directory failed: {}", e),
+        }
+    }
+
+    let sounds = get_cache_directory().join("sounds");
+    if sounds.is_dir() {
+        return Some(sounds);
+    }
+
+    DEFAULT_SOUNDS_DIRECTORIES
+        .iter()
+        .find_map(|directory| validate_directory(directory).ok())
+        .map(PathBuf::from)
+}
+
+// Where the sounds directory was looked for, shown when it wasn't found
+pub fn expected_sounds_directory() -> PathBuf {
+    match config::get_config_string("sounds_directory") {
+        Some(directory) => PathBuf::from(logic::resolve_path(&directory)),
+        None => get_cache_directory().join("sounds"),
+    }
+}
+
+pub fn get_sounds_directory() -> Option<PathBuf> {
+    logic::state().sounds_directory().lock().unwrap().clone()
+}
+
+// Call after changing sounds_directory or the cache directory
+pub fn reset_sounds_directory() {
+    let detected = detect_sounds_directory();
+    match &detected {
+        Some(directory) => log_info!("Sounds directory: {}", directory.display()),
+        None => log_warn!(
+            "No sounds directory detected at {}",
+            expected_sounds_directory().display()
+        ),
+    }
+    *logic::state().sounds_directory().lock().unwrap() = detected;
+}
+
+// A default directory other than the current one, e.g. Sober's when the Windows path was chosen
+pub fn find_alternative_directory() -> Option<String> {
+    let current = get_cache_directory();
+    DEFAULT_DIRECTORIES
+        .iter()
+        .filter_map(|directory| validate_directory(directory).ok())
+        .find(|directory| current != std::path::Path::new(directory))
+}
+
+// What the health check needs to know about the cache directory
+pub struct DirectoryHealth {
+    pub reachable: bool,
+    pub missing_folders: Vec<&'static str>,
+    pub file_count: usize,
+    pub samples: Vec<Vec<u8>>, // Decompressed prefixes of some cached files
+}
+
+pub fn health(sample_size: usize) -> DirectoryHealth {
+    if !is_reachable() {
+        return DirectoryHealth {
+            reachable: false,
+            missing_folders: Vec::new(),
+            file_count: 0,
+            samples: Vec::new(),
+        };
+    }
+
+    let cache_dir = get_cache_directory();
+    // The sounds directory has its own check as it can be elsewhere
+    let missing_folders = ["http"]
+        .into_iter()
+        .filter(|folder| !cache_dir.join(folder).is_dir())
+        .collect();
+
+    let dir = get_category_cache_directory(logic::Category::All);
+    let paths: Vec<PathBuf> = with_timeout(move || {
+        fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default()
+    })
+    .unwrap_or_default();
+
+    // Same detection as refresh
+    let samples = paths
+        .iter()
+        .take(sample_size)
+        .filter_map(|path| with_retries(|| read_prefix(path, 2048)).ok())
+        .filter_map(|buffer| logic::decompress_prefix(&buffer, 2048))
+        .collect();
+
+    DirectoryHealth {
+        reachable: true,
+        missing_folders,
+        file_count: paths.len(),
+        samples,
+    }
+}
+
+pub fn validate_directory(directory: &str) -> Result<String, logic::Error> {
     let resolved_directory = logic::resolve_path(directory);
 
     match fs::metadata(&resolved_directory) {
@@ -151,25 +397,29 @@ pub fn validate_directory(directory: &str) -> Result<String, String> {
                 // Successfully detected a directory, we can return it
                 Ok(resolved_directory)
             } else {
-                Err(format!("{resolved_directory}: Not a directory"))
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::NotADirectory,
+                    format!("{resolved_directory}: Not a directory"),
+                )
+                .into())
             }
         }
-        Err(e) => {
-            Err(e.to_string()) // Convert to correct data type
-        }
+        Err(e) => Err(e.into()),
     }
 }
 
 pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
+    if read_only::is_enabled() {
+        log_warn!("Not clearing the cache directory in read-only mode");
+        return;
+    }
     let dir = get_cache_directory();
 
-    // Sanity check
-    if dir == PathBuf::from("/") || dir == PathBuf::from("") || dir == PathBuf::new() {
-        log_error!("Unable to clear cache - cache directory is not acceptable.");
+    if let Err(e) = clear::check_cache_directory(&dir) {
+        log_error!("Unable to clear cache - {}", e);
+        logic::update_status(locale::get_message(locale, "error-check-logs", None));
         return;
     }
-    assert_ne!(dir, PathBuf::from("/"));
-    assert_ne!(dir, PathBuf::from(""));
 
     // Read directory
     let entries: Vec<_> = match fs::read_dir(dir) {
@@ -192,6 +442,13 @@ pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
         args.set("item", count);
         args.set("total", total);
 
+        // Stopping between files, whatever was deleted already stays deleted
+        if logic::get_cancel_task() {
+            log_info!("Clearing cancelled after {} of {} files", count, total);
+            logic::update_status(locale::get_message(locale, "cancelled-after", Some(&args)));
+            return;
+        }
+
         count += 1; // Increase counter for progress
         logic::update_progress(count as f32 / total as f32); // Convert to f32 to allow floating point output
 
@@ -245,18 +502,109 @@ pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
     }
 }
 
+// Reads the start of a file and finds its category, None if it's in another category
+fn classify(
+    entry: std::io::Result<fs::DirEntry>,
+    category: logic::Category,
+    index: &Mutex<Option<ScanIndex>>,
+) -> std::io::Result<Option<logic::AssetInfo>> {
+    let path = entry?.path();
+
+    if category == logic::Category::Music {
+        return Ok(Some(create_asset_info_unchecked(&path, category)));
+    }
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let metadata = with_retries(|| fs::metadata(&path))?;
+    let size = metadata.len();
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_nanos() as i64);
+
+    let cached = match (index.lock().unwrap().as_mut(), modified) {
+        (Some(index), Some(modified)) => index.get(&name, size, modified),
+        _ => None,
+    };
+    let entry = match cached {
+        Some(entry) => entry,
+        None => {
+            // Reading only the start of the file, the same as the database
+            let prefix_length = logic::get_prefix_length();
+            let buffer = with_retries(|| read_prefix(&path, prefix_length))?;
+
+            // Compressed files need decompressing before the headers can be found
+            let prefix = match logic::decompress_prefix(&buffer, prefix_length) {
+                Some(prefix) => prefix,
+                None => {
+                    logic::decompress_prefix(&fs::read(&path)?, prefix_length).unwrap_or_default()
+                }
+            };
+
+            let entry = scan_index::Entry::sniff(&buffer, &prefix, size);
+            if let (Some(index), Some(modified)) = (index.lock().unwrap().as_mut(), modified) {
+                index.insert(name, size, modified, entry.clone());
+            }
+            entry
+        }
+    };
+
+    // Listed once even if several headers match
+    Ok(entry.listing(category).map(|(category, header)| {
+        let mut asset_info = create_asset_info_unchecked(&path, category);
+        asset_info.header = header;
+        asset_info.actual_size = entry.actual_size;
+        asset_info.actual_size_approximate = entry.actual_size_approximate;
+        asset_info.origin_url = entry.origin_url.clone();
+        asset_info
+    }))
+}
+
 pub fn refresh(
     category: logic::Category,
     cli_list_mode: bool,
     locale: &FluentBundle<Arc<FluentResource>>,
-) {
+) -> SourceSummary {
     let dir = get_category_cache_directory(category);
 
-    let headers = logic::get_headers(&category);
+    let mut summary = SourceSummary::default();
+
+    // Not an error, the Music tab explains where it was looked for
+    if category == logic::Category::Music && get_sounds_directory().is_none() {
+        log_warn!(
+            "No sounds directory detected at {}",
+            expected_sounds_directory().display()
+        );
+        return summary;
+    }
+
+    if !is_reachable() {
+        let mut args = FluentArgs::new();
+        args.set(
+            "directory",
+            get_cache_directory().to_string_lossy().to_string(),
+        );
+        logic::update_status(locale::get_message(
+            locale,
+            "error-cache-directory-unreachable",
+            Some(&args),
+        ));
+        summary.errors += 1;
+        summary.unavailable = true;
+        return summary;
+    }
 
     // Read directory
-    let entries: Vec<_> = match fs::read_dir(dir) {
-        Ok(directory_read) => directory_read.collect(),
+    let entries: Vec<_> = match with_timeout(move || {
+        fs::read_dir(dir).map(|directory_read| directory_read.collect::<Vec<_>>())
+    })
+    .unwrap_or_else(|| Err(std::io::ErrorKind::TimedOut.into()))
+    {
+        Ok(entries) => entries,
         Err(e) => {
             // Abort operation, error occurred
             logic::update_status(locale::get_message(
@@ -265,24 +613,32 @@ pub fn refresh(
                 None,
             ));
             log_error!("Error listing directory: {e}");
-            return;
+            summary.errors += 1;
+            summary.unavailable = true;
+            return summary;
         }
     };
 
+    // Sounds aren't sniffed, they're all listed
+    let index =
+        Mutex::new((category != logic::Category::Music).then(|| ScanIndex::load(scan_index::FILE)));
+
     // Get amount and initialise counter for progress
     let total = entries.len();
     let mut count = 0;
-
-    // Tell the user that there is no files to list to make it easy to tell that the program is working and it isn't broken
-    if total == 0 {
-        logic::update_file_list(logic::create_no_files(locale), cli_list_mode);
-    }
-    // Filter the files out
-    for entry in entries {
-        if logic::get_stop_list_running() {
-            break; // Stop if another thread requests to stop this task.
-        }
-
+    let mut batch = logic::ListBatch::new(cli_list_mode);
+
+    // One thread hands out the entries, the workers read and sniff them, and they're put back in
+    // the order they were listed in here so the list comes out the same every time
+    let threads = thread::available_parallelism()
+        .map_or(1, |threads| threads.get())
+        .min(MAX_LIST_THREADS)
+        .min(total)
+        .max(1);
+
+    let mut waiting = BTreeMap::new(); // Finished before the ones listed earlier
+    let mut next = 0;
+    let mut collect = |position: usize, result: std::io::Result<Option<logic::AssetInfo>>| {
         count += 1; // Increase counter for progress
         logic::update_progress(count as f32 / total as f32); // Convert to f32 to allow floating point output
 
@@ -291,67 +647,147 @@ pub fn refresh(
         args.set("item", count);
         args.set("total", total);
 
-        let result = {
-            let headers = &headers;
-            let category = &category;
-            move || -> std::io::Result<()> {
-                let path = entry?.path();
-
-                if category == &logic::Category::Music {
-                    logic::update_file_list(
-                        create_asset_info_unchecked(&path, *category),
-                        cli_list_mode,
-                    );
+        let asset_info = match result {
+            Ok(asset_info) => {
+                if asset_info.is_some() {
+                    summary.listed += 1;
                 } else {
-                    let mut file = fs::File::open(&path)?;
-
-                    // Reading the first 2048 bytes of the file
-                    let mut buffer = vec![0; 2048];
-                    let bytes_read = file.read(&mut buffer)?;
-                    buffer.truncate(bytes_read);
-
-                    for header in headers {
-                        // Check if header is not empty before actually checking file
-                        if !header.is_empty() {
-                            // Add it to the list if the header is inside of the file.
-                            if logic::bytes_contains(&buffer, header.as_bytes()) {
-                                let asset_info = if *category == logic::Category::All {
-                                    create_asset_info_unchecked(&path, determine_category(&buffer))
-                                } else {
-                                    create_asset_info_unchecked(&path, *category)
-                                };
-                                logic::update_file_list(asset_info, cli_list_mode);
-                            }
-                        }
-                    }
+                    summary.skipped += 1; // Another category
                 }
-
-                Ok(())
-            }
-        }();
-        match result {
-            Ok(()) => {
                 logic::update_status(locale::get_message(locale, "filtering-files", Some(&args)));
+                asset_info
             }
             Err(e) => {
+                summary.errors += 1;
                 log_error!("Couldn't open file: {}", e);
                 logic::update_status(locale::get_message(
                     locale,
                     "failed-opening-file",
                     Some(&args),
                 ));
+                None
+            }
+        };
+
+        waiting.insert(position, asset_info);
+        while let Some(asset_info) = waiting.remove(&next) {
+            next += 1;
+            if let Some(asset_info) = asset_info {
+                batch.push(asset_info);
             }
         }
+    };
+
+    if threads == 1 {
+        // Handing entries between threads costs more than it saves with one core
+        for (position, entry) in entries.into_iter().enumerate() {
+            if logic::get_stop_list_running() {
+                break; // Stop if another thread requests to stop this task.
+            }
+            collect(position, classify(entry, category, &index));
+        }
+    } else {
+        let (entry_sender, entry_receiver) = mpsc::sync_channel(LIST_QUEUE_LENGTH);
+        let entry_receiver = Mutex::new(entry_receiver);
+        let (result_sender, result_receiver) = mpsc::channel();
+
+        let state = logic::state();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                state.run(|| {
+                    for entry in entries.into_iter().enumerate() {
+                        if logic::get_stop_list_running() || entry_sender.send(entry).is_err() {
+                            break; // Stop if another thread requests to stop this task.
+                        }
+                    }
+                    drop(entry_sender); // The workers finish once it's empty
+                })
+            });
+
+            for _ in 0..threads {
+                let result_sender = result_sender.clone();
+                let (state, entry_receiver, index) = (&state, &entry_receiver, &index);
+                scope.spawn(move || {
+                    state.run(|| loop {
+                        let Ok((position, entry)) = entry_receiver.lock().unwrap().recv() else {
+                            break;
+                        };
+                        // Still emptied when stopping so the sending thread isn't left waiting
+                        if logic::get_stop_list_running() {
+                            continue;
+                        }
+                        if result_sender
+                            .send((position, classify(entry, category, index)))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    })
+                });
+            }
+            drop(result_sender);
+
+            for (position, result) in result_receiver {
+                if !logic::get_stop_list_running() {
+                    collect(position, result); // Anything still waiting isn't listed
+                }
+            }
+        });
+    }
+    batch.flush();
+
+    let index = index.into_inner().unwrap();
+    if let Some(index) = index {
+        index.save(!logic::get_stop_list_running());
+    }
+
+    let retries = take_retry_count();
+    if retries > 0 {
+        log_info!("Retried {} file reads while listing", retries);
     }
+
+    summary
+}
+
+pub fn read_asset(asset: &logic::AssetInfo) -> Result<Vec<u8>, logic::Error> {
+    let dir = get_category_cache_directory(asset.category);
+    let asset_path = dir.join(&asset.name);
+    // One stuck read shouldn't freeze a whole extraction
+    Ok(with_retries(|| {
+        let mut file = open_asset(&asset_path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(bytes)
+    })?)
 }
 
-pub fn read_asset(asset: &logic::AssetInfo) -> Result<Vec<u8>, std::io::Error> {
+// Only the start of the file, for detecting headers again without reading all of it
+pub fn read_asset_prefix(asset: &logic::AssetInfo, length: usize) -> Result<Vec<u8>, logic::Error> {
     let dir = get_category_cache_directory(asset.category);
     let asset_path = dir.join(&asset.name);
-    fs::read(asset_path)
+    Ok(with_retries(|| {
+        read_file_prefix(open_asset(&asset_path)?, length)
+    })?)
 }
 
-pub fn swap_assets(asset_a: &logic::AssetInfo, asset_b: &logic::AssetInfo) -> std::io::Result<()> {
+pub fn write_asset(asset: &logic::AssetInfo, bytes: &[u8]) -> Result<(), logic::Error> {
+    read_only::check()?;
+    let dir = get_category_cache_directory(asset.category);
+    let asset_path = dir.join(&asset.name);
+    Ok(fs::write(asset_path, bytes)?)
+}
+
+pub fn delete_asset(asset: &logic::AssetInfo) -> Result<(), logic::Error> {
+    read_only::check()?;
+    let dir = get_category_cache_directory(asset.category);
+    Ok(fs::remove_file(dir.join(&asset.name))?)
+}
+
+pub fn swap_assets(
+    asset_a: &logic::AssetInfo,
+    asset_b: &logic::AssetInfo,
+) -> Result<(), logic::Error> {
+    read_only::check()?;
     let dir_a = get_category_cache_directory(asset_a.category);
     let dir_b = get_category_cache_directory(asset_b.category);
 
@@ -366,7 +802,11 @@ pub fn swap_assets(asset_a: &logic::AssetInfo, asset_b: &logic::AssetInfo) -> st
     Ok(())
 }
 
-pub fn copy_assets(asset_a: &logic::AssetInfo, asset_b: &logic::AssetInfo) -> std::io::Result<()> {
+pub fn copy_assets(
+    asset_a: &logic::AssetInfo,
+    asset_b: &logic::AssetInfo,
+) -> Result<(), logic::Error> {
+    read_only::check()?;
     let dir_a = get_category_cache_directory(asset_a.category);
     let dir_b = get_category_cache_directory(asset_b.category);
 
@@ -379,20 +819,119 @@ pub fn copy_assets(asset_a: &logic::AssetInfo, asset_b: &logic::AssetInfo) -> st
 }
 
 pub fn get_cache_directory() -> PathBuf {
-    CACHE_DIRECTORY.lock().unwrap().clone()
+    logic::state().cache_directory().lock().unwrap().clone()
 }
 
 pub fn set_cache_directory(value: PathBuf) {
-    let mut cache_directory = CACHE_DIRECTORY.lock().unwrap();
-    *cache_directory = value;
+    *logic::state().cache_directory().lock().unwrap() = value;
+    *SESSION_DIRECTORY.lock().unwrap() = None; // Replaced by whatever was chosen
+    reset_sounds_directory(); // Usually inside the cache directory
+}
+
+// Used until RoExtract closes or another directory is chosen, e.g. a folder dropped on the window
+pub fn open_for_session(directory: &str) -> Result<PathBuf, logic::Error> {
+    let directory = PathBuf::from(validate_directory(directory)?);
+    set_cache_directory(directory.clone());
+    *SESSION_DIRECTORY.lock().unwrap() = Some(directory.clone());
+    Ok(directory)
+}
+
+// None unless the directory was opened for the session
+pub fn get_session_directory() -> Option<PathBuf> {
+    SESSION_DIRECTORY.lock().unwrap().clone()
 }
 
-pub fn create_asset_info(asset: &str, category: logic::Category) -> Option<logic::AssetInfo> {
+pub fn create_asset_info(
+    asset: &str,
+    category: logic::Category,
+) -> Result<logic::AssetInfo, logic::Error> {
     let path = get_category_cache_directory(category).join(asset);
 
     if path.exists() {
-        Some(create_asset_info_unchecked(&path, category))
+        let mut asset_info = create_asset_info_unchecked(&path, category);
+        if let Ok(buffer) = read_prefix(&path, 32) {
+            (asset_info.actual_size, asset_info.actual_size_approximate) =
+                logic::get_actual_size(&buffer, asset_info.size);
+        }
+        Ok(asset_info)
     } else {
-        None
+        Err(logic::Error::NotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{
+        fixtures::{self, Fixture, FixtureAsset},
+        Category,
+    };
+    use std::io::ErrorKind;
+
+    const BENCHMARK_FILES: usize = 50_000;
+
+    // cargo test --release refresh_50k_files -- --ignored --nocapture
+    #[test]
+    #[ignore = "benchmark, run it with --release"]
+    fn refresh_50k_files() {
+        let payloads = [
+            fixtures::png(),
+            fixtures::ogg(),
+            fixtures::json(),
+            fixtures::ktx2(),
+            fixtures::mesh(),
+        ];
+        let mut fixture = Fixture::new();
+        for i in 0..BENCHMARK_FILES {
+            fixture.add_file(
+                FixtureAsset::new(&format!("{i:032x}"), payloads[i % payloads.len()].clone())
+                    .preamble(fixtures::http_preamble("https://c0.rbxcdn.com/benchmark")),
+            );
+        }
+        let state = fixture.state();
+
+        let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+        for run in 1..=3 {
+            let summary = state.refresh(Category::All).unwrap();
+            assert_eq!(summary.cache_directory.listed, BENCHMARK_FILES);
+            println!(
+                "Run {run}: listed {BENCHMARK_FILES} files in {} ms ({threads} CPUs)",
+                summary.duration_ms
+            );
+        }
+    }
+
+    // A reader failing with these errors in turn, then reading "ok". Returns it with how often it was called
+    fn flaky_read(errors: &[ErrorKind]) -> (std::io::Result<&'static str>, usize) {
+        let mut attempts = 0;
+        let result = with_retries(|| {
+            attempts += 1;
+            match errors.get(attempts - 1) {
+                Some(kind) => Err((*kind).into()),
+                None => Ok("ok"),
+            }
+        });
+        (result, attempts)
+    }
+
+    #[test]
+    fn transient_errors_are_retried() {
+        let (result, attempts) = flaky_read(&[ErrorKind::Interrupted, ErrorKind::WouldBlock]);
+        assert_eq!(result.unwrap(), "ok");
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn permanent_errors_fail_fast() {
+        let (result, attempts) = flaky_read(&[ErrorKind::NotFound]);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn retries_run_out() {
+        let (result, attempts) = flaky_read(&[ErrorKind::Interrupted; RETRY_DELAYS.len() + 1]);
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::Interrupted);
+        assert_eq!(attempts, RETRY_DELAYS.len() + 1);
     }
 }
@@ -0,0 +1,356 @@
+// Clearing the cache. The GUI always clears everything, the CLI can also clear only some assets.
+use fluent_bundle::{FluentBundle, FluentResource};
+use serde::Serialize;
+use std::{
+    collections::HashSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use crate::logic::{
+    self, cache_directory, error_code::ErrorCode, modifications, read_only, sql_database,
+    AssetInfo, Category,
+};
+
+// Folders Roblox makes in the cache directory, it isn't cleared without one of them
+const CACHE_MARKERS: [&str; 2] = ["http", "sounds"];
+
+#[derive(Debug)]
+pub enum ClearError {
+    ReadOnly(read_only::ReadOnlyMode),
+    UnsafePath(PathBuf, &'static str), // Why it isn't cleared
+}
+
+impl fmt::Display for ClearError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClearError::ReadOnly(e) => e.fmt(f),
+            ClearError::UnsafePath(path, reason) => {
+                write!(f, "Refusing to clear {}: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClearError {}
+
+impl ClearError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ClearError::ReadOnly(_) => ErrorCode::ReadOnly,
+            ClearError::UnsafePath(_, _) => ErrorCode::UnsafePath,
+        }
+    }
+}
+
+impl From<read_only::ReadOnlyMode> for ClearError {
+    fn from(error: read_only::ReadOnlyMode) -> Self {
+        ClearError::ReadOnly(error)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClearScope {
+    pub category: Option<Category>, // All only matches assets whose category is unknown, like search
+    pub older_than: Option<Duration>, // Last modified at least this long ago
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClearSummary {
+    pub removed: usize,
+    pub failed: usize,
+    pub bytes: u64,
+    pub cancelled: bool, // The assets after where it stopped aren't counted
+}
+
+impl ClearScope {
+    // Everything is cleared by deleting the folders and database, not asset by asset
+    pub fn is_everything(&self) -> bool {
+        self.category.is_none() && self.older_than.is_none()
+    }
+
+    fn contains(&self, asset: &AssetInfo) -> bool {
+        if self
+            .category
+            .is_some_and(|category| category != asset.category)
+        {
+            return false;
+        }
+
+        match self.older_than {
+            // Assets without a time are kept, they can't be known to be old
+            Some(older_than) => asset.last_modified.is_some_and(|last_modified| {
+                SystemTime::now()
+                    .duration_since(last_modified)
+                    .is_ok_and(|age| age >= older_than)
+            }),
+            None => true,
+        }
+    }
+}
+
+// e.g. "90s", "30m", "12h", "7d" or "2w"
+pub fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let duration = duration.trim();
+    let split = duration
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("{duration} has no unit, use s, m, h, d or w"))?;
+    let (amount, unit) = duration.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("{duration} doesn't start with a number"))?;
+
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("Unknown unit {unit}, use s, m, h, d or w")),
+    };
+    Ok(Duration::from_secs(amount * seconds))
+}
+
+// What would be removed, this lists the assets so it replaces the file list
+pub fn plan(scope: ClearScope) -> Vec<AssetInfo> {
+    // Music is only found by listing the music folder
+    let sources = match scope.category {
+        Some(Category::Music) => vec![Category::Music],
+        Some(_) => vec![Category::All],
+        None => vec![Category::All, Category::Music],
+    };
+
+    let mut assets = Vec::new();
+    for source in sources {
+        logic::refresh(source, false, true);
+        assets.extend(
+            logic::get_file_list()
+                .into_iter()
+                .filter(|asset| scope.contains(asset)),
+        );
+    }
+    assets
+}
+
+fn is_home(path: &Path) -> bool {
+    [
+        std::env::home_dir(),
+        Some(PathBuf::from(logic::resolve_path("~"))),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|home| fs::canonicalize(home).is_ok_and(|home| home == path))
+}
+
+// Everything inside these folders gets deleted, so a misconfigured path must never be one
+fn check_folder(folder: &Path) -> Result<PathBuf, ClearError> {
+    let refuse = |reason| Err(ClearError::UnsafePath(folder.to_path_buf(), reason));
+
+    if folder.as_os_str().is_empty() {
+        return refuse("the path is empty");
+    }
+    let Ok(canonical) = fs::canonicalize(folder) else {
+        return refuse("it doesn't exist");
+    };
+    if !canonical.is_dir() {
+        return refuse("it isn't a folder");
+    }
+    if canonical.parent().is_none() {
+        return refuse("it is the root of a drive");
+    }
+    if is_home(&canonical) {
+        return refuse("it is the home folder");
+    }
+    Ok(canonical)
+}
+
+// The database and the rbx-storage folder next to it are deleted
+pub fn check_database(database: &Path) -> Result<(), ClearError> {
+    if !database.is_file() {
+        return Err(ClearError::UnsafePath(
+            database.to_path_buf(),
+            "the database doesn't exist",
+        ));
+    }
+    match database.parent() {
+        Some(folder) => check_folder(folder).map(|_| ()),
+        None => Err(ClearError::UnsafePath(
+            database.to_path_buf(),
+            "it has no parent folder",
+        )),
+    }
+}
+
+// An empty folder is fine, it's what's left after clearing
+pub fn check_cache_directory(directory: &Path) -> Result<(), ClearError> {
+    let directory = check_folder(directory)?;
+    let is_empty = fs::read_dir(&directory).is_ok_and(|mut entries| entries.next().is_none());
+    if !is_empty
+        && !CACHE_MARKERS
+            .iter()
+            .any(|marker| directory.join(marker).is_dir())
+    {
+        return Err(ClearError::UnsafePath(
+            directory,
+            "it doesn't look like a Roblox cache, it has no http or sounds folder",
+        ));
+    }
+    Ok(())
+}
+
+// Both are checked before deleting anything so a bad path doesn't leave the cache half cleared
+pub fn check_paths() -> Result<(), ClearError> {
+    if let Some(database) = sql_database::get_database_path() {
+        check_database(&database)?;
+    }
+    check_cache_directory(&cache_directory::get_cache_directory())
+}
+
+// Used by the GUI's clear cache button
+pub fn clear_everything(locale: &FluentBundle<Arc<FluentResource>>) -> Result<(), ClearError> {
+    read_only::check()?;
+    check_paths()?;
+
+    sql_database::clear_cache(locale);
+    if logic::get_cancel_task() {
+        return Ok(()); // The database is deleted in one go, so this is the only place to stop
+    }
+    cache_directory::clear_cache(locale);
+
+    // The modified assets no longer exist, some still do if it was cancelled
+    if !logic::get_cancel_task() {
+        modifications::clear();
+    }
+    Ok(())
+}
+
+fn delete_asset(asset: &AssetInfo) -> Result<(), logic::Error> {
+    // Either copy left behind would be listed again
+    if asset.in_both_sources() {
+        sql_database::delete_asset(&asset.in_source(logic::Source::Sql))?;
+        cache_directory::delete_asset(&asset.in_source(logic::Source::File))
+    } else if asset.from_sql {
+        sql_database::delete_asset(asset)
+    } else {
+        cache_directory::delete_asset(asset)
+    }
+}
+
+// Call start_task() first, assets is what plan() returned
+pub fn clear(
+    scope: ClearScope,
+    assets: &[AssetInfo],
+    locale: &FluentBundle<Arc<FluentResource>>,
+) -> Result<ClearSummary, ClearError> {
+    let mut attempted = assets.len();
+    if scope.is_everything() {
+        clear_everything(locale)?;
+    } else {
+        read_only::check()?;
+        for (i, asset) in assets.iter().enumerate() {
+            // Stopping between assets, e.g. Ctrl+C in the CLI
+            if logic::get_cancel_task() {
+                attempted = i;
+                break;
+            }
+            match delete_asset(asset) {
+                Ok(()) => modifications::forget(&asset.name),
+                Err(e) => log_error!("Failed to delete {}: {}", asset.name, e),
+            }
+        }
+    }
+
+    // Count what is actually gone, clearing everything can partly fail without knowing which assets
+    let remaining: HashSet<String> = plan(scope).into_iter().map(|asset| asset.name).collect();
+    let mut summary = ClearSummary {
+        cancelled: logic::get_cancel_task(),
+        ..Default::default()
+    };
+    for asset in &assets[..attempted] {
+        if remaining.contains(&asset.name) {
+            summary.failed += 1;
+        } else {
+            summary.removed += 1;
+            summary.bytes += asset.size;
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::fixtures::Fixture;
+
+    fn refused(result: Result<(), ClearError>) -> &'static str {
+        match result {
+            Err(ClearError::UnsafePath(_, reason)) => reason,
+            other => panic!("expected an unsafe path, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn hostile_cache_directories_are_refused() {
+        let fixture = Fixture::new();
+        let file = fixture.root.join("not-a-folder");
+        fs::write(&file, b"file").unwrap();
+        let documents = fixture.root.join("documents");
+        fs::create_dir_all(&documents).unwrap();
+        fs::write(documents.join("essay.txt"), b"keep me").unwrap();
+
+        assert_eq!(
+            refused(check_cache_directory(Path::new(""))),
+            "the path is empty"
+        );
+        assert_eq!(
+            refused(check_cache_directory(&fixture.root.join("missing"))),
+            "it doesn't exist"
+        );
+        assert_eq!(refused(check_cache_directory(&file)), "it isn't a folder");
+        assert!(matches!(
+            check_cache_directory(&documents),
+            Err(ClearError::UnsafePath(_, _))
+        ));
+
+        let root = fs::canonicalize("/").unwrap();
+        assert_eq!(
+            refused(check_cache_directory(&root)),
+            "it is the root of a drive"
+        );
+        if let Some(home) = std::env::home_dir().filter(|home| home.is_dir()) {
+            assert_eq!(
+                refused(check_cache_directory(&home)),
+                "it is the home folder"
+            );
+        }
+    }
+
+    #[test]
+    fn cache_directories_are_allowed() {
+        let fixture = Fixture::new();
+        check_cache_directory(&fixture.cache_directory()).unwrap();
+
+        // What's left once it's been cleared
+        let empty = fixture.root.join("empty");
+        fs::create_dir_all(&empty).unwrap();
+        check_cache_directory(&empty).unwrap();
+    }
+
+    #[test]
+    fn database_has_to_be_a_file() {
+        let fixture = Fixture::new();
+        assert_eq!(
+            refused(check_database(&fixture.database_path())),
+            "the database doesn't exist"
+        );
+        assert_eq!(
+            refused(check_database(&fixture.root)),
+            "the database doesn't exist"
+        );
+
+        fs::write(fixture.database_path(), b"").unwrap();
+        check_database(&fixture.database_path()).unwrap();
+    }
+}
@@ -0,0 +1,316 @@
+// Contact sheets for reviewing a texture dump at a glance. Images are shrunk onto a grid with their
+// alias underneath and written as numbered PNG pages. Images that can't be decoded (e.g. KTX) get
+// a crossed out placeholder so the grid still lines up with the list.
+use ab_glyph::{point, Font, FontArc, PxScale, ScaleFont};
+use fluent_bundle::FluentArgs;
+use image::{imageops, Rgba, RgbaImage};
+use std::{fmt, fs, path::PathBuf, time::Instant};
+
+use crate::{
+    config, locale,
+    logic::{self, AssetInfo},
+};
+
+const PADDING: u32 = 8; // Around each cell
+const CAPTION_HEIGHT: u32 = 18;
+const CAPTION_SIZE: f32 = 14.0;
+const BACKGROUND: Rgba<u8> = Rgba([255, 255, 255, 255]);
+const PLACEHOLDER: Rgba<u8> = Rgba([220, 220, 220, 255]);
+const PLACEHOLDER_CROSS: Rgba<u8> = Rgba([160, 160, 160, 255]);
+const CAPTION_COLOUR: [u8; 3] = [0, 0, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SheetOptions {
+    pub columns: u32,
+    pub rows: u32, // Per page, more pages are written when there are more images
+    pub thumbnail_size: u32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SheetSummary {
+    pub pages: usize,
+    pub images: usize,
+    pub failed: usize, // Couldn't be decoded, shown as placeholders
+    pub cancelled: bool,
+    pub duration_ms: u64,
+}
+
+impl Default for SheetOptions {
+    fn default() -> Self {
+        SheetOptions {
+            columns: config::get_config_u64("contact_sheet_columns").unwrap_or(8) as u32,
+            rows: config::get_config_u64("contact_sheet_rows").unwrap_or(6) as u32,
+            thumbnail_size: config::get_config_u64("contact_sheet_thumbnail_size").unwrap_or(128)
+                as u32,
+        }
+    }
+}
+
+impl SheetOptions {
+    // Keeps pages to a size image viewers can open
+    fn clamped(self) -> Self {
+        SheetOptions {
+            columns: self.columns.clamp(1, 64),
+            rows: self.rows.clamp(1, 64),
+            thumbnail_size: self.thumbnail_size.clamp(16, 1024),
+        }
+    }
+
+    fn cell_size(&self) -> (u32, u32) {
+        (
+            self.thumbnail_size + PADDING * 2,
+            self.thumbnail_size + CAPTION_HEIGHT + PADDING * 2,
+        )
+    }
+}
+
+impl fmt::Display for SheetSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Wrote {} contact sheet pages with {} images in {:.1}s, {} couldn't be decoded",
+            self.pages,
+            self.images,
+            self.duration_ms as f64 / 1000.0,
+            self.failed
+        )?;
+        if self.cancelled {
+            write!(f, ", cancelled before finishing")?;
+        }
+        Ok(())
+    }
+}
+
+// The same font as the GUI, so captions can show anything an alias can
+fn caption_font() -> Option<FontArc> {
+    let fonts = egui::FontDefinitions::default();
+    let data = fonts.font_data.get("Ubuntu-Light")?;
+    FontArc::try_from_vec(data.font.to_vec()).ok()
+}
+
+fn text_width(font: &FontArc, text: &str) -> f32 {
+    let font = font.as_scaled(PxScale::from(CAPTION_SIZE));
+    text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum()
+}
+
+// Cut long aliases short with an ellipsis so they don't run into the next cell
+fn fit_caption(font: &FontArc, text: &str, max_width: f32) -> String {
+    if text_width(font, text) <= max_width {
+        return text.to_owned();
+    }
+    let mut caption: String = text.to_owned();
+    while !caption.is_empty() && text_width(font, &format!("{caption}…")) > max_width {
+        caption.pop();
+    }
+    format!("{caption}…")
+}
+
+fn draw_caption(page: &mut RgbaImage, font: &FontArc, text: &str, x: u32, y: u32) {
+    let scale = PxScale::from(CAPTION_SIZE);
+    let scaled = font.as_scaled(scale);
+    let mut caret = x as f32;
+
+    for c in text.chars() {
+        let glyph_id = scaled.glyph_id(c);
+        let glyph =
+            glyph_id.with_scale_and_position(scale, point(caret, y as f32 + scaled.ascent()));
+        caret += scaled.h_advance(glyph_id);
+
+        if let Some(outlined) = font.outline_glyph(glyph) {
+            let bounds = outlined.px_bounds();
+            outlined.draw(|glyph_x, glyph_y, coverage| {
+                let pixel_x = bounds.min.x as i32 + glyph_x as i32;
+                let pixel_y = bounds.min.y as i32 + glyph_y as i32;
+                if pixel_x < 0 || pixel_y < 0 {
+                    return;
+                }
+                if let Some(pixel) = page.get_pixel_mut_checked(pixel_x as u32, pixel_y as u32) {
+                    // Blend towards the caption colour by how much of the pixel the glyph covers
+                    for channel in 0..3 {
+                        let background = pixel[channel] as f32;
+                        let colour = CAPTION_COLOUR[channel] as f32;
+                        pixel[channel] = (background + (colour - background) * coverage) as u8;
+                    }
+                }
+            });
+        }
+    }
+}
+
+fn draw_placeholder(page: &mut RgbaImage, x: u32, y: u32, size: u32) {
+    for offset_y in 0..size {
+        for offset_x in 0..size {
+            let on_cross = offset_x == offset_y || offset_x == size - 1 - offset_y;
+            page.put_pixel(
+                x + offset_x,
+                y + offset_y,
+                if on_cross {
+                    PLACEHOLDER_CROSS
+                } else {
+                    PLACEHOLDER
+                },
+            );
+        }
+    }
+}
+
+fn decode_thumbnail(asset: &AssetInfo, size: u32) -> Option<RgbaImage> {
+    let bytes = match logic::extract_asset_to_bytes(asset.clone()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log_warn!("Failed to read {} for the contact sheet: {}", asset.name, e);
+            return None;
+        }
+    };
+    match image::load_from_memory(&bytes) {
+        Ok(image) => Some(image.thumbnail(size, size).to_rgba8()),
+        Err(e) => {
+            log_warn!(
+                "Failed to decode {} for the contact sheet: {}",
+                asset.name,
+                e
+            );
+            None
+        }
+    }
+}
+
+// None if cancelled part way through the page
+fn render_page(
+    assets: &[AssetInfo],
+    options: &SheetOptions,
+    font: Option<&FontArc>,
+    summary: &mut SheetSummary,
+    done: &mut usize,
+    total: usize,
+) -> Option<RgbaImage> {
+    let locale = locale::get_locale(None);
+    let (cell_width, cell_height) = options.cell_size();
+    let rows = assets.len().div_ceil(options.columns as usize) as u32;
+    let mut page =
+        RgbaImage::from_pixel(cell_width * options.columns, cell_height * rows, BACKGROUND);
+
+    for (i, asset) in assets.iter().enumerate() {
+        // Stopping between images, the pages already written are kept
+        if logic::get_cancel_task() {
+            return None;
+        }
+
+        let x = (i as u32 % options.columns) * cell_width + PADDING;
+        let y = (i as u32 / options.columns) * cell_height + PADDING;
+
+        match decode_thumbnail(asset, options.thumbnail_size) {
+            Some(thumbnail) => {
+                // Centred in the cell, thumbnails keep their aspect ratio
+                let thumbnail_x = x + (options.thumbnail_size - thumbnail.width()) / 2;
+                let thumbnail_y = y + (options.thumbnail_size - thumbnail.height()) / 2;
+                imageops::overlay(
+                    &mut page,
+                    &thumbnail,
+                    thumbnail_x as i64,
+                    thumbnail_y as i64,
+                );
+            }
+            None => {
+                summary.failed += 1;
+                draw_placeholder(&mut page, x, y, options.thumbnail_size);
+            }
+        }
+
+        if let Some(font) = font {
+            let caption = fit_caption(
+                font,
+                &logic::display_text(&config::get_asset_alias(&asset.name)),
+                options.thumbnail_size as f32,
+            );
+            draw_caption(&mut page, font, &caption, x, y + options.thumbnail_size + 2);
+        }
+
+        summary.images += 1;
+        *done += 1;
+        logic::update_progress(*done as f32 / total as f32);
+        let mut args = FluentArgs::new();
+        args.set("item", *done);
+        args.set("total", total);
+        logic::update_status(locale::get_message(
+            &locale,
+            "making-contact-sheet",
+            Some(&args),
+        ));
+    }
+
+    Some(page)
+}
+
+// Pages are written as contact-sheet-001.png and so on. Returns the summary if yield_for_thread is set
+pub fn generate(
+    assets: Vec<AssetInfo>,
+    destination: PathBuf,
+    options: SheetOptions,
+    yield_for_thread: bool,
+) -> Option<SheetSummary> {
+    // Stop multiple threads from running
+    if !logic::start_task() {
+        return None;
+    }
+
+    let handle = logic::state::spawn(move || {
+        let start = Instant::now();
+        let options = options.clamped();
+        let font = caption_font();
+        if font.is_none() {
+            log_warn!("No font for contact sheet captions, leaving them out");
+        }
+
+        let mut summary = SheetSummary::default();
+        if let Err(e) = fs::create_dir_all(&destination) {
+            log_error!("Failed to create {}: {}", destination.display(), e);
+        }
+
+        let per_page = (options.columns * options.rows) as usize;
+        let total = assets.len();
+        let mut done = 0;
+        for (page_number, page_assets) in assets.chunks(per_page).enumerate() {
+            let Some(page) = render_page(
+                page_assets,
+                &options,
+                font.as_ref(),
+                &mut summary,
+                &mut done,
+                total,
+            ) else {
+                summary.cancelled = true;
+                break;
+            };
+
+            let path = destination.join(format!("contact-sheet-{:03}.png", page_number + 1));
+            match page.save(&path) {
+                Ok(()) => summary.pages += 1,
+                Err(e) => log_error!("Failed to write {}: {}", path.display(), e),
+            }
+        }
+
+        summary.duration_ms = start.elapsed().as_millis() as u64;
+        log_info!("{}", summary);
+
+        logic::finish_task(); // Allow other threads to run again
+        let locale = locale::get_locale(None);
+        let mut args = FluentArgs::new();
+        args.set("pages", summary.pages);
+        args.set("images", summary.images);
+        args.set("failed", summary.failed);
+        logic::update_status(locale::get_message(
+            &locale,
+            "contact-sheet-written",
+            Some(&args),
+        ));
+        summary
+    });
+
+    if yield_for_thread {
+        // Will wait for the thread instead of quitting immediately
+        handle.join().ok()
+    } else {
+        None
+    }
+}
@@ -0,0 +1,132 @@
+// The error for reading, writing and extracting assets, so callers can match on what went wrong
+// instead of reading messages. Errors that only come from the OS stay as Io, ErrorCode::of() still
+// tells them apart. Localised messages for the status bar are all made here.
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use std::{fmt, io, sync::Arc};
+
+use crate::{
+    locale,
+    logic::{
+        error_code::ErrorCode, format_size, read_only::ReadOnlyMode, temp_space::TempSpaceExhausted,
+    },
+};
+
+#[derive(Debug)]
+pub enum Error {
+    HeaderNotFound,
+    NotFound,               // Not in the cache directory or the database
+    Evicted,                // Roblox removed the asset's content but kept its entry
+    Corrupt,                // Doesn't match the digest in its HTTP preamble
+    Decode(String),         // Converting it failed, e.g. a KTX texture in a format that isn't read
+    InvalidAssetId(String), // Database ids are hex
+    NoConnection,           // There's no database open
+    ReadOnly,
+    Sql(rusqlite::Error),
+    Io(io::Error),
+}
+
+impl Error {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::HeaderNotFound => ErrorCode::HeaderNotFound,
+            Error::NotFound => ErrorCode::NotFound,
+            Error::Evicted => ErrorCode::Evicted,
+            Error::Corrupt => ErrorCode::Corrupt,
+            Error::Decode(_) => ErrorCode::InvalidData,
+            Error::InvalidAssetId(_) => ErrorCode::InvalidId,
+            Error::NoConnection => ErrorCode::NoDatabase,
+            Error::ReadOnly => ErrorCode::ReadOnly,
+            Error::Sql(rusqlite::Error::QueryReturnedNoRows) => ErrorCode::NotFound,
+            Error::Sql(_) => ErrorCode::Io,
+            Error::Io(e) => ErrorCode::of(e),
+        }
+    }
+
+    // "E_WRITE: Permission denied (os error 13)", for logs and stderr
+    pub fn describe(&self) -> String {
+        format!("{}: {}", self.code(), self)
+    }
+
+    // For the status bar
+    pub fn message(&self, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+        let mut args = FluentArgs::new();
+        let id = match self {
+            Error::HeaderNotFound => "error-header-not-found",
+            Error::NotFound => "error-asset-not-found",
+            Error::Evicted => "error-asset-evicted",
+            Error::Corrupt => "error-asset-corrupt",
+            Error::InvalidAssetId(id) => {
+                args.set("id", id.clone());
+                "error-invalid-asset-id"
+            }
+            Error::NoConnection => "error-no-database",
+            Error::ReadOnly => "error-read-only",
+            Error::Io(e) if e.get_ref().is_some_and(|inner| inner.is::<ReadOnlyMode>()) => {
+                "error-read-only"
+            }
+            Error::Io(e) => match e
+                .get_ref()
+                .and_then(|inner| inner.downcast_ref::<TempSpaceExhausted>())
+            {
+                Some(exhausted) => {
+                    args.set("size", format_size(exhausted.needed));
+                    "error-temp-space-exhausted"
+                }
+                None => {
+                    args.set("error", self.describe());
+                    "error-with-code"
+                }
+            },
+            Error::Decode(_) | Error::Sql(_) => {
+                args.set("error", self.describe());
+                "error-with-code"
+            }
+        };
+        locale::get_message(locale, id, Some(&args))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::HeaderNotFound => write!(f, "No known header in the asset"),
+            Error::NotFound => write!(f, "The asset isn't in the cache directory or the database"),
+            Error::Evicted => write!(f, "This entry's data is no longer in the cache"),
+            Error::Corrupt => write!(f, "The asset doesn't match the checksum stored with it"),
+            Error::Decode(reason) => write!(f, "The asset couldn't be decoded: {reason}"),
+            Error::InvalidAssetId(id) => write!(f, "'{id}' isn't a valid asset id"),
+            Error::NoConnection => write!(f, "No SQL connection!"),
+            Error::ReadOnly => write!(f, "{ReadOnlyMode}"),
+            Error::Sql(e) => write!(f, "{e}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Sql(e) => Some(e),
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Error::Sql(error)
+    }
+}
+
+impl From<ReadOnlyMode> for Error {
+    fn from(_: ReadOnlyMode) -> Self {
+        Error::ReadOnly
+    }
+}
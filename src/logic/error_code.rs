@@ -0,0 +1,216 @@
+// Stable codes for errors, put in front of error messages in logs and on stderr and next to them in
+// JSON, so scripts don't have to read messages that can be localised or written by the OS.
+// Codes are never renamed or reused once released, add a new one instead.
+//
+// | Code               | Meaning                                                        |
+// |--------------------|----------------------------------------------------------------|
+// | E_NO_DB            | There's no database, or it couldn't be opened                  |
+// | E_HEX              | An asset id isn't valid hex                                    |
+// | E_HEADER_NOT_FOUND | No known header in the asset, it's written out as it is        |
+// | E_WRITE            | Writing the extracted file failed                              |
+// | E_BUSY             | Another task is running                                        |
+// | E_EVICTED          | Roblox removed the asset's content but kept its entry          |
+// | E_READ_ONLY        | Read-only mode is on                                           |
+// | E_NOT_FOUND        | The asset or file doesn't exist                                |
+// | E_PERMISSION       | The OS denied access                                           |
+// | E_TIMED_OUT        | The cache directory stopped responding                         |
+// | E_INVALID_DATA     | The asset couldn't be decoded                                  |
+// | E_STORAGE_FULL     | Out of disk space, or out of space in the temp directory       |
+// | E_TOO_LARGE        | Over a size limit                                              |
+// | E_UNSAFE_PATH      | Refused to delete a folder that doesn't look like a cache      |
+// | E_BAD_REQUEST      | The server couldn't understand a request                       |
+// | E_UNAUTHORIZED     | The server was sent a missing or wrong token                   |
+// | E_CORRUPT          | The asset doesn't match the digest stored with it              |
+// | E_IO               | Any other error                                                |
+use serde::Serialize;
+use std::{fmt, io};
+use strum_macros::EnumIter;
+
+use crate::logic::{read_only::ReadOnlyMode, temp_space::TempSpaceExhausted};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ErrorCode {
+    NoDatabase,
+    InvalidId,
+    HeaderNotFound,
+    Write,
+    Busy,
+    Evicted,
+    ReadOnly,
+    NotFound,
+    PermissionDenied,
+    TimedOut,
+    InvalidData,
+    StorageFull,
+    TooLarge,
+    UnsafePath,
+    BadRequest,
+    Unauthorized,
+    Corrupt,
+    Io,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::NoDatabase => "E_NO_DB",
+            ErrorCode::InvalidId => "E_HEX",
+            ErrorCode::HeaderNotFound => "E_HEADER_NOT_FOUND",
+            ErrorCode::Write => "E_WRITE",
+            ErrorCode::Busy => "E_BUSY",
+            ErrorCode::Evicted => "E_EVICTED",
+            ErrorCode::ReadOnly => "E_READ_ONLY",
+            ErrorCode::NotFound => "E_NOT_FOUND",
+            ErrorCode::PermissionDenied => "E_PERMISSION",
+            ErrorCode::TimedOut => "E_TIMED_OUT",
+            ErrorCode::InvalidData => "E_INVALID_DATA",
+            ErrorCode::StorageFull => "E_STORAGE_FULL",
+            ErrorCode::TooLarge => "E_TOO_LARGE",
+            ErrorCode::UnsafePath => "E_UNSAFE_PATH",
+            ErrorCode::BadRequest => "E_BAD_REQUEST",
+            ErrorCode::Unauthorized => "E_UNAUTHORIZED",
+            ErrorCode::Corrupt => "E_CORRUPT",
+            ErrorCode::Io => "E_IO",
+        }
+    }
+
+    // The code for an io::Error from anywhere in RoExtract, falling back to what kind of error the OS
+    // gave. logic::Error::code() uses this for its Io variant
+    pub fn of(error: &io::Error) -> Self {
+        if let Some(inner) = error.get_ref() {
+            if let Some(coded) = inner.downcast_ref::<CodedError>() {
+                return coded.code;
+            }
+            if inner.is::<ReadOnlyMode>() {
+                return ErrorCode::ReadOnly;
+            }
+            if inner.is::<TempSpaceExhausted>() {
+                return ErrorCode::StorageFull;
+            }
+        }
+
+        match error.kind() {
+            io::ErrorKind::NotFound => ErrorCode::NotFound,
+            io::ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+            io::ErrorKind::TimedOut => ErrorCode::TimedOut,
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => ErrorCode::InvalidData,
+            io::ErrorKind::StorageFull => ErrorCode::StorageFull,
+            io::ErrorKind::FileTooLarge => ErrorCode::TooLarge,
+            _ => ErrorCode::Io,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for ErrorCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+// For errors whose code can't be told from their kind, e.g. a write failing with NotFound
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    message: String,
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+pub fn with_code(code: ErrorCode, kind: io::ErrorKind, message: impl fmt::Display) -> io::Error {
+    io::Error::new(
+        kind,
+        CodedError {
+            code,
+            message: message.to_string(),
+        },
+    )
+}
+
+// "E_WRITE: Permission denied (os error 13)", for logs and stderr
+pub fn describe(error: &io::Error) -> String {
+    format!("{}: {}", ErrorCode::of(error), error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use strum::IntoEnumIterator;
+
+    // The codes in the table at the top of this file, in order
+    fn documented() -> Vec<&'static str> {
+        include_str!("error_code.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("// | E_"))
+            .filter_map(|row| {
+                let end = row.find(' ')?;
+                Some(&row[..end])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn codes_are_unique() {
+        let mut seen = HashSet::new();
+        for code in ErrorCode::iter() {
+            assert!(seen.insert(code.as_str()), "{code:?} reuses {code}");
+        }
+    }
+
+    #[test]
+    fn codes_match_the_table() {
+        let codes: Vec<&str> = ErrorCode::iter()
+            .map(|code| code.as_str().strip_prefix("E_").unwrap())
+            .collect();
+        assert_eq!(codes, documented());
+
+        // Released codes never change
+        for (code, expected) in [
+            (ErrorCode::NoDatabase, "E_NO_DB"),
+            (ErrorCode::InvalidId, "E_HEX"),
+            (ErrorCode::HeaderNotFound, "E_HEADER_NOT_FOUND"),
+            (ErrorCode::Write, "E_WRITE"),
+            (ErrorCode::Busy, "E_BUSY"),
+            (ErrorCode::Evicted, "E_EVICTED"),
+            (ErrorCode::ReadOnly, "E_READ_ONLY"),
+            (ErrorCode::NotFound, "E_NOT_FOUND"),
+            (ErrorCode::PermissionDenied, "E_PERMISSION"),
+            (ErrorCode::TimedOut, "E_TIMED_OUT"),
+            (ErrorCode::InvalidData, "E_INVALID_DATA"),
+            (ErrorCode::StorageFull, "E_STORAGE_FULL"),
+            (ErrorCode::TooLarge, "E_TOO_LARGE"),
+            (ErrorCode::UnsafePath, "E_UNSAFE_PATH"),
+            (ErrorCode::BadRequest, "E_BAD_REQUEST"),
+            (ErrorCode::Unauthorized, "E_UNAUTHORIZED"),
+            (ErrorCode::Corrupt, "E_CORRUPT"),
+            (ErrorCode::Io, "E_IO"),
+        ] {
+            assert_eq!(code.as_str(), expected);
+            assert_eq!(
+                serde_json::to_string(&code).unwrap(),
+                format!("\"{expected}\"")
+            );
+        }
+    }
+
+    #[test]
+    fn coded_errors_keep_their_code() {
+        let error = with_code(ErrorCode::Write, io::ErrorKind::NotFound, "no folder");
+        assert_eq!(ErrorCode::of(&error), ErrorCode::Write);
+        assert_eq!(describe(&error), "E_WRITE: no folder");
+        let error = io::Error::from(io::ErrorKind::PermissionDenied);
+        assert_eq!(ErrorCode::of(&error), ErrorCode::PermissionDenied);
+    }
+}
@@ -0,0 +1,322 @@
+// Per-category extraction options, stored in config as
+// "extract_options": { "sounds": { "naming": "alias", "dedupe": true, "layout": "flat" }, ... }
+// Anything missing falls back to the global settings, CLI flags override everything.
+// A template in "extract_template" replaces naming and layout for every category, which are
+// presets for the same templates.
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use strum_macros::{Display, EnumIter};
+
+const DEFAULT_SILENCE_THRESHOLD: f64 = -50.0; // dBFS
+const DEFAULT_NORMALIZE_TARGET: f64 = -1.0; // dBFS
+
+use crate::{
+    config,
+    logic::{template::Template, wav_convert::AudioProcessing, Category},
+};
+
+#[derive(
+    ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumIter, Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Naming {
+    Id,
+    Alias,
+    Hash,      // SHA-256 of the extracted asset
+    Thumbnail, // "headshot-156" for avatar and asset thumbnails, the id for anything else
+}
+
+#[derive(
+    ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumIter, Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Layout {
+    Flat,
+    Category, // A folder for each category
+}
+
+// Filters from the image crate, fastest to best looking
+#[derive(
+    ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumIter, Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+#[value(rename_all = "lower")]
+pub enum ResizeFilter {
+    Nearest,
+    Triangle,
+    CatmullRom,
+    Gaussian,
+    Lanczos3,
+}
+
+// What to do when something is already at an extracted file's path
+#[derive(
+    ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, EnumIter, Display,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum OverwritePolicy {
+    Overwrite,
+    Skip,
+    Rename, // Add " (1)", " (2)" and so on before the extension
+}
+
+impl ResizeFilter {
+    pub fn filter_type(self) -> image::imageops::FilterType {
+        match self {
+            ResizeFilter::Nearest => image::imageops::FilterType::Nearest,
+            ResizeFilter::Triangle => image::imageops::FilterType::Triangle,
+            ResizeFilter::CatmullRom => image::imageops::FilterType::CatmullRom,
+            ResizeFilter::Gaussian => image::imageops::FilterType::Gaussian,
+            ResizeFilter::Lanczos3 => image::imageops::FilterType::Lanczos3,
+        }
+    }
+}
+
+// Downscaling for exported images, stored in config as "image_max_dimension" (0 is off),
+// "image_resize_filter" and "image_power_of_two"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageResize {
+    pub max_dimension: u32, // Longest side, 0 doesn't limit it
+    pub filter: ResizeFilter,
+    pub power_of_two: bool, // Snap each side down to a power of two
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtractOptions {
+    pub naming: Naming,
+    pub dedupe: bool, // Skip assets with the same content as one already extracted
+    pub layout: Layout,
+}
+
+// Set from CLI flags, None uses the per-category options
+#[derive(Clone, Debug, Default)]
+pub struct ExtractOverrides {
+    pub naming: Option<Naming>,
+    pub dedupe: Option<bool>,
+    pub layout: Option<Layout>,
+    pub template: Option<String>,
+    pub playlist: Option<bool>,
+    pub max_dimension: Option<u32>,
+    pub resize_filter: Option<ResizeFilter>,
+    pub power_of_two: Option<bool>,
+    pub overwrite: Option<OverwritePolicy>,
+    pub incremental: Option<bool>,
+    pub manifest: Option<bool>,
+    pub convert_to_png: Option<bool>,
+    pub convert_to_wav: Option<bool>,
+    pub verify: Option<bool>,
+    // Whether extract_dir lists the category first, None uses refresh_before_extract. extract_all
+    // lists Music and All unless it's Some(false), then the list already there is used for its category
+    pub refresh: Option<bool>,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        ExtractOptions {
+            naming: if config::get_config_bool("use_alias").unwrap_or(false) {
+                Naming::Alias
+            } else {
+                Naming::Id
+            },
+            dedupe: false,
+            layout: if config::get_config_bool("sort_into_subfolders").unwrap_or(false) {
+                Layout::Category
+            } else {
+                Layout::Flat
+            },
+        }
+    }
+}
+
+impl ExtractOptions {
+    pub fn with_overrides(self, overrides: &ExtractOverrides) -> Self {
+        ExtractOptions {
+            naming: overrides.naming.unwrap_or(self.naming),
+            dedupe: overrides.dedupe.unwrap_or(self.dedupe),
+            layout: overrides.layout.unwrap_or(self.layout),
+        }
+    }
+
+    // The template equivalent to naming and layout
+    pub fn preset_template(&self) -> String {
+        let name = match self.naming {
+            Naming::Id => "{id}",
+            Naming::Alias => "{alias}",
+            Naming::Hash => "{hash}",
+            Naming::Thumbnail => "{thumbnail}",
+        };
+        match self.layout {
+            Layout::Flat => format!("{name}.{{ext}}"),
+            Layout::Category => format!("{{category}}/{name}.{{ext}}"),
+        }
+    }
+}
+
+fn category_key(category: Category) -> String {
+    category.to_string()
+}
+
+pub fn get_options(category: Category) -> ExtractOptions {
+    let defaults = ExtractOptions::default();
+    let Some(options) = config::get_config()
+        .get("extract_options")
+        .and_then(|options| options.get(category_key(category)))
+        .cloned()
+    else {
+        return defaults;
+    };
+
+    // Fields are read one at a time so a partial entry keeps the other defaults
+    ExtractOptions {
+        naming: options
+            .get("naming")
+            .and_then(|naming| serde_json::from_value(naming.clone()).ok())
+            .unwrap_or(defaults.naming),
+        dedupe: options
+            .get("dedupe")
+            .and_then(|dedupe| dedupe.as_bool())
+            .unwrap_or(defaults.dedupe),
+        layout: options
+            .get("layout")
+            .and_then(|layout| serde_json::from_value(layout.clone()).ok())
+            .unwrap_or(defaults.layout),
+    }
+}
+
+pub fn set_options(category: Category, options: ExtractOptions) {
+    let mut all_options = config::get_config()
+        .get("extract_options")
+        .cloned()
+        .unwrap_or(json!({}));
+    all_options[category_key(category)] = json!(options);
+    config::set_config_value("extract_options", all_options);
+}
+
+// Empty when naming and layout are used instead
+pub fn get_template() -> String {
+    config::get_config_string("extract_template").unwrap_or_default()
+}
+
+pub fn set_template(template: &str) {
+    config::set_config_value("extract_template", json!(template));
+}
+
+// The CLI template, then the configured template, then the category's preset
+pub fn resolve_template(
+    options: &ExtractOptions,
+    overrides: &ExtractOverrides,
+) -> Result<Template, String> {
+    let template = overrides.template.clone().unwrap_or_else(get_template);
+    if template.is_empty() {
+        Template::parse(&options.preset_template())
+    } else {
+        Template::parse(&template)
+    }
+}
+
+// Write an .m3u8 playlist of the extracted audio
+pub fn get_playlist(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .playlist
+        .unwrap_or_else(|| config::get_config_bool("export_playlist").unwrap_or(false))
+}
+
+pub fn get_overwrite_policy(overrides: &ExtractOverrides) -> OverwritePolicy {
+    overrides.overwrite.unwrap_or_else(|| {
+        config::get_config()
+            .get("overwrite_policy")
+            .and_then(|overwrite| serde_json::from_value(overwrite.clone()).ok())
+            .unwrap_or(OverwritePolicy::Overwrite)
+    })
+}
+
+// Skip assets whose file is already there with the same size and modification time
+pub fn get_incremental(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .incremental
+        .unwrap_or_else(|| config::get_config_bool("incremental_extract").unwrap_or(false))
+}
+
+// Write a manifest.json of what was extracted
+pub fn get_manifest(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .manifest
+        .unwrap_or_else(|| config::get_config_bool("export_manifest").unwrap_or(false))
+}
+
+// Write WebP and KTX textures as PNG
+pub fn get_convert_to_png(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .convert_to_png
+        .unwrap_or_else(|| config::get_config_bool("convert_images_to_png").unwrap_or(false))
+}
+
+// Write OGG audio as WAV
+pub fn get_convert_to_wav(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .convert_to_wav
+        .unwrap_or_else(|| config::get_config_bool("convert_audio_to_wav").unwrap_or(false))
+}
+
+// Refuse payloads that don't match the digest in their HTTP preamble, see verify::check()
+pub fn get_verify_on_extract(overrides: &ExtractOverrides) -> bool {
+    overrides
+        .verify
+        .unwrap_or_else(|| config::get_config_bool("verify_on_extract").unwrap_or(true))
+}
+
+// Only used when converting to WAV, a level is only set if that step is on
+pub fn get_audio_processing() -> AudioProcessing {
+    let level = |toggle: &str, key: &str, default: f64| {
+        config::get_config_bool(toggle).unwrap_or(false).then(|| {
+            config::get_config()
+                .get(key)
+                .and_then(|level| level.as_f64())
+                .unwrap_or(default)
+        })
+    };
+    AudioProcessing {
+        trim_silence: level(
+            "trim_silence",
+            "silence_threshold",
+            DEFAULT_SILENCE_THRESHOLD,
+        ),
+        normalize: level(
+            "normalize_audio",
+            "normalize_target",
+            DEFAULT_NORMALIZE_TARGET,
+        ),
+    }
+}
+
+pub fn get_resize_filter() -> ResizeFilter {
+    config::get_config()
+        .get("image_resize_filter")
+        .and_then(|filter| serde_json::from_value(filter.clone()).ok())
+        .unwrap_or(ResizeFilter::Lanczos3)
+}
+
+// None if images are extracted as they are
+pub fn get_image_resize(overrides: &ExtractOverrides) -> Option<ImageResize> {
+    let max_dimension = overrides
+        .max_dimension
+        .unwrap_or_else(|| config::get_config_u64("image_max_dimension").unwrap_or(0) as u32);
+    let power_of_two = overrides
+        .power_of_two
+        .unwrap_or_else(|| config::get_config_bool("image_power_of_two").unwrap_or(false));
+
+    if max_dimension == 0 && !power_of_two {
+        return None;
+    }
+
+    Some(ImageResize {
+        max_dimension,
+        filter: overrides.resize_filter.unwrap_or_else(get_resize_filter),
+        power_of_two,
+    })
+}
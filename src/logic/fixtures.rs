@@ -0,0 +1,1060 @@
+// Cache directories and databases built from scratch for tests, so changes to detection and extraction
+// can be checked against real files without a Roblox install. Each payload builder gives the smallest
+// asset that's still valid enough for RoExtract, and a Fixture writes them the way Roblox stores them:
+// optionally behind an HTTP cache preamble, compressed, cut short or followed by Roblox's metadata.
+use base64::{prelude::BASE64_STANDARD, Engine};
+use image::{codecs, ExtendedColorType, ImageEncoder};
+use md5::{Digest, Md5};
+use rusqlite::{params, Connection};
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::logic::{state::ExtractorState, Category, KTX1_IDENTIFIER, KTX2_IDENTIFIER};
+
+// Every fixture file and row gets this modification time, so manifests don't change between runs
+pub const MODIFIED: u64 = 1_700_000_000; // Unix seconds
+
+static NEXT_FIXTURE: AtomicUsize = AtomicUsize::new(0); // Tests run in parallel
+
+fn image_pixels(width: u32, height: u32) -> Vec<u8> {
+    (0..width * height)
+        .flat_map(|i| [(i * 40) as u8, (i * 90) as u8, 200, 255])
+        .collect()
+}
+
+pub fn png() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    codecs::png::PngEncoder::new(&mut bytes)
+        .write_image(&image_pixels(4, 4), 4, 4, ExtendedColorType::Rgba8)
+        .unwrap();
+    bytes
+}
+
+pub fn webp() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    codecs::webp::WebPEncoder::new_lossless(&mut bytes)
+        .write_image(&image_pixels(4, 4), 4, 4, ExtendedColorType::Rgba8)
+        .unwrap();
+    bytes
+}
+
+// One page of a logical stream, the CRC isn't checked by anything RoExtract does
+pub fn ogg_page(flags: u8, sequence: u32, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() < 255, "one segment per page");
+    let mut page = b"OggS".to_vec();
+    page.push(0); // Version
+    page.push(flags);
+    page.extend_from_slice(&0u64.to_le_bytes()); // Granule position
+    page.extend_from_slice(&1u32.to_le_bytes()); // Stream serial number
+    page.extend_from_slice(&sequence.to_le_bytes());
+    page.extend_from_slice(&[0; 4]); // CRC
+    page.push(1);
+    page.push(payload.len() as u8);
+    page.extend_from_slice(payload);
+    page
+}
+
+// A first page, a page in the middle and a last page with the end of stream flag
+pub fn ogg() -> Vec<u8> {
+    let mut bytes = ogg_page(0x02, 0, b"\x01vorbis fixture");
+    bytes.extend(ogg_page(0x00, 1, b"\x03vorbis comments"));
+    bytes.extend(ogg_page(0x04, 2, &[0x55; 40]));
+    bytes
+}
+
+// Real Vorbis that decodes, one block of samples per channel
+pub fn vorbis(sample_rate: u32, channels: &[Vec<f32>]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = vorbis_rs::VorbisEncoderBuilder::new_with_serial(
+        sample_rate.try_into().unwrap(),
+        (channels.len() as u8).try_into().unwrap(),
+        &mut bytes,
+        1,
+    )
+    .build()
+    .unwrap();
+    encoder.encode_audio_block(channels).unwrap();
+    encoder.finish().unwrap();
+    bytes
+}
+
+// Version 1, one 2x2 RGBA mipmap level
+pub fn ktx1() -> Vec<u8> {
+    let mut bytes = KTX1_IDENTIFIER.to_vec();
+    for field in [
+        0x04030201u32, // Endianness
+        0x1401,        // GL_UNSIGNED_BYTE
+        1,             // Type size
+        0x1908,        // GL_RGBA
+        0x8058,        // GL_RGBA8
+        0x1908,        // GL_RGBA
+        2,             // Width
+        2,             // Height
+        0,             // Depth
+        0,             // Array elements
+        1,             // Faces
+        1,             // Mipmap levels
+        0,             // Key/value data
+    ] {
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    bytes.extend_from_slice(&16u32.to_le_bytes());
+    bytes.extend_from_slice(&[0x7F; 16]);
+    bytes
+}
+
+// Version 2, one 2x2 R8G8B8A8 mipmap level after the level index
+pub fn ktx2() -> Vec<u8> {
+    const DATA_OFFSET: u64 = 104; // Header, index and one level
+    let mut bytes = KTX2_IDENTIFIER.to_vec();
+    for field in [37u32, 1, 2, 2, 0, 0, 1, 1, 0] {
+        // Format, type size, width, height, depth, layers, faces, levels, supercompression
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    for field in [0u32, 0, 0, 0] {
+        // No data format descriptor or key/value data
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    for field in [0u64, 0, DATA_OFFSET, 16, 16] {
+        // No supercompression data, then the level's offset, length and uncompressed length
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    assert_eq!(bytes.len() as u64, DATA_OFFSET);
+    bytes.extend_from_slice(&[0x3C; 16]);
+    bytes
+}
+
+// An empty ID3 tag then mp3_frames()
+pub fn mp3() -> Vec<u8> {
+    let mut bytes = b"ID3\x04\x00\x00\x00\x00\x00\x00".to_vec();
+    bytes.extend(mp3_frames());
+    bytes
+}
+
+// Two 128 kbit/s frames with no tag, MPEG frames are only believed if another follows
+pub fn mp3_frames() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for _ in 0..2 {
+        bytes.extend_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+        bytes.extend_from_slice(&[0x00; 413]);
+    }
+    bytes
+}
+
+pub fn rbxm() -> Vec<u8> {
+    let mut bytes = b"<roblox!\x89\xff\r\n\x1a\n\x00\x00".to_vec();
+    bytes.extend_from_slice(&[0x00; 24]);
+    bytes.extend_from_slice(b"END\x00\x00\x00\x00\x00</roblox>");
+    bytes
+}
+
+pub fn mesh() -> Vec<u8> {
+    b"version 1.00\n1\n[0,0,0][0,1,0][0,0,0][1,0,0][0,1,0][0,0,0][0,0,1][0,1,0][0,0,0]".to_vec()
+}
+
+// An OpenType font with one table record
+pub fn font() -> Vec<u8> {
+    let mut bytes = b"OTTO\x00\x01\x00\x10\x00\x00\x00\x00".to_vec();
+    bytes.extend_from_slice(b"CFF ");
+    bytes.extend_from_slice(&[0x00; 4]); // Checksum
+    bytes.extend_from_slice(&28u32.to_be_bytes());
+    bytes.extend_from_slice(&4u32.to_be_bytes());
+    bytes.extend_from_slice(&[0x01, 0x00, 0x04, 0x01]);
+    bytes
+}
+
+pub fn json() -> Vec<u8> {
+    br#"{"fixture": true, "values": [1, 2, 3]}"#.to_vec()
+}
+
+// What Roblox's HTTP cache puts in front of the payload. ID3 tags are only believed with the
+// binary/octet-stream content type the CDN sends
+pub fn http_preamble(url: &str) -> Vec<u8> {
+    format!("RBXH\x00\x00\x00{url}\r\nHTTP/1.1 200 OK\r\ncontent-type: binary/octet-stream\r\n\r\n")
+        .into_bytes()
+}
+
+// The same, with the Content-Length and Content-MD5 of the body so it can be verified
+pub fn http_preamble_with_digest(url: &str, payload: &[u8]) -> Vec<u8> {
+    let digest = BASE64_STANDARD.encode(Md5::digest(payload));
+    format!(
+        "RBXH\x00\x00\x00{url}\r\nHTTP/1.1 200 OK\r\ncontent-type: binary/octet-stream\r\ncontent-length: {}\r\ncontent-md5: {digest}\r\n\r\n",
+        payload.len()
+    )
+    .into_bytes()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Gzip,
+    Deflate,
+}
+
+pub fn compress(compression: Compression, bytes: &[u8]) -> Vec<u8> {
+    match compression {
+        Compression::Zstd => zstd::encode_all(bytes, 0).unwrap(),
+        Compression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        }
+        Compression::Deflate => {
+            let mut encoder =
+                flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        }
+    }
+}
+
+// One asset as Roblox would store it
+#[derive(Debug, Clone)]
+pub struct FixtureAsset {
+    pub name: String,
+    pub payload: Vec<u8>,
+    pub preamble: Vec<u8>, // In front of the payload, e.g. http_preamble()
+    pub trailer: Vec<u8>,  // After the payload, e.g. Roblox's metadata
+    pub compression: Option<Compression>, // Of the preamble, payload and trailer together
+    pub truncate: Option<usize>, // Bytes kept of what's stored
+}
+
+impl FixtureAsset {
+    pub fn new(name: &str, payload: Vec<u8>) -> Self {
+        FixtureAsset {
+            name: name.to_owned(),
+            payload,
+            preamble: Vec::new(),
+            trailer: Vec::new(),
+            compression: None,
+            truncate: None,
+        }
+    }
+
+    pub fn preamble(mut self, preamble: Vec<u8>) -> Self {
+        self.preamble = preamble;
+        self
+    }
+
+    pub fn trailer(mut self, trailer: Vec<u8>) -> Self {
+        self.trailer = trailer;
+        self
+    }
+
+    pub fn compressed(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn truncated(mut self, length: usize) -> Self {
+        self.truncate = Some(length);
+        self
+    }
+
+    // The bytes written to the file or row
+    pub fn stored(&self) -> Vec<u8> {
+        let mut bytes = self.preamble.clone();
+        bytes.extend_from_slice(&self.payload);
+        bytes.extend_from_slice(&self.trailer);
+        if let Some(compression) = self.compression {
+            bytes = compress(compression, &bytes);
+        }
+        if let Some(length) = self.truncate {
+            bytes.truncate(length);
+        }
+        bytes
+    }
+}
+
+// A cache directory and rbx-storage.db in a temp directory of their own, deleted when dropped
+pub struct Fixture {
+    pub root: PathBuf,
+    database: Option<Connection>,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "RoExtract-fixture-{}-{}",
+            std::process::id(),
+            NEXT_FIXTURE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&root);
+        for directory in ["cache/http", "cache/sounds", "temp"] {
+            fs::create_dir_all(root.join(directory)).unwrap();
+        }
+        Fixture {
+            root,
+            database: None,
+        }
+    }
+
+    pub fn cache_directory(&self) -> PathBuf {
+        self.root.join("cache")
+    }
+
+    pub fn database_path(&self) -> PathBuf {
+        self.root.join("rbx-storage.db")
+    }
+
+    fn write_file(&self, path: &Path, asset: &FixtureAsset) {
+        fs::write(path, asset.stored()).unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(MODIFIED);
+        let modified = filetime::FileTime::from_system_time(modified);
+        filetime::set_file_times(path, modified, modified).unwrap();
+    }
+
+    // A file in cache/http, where everything but music is
+    pub fn add_file(&mut self, asset: FixtureAsset) -> &mut Self {
+        self.write_file(
+            &self.cache_directory().join("http").join(&asset.name),
+            &asset,
+        );
+        self
+    }
+
+    // A file in cache/sounds, listed on the Music tab
+    pub fn add_music(&mut self, asset: FixtureAsset) -> &mut Self {
+        self.write_file(
+            &self.cache_directory().join("sounds").join(&asset.name),
+            &asset,
+        );
+        self
+    }
+
+    // A row in rbx-storage.db, the name has to be hex as it's the row's id
+    pub fn add_row(&mut self, asset: FixtureAsset) -> &mut Self {
+        let path = self.database_path();
+        let conn = self.database.get_or_insert_with(|| {
+            let conn = Connection::open(path).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE files (id BLOB PRIMARY KEY, size INTEGER, ttl INTEGER, content BLOB);",
+            )
+            .unwrap();
+            conn
+        });
+        let stored = asset.stored();
+        conn.execute(
+            "INSERT INTO files (id, size, ttl, content) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                hex::decode(&asset.name).unwrap(),
+                stored.len() as u64,
+                MODIFIED,
+                stored
+            ],
+        )
+        .unwrap();
+        self
+    }
+
+    // A state reading only this fixture, with the database if any rows were added
+    pub fn state(&self) -> Arc<ExtractorState> {
+        let database = self.database.is_some().then(|| self.database_path());
+        ExtractorState::new(self.cache_directory(), database, self.root.join("temp")).unwrap()
+    }
+
+    // Where tests can extract to, inside the fixture so it's deleted with it
+    pub fn output_directory(&self, name: &str) -> PathBuf {
+        let path = self.root.join("output").join(name);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        self.database = None; // Closed before its file is deleted
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+// One asset of every category, each stored a different way. The expected category and extension are
+// what refresh and extract_dir should find
+pub fn every_category() -> Vec<(FixtureAsset, Category, &'static str)> {
+    vec![
+        (
+            FixtureAsset::new("a1", png())
+                .preamble(http_preamble("https://c0.rbxcdn.com/a1"))
+                .trailer(b"\x00roblox-metadata\x00".to_vec()),
+            Category::Images,
+            "png",
+        ),
+        (
+            FixtureAsset::new("a2", webp()).compressed(Compression::Zstd),
+            Category::Images,
+            "webp",
+        ),
+        (FixtureAsset::new("a3", ogg()), Category::Sounds, "ogg"),
+        (
+            FixtureAsset::new("a4", mp3())
+                .preamble(http_preamble("https://c3.rbxcdn.com/a4"))
+                .compressed(Compression::Gzip),
+            Category::Sounds,
+            "mp3",
+        ),
+        (
+            FixtureAsset::new("a5", ktx1()).preamble(http_preamble("https://c1.rbxcdn.com/a5")),
+            Category::Ktx,
+            "ktx",
+        ),
+        (
+            FixtureAsset::new("a6", ktx2()).preamble(http_preamble("https://c2.rbxcdn.com/a6")),
+            Category::Ktx,
+            "ktx2",
+        ),
+        (FixtureAsset::new("a7", rbxm()), Category::Rbxm, "rbxm"),
+        (
+            FixtureAsset::new("a8", mesh()).compressed(Compression::Deflate),
+            Category::Mesh,
+            "mesh",
+        ),
+        (FixtureAsset::new("a9", font()), Category::Fonts, "otf"),
+        (FixtureAsset::new("b1", json()), Category::Json, "json"),
+        // Cut off before IEND, so it's extracted up to where it stops
+        (
+            FixtureAsset::new("b2", png()).truncated(50),
+            Category::Images,
+            "png",
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        locale,
+        logic::{
+            self,
+            extract_options::{ExtractOverrides, Naming},
+            manifest, ListState, Progress,
+        },
+    };
+
+    // Checked in next to this file, UPDATE_GOLDEN=1 cargo test rewrites them after an intended change
+    const GOLDEN_LISTING: &str = include_str!("fixtures/golden/listing.txt");
+    const GOLDEN_MANIFEST: &str = include_str!("fixtures/golden/manifest.json");
+
+    fn compare_golden(file: &str, expected: &str, actual: &str) {
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("src/logic/fixtures/golden")
+                .join(file);
+            fs::write(path, actual).unwrap();
+        } else {
+            assert_eq!(expected, actual, "{file} has changed");
+        }
+    }
+
+    // Rows get a hex id of their own, so they aren't merged with the files
+    fn row(mut asset: FixtureAsset) -> FixtureAsset {
+        asset.name = format!("0f{}", asset.name);
+        asset
+    }
+
+    // The bytes extract_dir should write, the payload unless the stored asset was cut short
+    fn expected_output(asset: &FixtureAsset) -> Vec<u8> {
+        match asset.truncate {
+            Some(_) => asset.stored(),
+            None => asset.payload.clone(),
+        }
+    }
+
+    #[test]
+    fn golden_refresh_and_extract() {
+        let mut fixture = Fixture::new();
+        for (asset, _, _) in every_category() {
+            fixture.add_file(asset.clone());
+            fixture.add_row(row(asset));
+        }
+        let state = fixture.state();
+
+        state.refresh(Category::All).unwrap();
+        let mut listing: Vec<String> = state
+            .run(logic::get_file_list)
+            .iter()
+            .map(|asset| {
+                format!(
+                    "{} {} {}",
+                    asset.name,
+                    asset.category,
+                    asset.header.as_deref().unwrap_or("-")
+                )
+            })
+            .collect();
+        listing.sort();
+        compare_golden("listing.txt", GOLDEN_LISTING, &(listing.join("\n") + "\n"));
+
+        let destination = fixture.output_directory("all");
+        let overrides = ExtractOverrides {
+            manifest: Some(true),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        let summary = state
+            .extract_dir(destination.clone(), Category::All, overrides)
+            .unwrap();
+        assert!(summary.failed.is_empty(), "{:?}", summary.failed);
+
+        for (asset, category, extension) in every_category() {
+            for asset in [row(asset.clone()), asset] {
+                let path = destination.join(format!("{}.{extension}", asset.name));
+                let output = fs::read(&path).unwrap_or_else(|e| panic!("{}: {e}", path.display()));
+                assert!(
+                    output == expected_output(&asset),
+                    "{} ({category}) isn't byte-exact",
+                    asset.name
+                );
+            }
+        }
+
+        let manifest = fs::read_to_string(destination.join(manifest::FILE_NAME)).unwrap();
+        compare_golden("manifest.json", GOLDEN_MANIFEST, &manifest);
+    }
+
+    #[test]
+    fn music_is_listed_from_sounds() {
+        let mut fixture = Fixture::new();
+        let music = FixtureAsset::new("m1", ogg());
+        fixture.add_music(music.clone());
+        fixture.add_file(FixtureAsset::new("a3", ogg()));
+        let state = fixture.state();
+
+        state.refresh(Category::Music).unwrap();
+        let names: Vec<String> = state
+            .run(logic::get_file_list)
+            .into_iter()
+            .map(|asset| asset.name)
+            .collect();
+        assert_eq!(names, ["m1"]);
+
+        let destination = fixture.output_directory("music");
+        let overrides = ExtractOverrides {
+            refresh: Some(false),
+            ..Default::default()
+        };
+        state
+            .extract_dir(destination.clone(), Category::Music, overrides)
+            .unwrap();
+        assert_eq!(fs::read(destination.join("m1.ogg")).unwrap(), music.payload);
+    }
+
+    #[test]
+    fn extract_all_lists_only_what_is_not_current() {
+        let mut fixture = Fixture::new();
+        fixture.add_music(FixtureAsset::new("m1", ogg()));
+        fixture.add_file(FixtureAsset::new("a1", png()));
+        let state = fixture.state();
+        state.refresh(Category::All).unwrap();
+
+        // Each refresh pass starts a new list generation
+        let extract_all = |name: &str, refresh| {
+            let destination = fixture.output_directory(name);
+            let overrides = ExtractOverrides {
+                refresh,
+                ..Default::default()
+            };
+            let before = state.run(logic::get_list_generation);
+            let summary = state
+                .run(|| logic::extract_all(destination.clone(), true, overrides))
+                .unwrap();
+            assert_eq!(summary.processed(), 2, "{name}");
+            assert!(destination.join("m1.ogg").is_file(), "{name}");
+            (state.run(logic::get_list_generation) - before, destination)
+        };
+
+        // The All list is current, only music is listed, and the All list is put back afterwards
+        let (passes, _) = extract_all("current", Some(false));
+        assert_eq!(passes, 1);
+        assert_eq!(state.run(logic::get_list_category), Category::All);
+        let names: Vec<String> = state
+            .run(logic::get_file_list)
+            .into_iter()
+            .map(|asset| asset.name)
+            .collect();
+        assert_eq!(names, ["a1"]);
+
+        let (passes, _) = extract_all("refreshed", None);
+        assert_eq!(passes, 2); // Music and All
+    }
+
+    #[test]
+    fn sources_say_why_an_asset_is_missing() {
+        use logic::{cache_directory, error_code::ErrorCode, sql_database, Error};
+
+        let mut fixture = Fixture::new();
+        fixture.add_file(FixtureAsset::new("a1", png()));
+        fixture.add_row(FixtureAsset::new("ab12", png()));
+        let state = fixture.state();
+        state.run(|| {
+            assert!(cache_directory::create_asset_info("a1", Category::All).is_ok());
+            assert!(matches!(
+                cache_directory::create_asset_info("a2", Category::All),
+                Err(Error::NotFound)
+            ));
+            assert!(sql_database::create_asset_info("ab12", Category::All).is_ok());
+            assert!(matches!(
+                sql_database::create_asset_info("not-hex", Category::All),
+                Err(Error::InvalidAssetId(_))
+            ));
+            let missing = sql_database::create_asset_info("cd34", Category::All).unwrap_err();
+            assert_eq!(missing.code(), ErrorCode::NotFound);
+
+            // Missing from both still gives something to show
+            let placeholder = logic::create_asset_info("a2", Category::All);
+            assert!(!placeholder.from_file && !placeholder.from_sql);
+        });
+
+        let state = Fixture::new().state();
+        state.run(|| {
+            assert!(matches!(
+                sql_database::create_asset_info("ab12", Category::All),
+                Err(Error::NoConnection)
+            ));
+        });
+    }
+
+    #[test]
+    fn textures_converted_to_png_are_resized() {
+        let mut fixture = Fixture::new();
+        fixture.add_file(FixtureAsset::new("k1", ktx1()));
+        let state = fixture.state();
+        state.refresh(Category::Ktx).unwrap();
+
+        let destination = fixture.output_directory("resized");
+        let overrides = ExtractOverrides {
+            convert_to_png: Some(true),
+            max_dimension: Some(1),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        let summary = state
+            .extract_dir(destination.clone(), Category::Ktx, overrides)
+            .unwrap();
+        assert_eq!(summary.resized.len(), 1);
+        assert_eq!(
+            image::image_dimensions(destination.join("k1.png")).unwrap(),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn incremental_goes_by_the_history_first() {
+        let mut fixture = Fixture::new();
+        fixture.add_file(FixtureAsset::new("h1", png()));
+        let state = fixture.state();
+        state.run(|| crate::config::set_config_value("extraction_history", true.into()));
+        state.refresh(Category::Images).unwrap();
+
+        let destination = fixture.output_directory("incremental");
+        let overrides = ExtractOverrides {
+            incremental: Some(true),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        let extract = || {
+            state
+                .extract_dir(destination.clone(), Category::Images, overrides.clone())
+                .unwrap()
+        };
+        assert_eq!(extract().written, 1);
+
+        // The asset changes but keeps its modification time. Only reading it would show that, so
+        // the history saying the file is still there is enough to skip it
+        fixture.add_file(FixtureAsset::new("h1", webp()));
+        let summary = extract();
+        assert_eq!((summary.written, summary.skipped), (0, 1));
+
+        // Once the file has changed the history isn't believed, and it's checked after reading
+        let output = destination.join("h1.png");
+        let modified = fs::metadata(&output).unwrap().modified().unwrap();
+        fs::write(&output, b"edited").unwrap();
+        filetime::set_file_mtime(&output, filetime::FileTime::from_system_time(modified)).unwrap();
+        assert_eq!(extract().written, 1);
+        assert_eq!(fs::read(destination.join("h1.webp")).unwrap(), webp());
+    }
+
+    #[test]
+    fn tagless_mp3_is_listed_as_a_sound() {
+        let mut fixture = Fixture::new();
+        let frames = mp3_frames();
+        fixture.add_file(
+            FixtureAsset::new("t1", frames.clone())
+                .preamble(http_preamble("https://c5.rbxcdn.com/t1")),
+        );
+        // A frame sync with nothing after it is random bytes, not audio
+        let mut lone_frame = vec![0x00; 16];
+        lone_frame.extend_from_slice(&frames[..4]);
+        lone_frame.extend_from_slice(&[0x00; 64]);
+        fixture.add_file(FixtureAsset::new("t2", lone_frame));
+        let state = fixture.state();
+
+        state.refresh(Category::Sounds).unwrap();
+        let listed = state.run(logic::get_file_list);
+        let names: Vec<&str> = listed.iter().map(|asset| asset.name.as_str()).collect();
+        assert_eq!(names, ["t1"]);
+        assert_eq!(listed[0].header.as_deref(), Some("MPEG"));
+
+        let destination = fixture.output_directory("tagless");
+        let overrides = ExtractOverrides {
+            refresh: Some(false),
+            ..Default::default()
+        };
+        state
+            .extract_dir(destination.clone(), Category::Sounds, overrides)
+            .unwrap();
+        // Cut from the first frame, without the preamble
+        assert_eq!(fs::read(destination.join("t1.mp3")).unwrap(), frames);
+    }
+
+    #[test]
+    fn text_mentioning_png_is_not_an_image() {
+        let mut fixture = Fixture::new();
+        let text = b"Exported as PNG, see the PNG settings for the size".to_vec();
+        assert_eq!(logic::determine_category(&text), Category::All);
+        fixture.add_file(FixtureAsset::new("p1", text.clone()));
+        fixture.add_file(FixtureAsset::new("p2", png()));
+        fixture.add_row(FixtureAsset::new("ab12", text));
+        fixture.add_row(FixtureAsset::new("cd34", png()));
+        let state = fixture.state();
+
+        // In both backends, only the real PNGs are listed as images
+        state.refresh(Category::Images).unwrap();
+        let mut names: Vec<String> = state
+            .run(logic::get_file_list)
+            .iter()
+            .map(|asset| asset.name.clone())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["cd34", "p2"]);
+    }
+
+    #[test]
+    fn writes_are_validated() {
+        use logic::{PayloadInfo, WriteValidation, WriteWarning};
+
+        let mut fixture = Fixture::new();
+        let (image, sound) = (png(), ogg());
+        fixture.add_file(FixtureAsset::new("w1", image.clone()));
+        fixture.add_file(FixtureAsset::new("w2", sound.clone()));
+        let state = fixture.state();
+        state.refresh(Category::All).unwrap();
+        let listed = state.run(logic::get_file_list);
+        let asset = |name: &str| {
+            listed
+                .iter()
+                .find(|asset| asset.name == name)
+                .unwrap()
+                .clone()
+        };
+        let size = image.len() as u64;
+
+        state.run(|| {
+            // Another image about as big
+            assert_eq!(
+                logic::validate_write(&asset("w1"), &PayloadInfo::from_bytes(&image)),
+                WriteValidation::Ok
+            );
+            assert_eq!(
+                logic::validate_write(&asset("w2"), &PayloadInfo::from_bytes(&image)),
+                WriteValidation::NeedsConfirmation(vec![WriteWarning::CategoryMismatch {
+                    destination: Category::Sounds,
+                    payload: Category::Images,
+                }])
+            );
+            let larger = PayloadInfo {
+                category: Category::Images,
+                size: size * 4,
+            };
+            assert_eq!(
+                logic::validate_write(&asset("w1"), &larger),
+                WriteValidation::NeedsConfirmation(vec![WriteWarning::SizeDisparity {
+                    destination: size,
+                    payload: size * 4,
+                }])
+            );
+            // Nothing to compare a category that wasn't detected to
+            let undetected = PayloadInfo {
+                category: Category::All,
+                size,
+            };
+            assert_eq!(
+                logic::validate_write(&asset("w2"), &undetected),
+                WriteValidation::Ok
+            );
+
+            // Without force, the swap is held back for confirmation
+            let validation = logic::swap_assets(asset("w1"), asset("w2"), false).unwrap();
+            assert!(matches!(validation, WriteValidation::NeedsConfirmation(_)));
+        });
+        let directory = fixture.cache_directory().join("http");
+        assert_eq!(fs::read(directory.join("w1")).unwrap(), image);
+        assert_eq!(fs::read(directory.join("w2")).unwrap(), sound);
+    }
+
+    #[test]
+    fn empty_sources() {
+        let fixture = Fixture::new();
+        let temp = fixture.root.join("temp");
+        // No cache directory and no database, then an empty cache directory and a database with no rows
+        let missing =
+            ExtractorState::new(fixture.root.join("missing"), None, temp.clone()).unwrap();
+        Connection::open(fixture.database_path())
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE files (id BLOB PRIMARY KEY, size INTEGER, ttl INTEGER, content BLOB);",
+            )
+            .unwrap();
+        let empty = ExtractorState::new(
+            fixture.cache_directory(),
+            Some(fixture.database_path()),
+            temp,
+        )
+        .unwrap();
+
+        for (name, state) in [("missing", missing), ("empty", empty)] {
+            let summary = state.refresh(Category::All).unwrap();
+            assert_eq!(summary.cache_directory.listed, 0, "{name}");
+            assert_eq!(summary.database.listed, 0, "{name}");
+            assert_eq!(state.run(logic::get_list_state), ListState::Empty, "{name}");
+            assert_eq!(state.run(logic::get_progress), Progress::Idle, "{name}");
+
+            let overrides = ExtractOverrides {
+                refresh: Some(false),
+                ..Default::default()
+            };
+            let summary = state
+                .extract_dir(fixture.output_directory(name), Category::All, overrides)
+                .unwrap();
+            assert_eq!(summary.total, 0, "{name}");
+            // Finished straight away, not 0 out of 0
+            assert_eq!(*state.progress.lock().unwrap(), Some(1.0), "{name}");
+            let locale = locale::get_locale(None);
+            assert_eq!(
+                summary.message(&locale),
+                locale::get_message(&locale, "no-files-to-extract", None)
+            );
+        }
+    }
+
+    #[test]
+    fn stopping_a_refresh_is_quick() {
+        const ROWS: usize = 20_000; // Seconds to list in a debug build
+        let mut fixture = Fixture::new();
+        fixture.add_row(FixtureAsset::new("ab12", png()));
+        // Inserted together, one at a time would take longer than the test
+        Connection::open(fixture.database_path())
+            .unwrap()
+            .execute(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+                 INSERT INTO files (id, size, ttl, content)
+                 SELECT randomblob(16), ?2, ?3, ?4 FROM n",
+                params![ROWS, png().len(), MODIFIED, png()],
+            )
+            .unwrap();
+        let state = fixture.state();
+
+        state.run(|| logic::refresh(Category::Images, false, false));
+        // Until it's counted the rows and is going through them
+        let started = Instant::now();
+        while state
+            .progress
+            .lock()
+            .unwrap()
+            .is_none_or(|progress| progress == 0.0)
+        {
+            assert!(started.elapsed() < Duration::from_secs(10), "never started");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let stopping = Instant::now();
+        logic::stop_listing(&state);
+        assert!(
+            state.progress.lock().unwrap().unwrap() < 1.0,
+            "finished already"
+        );
+        while state.run(logic::get_list_task_running) {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let stopped_in = stopping.elapsed();
+        assert!(
+            stopped_in < Duration::from_millis(500),
+            "took {stopped_in:?} to stop"
+        );
+        // What was listed before the stop is dropped
+        assert!(state.run(logic::get_file_list).is_empty());
+    }
+
+    #[test]
+    fn switching_tabs_quickly() {
+        let mut fixture = Fixture::new();
+        for name in ["5a01", "5a02", "5a03"] {
+            fixture.add_row(FixtureAsset::new(name, ogg()));
+        }
+        fixture.add_file(FixtureAsset::new("s4", ogg()));
+        // Enough images that listing them all takes seconds in a debug build
+        Connection::open(fixture.database_path())
+            .unwrap()
+            .execute(
+                "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < ?1)
+                 INSERT INTO files (id, size, ttl, content)
+                 SELECT randomblob(16), ?2, ?3, ?4 FROM n",
+                params![20_000, png().len(), MODIFIED, png()],
+            )
+            .unwrap();
+        let state = fixture.state();
+
+        let switching = Instant::now();
+        for category in [
+            Category::Images,
+            Category::Sounds,
+            Category::Fonts,
+            Category::Images,
+        ] {
+            state.run(|| logic::refresh(category, false, false));
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let summary = state.refresh(Category::Sounds).unwrap();
+        // Waiting for the earlier refreshes to stop, on top of listing the sounds
+        let handoff = switching
+            .elapsed()
+            .saturating_sub(Duration::from_millis(summary.duration_ms + 80));
+        assert!(
+            handoff < Duration::from_millis(100),
+            "took {handoff:?} to take over"
+        );
+
+        // Only the last tab's assets, none left over from the others
+        assert_eq!(state.run(logic::get_list_category), Category::Sounds);
+        let mut names: Vec<String> = state
+            .run(logic::get_file_list)
+            .iter()
+            .inspect(|asset| assert_eq!(asset.category, Category::Sounds, "{}", asset.name))
+            .map(|asset| asset.name.clone())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["5a01", "5a02", "5a03", "s4"]);
+    }
+
+    #[test]
+    fn corrupt_assets_are_not_extracted() {
+        let mut fixture = Fixture::new();
+        let png = png();
+        let preamble = http_preamble_with_digest("https://c0.rbxcdn.com/v1", &png);
+        fixture.add_file(FixtureAsset::new("v1", png.clone()).preamble(preamble.clone()));
+        let mut damaged = png.clone();
+        let last = damaged.len() - 1;
+        damaged[last] ^= 0xff;
+        fixture.add_file(FixtureAsset::new("v2", damaged).preamble(preamble));
+        let state = fixture.state();
+        state.refresh(Category::Images).unwrap();
+
+        let destination = fixture.output_directory("verified");
+        let overrides = ExtractOverrides {
+            manifest: Some(true),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        let summary = state
+            .extract_dir(destination.clone(), Category::Images, overrides)
+            .unwrap();
+        assert_eq!(summary.written, 1);
+        assert_eq!(summary.corrupt, ["v2"]);
+        assert!(summary.failed.is_empty(), "{:?}", summary.failed);
+        assert_eq!(fs::read(destination.join("v1.png")).unwrap(), png);
+        assert!(!destination.join("v2.png").exists());
+
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&fs::read(destination.join(manifest::FILE_NAME)).unwrap())
+                .unwrap();
+        let corrupt: Vec<&str> = manifest
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry["corrupt"] == true)
+            .map(|entry| entry["source_name"].as_str().unwrap())
+            .collect();
+        assert_eq!(corrupt, ["v2"]);
+
+        // Written like any other asset with verification off
+        let destination = fixture.output_directory("unverified");
+        let overrides = ExtractOverrides {
+            verify: Some(false),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        let summary = state
+            .extract_dir(destination.clone(), Category::Images, overrides)
+            .unwrap();
+        assert_eq!(summary.written, 2);
+        assert!(summary.corrupt.is_empty());
+    }
+
+    #[test]
+    fn thumbnails_are_found_by_their_url() {
+        let mut fixture = Fixture::new();
+        let headshot = "https://thumbnails.roblox.com/v1/users/avatar-headshot?userIds=156";
+        fixture.add_file(FixtureAsset::new("t1", png()).preamble(http_preamble(headshot)));
+        fixture.add_file(FixtureAsset::new("t2", png()).preamble(http_preamble(
+            "https://tr.rbxcdn.com/hash/420/420/Avatar/Png",
+        )));
+        fixture.add_file(
+            FixtureAsset::new("t3", png()).preamble(http_preamble("https://c0.rbxcdn.com/t3")),
+        );
+        let state = fixture.state();
+        state.refresh(Category::Images).unwrap();
+
+        let filter = logic::FilterQuery {
+            thumbnails: true,
+            ..Default::default()
+        };
+        let mut thumbnails = state.run(|| {
+            logic::filter_file_list(&filter);
+            logic::get_filtered_file_list()
+        });
+        thumbnails.sort_by(|a, b| a.name.cmp(&b.name));
+        let found: Vec<(&str, Option<u64>)> = thumbnails
+            .iter()
+            .map(|asset| (asset.name.as_str(), asset.thumbnail().and_then(|t| t.id)))
+            .collect();
+        assert_eq!(found, [("t1", Some(156)), ("t2", None)]);
+        assert_eq!(thumbnails[0].origin_url.as_deref(), Some(headshot));
+
+        // Named by the id in the URL, the asset id without one
+        let destination = fixture.output_directory("thumbnails");
+        let overrides = ExtractOverrides {
+            naming: Some(Naming::Thumbnail),
+            manifest: Some(true),
+            refresh: Some(false),
+            ..Default::default()
+        };
+        state
+            .extract_dir(destination.clone(), Category::Images, overrides)
+            .unwrap();
+        for file in ["headshot-156.png", "t2.png", "t3.png"] {
+            assert!(destination.join(file).exists(), "{file}");
+        }
+        let manifest: serde_json::Value =
+            serde_json::from_slice(&fs::read(destination.join(manifest::FILE_NAME)).unwrap())
+                .unwrap();
+        let headshot_entry = manifest
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["source_name"] == "t1")
+            .unwrap();
+        assert_eq!(
+            headshot_entry["thumbnail"],
+            serde_json::json!({"kind": "headshot", "id": 156})
+        );
+    }
+}
@@ -0,0 +1,270 @@
+// Checks for the usual reasons the asset lists are empty: Roblox hasn't cached anything yet or
+// the wrong folder/database was chosen. Results are added one at a time as each check finishes.
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use std::{
+    sync::{Arc, LazyLock, Mutex},
+    thread,
+};
+
+use crate::{config, locale, logic};
+
+const SAMPLE_SIZE: usize = 20; // Assets to classify from each source
+
+static RESULTS: LazyLock<Mutex<Vec<CheckResult>>> = LazyLock::new(|| Mutex::new(Vec::new()));
+static RUNNING: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Pass,
+    Warn,
+    Fail,
+}
+
+// Something the user can apply with one click
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Fix {
+    CacheDirectory(String),
+    Database(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub title: String,
+    pub state: CheckState,
+    pub message: String,
+    pub action: Option<String>, // Suggested action
+    pub fix: Option<Fix>,
+}
+
+impl Fix {
+    pub fn apply(&self) {
+        match self {
+            Fix::CacheDirectory(directory) => {
+                config::set_config_value("cache_directory", directory.clone().into());
+                logic::cache_directory::set_cache_directory(
+                    logic::cache_directory::detect_directory(),
+                );
+            }
+            Fix::Database(path) => {
+                config::set_config_value("sql_database", path.clone().into());
+                let _ = logic::sql_database::reset_database();
+            }
+        }
+        run(); // Check again with the fix applied
+    }
+}
+
+fn add_result(result: CheckResult) {
+    RESULTS.lock().unwrap().push(result);
+}
+
+fn check_database(locale: &FluentBundle<Arc<FluentResource>>) -> Vec<Vec<u8>> {
+    let title = locale::get_message(locale, "health-database", None);
+    let alternative = logic::sql_database::find_alternative_database().map(Fix::Database);
+
+    let Some(health) = logic::sql_database::health(SAMPLE_SIZE) else {
+        add_result(CheckResult {
+            title,
+            state: CheckState::Fail,
+            message: locale::get_message(locale, "health-database-missing", None),
+            action: Some(locale::get_message(
+                locale,
+                "health-action-choose-database",
+                None,
+            )),
+            fix: alternative,
+        });
+        return Vec::new();
+    };
+
+    let mut args = FluentArgs::new();
+    args.set("path", health.path.clone());
+    args.set("count", health.rows);
+
+    if health.rows == 0 {
+        add_result(CheckResult {
+            title,
+            state: CheckState::Warn,
+            message: locale::get_message(locale, "health-database-empty", Some(&args)),
+            action: Some(locale::get_message(
+                locale,
+                "health-action-launch-roblox",
+                None,
+            )),
+            fix: alternative,
+        });
+    } else {
+        add_result(CheckResult {
+            title,
+            state: CheckState::Pass,
+            message: locale::get_message(locale, "health-database-ok", Some(&args)),
+            action: None,
+            fix: None,
+        });
+    }
+
+    health.samples
+}
+
+fn check_cache_directory(locale: &FluentBundle<Arc<FluentResource>>) -> Vec<Vec<u8>> {
+    let title = locale::get_message(locale, "health-cache-directory", None);
+    let alternative = logic::cache_directory::find_alternative_directory().map(Fix::CacheDirectory);
+    let health = logic::cache_directory::health(SAMPLE_SIZE);
+
+    let mut args = FluentArgs::new();
+    args.set(
+        "directory",
+        logic::cache_directory::get_cache_directory()
+            .to_string_lossy()
+            .to_string(),
+    );
+    args.set("count", health.file_count);
+    args.set("folders", health.missing_folders.join(", "));
+
+    let (state, message, action) = if !health.reachable {
+        (
+            CheckState::Fail,
+            "health-cache-directory-unreachable",
+            Some("health-action-choose-directory"),
+        )
+    } else if !health.missing_folders.is_empty() {
+        (
+            CheckState::Fail,
+            "health-cache-directory-missing-folders",
+            Some("health-action-choose-directory"),
+        )
+    } else if health.file_count == 0 {
+        (
+            CheckState::Warn,
+            "health-cache-directory-empty",
+            Some("health-action-launch-roblox"),
+        )
+    } else {
+        (CheckState::Pass, "health-cache-directory-ok", None)
+    };
+
+    add_result(CheckResult {
+        title,
+        state,
+        message: locale::get_message(locale, message, Some(&args)),
+        action: action.map(|action| locale::get_message(locale, action, None)),
+        fix: if state == CheckState::Pass {
+            None
+        } else {
+            alternative
+        },
+    });
+
+    health.samples
+}
+
+// Music is only listed from the sounds directory, so the Music tab is empty without it
+fn check_sounds_directory(locale: &FluentBundle<Arc<FluentResource>>) {
+    let title = locale::get_message(locale, "health-sounds-directory", None);
+    logic::cache_directory::reset_sounds_directory(); // It may have been created since starting
+
+    let mut args = FluentArgs::new();
+    let result = match logic::cache_directory::get_sounds_directory() {
+        Some(directory) => {
+            args.set("directory", directory.to_string_lossy().to_string());
+            CheckResult {
+                title,
+                state: CheckState::Pass,
+                message: locale::get_message(locale, "health-sounds-directory-ok", Some(&args)),
+                action: None,
+                fix: None,
+            }
+        }
+        None => {
+            args.set(
+                "directory",
+                logic::cache_directory::expected_sounds_directory()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            CheckResult {
+                title,
+                state: CheckState::Warn,
+                message: locale::get_message(
+                    locale,
+                    "health-sounds-directory-missing",
+                    Some(&args),
+                ),
+                action: Some(locale::get_message(
+                    locale,
+                    "health-action-choose-sounds-directory",
+                    None,
+                )),
+                fix: None,
+            }
+        }
+    };
+    add_result(result);
+}
+
+// Assets that can't be classified usually mean the folder isn't a Roblox cache
+fn check_classification(locale: &FluentBundle<Arc<FluentResource>>, samples: Vec<Vec<u8>>) {
+    let title = locale::get_message(locale, "health-classification", None);
+    let known = samples
+        .iter()
+        .filter(|sample| logic::determine_category(sample) != logic::Category::All)
+        .count();
+
+    let mut args = FluentArgs::new();
+    args.set("known", known);
+    args.set("total", samples.len());
+
+    let (state, message, action) = if samples.is_empty() {
+        (
+            CheckState::Warn,
+            "health-classification-skipped",
+            Some("health-action-launch-roblox"),
+        )
+    } else if known == 0 {
+        (
+            CheckState::Fail,
+            "health-classification-none",
+            Some("health-action-choose-directory"),
+        )
+    } else {
+        (CheckState::Pass, "health-classification-ok", None)
+    };
+
+    add_result(CheckResult {
+        title,
+        state,
+        message: locale::get_message(locale, message, Some(&args)),
+        action: action.map(|action| locale::get_message(locale, action, None)),
+        fix: None,
+    });
+}
+
+pub fn run() {
+    {
+        let mut running = RUNNING.lock().unwrap();
+        if *running {
+            return;
+        }
+        *running = true;
+    }
+    RESULTS.lock().unwrap().clear();
+
+    thread::spawn(|| {
+        let locale = locale::get_locale(None);
+
+        let mut samples = check_database(&locale);
+        samples.extend(check_cache_directory(&locale));
+        check_sounds_directory(&locale);
+        check_classification(&locale, samples);
+
+        *RUNNING.lock().unwrap() = false;
+    });
+}
+
+pub fn get_results() -> Vec<CheckResult> {
+    RESULTS.lock().unwrap().clone()
+}
+
+pub fn is_running() -> bool {
+    *RUNNING.lock().unwrap()
+}
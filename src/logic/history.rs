@@ -0,0 +1,298 @@
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use std::{
+    path::Path,
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use crate::{
+    config,
+    logic::{self, read_only},
+};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION"); // Stored with each entry
+const HISTORY_FILE: &str = "RoExtract-history.db";
+const DEFAULT_MAX_ENTRIES: u64 = 100_000;
+
+// A state's history database, shared with the states detached from it
+#[derive(Default)]
+pub struct History {
+    // Opened the first time it's needed, the file is only created once something is recorded
+    connection: Mutex<Option<Connection>>,
+    count: Mutex<Option<u64>>, // Counted once, then kept up to date
+    generation: Mutex<u64>,    // Increases whenever entries are added or removed
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub asset: String,
+    pub hash: String,
+    pub destination: String,
+    pub timestamp: SystemTime,
+    pub version: String,
+}
+
+fn open_database(create: bool) -> Option<Connection> {
+    log_debug!("logic::history::open_database({create})");
+    let path = config::get_config_directory().join(HISTORY_FILE);
+    if !path.exists() && (!create || read_only::is_enabled()) {
+        return None; // Nothing has been recorded yet
+    }
+
+    let connection = match Connection::open(&path) {
+        Ok(connection) => connection,
+        Err(e) => {
+            log_error!("Failed to open history database {}: {}", path.display(), e);
+            return None;
+        }
+    };
+
+    let result = connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS history (
+            asset TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            destination TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            version TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS history_asset ON history (asset);",
+    );
+
+    match result {
+        Ok(_) => Some(connection),
+        Err(e) => {
+            log_error!("Failed to create history table: {}", e);
+            None
+        }
+    }
+}
+
+// Reading doesn't create the database, recording does
+fn connection(history: &History, create: bool) -> MutexGuard<'_, Option<Connection>> {
+    let mut connection = history.connection.lock().unwrap();
+    if connection.is_none() {
+        *connection = open_database(create);
+    }
+    connection
+}
+
+fn changed(history: &History, count: impl FnOnce(u64) -> u64) {
+    let mut cached = history.count.lock().unwrap();
+    if let Some(cached) = cached.as_mut() {
+        *cached = count(*cached);
+    }
+    *history.generation.lock().unwrap() += 1;
+}
+
+fn row_to_entry(row: &rusqlite::Row) -> Result<HistoryEntry, rusqlite::Error> {
+    let timestamp: u64 = row.get(3)?;
+    Ok(HistoryEntry {
+        asset: row.get(0)?,
+        hash: row.get(1)?,
+        destination: row.get(2)?,
+        timestamp: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp),
+        version: row.get(4)?,
+    })
+}
+
+pub fn is_enabled() -> bool {
+    config::get_config_bool("extraction_history").unwrap_or(false)
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+// Called after every successful extraction, does nothing if history is disabled
+pub fn record(asset: &str, bytes: &[u8], destination: &Path) {
+    if !is_enabled() || read_only::is_enabled() {
+        return;
+    }
+    log_debug!("logic::history::record({asset}, {})", destination.display());
+
+    // Extracting to the temp directory is used for opening files, not worth recording
+    if destination.starts_with(logic::get_temp_dir()) {
+        return;
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    {
+        let state = logic::state();
+        let connection = connection(&state.history, true);
+        if let Some(conn) = &*connection {
+            match conn.execute(
+                "INSERT INTO history (asset, hash, destination, timestamp, version) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    asset,
+                    hash_bytes(bytes),
+                    destination.to_string_lossy(),
+                    timestamp,
+                    VERSION
+                ],
+            ) {
+                Ok(_) => changed(&state.history, |count| count + 1),
+                Err(e) => log_error!("Failed to record extraction history: {}", e),
+            }
+        }
+    }
+}
+
+// Keeps the database bounded, called once an extraction has finished rather than for every file
+pub fn prune_to_limit() {
+    if read_only::is_enabled() {
+        return;
+    }
+    let max_entries = config::get_config_u64("history_max_entries").unwrap_or(DEFAULT_MAX_ENTRIES);
+    if count() <= max_entries {
+        return;
+    }
+    if let Err(e) = prune(max_entries) {
+        log_error!("Failed to prune extraction history: {}", e);
+    }
+}
+
+// Increases whenever entries are added or removed, for knowing when a search needs redoing
+pub fn get_generation() -> u64 {
+    *logic::state().history.generation.lock().unwrap()
+}
+
+// Search by asset id, newest first
+pub fn search(query: &str, limit: u64) -> Vec<HistoryEntry> {
+    log_debug!("logic::history::search({query}, {limit})");
+    let state = logic::state();
+    let connection = connection(&state.history, false);
+
+    if let Some(conn) = &*connection {
+        let result = conn
+            .prepare(
+                "SELECT asset, hash, destination, timestamp, version FROM history
+                WHERE asset LIKE ?1 ORDER BY timestamp DESC, rowid DESC LIMIT ?2",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map(params![format!("%{query}%"), limit], row_to_entry)?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match result {
+            Ok(entries) => entries,
+            Err(e) => {
+                log_error!("Failed to search extraction history: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+// Every time this asset has been extracted, newest first
+pub fn get_entries(asset: &str) -> Vec<HistoryEntry> {
+    log_debug!("logic::history::get_entries({asset})");
+    let state = logic::state();
+    let connection = connection(&state.history, false);
+
+    if let Some(conn) = &*connection {
+        let result = conn
+            .prepare(
+                "SELECT asset, hash, destination, timestamp, version FROM history
+                WHERE asset = ?1 ORDER BY timestamp DESC, rowid DESC",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map(params![asset], row_to_entry)?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match result {
+            Ok(entries) => entries,
+            Err(e) => {
+                log_error!("Failed to read extraction history: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+// The last time this asset was extracted
+pub fn latest(asset: &str) -> Option<HistoryEntry> {
+    let state = logic::state();
+    let connection = connection(&state.history, false);
+
+    if let Some(conn) = &*connection {
+        conn.query_row(
+            "SELECT asset, hash, destination, timestamp, version FROM history
+            WHERE asset = ?1 ORDER BY timestamp DESC, rowid DESC LIMIT 1",
+            params![asset],
+            row_to_entry,
+        )
+        .ok()
+    } else {
+        None
+    }
+}
+
+pub fn contains(asset: &str) -> bool {
+    let state = logic::state();
+    let connection = connection(&state.history, false);
+
+    if let Some(conn) = &*connection {
+        conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM history WHERE asset = ?1)",
+            params![asset],
+            |row| row.get(0),
+        )
+        .unwrap_or(false)
+    } else {
+        false
+    }
+}
+
+pub fn count() -> u64 {
+    let state = logic::state();
+    if let Some(count) = *state.history.count.lock().unwrap() {
+        return count;
+    }
+    let connection = connection(&state.history, false);
+
+    let count = if let Some(conn) = &*connection {
+        conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0))
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    // Not cached while there's no database, it's counted again once it's created
+    if connection.is_some() {
+        *state.history.count.lock().unwrap() = Some(count);
+    }
+    count
+}
+
+// Only keep the newest entries, returns how many entries were removed
+pub fn prune(keep: u64) -> Result<usize, rusqlite::Error> {
+    log_debug!("logic::history::prune({keep})");
+    read_only::check()?;
+    let state = logic::state();
+    let connection = connection(&state.history, false);
+
+    if let Some(conn) = &*connection {
+        let removed = conn.execute(
+            "DELETE FROM history WHERE rowid NOT IN
+            (SELECT rowid FROM history ORDER BY timestamp DESC, rowid DESC LIMIT ?1)",
+            params![keep],
+        )?;
+        changed(&state.history, |count| count.saturating_sub(removed as u64));
+        Ok(removed)
+    } else {
+        Err(rusqlite::Error::InvalidQuery)
+    }
+}
+
+pub fn clear() -> Result<usize, rusqlite::Error> {
+    prune(0)
+}
@@ -0,0 +1,65 @@
+// Downscales extracted images, e.g. to normalise a texture pack. Images are only ever made
+// smaller and keep their format. KTX textures can't be decoded, so they're only resized once
+// they've been converted to PNG.
+use image::ImageReader;
+use std::io::Cursor;
+
+use crate::logic::extract_options::ImageResize;
+
+pub struct Resized {
+    pub bytes: Vec<u8>,
+    pub original: (u32, u32),
+    pub output: (u32, u32),
+}
+
+fn previous_power_of_two(n: u32) -> u32 {
+    if n == 0 {
+        1
+    } else {
+        1 << (31 - n.leading_zeros())
+    }
+}
+
+fn target_size((width, height): (u32, u32), settings: ImageResize) -> (u32, u32) {
+    let (mut width, mut height) = (width, height);
+
+    let longest = width.max(height);
+    if settings.max_dimension > 0 && longest > settings.max_dimension {
+        // Keep the aspect ratio
+        let scale = settings.max_dimension as f64 / longest as f64;
+        width = ((width as f64 * scale).round() as u32).max(1);
+        height = ((height as f64 * scale).round() as u32).max(1);
+    }
+
+    if settings.power_of_two {
+        width = previous_power_of_two(width);
+        height = previous_power_of_two(height);
+    }
+
+    (width, height)
+}
+
+// bytes must start at the image header, None if the image is already within bounds
+pub fn resize(bytes: &[u8], settings: ImageResize) -> Result<Option<Resized>, image::ImageError> {
+    let format = image::guess_format(bytes)?;
+
+    // Reading the dimensions doesn't decode the whole image
+    let original = ImageReader::with_format(Cursor::new(bytes), format).into_dimensions()?;
+    let output = target_size(original, settings);
+    if output == original {
+        return Ok(None);
+    }
+
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    let resized = image.resize_exact(output.0, output.1, settings.filter.filter_type());
+
+    // Same format so the extension doesn't change, WebP is written lossless
+    let mut encoded = Vec::new();
+    resized.write_to(&mut Cursor::new(&mut encoded), format)?;
+
+    Ok(Some(Resized {
+        bytes: encoded,
+        original,
+        output,
+    }))
+}
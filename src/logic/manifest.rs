@@ -0,0 +1,131 @@
+// manifest.json, written next to extracted files so other tools can tell which file came from which
+// asset. It's written once the extraction has finished, to a temporary file that's then renamed over
+// the old one, so a cancelled or failed run never leaves half a manifest behind.
+use serde::Serialize;
+use std::{collections::HashMap, fs, path::Path, time::SystemTime};
+
+use crate::logic::{thumbnail::Thumbnail, wav_convert::AudioChanges, AssetInfo, Category};
+
+pub const FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ManifestEntry {
+    pub source_name: String,
+    pub output_file: String, // Relative to the destination, with / between folders
+    pub category: Category,
+    pub size: u64,                  // Of the output file
+    pub last_modified: Option<u64>, // Unix seconds
+    pub from_sql: bool,
+    pub from_file: bool,
+    // Where Roblox downloaded it from, see logic::origin_url()
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_url: Option<String>,
+    // The kind of avatar or asset thumbnail and the id in its URL
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<Thumbnail>,
+    // The asset with the same content that was written instead, output_file is that asset's file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<String>,
+    // Silence trimmed and gain applied while converting to WAV
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub audio: Option<AudioChanges>,
+    // Not written, the payload doesn't match the digest stored with it
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub corrupt: bool,
+}
+
+impl ManifestEntry {
+    pub fn new(asset: &AssetInfo, destination: &Path, output: &Path, size: u64) -> Self {
+        let relative = output.strip_prefix(destination).unwrap_or(output);
+        let output_file: Vec<String> = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().to_string())
+            .collect();
+        ManifestEntry {
+            source_name: asset.name.clone(),
+            output_file: output_file.join("/"),
+            category: asset.category,
+            size,
+            last_modified: asset.last_modified.and_then(|last_modified| {
+                last_modified
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .ok()
+                    .map(|duration| duration.as_secs())
+            }),
+            from_sql: asset.from_sql,
+            from_file: asset.from_file,
+            origin_url: asset.origin_url.clone(),
+            thumbnail: asset.thumbnail(),
+            duplicate_of: None,
+            audio: None,
+            corrupt: false,
+        }
+    }
+
+    pub fn with_audio(self, audio: Option<AudioChanges>) -> Self {
+        ManifestEntry { audio, ..self }
+    }
+
+    // Skipped by dedupe, output_file is filled in by link_duplicates() once everything is written
+    pub fn duplicate(asset: &AssetInfo, original: &str, size: u64) -> Self {
+        ManifestEntry {
+            duplicate_of: Some(original.to_owned()),
+            ..Self::new(asset, Path::new(""), Path::new(""), size)
+        }
+    }
+
+    // Failed verification, so there's no output_file
+    pub fn corrupt(asset: &AssetInfo, size: u64) -> Self {
+        ManifestEntry {
+            corrupt: true,
+            ..Self::new(asset, Path::new(""), Path::new(""), size)
+        }
+    }
+}
+
+pub fn link_duplicates(entries: &mut [ManifestEntry]) {
+    let output_files: HashMap<String, String> = entries
+        .iter()
+        .filter(|entry| entry.duplicate_of.is_none())
+        .map(|entry| (entry.source_name.clone(), entry.output_file.clone()))
+        .collect();
+    for entry in entries {
+        if let Some(output_file) = entry
+            .duplicate_of
+            .as_ref()
+            .and_then(|original| output_files.get(original))
+        {
+            entry.output_file = output_file.clone();
+        }
+    }
+}
+
+pub fn serialise(entries: &[ManifestEntry]) -> Option<Vec<u8>> {
+    match serde_json::to_vec_pretty(entries) {
+        Ok(data) => Some(data),
+        Err(e) => {
+            log_error!("Failed to serialise manifest: {}", e);
+            None
+        }
+    }
+}
+
+pub fn write(destination: &Path, entries: &[ManifestEntry]) {
+    let path = destination.join(FILE_NAME);
+    let temp_path = destination.join(format!("{FILE_NAME}.tmp"));
+
+    let Some(data) = serialise(entries) else {
+        return;
+    };
+    if let Err(e) = fs::write(&temp_path, data) {
+        log_error!("Failed to write manifest {}: {}", temp_path.display(), e);
+        return;
+    }
+    match fs::rename(&temp_path, &path) {
+        Ok(()) => log_info!("Wrote manifest with {} entries", entries.len()),
+        Err(e) => {
+            log_error!("Failed to write manifest {}: {}", path.display(), e);
+            fs::remove_file(&temp_path).ok(); // Nothing else to do if this fails too
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use rusqlite::{params, Connection};
+use std::{
+    collections::HashSet,
+    fs,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+    time::SystemTime,
+};
+
+use crate::{
+    config,
+    logic::{self, cache_directory, read_only, sql_database, AssetInfo, Category},
+};
+
+const MODIFICATIONS_FILE: &str = "RoExtract-modifications.db";
+const BACKUP_DIRECTORY: &str = "RoExtract-backups";
+
+static CONNECTION: LazyLock<Mutex<Option<Connection>>> =
+    LazyLock::new(|| Mutex::new(open_database()));
+// Checked for every row of the file list, so keep it in memory
+static MODIFIED_ASSETS: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(load_modified_assets()));
+
+#[derive(Debug, Clone)]
+pub struct Modification {
+    pub asset: String,
+    pub operation: String,
+    pub timestamp: SystemTime,
+    pub backup: Option<PathBuf>,
+    pub category: Category,
+    pub from_sql: bool,
+}
+
+fn open_database() -> Option<Connection> {
+    log_debug!("logic::modifications::open_database()");
+    let path = config::get_config_directory().join(MODIFICATIONS_FILE);
+    if read_only::is_enabled() && !path.exists() {
+        return None; // Nothing has been modified, and it shouldn't be created now
+    }
+
+    let connection = match Connection::open(&path) {
+        Ok(connection) => connection,
+        Err(e) => {
+            log_error!(
+                "Failed to open modifications database {}: {}",
+                path.display(),
+                e
+            );
+            return None;
+        }
+    };
+
+    let result = connection.execute_batch(
+        "CREATE TABLE IF NOT EXISTS modifications (
+            asset TEXT NOT NULL,
+            operation TEXT NOT NULL,
+            timestamp INTEGER NOT NULL,
+            backup TEXT,
+            category TEXT NOT NULL,
+            from_sql INTEGER NOT NULL
+        );",
+    );
+
+    match result {
+        Ok(_) => Some(connection),
+        Err(e) => {
+            log_error!("Failed to create modifications table: {}", e);
+            None
+        }
+    }
+}
+
+fn load_modified_assets() -> HashSet<String> {
+    get_modifications()
+        .into_iter()
+        .map(|modification| modification.asset)
+        .collect()
+}
+
+fn get_backup_directory() -> PathBuf {
+    config::get_config_directory().join(BACKUP_DIRECTORY)
+}
+
+fn parse_category(category: &str) -> Category {
+    category.parse().unwrap_or(Category::All)
+}
+
+fn row_to_modification(row: &rusqlite::Row) -> Result<Modification, rusqlite::Error> {
+    let timestamp: u64 = row.get(2)?;
+    let backup: Option<String> = row.get(3)?;
+    let category: String = row.get(4)?;
+    Ok(Modification {
+        asset: row.get(0)?,
+        operation: row.get(1)?,
+        timestamp: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(timestamp),
+        backup: backup.map(PathBuf::from),
+        category: parse_category(&category),
+        from_sql: row.get(5)?,
+    })
+}
+
+// Save the raw bytes of an asset before it gets written to, called before every write
+pub fn backup(asset: &AssetInfo) -> Option<PathBuf> {
+    log_debug!("logic::modifications::backup({})", asset.name);
+
+    let bytes = if asset.from_sql {
+        sql_database::read_asset(asset)
+    } else {
+        cache_directory::read_asset(asset)
+    };
+
+    let bytes = match bytes {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log_warn!("Failed to read {} for backup: {}", asset.name, e);
+            return None;
+        }
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+
+    let directory = get_backup_directory();
+    let path = directory.join(format!("{timestamp}-{}", asset.name));
+    match fs::create_dir_all(&directory).and_then(|_| fs::write(&path, bytes)) {
+        Ok(_) => Some(path),
+        Err(e) => {
+            log_error!("Failed to back up {}: {}", asset.name, e);
+            None
+        }
+    }
+}
+
+// Called after RoExtract writes to an asset
+pub fn record(asset: &AssetInfo, operation: &str, backup: Option<PathBuf>) {
+    log_debug!("logic::modifications::record({}, {operation})", asset.name);
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+
+    {
+        let connection = CONNECTION.lock().unwrap();
+        if let Some(conn) = &*connection {
+            if let Err(e) = conn.execute(
+                "INSERT INTO modifications (asset, operation, timestamp, backup, category, from_sql) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    asset.name,
+                    operation,
+                    timestamp,
+                    backup.map(|path| path.to_string_lossy().to_string()),
+                    asset.category.to_string(),
+                    asset.from_sql
+                ],
+            ) {
+                log_error!("Failed to record modification: {}", e);
+            }
+        }
+    }
+
+    MODIFIED_ASSETS.lock().unwrap().insert(asset.name.clone());
+}
+
+// Newest first
+pub fn get_modifications() -> Vec<Modification> {
+    log_debug!("logic::modifications::get_modifications()");
+    let connection = CONNECTION.lock().unwrap();
+
+    if let Some(conn) = &*connection {
+        let result = conn
+            .prepare(
+                "SELECT asset, operation, timestamp, backup, category, from_sql FROM modifications
+                ORDER BY timestamp DESC, rowid DESC",
+            )
+            .and_then(|mut stmt| {
+                stmt.query_map([], row_to_modification)?
+                    .collect::<Result<Vec<_>, _>>()
+            });
+
+        match result {
+            Ok(modifications) => modifications,
+            Err(e) => {
+                log_error!("Failed to read modifications: {}", e);
+                Vec::new()
+            }
+        }
+    } else {
+        Vec::new()
+    }
+}
+
+pub fn is_modified(asset: &str) -> bool {
+    MODIFIED_ASSETS.lock().unwrap().contains(asset)
+}
+
+pub fn count() -> usize {
+    MODIFIED_ASSETS.lock().unwrap().len()
+}
+
+// Restore every modified asset from its backup, newest first so the oldest backup wins.
+// Returns how many assets couldn't be restored.
+pub fn revert_all() -> Result<usize, read_only::ReadOnlyMode> {
+    read_only::check()?;
+    log_info!("Reverting all modifications");
+    let mut failed = 0;
+
+    for modification in get_modifications() {
+        let Some(backup) = &modification.backup else {
+            log_warn!("No backup for {}, can't revert", modification.asset);
+            failed += 1;
+            continue;
+        };
+
+        let asset = AssetInfo {
+            name: modification.asset.clone(),
+            size: 0,
+            actual_size: 0,
+            actual_size_approximate: false,
+            last_modified: None,
+            from_file: !modification.from_sql,
+            from_sql: modification.from_sql,
+            prefer_sql: false,
+            other_name: None,
+            evicted: false,
+            header: None,
+            hash: None,
+            origin_url: None,
+            category: modification.category,
+        };
+
+        let result = fs::read(backup)
+            .map_err(logic::Error::from)
+            .and_then(|bytes| {
+                if asset.from_sql {
+                    sql_database::write_asset(&asset, &bytes)
+                } else {
+                    cache_directory::write_asset(&asset, &bytes)
+                }
+            });
+
+        if let Err(e) = result {
+            log_error!("Failed to revert {}: {}", modification.asset, e);
+            failed += 1;
+        }
+    }
+
+    clear();
+    Ok(failed)
+}
+
+// Forget an asset's modifications when it has been deleted
+pub fn forget(asset: &str) {
+    log_debug!("logic::modifications::forget({asset})");
+    {
+        let connection = CONNECTION.lock().unwrap();
+        if let Some(conn) = &*connection {
+            if let Err(e) =
+                conn.execute("DELETE FROM modifications WHERE asset = ?1", params![asset])
+            {
+                log_error!("Failed to forget modifications of {}: {}", asset, e);
+            }
+        }
+    }
+
+    MODIFIED_ASSETS.lock().unwrap().remove(asset);
+}
+
+// Forget every modification, e.g. when the cache has been cleared
+pub fn clear() {
+    log_debug!("logic::modifications::clear()");
+    {
+        let connection = CONNECTION.lock().unwrap();
+        if let Some(conn) = &*connection {
+            if let Err(e) = conn.execute("DELETE FROM modifications", []) {
+                log_error!("Failed to clear modifications: {}", e);
+            }
+        }
+    }
+
+    let directory = get_backup_directory();
+    if directory.exists() {
+        if let Err(e) = fs::remove_dir_all(&directory) {
+            log_error!("Failed to remove backups: {}", e);
+        }
+    }
+
+    MODIFIED_ASSETS.lock().unwrap().clear();
+}
@@ -0,0 +1,117 @@
+// Free-form notes on assets, e.g. where a sound was found. They're kept in their own file next to the
+// config so long notes don't bloat it, and saved when RoExtract closes like the config.
+use std::{
+    collections::HashMap,
+    fs,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::{
+    config,
+    logic::{self, Category},
+};
+
+const NOTES_FILE: &str = "RoExtract-notes.json";
+pub const MAX_NOTE_LENGTH: usize = 2000; // Characters, longer notes are cut off
+
+static NOTES: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(|| Mutex::new(read_notes_file()));
+static CHANGED: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(false)); // Since last saved
+
+fn get_notes_file() -> PathBuf {
+    config::get_config_directory().join(NOTES_FILE)
+}
+
+fn read_notes_file() -> HashMap<String, String> {
+    match fs::read(get_notes_file()) {
+        Ok(bytes) => match serde_json::from_slice(&bytes) {
+            Ok(notes) => notes,
+            Err(e) => {
+                log_warn!("Failed to parse notes file! {}", e);
+                HashMap::new()
+            }
+        },
+        Err(_) => HashMap::new(), // Most likely no notes yet
+    }
+}
+
+pub fn save_notes_file() {
+    let mut changed = CHANGED.lock().unwrap();
+    if !*changed {
+        return;
+    }
+
+    let notes = NOTES.lock().unwrap().clone();
+    let path = get_notes_file();
+    let result = if notes.is_empty() {
+        // Don't leave an empty file behind once every note is removed
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    } else {
+        serde_json::to_vec_pretty(&notes)
+            .map_err(std::io::Error::other)
+            .and_then(|data| fs::write(&path, data))
+    };
+
+    match result {
+        Ok(()) => *changed = false,
+        Err(e) => log_error!("Failed to write notes file {}: {}", path.display(), e),
+    }
+}
+
+pub fn get_note(asset: &str) -> String {
+    NOTES
+        .lock()
+        .unwrap()
+        .get(asset)
+        .cloned()
+        .unwrap_or_default()
+}
+
+// An empty note removes it
+pub fn set_note(asset: &str, note: &str) {
+    let mut notes = NOTES.lock().unwrap();
+    if note.trim().is_empty() {
+        if notes.remove(asset).is_none() {
+            return;
+        }
+    } else {
+        notes.insert(
+            asset.to_owned(),
+            note.chars().take(MAX_NOTE_LENGTH).collect(),
+        );
+    }
+    *CHANGED.lock().unwrap() = true;
+}
+
+pub fn get_notes() -> Vec<(String, String)> {
+    let mut notes: Vec<(String, String)> = NOTES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(asset, note)| (asset.clone(), note.clone()))
+        .collect();
+    notes.sort();
+    notes
+}
+
+pub fn count() -> usize {
+    NOTES.lock().unwrap().len()
+}
+
+// Notes on assets that are no longer in the cache or database, for cleaning up
+pub fn find_orphaned() -> Vec<String> {
+    get_notes()
+        .into_iter()
+        .map(|(asset, _)| asset)
+        .filter(|asset| {
+            [Category::All, Category::Music].iter().all(|category| {
+                let info = logic::create_asset_info(asset, *category);
+                !info.from_file && !info.from_sql
+            })
+        })
+        .collect()
+}
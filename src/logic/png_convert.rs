@@ -0,0 +1,158 @@
+// Converting extracted WebP and KTX textures to PNG, which more tools can open. WebP is decoded with
+// the image crate. KTX is read here, but only version 1 textures with uncompressed 8-bit pixels,
+// block compressed ones (ETC, ASTC, BC) are left as they are.
+use image::{DynamicImage, GrayAlphaImage, GrayImage, ImageFormat, RgbImage, RgbaImage};
+use std::io::Cursor;
+
+use crate::logic::{get_extension, Error, ExtractedAsset, KTX1_IDENTIFIER};
+
+const KTX_HEADER_SIZE: usize = 64;
+
+// OpenGL enums used by the KTX header
+const GL_UNSIGNED_BYTE: u32 = 0x1401;
+const GL_ALPHA: u32 = 0x1906;
+const GL_RGB: u32 = 0x1907;
+const GL_RGBA: u32 = 0x1908;
+const GL_LUMINANCE: u32 = 0x1909;
+const GL_LUMINANCE_ALPHA: u32 = 0x190A;
+const GL_BGRA: u32 = 0x80E1;
+
+fn read_u32(bytes: &[u8], offset: usize, big_endian: bool) -> Option<u32> {
+    let word: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+    Some(if big_endian {
+        u32::from_be_bytes(word)
+    } else {
+        u32::from_le_bytes(word)
+    })
+}
+
+// The first mipmap level of the first face, which is the full size texture
+fn decode_ktx(bytes: &[u8]) -> Result<DynamicImage, String> {
+    if !bytes.starts_with(&KTX1_IDENTIFIER) {
+        return Err("only KTX version 1 is supported".to_owned());
+    }
+    // Written as 0x04030201 in the order of whoever made the file
+    let big_endian = match bytes.get(12..16) {
+        Some([0x01, 0x02, 0x03, 0x04]) => false,
+        Some([0x04, 0x03, 0x02, 0x01]) => true,
+        _ => return Err("invalid KTX endianness".to_owned()),
+    };
+    let field = |index: usize| {
+        read_u32(bytes, 16 + index * 4, big_endian).ok_or("KTX header is cut short".to_owned())
+    };
+    let gl_type = field(0)?;
+    let gl_format = field(2)?;
+    let gl_internal_format = field(3)?;
+    let width = field(5)?;
+    let height = field(6)?.max(1); // 0 for 1D textures
+    let key_value_size = field(11)? as usize;
+
+    if gl_type == 0 {
+        return Err(format!(
+            "compressed KTX format 0x{gl_internal_format:04X} isn't supported"
+        ));
+    }
+    if gl_type != GL_UNSIGNED_BYTE {
+        return Err(format!("KTX pixel type 0x{gl_type:04X} isn't supported"));
+    }
+    let channels = match gl_format {
+        GL_ALPHA | GL_LUMINANCE => 1,
+        GL_LUMINANCE_ALPHA => 2,
+        GL_RGB => 3,
+        GL_RGBA | GL_BGRA => 4,
+        _ => {
+            return Err(format!(
+                "KTX pixel format 0x{gl_format:04X} isn't supported"
+            ))
+        }
+    };
+
+    let data_start = KTX_HEADER_SIZE + key_value_size + 4; // After the level's size
+    let row_size = width as usize * channels;
+    let padded_row_size = row_size.div_ceil(4) * 4; // Rows are padded to 4 bytes
+    let data = bytes
+        .get(data_start..data_start + padded_row_size * height as usize)
+        .ok_or("KTX pixel data is cut short".to_owned())?;
+    let mut pixels: Vec<u8> = data
+        .chunks(padded_row_size)
+        .flat_map(|row| &row[..row_size])
+        .copied()
+        .collect();
+
+    let invalid_size = || "KTX pixel data doesn't match its size".to_owned();
+    Ok(match gl_format {
+        GL_ALPHA => {
+            // Kept as transparency over white, like it would be drawn
+            let pixels = pixels.iter().flat_map(|&a| [255, 255, 255, a]).collect();
+            DynamicImage::ImageRgba8(
+                RgbaImage::from_raw(width, height, pixels).ok_or_else(invalid_size)?,
+            )
+        }
+        GL_LUMINANCE => DynamicImage::ImageLuma8(
+            GrayImage::from_raw(width, height, pixels).ok_or_else(invalid_size)?,
+        ),
+        GL_LUMINANCE_ALPHA => DynamicImage::ImageLumaA8(
+            GrayAlphaImage::from_raw(width, height, pixels).ok_or_else(invalid_size)?,
+        ),
+        GL_RGB => DynamicImage::ImageRgb8(
+            RgbImage::from_raw(width, height, pixels).ok_or_else(invalid_size)?,
+        ),
+        _ => {
+            if gl_format == GL_BGRA {
+                pixels
+                    .chunks_exact_mut(4)
+                    .for_each(|pixel| pixel.swap(0, 2));
+            }
+            DynamicImage::ImageRgba8(
+                RgbaImage::from_raw(width, height, pixels).ok_or_else(invalid_size)?,
+            )
+        }
+    })
+}
+
+fn to_png(header: &str, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+    let image = match header {
+        "WEBP" => image::load_from_memory_with_format(bytes, ImageFormat::WebP)
+            .map_err(|e| Error::Decode(e.to_string()))?,
+        "KTX" => decode_ktx(bytes).map_err(Error::Decode)?,
+        _ => {
+            return Err(Error::Decode(format!(
+                "{header} isn't a texture that can be converted"
+            )))
+        }
+    };
+    let mut encoded = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+        .map_err(|e| Error::Decode(e.to_string()))?;
+    Ok(encoded)
+}
+
+// WebP and KTX become PNG, anything else is left as it is. False if it couldn't be decoded and is
+// still in its own format
+pub fn convert(name: &str, extracted: &mut ExtractedAsset) -> bool {
+    let Some(header) = extracted
+        .header
+        .clone()
+        .filter(|header| matches!(header.as_str(), "WEBP" | "KTX"))
+    else {
+        return true;
+    };
+    match to_png(&header, &extracted.bytes) {
+        Ok(bytes) => {
+            extracted.bytes = bytes;
+            extracted.header = Some("PNG".to_owned());
+            extracted.suggested_extension = Some(get_extension("PNG"));
+            true
+        }
+        Err(e) => {
+            log_warn!(
+                "Failed to convert {} to PNG, extracting as {}: {}",
+                name,
+                get_extension(&header),
+                e
+            );
+            false
+        }
+    }
+}
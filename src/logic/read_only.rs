@@ -0,0 +1,55 @@
+// Read-only mode, for inspecting a cache without changing anything. Checked by everything that
+// writes to the cache directory, the database or RoExtract's own databases, not only by the GUI.
+use std::{
+    fmt,
+    sync::{LazyLock, Mutex},
+};
+
+use crate::config;
+
+static READ_ONLY: LazyLock<Mutex<bool>> =
+    LazyLock::new(|| Mutex::new(config::get_config_bool("read_only").unwrap_or(false)));
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadOnlyMode;
+
+impl fmt::Display for ReadOnlyMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "RoExtract is in read-only mode")
+    }
+}
+
+impl std::error::Error for ReadOnlyMode {}
+
+impl From<ReadOnlyMode> for std::io::Error {
+    fn from(error: ReadOnlyMode) -> Self {
+        std::io::Error::new(std::io::ErrorKind::PermissionDenied, error)
+    }
+}
+
+impl From<ReadOnlyMode> for rusqlite::Error {
+    fn from(error: ReadOnlyMode) -> Self {
+        rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_READONLY),
+            Some(error.to_string()),
+        )
+    }
+}
+
+pub fn is_enabled() -> bool {
+    *READ_ONLY.lock().unwrap()
+}
+
+// The settings toggle also saves it to the config, --read-only only lasts for that run
+pub fn set_enabled(enabled: bool) {
+    *READ_ONLY.lock().unwrap() = enabled;
+}
+
+// Call before writing anything, e.g. read_only::check()?
+pub fn check() -> Result<(), ReadOnlyMode> {
+    if is_enabled() {
+        Err(ReadOnlyMode)
+    } else {
+        Ok(())
+    }
+}
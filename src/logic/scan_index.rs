@@ -0,0 +1,459 @@
+// What listing found out about each asset, so the next refresh only reads the ones that are new or
+// have changed since. An asset is read again once its size or modification time changes, and the
+// whole index is dropped when the scan window or the version changes what would be found.
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    config,
+    logic::{self, read_only, Category},
+};
+
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+const DETECTION_REVISION: u32 = 2; // Bumped when what's detected changes between releases
+const INDEX_FILE: &str = "RoExtract-scan-index.db";
+
+// Where an asset was listed from, an asset can be in both
+pub const FILE: &str = "file";
+pub const SQL: &str = "sql";
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub actual_size: u64,
+    pub actual_size_approximate: bool,
+    earliest_header: Option<String>, // Out of every category's headers, decides which tabs list it
+    detected: Option<(Category, String)>, // Shown on the All tab
+    pub origin_url: Option<String>,  // From the HTTP preamble, see logic::origin_url()
+}
+
+impl Entry {
+    // raw is the start of the asset as stored, prefix is the same decompressed
+    pub fn sniff(raw: &[u8], prefix: &[u8], size: u64) -> Entry {
+        let (actual_size, actual_size_approximate) = logic::get_actual_size(raw, size);
+        let headers = logic::get_headers(&Category::All);
+        let mut entry = Entry {
+            actual_size,
+            actual_size_approximate,
+            earliest_header: logic::find_listing_header(prefix, &headers).cloned(),
+            detected: logic::detect_format(prefix),
+            origin_url: logic::origin_url(prefix),
+        };
+
+        // Raw deflate has no magic, so it's only tried once nothing is found
+        if entry.earliest_header.is_none() {
+            if let Some(inflated) = logic::inflate_prefix(prefix, logic::get_prefix_length()) {
+                entry.earliest_header = logic::find_listing_header(&inflated, &headers).cloned();
+                entry.detected = logic::detect_format(&inflated);
+                entry.origin_url = logic::origin_url(&inflated);
+                entry.actual_size_approximate = true; // Nothing says how big it is
+            }
+        }
+        entry
+    }
+
+    // The category and header to list it under on a tab, None if the tab doesn't list it
+    pub fn listing(&self, category: Category) -> Option<(Category, Option<String>)> {
+        let earliest = self.earliest_header.as_ref()?;
+        if category == Category::All {
+            Some(match &self.detected {
+                Some((category, header)) => (*category, Some(header.clone())),
+                None => (Category::All, None),
+            })
+        } else {
+            logic::get_headers(&category)
+                .contains(earliest)
+                .then(|| (category, Some(earliest.clone())))
+        }
+    }
+}
+
+// One refresh of one source, load() it before listing and save() it after
+pub struct ScanIndex {
+    source: &'static str,
+    entries: HashMap<String, (u64, i64, Entry)>, // Size and modification time it was read at
+    seen: HashSet<String>,
+    changed: Vec<(String, u64, i64, Entry)>,
+    reused: usize,
+}
+
+pub fn open_database() -> Option<Connection> {
+    log_debug!("logic::scan_index::open_database()");
+    let path = config::get_config_directory().join(INDEX_FILE);
+    if read_only::is_enabled() && !path.exists() {
+        return None; // Nothing would be saved anyway
+    }
+
+    let connection = match Connection::open(&path) {
+        Ok(connection) => connection,
+        Err(e) => {
+            log_error!("Failed to open scan index {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    // Another RoExtract process could be saving its scan
+    let _ = connection.busy_timeout(std::time::Duration::from_secs(5));
+
+    match create_tables(&connection) {
+        Ok(_) => Some(connection),
+        Err(e) => {
+            log_error!("Failed to create scan index tables: {}", e);
+            None
+        }
+    }
+}
+
+// Everything in it is the same in any language: the categories' tab ids, headers and URLs
+fn create_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS entries (
+            source TEXT NOT NULL,
+            asset TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            modified INTEGER NOT NULL,
+            actual_size INTEGER NOT NULL,
+            actual_size_approximate INTEGER NOT NULL,
+            earliest_header TEXT,
+            detected_category TEXT,
+            detected_header TEXT,
+            origin_url TEXT,
+            PRIMARY KEY (source, asset)
+        );
+        CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );",
+    )?;
+    add_origin_url(conn)
+}
+
+// Indexes from before origin URLs were kept don't have the column
+fn add_origin_url(conn: &Connection) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('entries') WHERE name = 'origin_url'",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute("ALTER TABLE entries ADD COLUMN origin_url TEXT", [])?;
+    }
+    Ok(())
+}
+
+// Anything that changes what listing finds, the entries are only valid for the same fingerprint
+fn fingerprint() -> String {
+    format!(
+        "{} {} {}",
+        VERSION,
+        DETECTION_REVISION,
+        logic::get_scan_window()
+    )
+}
+
+fn load_entries(
+    conn: &Connection,
+    source: &str,
+) -> rusqlite::Result<HashMap<String, (u64, i64, Entry)>> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'fingerprint'",
+            [],
+            |row| row.get(0),
+        )
+        .ok();
+    if stored.as_deref() != Some(fingerprint().as_str()) {
+        if !read_only::is_enabled() {
+            log_info!("Scan index is out of date, every asset will be read again");
+            conn.execute("DELETE FROM entries", [])?;
+            conn.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('fingerprint', ?1)",
+                params![fingerprint()],
+            )?;
+        }
+        return Ok(HashMap::new());
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT asset, size, modified, actual_size, actual_size_approximate, earliest_header,
+        detected_category, detected_header, origin_url FROM entries WHERE source = ?1",
+    )?;
+    let rows = stmt.query_map(params![source], |row| {
+        let detected_category: Option<String> = row.get(6)?;
+        let detected_header: Option<String> = row.get(7)?;
+        let entry = Entry {
+            actual_size: row.get(3)?,
+            actual_size_approximate: row.get(4)?,
+            earliest_header: row.get(5)?,
+            detected: detected_category
+                .as_deref()
+                .and_then(Category::from_tab_id)
+                .zip(detected_header),
+            origin_url: row.get(8)?,
+        };
+        Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?, entry)))
+    })?;
+    rows.collect()
+}
+
+impl ScanIndex {
+    pub fn load(source: &'static str) -> ScanIndex {
+        let state = logic::state();
+        let connection = state.scan_index().lock().unwrap();
+        let entries = match &*connection {
+            Some(conn) => load_entries(conn, source).unwrap_or_else(|e| {
+                log_error!("Failed to load scan index: {}", e);
+                HashMap::new()
+            }),
+            None => HashMap::new(),
+        };
+
+        ScanIndex {
+            source,
+            entries,
+            seen: HashSet::new(),
+            changed: Vec::new(),
+            reused: 0,
+        }
+    }
+
+    // What was found last time, None if it's new or has changed since
+    pub fn get(&mut self, asset: &str, size: u64, modified: i64) -> Option<Entry> {
+        self.seen.insert(asset.to_owned());
+        match self.entries.get(asset) {
+            Some((stored_size, stored_modified, entry))
+                if *stored_size == size && *stored_modified == modified =>
+            {
+                self.reused += 1;
+                Some(entry.clone())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn insert(&mut self, asset: String, size: u64, modified: i64, entry: Entry) {
+        self.seen.insert(asset.clone());
+        self.changed.push((asset, size, modified, entry));
+    }
+
+    // Entries that weren't seen are only dropped if the whole source was listed
+    pub fn save(self, complete: bool) {
+        log_info!(
+            "Scan index ({}): {} reused, {} read",
+            self.source,
+            self.reused,
+            self.changed.len()
+        );
+        if read_only::is_enabled() {
+            return;
+        }
+
+        let state = logic::state();
+        let mut connection = state.scan_index().lock().unwrap();
+        let Some(conn) = &mut *connection else {
+            return;
+        };
+
+        let result = (|| -> rusqlite::Result<()> {
+            let transaction = conn.transaction()?;
+            {
+                let mut stmt = transaction.prepare(
+                    "INSERT OR REPLACE INTO entries (source, asset, size, modified, actual_size,
+                    actual_size_approximate, earliest_header, detected_category, detected_header,
+                    origin_url)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                )?;
+                for (asset, size, modified, entry) in &self.changed {
+                    stmt.execute(params![
+                        self.source,
+                        asset,
+                        size,
+                        modified,
+                        entry.actual_size,
+                        entry.actual_size_approximate,
+                        entry.earliest_header,
+                        entry
+                            .detected
+                            .as_ref()
+                            .map(|(category, _)| category.tab_id()),
+                        entry.detected.as_ref().map(|(_, header)| header),
+                        entry.origin_url,
+                    ])?;
+                }
+
+                if complete {
+                    let mut stmt = transaction
+                        .prepare("DELETE FROM entries WHERE source = ?1 AND asset = ?2")?;
+                    for asset in self.entries.keys() {
+                        if !self.seen.contains(asset) {
+                            stmt.execute(params![self.source, asset])?;
+                        }
+                    }
+                }
+            }
+            transaction.commit()
+        })();
+
+        if let Err(e) = result {
+            log_error!("Failed to save scan index: {}", e);
+        }
+    }
+}
+
+// Every asset is read again on the next refresh
+pub fn clear() -> Result<usize, rusqlite::Error> {
+    log_debug!("logic::scan_index::clear()");
+    read_only::check()?;
+    let state = logic::state();
+    let connection = state.scan_index().lock().unwrap();
+
+    if let Some(conn) = &*connection {
+        conn.execute("DELETE FROM entries", [])
+    } else {
+        Err(rusqlite::Error::InvalidQuery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        locale,
+        logic::{
+            fixtures::{self, Fixture, FixtureAsset},
+            KTX2_HEADER,
+        },
+    };
+    use strum::IntoEnumIterator;
+
+    fn columns(conn: &Connection, table: &str) -> Vec<String> {
+        let mut stmt = conn
+            .prepare("SELECT name FROM pragma_table_info(?1)")
+            .unwrap();
+        stmt.query_map(params![table], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        create_tables(&conn).unwrap(); // Opened again by the next run
+        assert_eq!(
+            columns(&conn, "entries"),
+            [
+                "source",
+                "asset",
+                "size",
+                "modified",
+                "actual_size",
+                "actual_size_approximate",
+                "earliest_header",
+                "detected_category",
+                "detected_header",
+                "origin_url",
+            ]
+        );
+        assert_eq!(columns(&conn, "meta"), ["key", "value"]);
+    }
+
+    #[test]
+    fn origin_url_is_added_to_old_indexes() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE entries (
+                source TEXT NOT NULL,
+                asset TEXT NOT NULL,
+                size INTEGER NOT NULL,
+                modified INTEGER NOT NULL,
+                actual_size INTEGER NOT NULL,
+                actual_size_approximate INTEGER NOT NULL,
+                earliest_header TEXT,
+                detected_category TEXT,
+                detected_header TEXT,
+                PRIMARY KEY (source, asset)
+            );",
+        )
+        .unwrap();
+        create_tables(&conn).unwrap();
+        assert_eq!(columns(&conn, "entries").last().unwrap(), "origin_url");
+    }
+
+    #[test]
+    fn entries_are_the_same_in_any_language() {
+        let mut fixture = Fixture::new();
+        let url = "https://c0.rbxcdn.com/i1";
+        fixture.add_file(
+            FixtureAsset::new("i1", fixtures::png()).preamble(fixtures::http_preamble(url)),
+        );
+        fixture.add_file(FixtureAsset::new("s1", fixtures::ogg()));
+        fixture.add_file(FixtureAsset::new("k1", fixtures::ktx2()));
+        let state = fixture.state();
+        let conn = Connection::open_in_memory().unwrap();
+        create_tables(&conn).unwrap();
+        *state.scan_index().lock().unwrap() = Some(conn);
+        state.run(|| config::set_config_value("language", "ru-RU".into()));
+        state.refresh(Category::All).unwrap();
+
+        let russian = locale::get_locale(Some("ru-RU"));
+        let localised: Vec<String> = Category::iter()
+            .map(|category| locale::get_message(&russian, category.locale_key(), None))
+            .collect();
+        let index = state.scan_index().lock().unwrap();
+        let conn = index.as_ref().unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT asset, detected_category, detected_header, origin_url FROM entries
+                WHERE source = ?1 ORDER BY asset",
+            )
+            .unwrap();
+        let entries: Vec<(String, String, String, Option<String>)> = stmt
+            .query_map(params![FILE], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "i1".to_owned(),
+                    "images".to_owned(),
+                    "PNG".to_owned(),
+                    Some(url.to_owned())
+                ),
+                (
+                    "k1".to_owned(),
+                    "ktx-files".to_owned(),
+                    KTX2_HEADER.to_owned(),
+                    None
+                ),
+                (
+                    "s1".to_owned(),
+                    "sounds".to_owned(),
+                    "OggS".to_owned(),
+                    None
+                ),
+            ]
+        );
+        for (asset, category, ..) in &entries {
+            assert!(!localised.contains(category), "{asset}: {category}");
+        }
+
+        let stored: String = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'fingerprint'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(
+            stored,
+            format!(
+                "{VERSION} {DETECTION_REVISION} {}",
+                logic::DEFAULT_SCAN_WINDOW
+            )
+        );
+    }
+}
@@ -1,63 +1,113 @@
 use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
 use rusqlite::params;
-use rusqlite::Connection;
+use rusqlite::{Connection, OpenFlags};
 use std::{
     fs,
+    path::{Path, PathBuf},
     sync::{Arc, LazyLock, Mutex},
     time::SystemTime,
 };
 
-use crate::{config, locale, logic};
+use crate::{
+    config, locale,
+    logic::{
+        self, clear, read_only,
+        scan_index::{self, ScanIndex},
+        summary::SourceSummary,
+    },
+};
 
 const DEFAULT_PATHS: [&str; 2] = [
     "%localappdata%\\Roblox\\rbx-storage.db",
     "~/.var/app/org.vinegarhq.Sober/data/sober/appData/rbx-storage.db",
 ]; // For windows and linux (sober)
-static CONNECTION: LazyLock<Mutex<Option<Connection>>> =
-    LazyLock::new(|| Mutex::new(open_database()));
+static PROMPT_FOR_DATABASE: LazyLock<Mutex<bool>> = LazyLock::new(|| Mutex::new(true)); // Ask for a path if none is detected
+static DETECTION_ERRORS: LazyLock<Mutex<Vec<String>>> = LazyLock::new(|| Mutex::new(Vec::new())); // Why each path failed to open
+static SESSION_DATABASE: LazyLock<Mutex<Option<PathBuf>>> = LazyLock::new(|| Mutex::new(None)); // Not saved to the config
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    ReadWrite,
+    ReadOnly,
+    Disconnected,
+}
+
+// Shown in the settings so the user can tell which database is in use
+#[derive(Debug, Clone)]
+pub struct DatabaseInfo {
+    pub path: Option<String>,
+    pub state: ConnectionState,
+    pub rows: Option<i64>,
+    pub size: Option<u64>,
+}
+
+// Read-only mode opens it so nothing can be written even by mistake
+pub fn open_connection(path: &str) -> rusqlite::Result<Connection> {
+    if read_only::is_enabled() {
+        Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI,
+        )
+    } else {
+        Connection::open(path)
+    }
+}
 
 pub fn open_database() -> Option<Connection> {
     log_debug!("logic::sql_database::open_database()");
-    let mut errors = "".to_owned();
+    let mut errors = Vec::new();
 
     // User-specified path from config
     if let Some(path) = config::get_config_string("sql_database") {
         log_debug!("Trying user-specified path: {}", path);
         match validate_file(&path) {
-            Ok(resolved_path) => match Connection::open(resolved_path) {
-                Ok(connection) => return Some(connection),
+            Ok(resolved_path) => match open_connection(&resolved_path) {
+                Ok(connection) => return connected(connection),
                 Err(e) => {
                     log_critical!("Detecting user-specified database failed: {}", e);
-                    errors.push_str(&e.to_string())
+                    errors.push(format!("{path}: {e}"))
                 }
             },
             Err(e) => {
                 log_critical!("Detecting user-specified database failed: {}", e);
-                errors.push_str(&e)
+                errors.push(format!("{path}: {e}"))
             }
         }
     }
 
     for path in DEFAULT_PATHS {
         match validate_file(path) {
-            Ok(resolved_path) => match Connection::open(resolved_path) {
-                Ok(connection) => return Some(connection),
-                Err(e) => errors.push_str(&e.to_string()),
+            Ok(resolved_path) => match open_connection(&resolved_path) {
+                Ok(connection) => return connected(connection),
+                Err(e) => errors.push(format!("{path}: {e}")),
             },
-            Err(e) => errors.push_str(&e),
+            Err(e) => errors.push(format!("{path}: {e}")),
         }
     }
+    *DETECTION_ERRORS.lock().unwrap() = errors.clone();
+
+    // The GUI asks through its own dialogs once the window is shown
+    if *PROMPT_FOR_DATABASE.lock().unwrap() && prompt_for_database() {
+        return open_database();
+    }
+
+    log_critical!("Database detection failed! {}", errors.join("; "));
+    None
+}
+
+// Tells the user detection failed and lets them pick a database, true if one was picked
+pub fn prompt_for_database() -> bool {
+    let locale = locale::get_locale(None);
 
-    // If it was unable to detect any path, tell the user
     let _ = native_dialog::DialogBuilder::message()
         .set_level(native_dialog::MessageLevel::Error)
         .set_title(locale::get_message(
-            &locale::get_locale(None),
+            &locale,
             "error-sql-detection-title",
             None,
         ))
         .set_text(locale::get_message(
-            &locale::get_locale(None),
+            &locale,
             "error-sql-detection-description",
             None,
         ))
@@ -67,41 +117,151 @@ pub fn open_database() -> Option<Connection> {
     let yes = native_dialog::DialogBuilder::message()
         .set_level(native_dialog::MessageLevel::Error)
         .set_title(locale::get_message(
-            &locale::get_locale(None),
+            &locale,
             "confirmation-custom-sql-title",
             None,
         ))
         .set_text(locale::get_message(
-            &locale::get_locale(None),
+            &locale,
             "confirmation-custom-sql-description",
             None,
         ))
         .confirm()
         .show()
-        .unwrap();
+        .unwrap_or(false);
 
     if yes {
         let option_path = native_dialog::DialogBuilder::file()
-            .open_single_dir()
+            .add_filter("SQLite", ["db"])
+            .open_single_file()
             .show()
-            .unwrap();
+            .unwrap_or(None);
         if let Some(path) = option_path {
             config::set_config_value(
                 "sql_database",
                 logic::resolve_path(path.to_string_lossy().as_ref()).into(),
             );
-            return open_database();
-        } else {
-            log_critical!("Database detection failed! {}", errors);
+            return true;
         }
-    } else {
-        log_critical!("Database detection failed! {}", errors);
     }
+    false
+}
 
-    None
+pub fn set_prompt_for_database(prompt: bool) {
+    *PROMPT_FOR_DATABASE.lock().unwrap() = prompt;
+}
+
+// Opens the database if it hasn't been yet, true if connected
+pub fn connect() -> bool {
+    logic::state().database().lock().unwrap().is_some()
+}
+
+// None if not connected
+pub fn get_database_path() -> Option<PathBuf> {
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
+    connection
+        .as_ref()
+        .and_then(|conn| conn.path())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
 }
 
-pub fn validate_file(path: &str) -> Result<String, String> {
+fn connected(connection: Connection) -> Option<Connection> {
+    DETECTION_ERRORS.lock().unwrap().clear();
+    Some(connection)
+}
+
+// Empty if the database opened
+pub fn get_detection_errors() -> Vec<String> {
+    DETECTION_ERRORS.lock().unwrap().clone()
+}
+
+// None while the database is busy, e.g. being opened or listed, so the GUI doesn't freeze
+pub fn info() -> Option<DatabaseInfo> {
+    let state = logic::state();
+    if !state.database_opened() {
+        return None; // Opening it here would block the GUI
+    }
+    let connection = state.database().try_lock().ok()?;
+
+    let Some(conn) = &*connection else {
+        return Some(DatabaseInfo {
+            path: None,
+            state: ConnectionState::Disconnected,
+            rows: None,
+            size: None,
+        });
+    };
+
+    let path = conn.path().map(|path| path.to_string());
+    let state = if conn.is_readonly(rusqlite::MAIN_DB).unwrap_or(false) {
+        ConnectionState::ReadOnly
+    } else {
+        ConnectionState::ReadWrite
+    };
+    let rows = conn
+        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+        .ok();
+    let size = path
+        .as_ref()
+        .and_then(|path| fs::metadata(path).ok())
+        .map(|metadata| metadata.len());
+
+    Some(DatabaseInfo {
+        path,
+        state,
+        rows,
+        size,
+    })
+}
+
+// A default database other than the current one, e.g. Sober's when the Windows path was chosen
+pub fn find_alternative_database() -> Option<String> {
+    let current = get_db_path();
+    DEFAULT_PATHS
+        .iter()
+        .filter_map(|path| validate_file(path).ok())
+        .find(|path| Some(path) != current.as_ref())
+}
+
+// What the health check needs to know about the database
+pub struct DatabaseHealth {
+    pub path: String,
+    pub rows: i64,
+    pub samples: Vec<Vec<u8>>, // Decompressed prefixes of some rows
+}
+
+pub fn health(sample_size: usize) -> Option<DatabaseHealth> {
+    let path = get_db_path()?;
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
+    let conn = connection.as_ref()?;
+
+    let rows: i64 = conn
+        .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+        .unwrap_or(0);
+
+    // Same detection as refresh, evicted rows have nothing to detect
+    let samples = conn
+        .prepare("SELECT substr(content, 1, 2048) FROM files WHERE length(content) > 0 LIMIT ?1")
+        .and_then(|mut stmt| {
+            stmt.query_map(params![sample_size as i64], |row| row.get::<_, Vec<u8>>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|prefix| logic::decompress_prefix(prefix, 2048))
+        .collect();
+
+    Some(DatabaseHealth {
+        path,
+        rows,
+        samples,
+    })
+}
+
+pub fn validate_file(path: &str) -> Result<String, logic::Error> {
     log_debug!("logic::sql_database::validate_file({path})");
     let resolved_path = logic::resolve_path(path);
 
@@ -112,17 +272,23 @@ pub fn validate_file(path: &str) -> Result<String, String> {
                 // Successfully detected a directory, we can return it
                 Ok(resolved_path)
             } else {
-                Err(format!("{resolved_path}: Not a file"))
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::IsADirectory,
+                    format!("{resolved_path}: Not a file"),
+                )
+                .into())
             }
         }
-        Err(e) => {
-            Err(e.to_string()) // Convert to correct data type
-        }
+        Err(e) => Err(e.into()),
     }
 }
 
 pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
     log_debug!("logic::sql_database::clear_cache(locale)");
+    if read_only::is_enabled() {
+        log_warn!("Not clearing the database in read-only mode");
+        return;
+    }
 
     logic::update_progress(0.0);
 
@@ -136,14 +302,15 @@ pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
     args.set("item", "1");
     args.set("total", "2");
 
-    let path: Option<String> = {
-        let connection = CONNECTION.lock().unwrap();
-        if let Some(conn) = &*connection {
-            conn.path().map(|p| p.to_string())
-        } else {
-            None
+    let path: Option<String> = get_database_path().map(|p| p.to_string_lossy().to_string());
+
+    if let Some(path) = &path {
+        if let Err(e) = clear::check_database(Path::new(path)) {
+            log_error!("{}", e);
+            logic::update_status(locale::get_message(locale, "error-check-logs", None));
+            return;
         }
-    };
+    }
 
     // Disconnect from database before deleting
     match clean_up() {
@@ -180,10 +347,11 @@ pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
             }
         }
 
-        match Connection::open(&path) {
+        match open_connection(&path) {
             Ok(connection) => {
                 log_info!("Reconnected to database at {}", &path);
-                let mut connection_lock = CONNECTION.lock().unwrap();
+                let state = logic::state();
+                let mut connection_lock = state.database().lock().unwrap();
                 connection_lock.replace(connection);
             }
             Err(e) => {
@@ -196,11 +364,6 @@ pub fn clear_cache(locale: &FluentBundle<Arc<FluentResource>>) {
     args.set("total", "2");
 
     if let Some(storage_folder) = storage_folder {
-        // I'm scared
-        assert_ne!(storage_folder, std::path::Path::new("."));
-        assert_ne!(storage_folder, std::path::Path::new("/"));
-        assert_ne!(storage_folder, std::path::Path::new("C:\\"));
-
         match fs::remove_dir_all(&storage_folder) {
             Ok(_) => {
                 logic::update_progress(1.0);
@@ -228,28 +391,39 @@ pub fn refresh(
     category: logic::Category,
     cli_list_mode: bool,
     locale: &FluentBundle<Arc<FluentResource>>,
-) {
+) -> SourceSummary {
     log_debug!("logic::sql_database::refresh({category}, {cli_list_mode}, locale)");
 
+    let mut summary = SourceSummary::default();
     if category == logic::Category::Music {
-        return; // Music category is specific to /sounds folder.
+        return summary; // Music category is specific to /sounds folder.
     }
 
-    let headers = logic::get_headers(&category);
     let mut args = FluentArgs::new();
 
-    let connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
 
     if let Some(conn) = &*connection {
+        // Counting and reading rows can each take a while on a big database, interrupt_listing() stops them
+        *state.listing_interrupt.lock().unwrap() = Some(conn.get_interrupt_handle());
+        if logic::get_stop_list_running() {
+            return finish_listing(&state, summary);
+        }
         let amount: Result<i64, _> =
             conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0));
+        if logic::get_stop_list_running() {
+            return finish_listing(&state, summary);
+        }
 
-        match conn
-            .prepare("SELECT id, size, ttl, substr(content, 1, 2048) as content_prefix FROM files")
-        {
+        let mut index = ScanIndex::load(scan_index::SQL);
+        let mut complete = false;
+        let prefix_length = logic::get_prefix_length();
+        // Only the rows that are new or have changed since the last scan have their content read
+        match conn.prepare("SELECT id, size, ttl, length(content) FROM files") {
             Ok(mut stmt) => {
                 let mut count: i64 = 0;
-                let result = stmt.query_map((), |row| {
+                let result = stmt.query_map([], |row| {
                     if let Ok(total) = amount {
                         args.set("item", count);
                         args.set("total", total);
@@ -266,25 +440,82 @@ pub fn refresh(
                     let last_modified = SystemTime::UNIX_EPOCH
                         .checked_add(std::time::Duration::from_secs(last_modified_timestamp));
 
-                    let bytes = row.get::<_, Vec<u8>>(3)?;
+                    let id = row.get::<_, Vec<u8>>(0)?;
+                    let size: u64 = row.get(1)?;
+                    let content_length = row.get::<_, Option<i64>>(3)?.unwrap_or_default();
+                    let name = hex::encode(&id);
+
+                    // Roblox has removed the content but kept the row, the category can't be known
+                    // so it's only listed on the All tab
+                    if content_length == 0 {
+                        if category != logic::Category::All {
+                            return Err(rusqlite::Error::InvalidQuery);
+                        }
+                        return Ok(logic::AssetInfo {
+                            name,
+                            size,
+                            actual_size: 0,
+                            actual_size_approximate: false,
+                            last_modified,
+                            from_file: false,
+                            from_sql: true,
+                            prefer_sql: false,
+                            other_name: None,
+                            evicted: true,
+                            header: None,
+                            hash: None,
+                            origin_url: None,
+                            category,
+                        });
+                    }
 
-                    let header_found = headers.iter().any(|header| {
-                        // Go through each header - if any returns true, we found it.
-                        logic::bytes_contains(&bytes, header.as_bytes())
-                    });
+                    let modified = last_modified_timestamp as i64;
+                    let entry = match index.get(&name, size, modified) {
+                        Some(entry) => entry,
+                        None => {
+                            let prefix: Vec<u8> = conn.query_row(
+                                "SELECT substr(content, 1, ?2) FROM files WHERE id = ?1",
+                                params![&id, prefix_length as i64],
+                                |row| row.get(0),
+                            )?;
+
+                            // Compressed rows need decompressing before the headers can be found
+                            let bytes = match logic::decompress_prefix(&prefix, prefix_length) {
+                                Some(bytes) => bytes,
+                                None => {
+                                    let content: Vec<u8> = conn.query_row(
+                                        "SELECT content FROM files WHERE id = ?1",
+                                        params![&id],
+                                        |row| row.get(0),
+                                    )?;
+                                    logic::decompress_prefix(&content, prefix_length)
+                                        .unwrap_or_default()
+                                }
+                            };
+
+                            let entry = scan_index::Entry::sniff(&prefix, &bytes, size);
+                            index.insert(name.clone(), size, modified, entry.clone());
+                            entry
+                        }
+                    };
 
-                    if header_found {
+                    // On the All tab the category is detected, the header shown is the one that decided it
+                    if let Some((category, header)) = entry.listing(category) {
                         Ok(logic::AssetInfo {
-                            name: hex::encode(row.get::<_, Vec<u8>>(0)?),
-                            _size: row.get(1)?,
+                            name,
+                            size,
+                            actual_size: entry.actual_size,
+                            actual_size_approximate: entry.actual_size_approximate,
                             last_modified,
                             from_file: false,
                             from_sql: true,
-                            category: if category == logic::Category::All {
-                                logic::determine_category(&bytes)
-                            } else {
-                                category
-                            }, // Determine category if all
+                            prefer_sql: false,
+                            other_name: None,
+                            evicted: false,
+                            header,
+                            hash: None,
+                            origin_url: entry.origin_url,
+                            category,
                         })
                     } else {
                         Err(rusqlite::Error::InvalidQuery) // Return error for this asset as it doesn't match
@@ -293,14 +524,33 @@ pub fn refresh(
 
                 match result {
                     Ok(entries) => {
-                        for entry in entries.flatten() {
-                            logic::update_file_list(entry, cli_list_mode);
+                        // Rows are only read as they're iterated, so this stops the scan itself
+                        let mut batch = logic::ListBatch::new(cli_list_mode);
+                        for entry in entries {
+                            // Checked after the row is read, it failed if the stop interrupted it
+                            if logic::get_stop_list_running() {
+                                break; // Stop if another thread requests to stop this task.
+                            }
+                            match entry {
+                                Ok(entry) => {
+                                    summary.listed += 1;
+                                    batch.push(entry);
+                                }
+                                Err(rusqlite::Error::InvalidQuery) => summary.skipped += 1, // Another category
+                                Err(_) => summary.errors += 1,
+                            }
                         }
+                        batch.flush();
+                        complete = !logic::get_stop_list_running();
+                    }
+                    Err(e) => {
+                        summary.errors += 1;
+                        log_error!("{}", e)
                     }
-                    Err(e) => log_error!("{}", e),
                 }
             }
             Err(e) => {
+                summary.errors += 1;
                 log_error!("Error happened when querying DB for listing files: {}", e);
                 logic::update_status(locale::get_message(
                     locale,
@@ -309,7 +559,10 @@ pub fn refresh(
                 ));
             }
         }
+        index.save(complete);
     } else {
+        summary.errors += 1;
+        summary.unavailable = true;
         log_error!("No SQL Connection!");
         logic::update_status(locale::get_message(
             locale,
@@ -317,77 +570,169 @@ pub fn refresh(
             Some(&args),
         ));
     }
+
+    finish_listing(&state, summary)
+}
+
+fn finish_listing(state: &logic::state::ExtractorState, summary: SourceSummary) -> SourceSummary {
+    *state.listing_interrupt.lock().unwrap() = None;
+    summary
+}
+
+// Stops the database's part of a refresh straight away, even in the middle of a query
+pub fn interrupt_listing() {
+    if let Some(handle) = &*logic::state().listing_interrupt.lock().unwrap() {
+        handle.interrupt();
+    }
+}
+
+// Ids are stored as the bytes the hex name stands for
+fn asset_id(name: &str) -> Result<Vec<u8>, logic::Error> {
+    hex::decode(name).map_err(|_| logic::Error::InvalidAssetId(name.to_owned()))
 }
 
-pub fn read_asset(asset: &logic::AssetInfo) -> Result<Vec<u8>, std::io::Error> {
+pub fn read_asset(asset: &logic::AssetInfo) -> Result<Vec<u8>, logic::Error> {
     log_debug!("logic::sql_database::read_asset({asset:?})");
-    let connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
 
     if let Some(conn) = &*connection {
-        let id_bytes = match hex::decode(&asset.name) {
-            Ok(bytes) => bytes,
-            Err(e) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)),
-        };
+        let id_bytes = asset_id(&asset.name)?;
 
-        conn.query_row(
+        let content: Option<Vec<u8>> = conn.query_row(
             "SELECT content FROM files WHERE id = ?1",
             params![id_bytes],
             |row| row.get(0),
-        )
-        .map_err(std::io::Error::other)
+        )?;
+
+        match content {
+            Some(content) if !content.is_empty() => Ok(content),
+            _ => Err(logic::Error::Evicted),
+        }
     } else {
-        Err(std::io::Error::other("No SQL connection!"))
+        Err(logic::Error::NoConnection)
     }
 }
 
-pub fn create_asset_info(asset: &str, category: logic::Category) -> Option<logic::AssetInfo> {
+// Only the start of the content, for detecting headers again without reading all of it
+pub fn read_asset_prefix(asset: &logic::AssetInfo, length: usize) -> Result<Vec<u8>, logic::Error> {
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
+
+    if let Some(conn) = &*connection {
+        let id_bytes = asset_id(&asset.name)?;
+
+        let prefix: Option<Vec<u8>> = conn.query_row(
+            "SELECT substr(content, 1, ?2) FROM files WHERE id = ?1",
+            params![id_bytes, length as i64],
+            |row| row.get(0),
+        )?;
+
+        match prefix {
+            Some(prefix) if !prefix.is_empty() => Ok(prefix),
+            _ => Err(logic::Error::Evicted),
+        }
+    } else {
+        Err(logic::Error::NoConnection)
+    }
+}
+
+pub fn create_asset_info(
+    asset: &str,
+    category: logic::Category,
+) -> Result<logic::AssetInfo, logic::Error> {
     log_debug!("logic::sql_database::create_asset_info({asset}, {category})");
-    let connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
 
     if let Some(conn) = &*connection {
-        let id_bytes = match hex::decode(asset) {
-            Ok(bytes) => bytes,
-            Err(_) => return None,
-        };
-        conn.query_row(
-            "SELECT id, size, ttl FROM files WHERE id = ?1",
+        let id_bytes = asset_id(asset)?;
+        Ok(conn.query_row(
+            "SELECT id, size, ttl, substr(content, 1, 32) as content_prefix FROM files WHERE id = ?1",
             params![id_bytes],
             |row| {
                 let last_modified_timestamp: u64 = row.get(2)?;
                 let last_modified = SystemTime::UNIX_EPOCH
                     .checked_add(std::time::Duration::from_secs(last_modified_timestamp)); // Convert u64 to SystemTime
 
+                let size: u64 = row.get(1)?;
+                let prefix = row.get::<_, Option<Vec<u8>>>(3)?.unwrap_or_default();
+                let (actual_size, actual_size_approximate) =
+                    logic::get_actual_size(&prefix, size);
+
                 Ok(logic::AssetInfo {
                     name: asset.to_string(),
-                    _size: row.get(1)?,
+                    size,
+                    actual_size,
+                    actual_size_approximate,
                     last_modified,
                     from_file: false,
                     from_sql: true,
+                    prefer_sql: false,
+                    other_name: None,
+                    evicted: prefix.is_empty(),
+                    header: None,
+                    hash: None,
+                    origin_url: None,
                     category,
                 })
             },
-        )
-        .ok()
+        )?)
     } else {
-        None
+        Err(logic::Error::NoConnection)
+    }
+}
+
+pub fn write_asset(asset: &logic::AssetInfo, bytes: &[u8]) -> Result<(), logic::Error> {
+    log_debug!("logic::sql_database::write_asset({asset:?})");
+    read_only::check()?;
+
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
+
+    if let Some(conn) = &*connection {
+        let id = asset_id(&asset.name)?;
+
+        conn.execute(
+            "UPDATE files SET content = ?1 WHERE id = ?2",
+            params![bytes, &id],
+        )?;
+        Ok(())
+    } else {
+        Err(logic::Error::NoConnection)
+    }
+}
+
+pub fn delete_asset(asset: &logic::AssetInfo) -> Result<(), logic::Error> {
+    log_debug!("logic::sql_database::delete_asset({asset:?})");
+    read_only::check()?;
+
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
+
+    if let Some(conn) = &*connection {
+        let id = asset_id(&asset.name)?;
+
+        conn.execute("DELETE FROM files WHERE id = ?1", params![&id])?;
+        Ok(())
+    } else {
+        Err(logic::Error::NoConnection)
     }
 }
 
 pub fn swap_assets(
     asset_a: &logic::AssetInfo,
     asset_b: &logic::AssetInfo,
-) -> Result<(), rusqlite::Error> {
+) -> Result<(), logic::Error> {
     log_debug!("logic::sql_database::swap_assets({asset_a:?}, {asset_b:?})");
+    read_only::check()?;
 
-    let mut connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let mut connection = state.database().lock().unwrap();
 
     if let Some(conn) = connection.as_mut() {
-        let id_a = hex::decode(&asset_a.name).map_err(|e| {
-            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
-        })?;
-        let id_b = hex::decode(&asset_b.name).map_err(|e| {
-            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
-        })?;
+        let id_a = asset_id(&asset_a.name)?;
+        let id_b = asset_id(&asset_b.name)?;
 
         let tx = conn.transaction()?;
 
@@ -414,25 +759,23 @@ pub fn swap_assets(
         tx.commit()?;
         Ok(())
     } else {
-        Err(rusqlite::Error::InvalidQuery)
+        Err(logic::Error::NoConnection)
     }
 }
 
 pub fn copy_assets(
     asset_a: &logic::AssetInfo,
     asset_b: &logic::AssetInfo,
-) -> Result<(), rusqlite::Error> {
+) -> Result<(), logic::Error> {
     log_debug!("logic::sql_database::copy_assets({asset_a:?}, {asset_b:?})");
+    read_only::check()?;
 
-    let connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
 
     if let Some(conn) = &*connection {
-        let id_a = hex::decode(&asset_a.name).map_err(|e| {
-            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
-        })?;
-        let id_b = hex::decode(&asset_b.name).map_err(|e| {
-            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Blob, Box::new(e))
-        })?;
+        let id_a = asset_id(&asset_a.name)?;
+        let id_b = asset_id(&asset_b.name)?;
 
         let content_a: Vec<u8> = conn.query_row(
             "SELECT content FROM files WHERE id = ?1",
@@ -445,14 +788,15 @@ pub fn copy_assets(
         )?;
         Ok(())
     } else {
-        Err(rusqlite::Error::InvalidQuery)
+        Err(logic::Error::NoConnection)
     }
 }
 
 pub fn get_db_path() -> Option<String> {
     log_debug!("logic::sql_database::get_db_path()");
 
-    let connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let connection = state.database().lock().unwrap();
 
     if let Some(conn) = &*connection {
         conn.path().map(|path| path.to_string())
@@ -461,12 +805,38 @@ pub fn get_db_path() -> Option<String> {
     }
 }
 
+// Used until RoExtract closes or another database is chosen, e.g. one dropped on the window
+pub fn open_for_session(path: &str) -> Result<PathBuf, logic::Error> {
+    let path = validate_file(path)?;
+    let connection = open_connection(&path)?;
+    // Any SQLite file opens, only Roblox's has assets in it
+    connection
+        .query_row("SELECT 1 FROM files LIMIT 1", [], |_| Ok(()))
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(()), // Empty, but it's the right database
+            e => Err(e),
+        })?;
+
+    let _ = clean_up();
+    *logic::state().database().lock().unwrap() = Some(connection);
+    let path = PathBuf::from(path);
+    *SESSION_DATABASE.lock().unwrap() = Some(path.clone());
+    Ok(path)
+}
+
+// None unless the database was opened for the session
+pub fn get_session_database() -> Option<PathBuf> {
+    SESSION_DATABASE.lock().unwrap().clone()
+}
+
 pub fn reset_database() -> Result<(), (Connection, rusqlite::Error)> {
     log_debug!("logic::sql_database::reset_database()");
 
     let result = clean_up();
+    *SESSION_DATABASE.lock().unwrap() = None; // The config's database is opened again
 
-    let mut connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let mut connection = state.database().lock().unwrap();
     *connection = open_database();
 
     result
@@ -475,7 +845,8 @@ pub fn reset_database() -> Result<(), (Connection, rusqlite::Error)> {
 pub fn clean_up() -> Result<(), (Connection, rusqlite::Error)> {
     log_debug!("logic::sql_database::clean_up()");
 
-    let mut connection = CONNECTION.lock().unwrap();
+    let state = logic::state();
+    let mut connection = state.database().lock().unwrap();
 
     // Store result for later
     let result = if let Some(conn) = connection.take() {
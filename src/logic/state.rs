@@ -0,0 +1,353 @@
+// Everything a scan or an extraction works on: the lists, status and progress, and where assets are
+// read from. logic's free functions use the state of the thread calling them, the default one unless
+// ExtractorState::run() was used, so a separate state can list and extract its own cache directory and
+// database without touching the real ones. Threads started by logic carry the state with them.
+use rusqlite::{Connection, InterruptHandle};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    collections::HashSet,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, Arc, LazyLock, Mutex, OnceLock},
+    thread,
+};
+
+#[cfg(test)]
+use crate::logic::{
+    extract_options::ExtractOverrides,
+    summary::{ExtractSummary, RefreshSummary},
+};
+use crate::{
+    config, locale,
+    logic::{
+        self, cache_directory, history::History, scan_index, sql_database, AssetInfo, Category,
+        ListState,
+    },
+};
+
+static DEFAULT_STATE: LazyLock<Arc<ExtractorState>> =
+    LazyLock::new(|| Arc::new(ExtractorState::detected()));
+
+thread_local! {
+    static CURRENT_STATE: RefCell<Option<Arc<ExtractorState>>> = const { RefCell::new(None) };
+}
+
+pub struct ExtractorState {
+    // Detected or opened the first time they're needed, for the default state that's after the
+    // config has loaded
+    status: OnceLock<Mutex<String>>,
+    temp_directory: OnceLock<PathBuf>,
+    cache_directory: OnceLock<Mutex<PathBuf>>,
+    sounds_directory: OnceLock<Mutex<Option<PathBuf>>>,
+    database: OnceLock<Mutex<Option<Connection>>>,
+    scan_index: OnceLock<Mutex<Option<Connection>>>,
+    config_file: OnceLock<PathBuf>,
+    config: OnceLock<Arc<Mutex<Value>>>, // Shared with the states detached from this one
+    scan_window: OnceLock<usize>,        // header_scan_window, read once
+
+    pub(crate) file_list: Mutex<Vec<AssetInfo>>,
+    pub(crate) list_state: Mutex<ListState>,
+    pub(crate) filtered_file_list: Mutex<Vec<AssetInfo>>,
+    pub(crate) file_list_generation: Mutex<u64>, // Increases whenever either list changes
+    pub(crate) filter_generation: Mutex<u64>, // Increases with every filter, older ones are dropped
+    pub(crate) request_repaint: Mutex<bool>,
+    pub(crate) progress: Mutex<Option<f32>>, // None until the total is known
+    pub(crate) list_task_running: Mutex<bool>,
+    pub(crate) stop_list_running: Mutex<bool>,
+    pub(crate) list_category: Mutex<Category>, // Category being listed
+    pub(crate) list_generation: Mutex<u64>,    // Increases with every refresh
+    pub(crate) task_running: Mutex<bool>,      // Delete/extract
+    pub(crate) cancel_task: Mutex<bool>,       // Stops extracting between files
+    pub(crate) stable_order: Mutex<bool>,      // Print CLI listings once sorted
+    pub(crate) process_lock: bool,             // Tasks also stop other RoExtract processes
+    pub(crate) listing_interrupt: Mutex<Option<InterruptHandle>>, // Stops the database's listing query
+    pub(crate) printed: Mutex<HashSet<String>>, // Listed to the CLI, an asset in both sources is printed once
+    pub(crate) long_listing: Mutex<bool>,       // Print CLI listings with categories and sizes
+    pub(crate) filter_error_shown: AtomicBool,  // The status says the filter's regex is invalid
+    pub(crate) history: Arc<History>,
+}
+
+impl ExtractorState {
+    fn detected() -> Self {
+        ExtractorState {
+            status: OnceLock::new(),
+            temp_directory: OnceLock::new(),
+            cache_directory: OnceLock::new(),
+            sounds_directory: OnceLock::new(),
+            database: OnceLock::new(),
+            scan_index: OnceLock::new(),
+            config_file: OnceLock::new(),
+            config: OnceLock::new(),
+            scan_window: OnceLock::new(),
+            file_list: Mutex::new(Vec::new()),
+            list_state: Mutex::new(ListState::Listing), // Nothing has been listed yet
+            filtered_file_list: Mutex::new(Vec::new()),
+            file_list_generation: Mutex::new(0),
+            filter_generation: Mutex::new(0),
+            request_repaint: Mutex::new(false),
+            progress: Mutex::new(None),
+            list_task_running: Mutex::new(false),
+            stop_list_running: Mutex::new(false),
+            list_category: Mutex::new(Category::All),
+            list_generation: Mutex::new(0),
+            task_running: Mutex::new(false),
+            cancel_task: Mutex::new(false),
+            stable_order: Mutex::new(false),
+            process_lock: true,
+            listing_interrupt: Mutex::new(None),
+            printed: Mutex::new(HashSet::new()),
+            long_listing: Mutex::new(false),
+            filter_error_shown: AtomicBool::new(false),
+            history: Arc::default(),
+        }
+    }
+
+    pub(crate) fn config_file(&self) -> &PathBuf {
+        self.config_file.get_or_init(config::detect_config_file)
+    }
+
+    fn shared_config(&self) -> &Arc<Mutex<Value>> {
+        self.config
+            .get_or_init(|| Arc::new(Mutex::new(config::read_config_file(self.config_file()))))
+    }
+
+    pub(crate) fn config(&self) -> &Mutex<Value> {
+        self.shared_config()
+    }
+
+    pub(crate) fn scan_window(&self) -> usize {
+        *self.scan_window.get_or_init(|| {
+            config::get_config_u64("header_scan_window")
+                .map_or(logic::DEFAULT_SCAN_WINDOW, |window| window as usize)
+        })
+    }
+
+    pub(crate) fn status(&self) -> &Mutex<String> {
+        self.status.get_or_init(|| {
+            Mutex::new(locale::get_message(
+                &locale::get_locale(None),
+                "idling",
+                None,
+            ))
+        })
+    }
+
+    pub(crate) fn temp_directory(&self) -> &PathBuf {
+        self.temp_directory.get_or_init(logic::create_temp_dir)
+    }
+
+    pub(crate) fn cache_directory(&self) -> &Mutex<PathBuf> {
+        self.cache_directory
+            .get_or_init(|| Mutex::new(cache_directory::detect_directory()))
+    }
+
+    pub(crate) fn sounds_directory(&self) -> &Mutex<Option<PathBuf>> {
+        self.sounds_directory
+            .get_or_init(|| Mutex::new(cache_directory::detect_sounds_directory()))
+    }
+
+    pub(crate) fn database(&self) -> &Mutex<Option<Connection>> {
+        self.database
+            .get_or_init(|| Mutex::new(sql_database::open_database()))
+    }
+
+    // False until something has needed the database, opening it can block
+    pub(crate) fn database_opened(&self) -> bool {
+        self.database.get().is_some()
+    }
+
+    // A state with lists of its own, reading the same cache directory and database. For work done in
+    // the background that shouldn't replace the list shown, it uses the same scan index, config and
+    // history.
+    pub(crate) fn detached(&self) -> Arc<Self> {
+        let database_path = self
+            .database()
+            .lock()
+            .unwrap()
+            .as_ref()
+            .and_then(|conn| conn.path())
+            .filter(|path| !path.is_empty())
+            .map(str::to_owned);
+        let database = database_path.and_then(|path| match sql_database::open_connection(&path) {
+            Ok(connection) => Some(connection),
+            Err(e) => {
+                log_error!("Failed to open the database again: {}", e);
+                None
+            }
+        });
+
+        let state = ExtractorState {
+            process_lock: false,
+            ..Self::detected()
+        };
+        let _ = state
+            .cache_directory
+            .set(Mutex::new(self.cache_directory().lock().unwrap().clone()));
+        let _ = state
+            .sounds_directory
+            .set(Mutex::new(self.sounds_directory().lock().unwrap().clone()));
+        let _ = state.database.set(Mutex::new(database));
+        let _ = state.temp_directory.set(self.temp_directory().clone());
+        let _ = state.config_file.set(self.config_file().clone());
+        let _ = state.config.set(self.shared_config().clone());
+        let _ = state.scan_window.set(self.scan_window());
+        Arc::new(ExtractorState {
+            history: self.history.clone(),
+            ..state
+        })
+    }
+
+    // Runs f with this as the state, e.g. state.run(logic::get_file_list)
+    pub fn run<T>(self: &Arc<Self>, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT_STATE.with(|current| current.replace(Some(self.clone())));
+        let result = f();
+        CURRENT_STATE.with(|current| *current.borrow_mut() = previous);
+        result
+    }
+
+    pub(crate) fn scan_index(&self) -> &Mutex<Option<Connection>> {
+        self.scan_index
+            .get_or_init(|| Mutex::new(scan_index::open_database()))
+    }
+}
+
+// Nothing in the app needs a state of its own, tests use them to work on fixtures
+#[cfg(test)]
+impl ExtractorState {
+    // A state of its own reading cache_directory and the database at database_path, if there is one.
+    // Its temp files go in temp_directory, which clean_up() deletes. It doesn't stop or get stopped
+    // by other RoExtract processes, and lists everything without the scan index. Its config starts
+    // empty and lives in temp_directory, along with its history.
+    pub fn new(
+        cache_directory: PathBuf,
+        database_path: Option<PathBuf>,
+        temp_directory: PathBuf,
+    ) -> rusqlite::Result<Arc<Self>> {
+        let database = match database_path {
+            Some(path) => Some(Connection::open(path)?),
+            None => None,
+        };
+        let sounds_directory = Some(cache_directory.join("sounds")).filter(|path| path.is_dir());
+
+        let state = ExtractorState {
+            process_lock: false,
+            ..Self::detected()
+        };
+        let _ = state.cache_directory.set(Mutex::new(cache_directory));
+        let _ = state.sounds_directory.set(Mutex::new(sounds_directory));
+        let _ = state.database.set(Mutex::new(database));
+        let _ = state.scan_index.set(Mutex::new(None));
+        let _ = state
+            .config_file
+            .set(temp_directory.join(config::DEFAULT_CONFIG_FILE));
+        let _ = state
+            .config
+            .set(Arc::new(Mutex::new(serde_json::json!({}))));
+        let _ = state.temp_directory.set(temp_directory);
+        Ok(Arc::new(state))
+    }
+
+    pub fn refresh(self: &Arc<Self>, category: Category) -> Option<RefreshSummary> {
+        self.run(|| logic::refresh(category, false, true))
+    }
+
+    pub fn extract_dir(
+        self: &Arc<Self>,
+        destination: PathBuf,
+        category: Category,
+        overrides: ExtractOverrides,
+    ) -> Option<ExtractSummary> {
+        self.run(|| logic::extract_dir(destination, category, true, overrides))
+    }
+}
+
+// The state of this thread
+pub fn state() -> Arc<ExtractorState> {
+    CURRENT_STATE
+        .with(|current| current.borrow().clone())
+        .unwrap_or_else(|| DEFAULT_STATE.clone())
+}
+
+// thread::spawn, with the new thread using this thread's state
+pub fn spawn<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> thread::JoinHandle<T> {
+    let state = state();
+    thread::spawn(move || state.run(f))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{
+        fixtures::{ogg, png, Fixture, FixtureAsset},
+        history, FilterQuery,
+    };
+    use std::sync::atomic::Ordering;
+
+    fn names(state: &Arc<ExtractorState>) -> Vec<String> {
+        state
+            .run(logic::get_file_list)
+            .into_iter()
+            .map(|asset| asset.name)
+            .collect()
+    }
+
+    #[test]
+    fn states_side_by_side() {
+        let mut first = Fixture::new();
+        first.add_file(FixtureAsset::new("a1", png()));
+        let mut second = Fixture::new();
+        second
+            .add_file(FixtureAsset::new("b1", ogg()))
+            .add_file(FixtureAsset::new("b2", ogg()));
+        let states = [first.state(), second.state()];
+
+        // Config set in one isn't seen by the other, the scan window is read when listing
+        states[0].run(|| {
+            config::set_asset_alias("a1", "first");
+            config::set_config_value("extraction_history", true.into());
+            config::set_config_value("header_scan_window", 0.into());
+        });
+
+        // Listed at the same time, each only sees its own cache directory
+        thread::scope(|scope| {
+            for state in &states {
+                scope.spawn(|| state.refresh(Category::All).unwrap());
+            }
+        });
+        assert_eq!(names(&states[0]), ["a1"]);
+        assert_eq!(names(&states[1]), ["b1", "b2"]);
+
+        assert_eq!(states[0].run(|| config::get_asset_alias("a1")), "first");
+        assert_eq!(states[1].run(|| config::get_asset_alias("a1")), "a1");
+        assert_eq!(states[0].scan_window(), 0);
+        assert_eq!(states[1].scan_window(), logic::DEFAULT_SCAN_WINDOW);
+
+        // Nor is its history
+        let overrides = ExtractOverrides {
+            refresh: Some(false),
+            ..Default::default()
+        };
+        for (state, fixture) in states.iter().zip([&first, &second]) {
+            state
+                .extract_dir(
+                    fixture.output_directory("side-by-side"),
+                    Category::All,
+                    overrides.clone(),
+                )
+                .unwrap();
+        }
+        assert_eq!(states[0].run(history::count), 1);
+        assert_eq!(states[1].run(history::count), 0);
+
+        // Nor the status of its filter
+        states[1].run(|| {
+            logic::filter_file_list(&FilterQuery {
+                text: "(".to_owned(),
+                regex: true,
+                ..Default::default()
+            })
+        });
+        assert!(!states[0].filter_error_shown.load(Ordering::Relaxed));
+        assert!(states[1].filter_error_shown.load(Ordering::Relaxed));
+    }
+}
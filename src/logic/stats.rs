@@ -0,0 +1,136 @@
+// How many assets the cache has of each category and how much space they take. Counting means
+// listing every category, the GUI does it with a state of its own so the list shown isn't replaced.
+use serde::Serialize;
+use std::{
+    sync::{Arc, LazyLock, Mutex},
+    thread,
+    time::SystemTime,
+};
+
+use strum::IntoEnumIterator;
+
+use crate::logic::{self, sql_database, state::ExtractorState, AssetInfo, Category};
+
+static STATISTICS: LazyLock<Mutex<Option<CacheStats>>> = LazyLock::new(|| Mutex::new(None));
+static RUNNING: LazyLock<Mutex<Option<Arc<ExtractorState>>>> = LazyLock::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GroupStats {
+    pub count: usize,
+    pub bytes: u64,
+    pub oldest: Option<SystemTime>, // Assets without a time aren't counted for these
+    pub newest: Option<SystemTime>,
+}
+
+impl GroupStats {
+    fn add(&mut self, asset: &AssetInfo) {
+        self.count += 1;
+        self.bytes += asset.size;
+        if let Some(last_modified) = asset.last_modified {
+            self.oldest = Some(
+                self.oldest
+                    .map_or(last_modified, |oldest| oldest.min(last_modified)),
+            );
+            self.newest = Some(
+                self.newest
+                    .map_or(last_modified, |newest| newest.max(last_modified)),
+            );
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    pub categories: Vec<(Category, GroupStats)>, // Only categories that have assets
+    pub total: GroupStats,
+    pub database: bool,    // Only the cache directory was counted without one
+    pub duplicates: usize, // In both the database and the cache directory, counted once
+    pub stopped: bool,     // Stopped before everything was counted
+}
+
+// Lists the category, or every category if None, which replaces the file list
+pub fn compute_statistics(category: Option<Category>) -> CacheStats {
+    // Music is only found by listing the music folder
+    let sources = match category {
+        Some(category) => vec![category],
+        None => vec![Category::All, Category::Music],
+    };
+
+    let mut stats = CacheStats {
+        database: sql_database::connect(),
+        ..Default::default()
+    };
+    let mut groups: Vec<GroupStats> = Category::iter().map(|_| GroupStats::default()).collect();
+    for source in sources {
+        if logic::get_stop_list_running() {
+            stats.stopped = true;
+            break;
+        }
+        match logic::refresh(source, false, true) {
+            Some(summary) if !summary.stopped => (),
+            _ => {
+                stats.stopped = true;
+                break;
+            }
+        }
+
+        for asset in logic::get_file_list() {
+            // Not a real asset
+            if !asset.from_file && !asset.from_sql {
+                continue;
+            }
+            if asset.in_both_sources() {
+                stats.duplicates += 1;
+            }
+            if let Some(i) = Category::iter().position(|category| category == asset.category) {
+                groups[i].add(&asset);
+            }
+            stats.total.add(&asset);
+        }
+    }
+
+    stats.categories = Category::iter()
+        .zip(groups)
+        .filter(|(_, group)| group.count > 0)
+        .collect();
+    stats
+}
+
+// Counts every category in the background, see get_statistics()
+pub fn run() {
+    let mut running = RUNNING.lock().unwrap();
+    if running.is_some() {
+        return;
+    }
+
+    let state = logic::state().detached();
+    *running = Some(state.clone());
+    thread::spawn(move || {
+        let stats = state.run(|| compute_statistics(None));
+        log_info!(
+            "Counted {} assets ({} bytes){}",
+            stats.total.count,
+            stats.total.bytes,
+            if stats.stopped { ", stopped" } else { "" }
+        );
+        *STATISTICS.lock().unwrap() = Some(stats);
+        *RUNNING.lock().unwrap() = None;
+    });
+}
+
+// The statistics are marked as stopped, a category that was being listed isn't counted
+pub fn stop() {
+    let running = RUNNING.lock().unwrap().clone();
+    if let Some(state) = running {
+        state.run(|| logic::stop_listing(&state));
+    }
+}
+
+pub fn is_running() -> bool {
+    RUNNING.lock().unwrap().is_some()
+}
+
+// None until counted once
+pub fn get_statistics() -> Option<CacheStats> {
+    STATISTICS.lock().unwrap().clone()
+}
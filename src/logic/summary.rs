@@ -0,0 +1,316 @@
+// What a refresh or an extraction did, returned when the caller waits for the thread (CLI) and
+// kept for the GUI otherwise. Serializable so they can be written out as JSON.
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource};
+use serde::Serialize;
+use std::{
+    fmt,
+    sync::{Arc, LazyLock, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    locale,
+    logic::{self, error_code::ErrorCode, Category},
+};
+
+static LAST_REFRESH: LazyLock<Mutex<Option<RefreshSummary>>> = LazyLock::new(|| Mutex::new(None));
+static LAST_EXTRACT_FAILURES: LazyLock<Mutex<Vec<(String, String)>>> =
+    LazyLock::new(|| Mutex::new(Vec::new())); // Asset and why, for the GUI to list
+
+// Counts for one place assets are listed from
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SourceSummary {
+    pub listed: usize,
+    pub skipped: usize, // Not the category being listed
+    pub errors: usize,
+    pub unavailable: bool, // Couldn't be opened or read at all
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshSummary {
+    pub category: Category,
+    pub database: SourceSummary,
+    pub cache_directory: SourceSummary,
+    pub stopped: bool, // Another refresh took over before this one finished
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractFailure {
+    pub asset: String,
+    pub code: ErrorCode,
+    pub reason: String, // Written by the OS, so it can be in any language
+}
+
+// An image that was downscaled on export
+#[derive(Debug, Clone, Serialize)]
+pub struct ResizedImage {
+    pub asset: String,
+    pub original: (u32, u32),
+    pub output: (u32, u32),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExtractSummary {
+    pub category: Category,
+    pub written: usize,
+    pub skipped: usize,    // Evicted, extracted before or duplicates
+    pub duplicates: usize, // Skipped for having the same content as another asset, also counted in skipped
+    pub failed: Vec<ExtractFailure>,
+    pub bytes: u64,
+    pub resized: Vec<ResizedImage>,
+    pub not_converted: usize, // Written in their own format because converting to PNG or WAV failed
+    pub corrupt: Vec<String>, // Not written, they don't match the digest stored with them
+    pub unreachable: bool,    // Stopped early because the cache directory disconnected
+    pub cancelled: bool,      // Stopped early by the user or by closing RoExtract
+    pub total: usize,         // Assets there were to extract
+    pub duration_ms: u64,
+}
+
+// What re-classifying the listed assets changed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReclassifySummary {
+    pub category: Category, // Tab that was re-classified
+    pub checked: usize,
+    pub moved: usize, // Now in a different category, including ones no longer on this tab
+    pub removed: usize, // No longer match this tab at all
+    pub reformatted: usize, // Same category, detected as a different format
+    pub errors: usize,
+    pub stopped: bool, // A refresh took over before this finished
+    pub duration_ms: u64,
+}
+
+fn format_seconds(duration_ms: u64) -> String {
+    format!("{:.1}", Duration::from_millis(duration_ms).as_secs_f64())
+}
+
+impl RefreshSummary {
+    pub fn listed(&self) -> usize {
+        self.database.listed + self.cache_directory.listed
+    }
+
+    // The database or the cache directory couldn't be read, so assets may be missing
+    pub fn unavailable(&self) -> bool {
+        self.database.unavailable || self.cache_directory.unavailable
+    }
+
+    // Shown when hovering over the refresh button
+    pub fn message(&self, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+        let mut args = FluentArgs::new();
+        args.set("listed", self.listed());
+        args.set(
+            "skipped",
+            self.database.skipped + self.cache_directory.skipped,
+        );
+        args.set("errors", self.database.errors + self.cache_directory.errors);
+        args.set("duration", format_seconds(self.duration_ms));
+        locale::get_message(locale, "refresh-summary", Some(&args))
+    }
+}
+
+impl ReclassifySummary {
+    pub fn message(&self, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+        let mut args = FluentArgs::new();
+        args.set("checked", self.checked);
+        args.set("moved", self.moved);
+        args.set("reformatted", self.reformatted);
+        args.set("errors", self.errors);
+        args.set("duration", format_seconds(self.duration_ms));
+        locale::get_message(locale, "reclassify-summary", Some(&args))
+    }
+}
+
+impl ExtractSummary {
+    // Assets that were looked at before stopping
+    pub fn processed(&self) -> usize {
+        self.written + self.skipped + self.failed.len() + self.corrupt.len()
+    }
+
+    pub fn new(category: Category) -> Self {
+        ExtractSummary {
+            category,
+            written: 0,
+            skipped: 0,
+            duplicates: 0,
+            failed: Vec::new(),
+            bytes: 0,
+            resized: Vec::new(),
+            not_converted: 0,
+            corrupt: Vec::new(),
+            unreachable: false,
+            cancelled: false,
+            total: 0,
+            duration_ms: 0,
+        }
+    }
+
+    pub fn message(&self, locale: &FluentBundle<Arc<FluentResource>>) -> String {
+        let mut args = FluentArgs::new();
+        if self.cancelled {
+            args.set("item", self.processed());
+            args.set("total", self.total);
+            return locale::get_message(locale, "cancelled-after", Some(&args));
+        }
+        if self.total == 0 {
+            return locale::get_message(locale, "no-files-to-extract", None);
+        }
+
+        args.set("written", self.written);
+        args.set("skipped", self.skipped);
+        args.set("failed", self.failed.len());
+        args.set("size", logic::format_size(self.bytes));
+        args.set("duration", format_seconds(self.duration_ms));
+        let mut message = if self.failed.is_empty() {
+            locale::get_message(locale, "extract-summary", Some(&args))
+        } else {
+            // Said first so it isn't mistaken for everything being extracted
+            args.set("total", self.total);
+            locale::get_message(locale, "extract-summary-failed", Some(&args))
+        };
+        if self.duplicates > 0 {
+            let mut args = FluentArgs::new();
+            args.set("summary", message);
+            args.set("unique", self.written);
+            args.set("duplicates", self.duplicates);
+            message = locale::get_message(locale, "extract-summary-duplicates", Some(&args));
+        }
+        if self.not_converted > 0 {
+            let mut args = FluentArgs::new();
+            args.set("summary", message);
+            args.set("count", self.not_converted);
+            message = locale::get_message(locale, "extract-summary-not-converted", Some(&args));
+        }
+        if !self.corrupt.is_empty() {
+            let mut args = FluentArgs::new();
+            args.set("summary", message);
+            args.set("count", self.corrupt.len());
+            message = locale::get_message(locale, "extract-summary-corrupt", Some(&args));
+        }
+        message
+    }
+}
+
+impl fmt::Display for SourceSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} listed, {} skipped, {} errors{}",
+            self.listed,
+            self.skipped,
+            self.errors,
+            if self.unavailable {
+                ", unavailable"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+impl fmt::Display for RefreshSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Listed {} {} assets in {}s (database: {}; cache directory: {}){}",
+            self.listed(),
+            self.category,
+            format_seconds(self.duration_ms),
+            self.database,
+            self.cache_directory,
+            if self.stopped { ", stopped early" } else { "" }
+        )
+    }
+}
+
+impl fmt::Display for ReclassifySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Re-classified {} {} assets in {}s, {} moved ({} off this tab), {} changed format, {} errors{}",
+            self.checked,
+            self.category,
+            format_seconds(self.duration_ms),
+            self.moved,
+            self.removed,
+            self.reformatted,
+            self.errors,
+            if self.stopped { ", stopped early" } else { "" }
+        )
+    }
+}
+
+impl fmt::Display for ExtractSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Extracted {} {} assets ({} bytes) in {}s, {} skipped, {} failed",
+            self.written,
+            self.category,
+            self.bytes,
+            format_seconds(self.duration_ms),
+            self.skipped,
+            self.failed.len()
+        )?;
+        if self.duplicates > 0 {
+            write!(
+                f,
+                ", {} unique, {} duplicates skipped",
+                self.written, self.duplicates
+            )?;
+        }
+        if !self.resized.is_empty() {
+            write!(f, ", {} images resized", self.resized.len())?;
+        }
+        if self.not_converted > 0 {
+            write!(f, ", {} couldn't be converted", self.not_converted)?;
+        }
+        if !self.corrupt.is_empty() {
+            write!(f, ", {} corrupt", self.corrupt.len())?;
+        }
+        if self.unreachable {
+            write!(f, ", the cache directory became unreachable")?;
+        }
+        if self.cancelled {
+            write!(
+                f,
+                ", cancelled after {} of {}",
+                self.processed(),
+                self.total
+            )?;
+        }
+        for failure in &self.failed {
+            write!(f, "\n  {}: {}", failure.asset, failure.reason)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn set_last_refresh(summary: RefreshSummary) {
+    *LAST_REFRESH.lock().unwrap() = Some(summary);
+}
+
+pub fn get_last_refresh() -> Option<RefreshSummary> {
+    LAST_REFRESH.lock().unwrap().clone()
+}
+
+pub fn set_last_extract_failures(summary: &ExtractSummary) {
+    *LAST_EXTRACT_FAILURES.lock().unwrap() = summary
+        .failed
+        .iter()
+        .map(|failure| {
+            (
+                failure.asset.clone(),
+                format!("{}: {}", failure.code, failure.reason),
+            )
+        })
+        .collect();
+}
+
+// Empty if the last extraction wrote everything it tried to
+pub fn get_last_extract_failures() -> Vec<(String, String)> {
+    LAST_EXTRACT_FAILURES.lock().unwrap().clone()
+}
+
+pub fn clear_last_extract_failures() {
+    LAST_EXTRACT_FAILURES.lock().unwrap().clear();
+}
@@ -0,0 +1,170 @@
+// Swap packs let users share a list of swaps/copies, e.g.
+// {
+//     "swaps": [
+//         { "asset_a": "<id>", "asset_b": "<id>", "operation": "copy", "category": "sounds", "hash_a": "<sha-256>" }
+//     ]
+// }
+// operation defaults to swap. category and the SHA-256 hashes of the extracted payloads are optional,
+// the hashes allow assets to be found by their content when the ids don't exist in this cache.
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+
+use crate::logic::{self, history, AssetInfo, Category};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    #[default]
+    Swap,
+    Copy,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SwapPackEntry {
+    pub asset_a: String,
+    pub asset_b: String,
+    #[serde(default)]
+    pub operation: Operation,
+    pub category: Option<Category>,
+    pub hash_a: Option<String>,
+    pub hash_b: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SwapPack {
+    pub swaps: Vec<SwapPackEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedSwap {
+    pub asset_a: AssetInfo,
+    pub asset_b: AssetInfo,
+    pub operation: Operation,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PreflightReport {
+    pub resolved: Vec<ResolvedSwap>,
+    pub missing: Vec<String>,
+    pub incompatible: Vec<String>,
+    pub matched_by_hash: usize,
+}
+
+// Hashes of every asset in the cache, only computed if an id can't be found. It's listed in a
+// detached state so the list shown, its tab and filter stay as they are.
+fn hash_cache() -> HashMap<String, AssetInfo> {
+    log_info!("Hashing cache to match swap pack assets by content");
+    let state = logic::state().detached();
+    state.run(|| {
+        logic::refresh(Category::All, false, true);
+
+        let mut hashes = HashMap::new();
+        for asset in logic::get_file_list() {
+            if !asset.from_file && !asset.from_sql {
+                continue; // Not a real asset
+            }
+            if let Ok(bytes) = logic::extract_asset_to_bytes(asset.clone()) {
+                hashes.insert(history::hash_bytes(&bytes), asset);
+            }
+        }
+        hashes
+    })
+}
+
+// Find the asset by id, falling back to the content hash. The bool is true if it was found by hash.
+fn resolve(
+    id: &str,
+    hash: Option<&str>,
+    category: Category,
+    hashes: &mut Option<HashMap<String, AssetInfo>>,
+) -> Option<(AssetInfo, bool)> {
+    let info = logic::create_asset_info(id, category);
+    if info.from_file || info.from_sql {
+        return Some((info, false));
+    }
+
+    let hash = hash?.to_lowercase();
+    hashes
+        .get_or_insert_with(hash_cache)
+        .get(&hash)
+        .map(|info| (info.clone(), true))
+}
+
+pub fn read_swap_pack(path: &Path) -> Result<SwapPack, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&bytes).map_err(|e| e.to_string())
+}
+
+// Check every swap in the pack without writing anything
+pub fn preflight(pack: &SwapPack) -> PreflightReport {
+    let mut report = PreflightReport::default();
+    let mut hashes = None;
+
+    for entry in &pack.swaps {
+        let category = entry.category.unwrap_or(Category::All);
+        let asset_a = resolve(
+            &entry.asset_a,
+            entry.hash_a.as_deref(),
+            category,
+            &mut hashes,
+        );
+        let asset_b = resolve(
+            &entry.asset_b,
+            entry.hash_b.as_deref(),
+            category,
+            &mut hashes,
+        );
+
+        let ((asset_a, hash_a), (asset_b, hash_b)) = match (asset_a, asset_b) {
+            (Some(a), Some(b)) => (a, b),
+            (a, b) => {
+                if a.is_none() {
+                    report.missing.push(entry.asset_a.clone());
+                }
+                if b.is_none() {
+                    report.missing.push(entry.asset_b.clone());
+                }
+                continue;
+            }
+        };
+
+        // Swapping assets of different types or sizes makes the client behave oddly
+        let payload_a = logic::PayloadInfo::from_asset(&asset_a);
+        let validation = logic::validate_write(&asset_b, &payload_a);
+        let declared_mismatch = match entry.category {
+            Some(Category::All) | Some(Category::Music) | None => false, // Not a detected category
+            Some(declared) => {
+                declared != payload_a.category
+                    || declared != logic::PayloadInfo::from_asset(&asset_b).category
+            }
+        };
+        if validation != logic::WriteValidation::Ok || declared_mismatch {
+            report
+                .incompatible
+                .push(format!("{} ↔ {}", entry.asset_a, entry.asset_b));
+            continue;
+        }
+
+        if hash_a || hash_b {
+            report.matched_by_hash += 1;
+        }
+        report.resolved.push(ResolvedSwap {
+            asset_a,
+            asset_b,
+            operation: entry.operation,
+        });
+    }
+
+    report
+}
+
+// Only called after the user confirms the preflight report, which already validated every swap
+pub fn apply(resolved: Vec<ResolvedSwap>) -> Result<(), logic::read_only::ReadOnlyMode> {
+    for swap in resolved {
+        match swap.operation {
+            Operation::Swap => logic::swap_assets(swap.asset_a, swap.asset_b, true)?,
+            Operation::Copy => logic::copy_assets(swap.asset_a, swap.asset_b, true)?,
+        };
+    }
+    Ok(())
+}
@@ -0,0 +1,60 @@
+// Stops two RoExtract processes deleting or extracting at the same time, e.g. the CLI clearing
+// the cache while the GUI extracts it. The OS releases the lock if the process crashes.
+use std::{
+    fs::{File, OpenOptions},
+    sync::{LazyLock, Mutex},
+};
+
+use crate::config;
+
+const LOCK_FILE: &str = "RoExtract-task.lock";
+
+static LOCK: LazyLock<Mutex<Option<File>>> = LazyLock::new(|| Mutex::new(None));
+
+// False if another process holds the lock
+pub fn acquire() -> bool {
+    let mut lock = LOCK.lock().unwrap();
+    if lock.is_some() {
+        return true; // Already held by this process
+    }
+
+    let path = config::get_config_directory().join(LOCK_FILE);
+    let file = match OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(e) => {
+            // Better to run than to never be able to run
+            log_warn!(
+                "Failed to open {}, running without it: {}",
+                path.display(),
+                e
+            );
+            return true;
+        }
+    };
+
+    match file.try_lock() {
+        Ok(()) => {
+            *lock = Some(file);
+            true
+        }
+        Err(std::fs::TryLockError::WouldBlock) => false,
+        Err(std::fs::TryLockError::Error(e)) => {
+            log_warn!(
+                "Failed to lock {}, running without it: {}",
+                path.display(),
+                e
+            );
+            true
+        }
+    }
+}
+
+pub fn release() {
+    // Closing the file unlocks it
+    LOCK.lock().unwrap().take();
+}
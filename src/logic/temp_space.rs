@@ -0,0 +1,139 @@
+// Checks before writing to the temp directory. On Linux it's often a RAM-backed tmpfs and filling
+// it can take the desktop down with it, so large files are written to a folder on disk instead.
+use std::{
+    env, fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{config, logic};
+
+const LARGE_FILE: u64 = 64 * 1024 * 1024; // Bytes, files this big go to the fallback directory
+const RESERVE: u64 = 256 * 1024 * 1024; // Left free for everything else using the temp directory
+const FALLBACK_FOLDER: &str = "RoExtract-temp"; // Inside the fallback, so cleaning up only removes our files
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TempSpaceExhausted {
+    pub needed: u64,
+}
+
+impl fmt::Display for TempSpaceExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Not enough space in the temp directory for {}",
+            logic::format_size(self.needed)
+        )
+    }
+}
+
+impl std::error::Error for TempSpaceExhausted {}
+
+impl From<TempSpaceExhausted> for std::io::Error {
+    fn from(error: TempSpaceExhausted) -> Self {
+        std::io::Error::new(std::io::ErrorKind::StorageFull, error)
+    }
+}
+
+// XDG cache directory, or %localappdata% on Windows
+fn cache_home() -> Option<PathBuf> {
+    if let Some(directory) = env::var_os("XDG_CACHE_HOME").filter(|directory| !directory.is_empty())
+    {
+        return Some(PathBuf::from(directory));
+    }
+    if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache"))
+    }
+}
+
+// Set with temp_fallback_directory in the config, the XDG cache directory is used otherwise
+pub fn get_fallback_directory() -> Option<PathBuf> {
+    let parent = match config::get_config_string("temp_fallback_directory") {
+        Some(directory) if !directory.is_empty() => PathBuf::from(directory),
+        _ => cache_home()?,
+    };
+    Some(parent.join(FALLBACK_FOLDER))
+}
+
+pub fn available_space(directory: &Path) -> Option<u64> {
+    fs4::available_space(directory).ok()
+}
+
+// Unknown free space is treated as enough, the write will fail on its own if it isn't
+fn has_room(directory: &Path, size: u64) -> bool {
+    available_space(directory).is_none_or(|available| available >= size.saturating_add(RESERVE))
+}
+
+// Where to write a temporary file of this size
+pub fn directory_for(size: u64) -> Result<PathBuf, TempSpaceExhausted> {
+    let temp_dir = logic::get_temp_dir();
+    if size < LARGE_FILE && has_room(&temp_dir, size) {
+        return Ok(temp_dir);
+    }
+
+    if let Some(fallback) = get_fallback_directory() {
+        match fs::create_dir_all(&fallback) {
+            Ok(()) if has_room(&fallback, size) => {
+                log_info!(
+                    "Writing {} to {} instead of the temp directory",
+                    logic::format_size(size),
+                    fallback.display()
+                );
+                return Ok(fallback);
+            }
+            Ok(()) => log_warn!("Not enough space in {} either", fallback.display()),
+            Err(e) => log_warn!(
+                "Failed to create temp fallback directory {}: {}",
+                fallback.display(),
+                e
+            ),
+        }
+    }
+
+    // Large but still fits, and there's nowhere better
+    if has_room(&temp_dir, size) {
+        return Ok(temp_dir);
+    }
+    log_error!(
+        "Not enough space in {} for {}",
+        temp_dir.display(),
+        logic::format_size(size)
+    );
+    Err(TempSpaceExhausted { needed: size })
+}
+
+fn directory_size(directory: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+// What RoExtract currently has in the temp directory and the fallback
+pub fn usage() -> u64 {
+    directory_size(&logic::get_temp_dir())
+        + get_fallback_directory()
+            .map(|fallback| directory_size(&fallback))
+            .unwrap_or(0)
+}
+
+pub fn clean_up() {
+    let Some(fallback) = get_fallback_directory() else {
+        return;
+    };
+    if !fallback.exists() {
+        return;
+    }
+    log_info!("Cleaning up {}", fallback.display());
+    if let Err(e) = fs::remove_dir_all(&fallback) {
+        log_error!("Failed to clean up directory: {}", e);
+    }
+}
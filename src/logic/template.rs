@@ -0,0 +1,426 @@
+// Output path templates for extraction, e.g. "{category}/{date:%Y-%m}/{alias}-{hash8}.{ext}"
+//
+// {id}             Asset id
+// {alias}          Alias, the id if there isn't one
+// {category}       Category of the asset, e.g. sounds
+// {ext}            Extension from the detected header, empty if none was detected
+// {hash}           SHA-256 of the extracted asset, {hash8} for the first 8 characters
+// {date}           Modified date, {date:%Y-%m} for a custom chrono format
+// {counter}        Counts up from 1 for every extracted asset, {counter:4} pads it to 4 digits
+// {thumbnail}      "headshot-156" for a thumbnail whose URL has the user or asset id, the id otherwise
+//
+// "/" in the template creates folders, "/" in placeholder values is replaced.
+use chrono::format::{Item, StrftimeItems};
+use std::{
+    path::{Component, Path, PathBuf},
+    time::SystemTime,
+};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Id,
+    Alias,
+    Category,
+    Extension,
+    Hash(Option<usize>), // Number of characters
+    Date(String),
+    Counter(usize), // Padding
+    Thumbnail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+// Everything a template can use for one asset
+pub struct TemplateValues<'a> {
+    pub id: &'a str,
+    pub alias: &'a str,
+    pub category: &'a str,
+    pub extension: &'a str,
+    pub hash: Option<&'a str>,
+    pub last_modified: Option<SystemTime>,
+    pub counter: usize,
+    pub thumbnail: Option<&'a str>, // See thumbnail::Thumbnail::name()
+}
+
+fn parse_placeholder(placeholder: &str, position: usize) -> Result<Segment, String> {
+    let (name, argument) = match placeholder.split_once(':') {
+        Some((name, argument)) => (name, Some(argument)),
+        None => (placeholder, None),
+    };
+
+    match (name, argument) {
+        ("id", None) => Ok(Segment::Id),
+        ("alias", None) => Ok(Segment::Alias),
+        ("category", None) => Ok(Segment::Category),
+        ("ext", None) => Ok(Segment::Extension),
+        ("hash", None) => Ok(Segment::Hash(None)),
+        ("date", None) => Ok(Segment::Date(DEFAULT_DATE_FORMAT.to_string())),
+        ("date", Some(format)) => {
+            if format.is_empty()
+                || StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+            {
+                Err(format!(
+                    "Invalid date format '{format}' at position {position}"
+                ))
+            } else {
+                Ok(Segment::Date(format.to_string()))
+            }
+        }
+        ("counter", None) => Ok(Segment::Counter(0)),
+        ("thumbnail", None) => Ok(Segment::Thumbnail),
+        ("counter", Some(padding)) => match padding.parse::<usize>() {
+            Ok(padding) if padding <= 20 => Ok(Segment::Counter(padding)),
+            _ => Err(format!(
+                "Invalid counter padding '{padding}' at position {position}"
+            )),
+        },
+        _ => {
+            // {hash8} etc.
+            if let Some(length) = name.strip_prefix("hash").filter(|_| argument.is_none()) {
+                match length.parse::<usize>() {
+                    Ok(length) if (1..=64).contains(&length) => {
+                        return Ok(Segment::Hash(Some(length)));
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Invalid hash length '{length}' at position {position}, must be 1 to 64"
+                        ))
+                    }
+                }
+            }
+            Err(format!(
+                "Unknown placeholder '{{{placeholder}}}' at position {position}"
+            ))
+        }
+    }
+}
+
+// Placeholder values can't create folders or escape the destination
+pub fn sanitise(value: &str) -> String {
+    let sanitised: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    if sanitised == "." || sanitised == ".." {
+        "_".to_string()
+    } else {
+        sanitised
+    }
+}
+
+// After the whole file name, set_extension() would turn an alias like "boss_theme_v1.2" into
+// "boss_theme_v1.ogg". Nothing is added if it already has the extension
+pub fn append_extension(path: PathBuf, extension: &str) -> PathBuf {
+    if path
+        .extension()
+        .is_some_and(|existing| existing.eq_ignore_ascii_case(extension))
+    {
+        return path;
+    }
+    match path.file_name() {
+        Some(file_name) => {
+            let mut file_name = file_name.to_owned();
+            file_name.push(".");
+            file_name.push(extension);
+            path.with_file_name(file_name)
+        }
+        None => path,
+    }
+}
+
+impl Template {
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' => {
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+
+                    let mut placeholder = String::new();
+                    let mut closed = false;
+                    for (_, c) in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        if c == '{' {
+                            return Err(format!(
+                                "Unexpected '{{' inside placeholder at position {position}"
+                            ));
+                        }
+                        placeholder.push(c);
+                    }
+
+                    if !closed {
+                        return Err(format!("Unclosed '{{' at position {position}"));
+                    }
+                    segments.push(parse_placeholder(&placeholder, position)?);
+                }
+                '}' => return Err(format!("Unexpected '}}' at position {position}")),
+                c => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+
+        let template = Template { segments };
+
+        // The file name can't be empty
+        if template
+            .segments
+            .last()
+            .is_none_or(|segment| matches!(segment, Segment::Text(text) if text.ends_with('/') || text.ends_with('\\')))
+        {
+            return Err("The template must end with a file name".to_string());
+        }
+        if template.segments.iter().any(|segment| {
+            matches!(segment, Segment::Text(text) if text.split(['/', '\\']).any(|part| part == ".."))
+        }) {
+            return Err("The template can't contain '..'".to_string());
+        }
+        // Placeholders never render separators, so any placeholder text shows where the path starts
+        let sample: String = template
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.as_str(),
+                _ => "x",
+            })
+            .collect();
+        if !is_relative(&sample) {
+            return Err("The template must be a relative path".to_string());
+        }
+
+        Ok(template)
+    }
+
+    // Hashing every asset is slow, only do it if needed
+    pub fn uses_hash(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Hash(_)))
+    }
+
+    // The counter is given out as assets are extracted, so skipping one changes the others' paths
+    pub fn uses_counter(&self) -> bool {
+        self.segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Counter(_)))
+    }
+
+    // Path relative to the extraction destination
+    pub fn render(&self, values: &TemplateValues) -> PathBuf {
+        let mut rendered = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Id => rendered.push_str(&sanitise(values.id)),
+                Segment::Alias => rendered.push_str(&sanitise(values.alias)),
+                Segment::Category => rendered.push_str(&sanitise(values.category)),
+                Segment::Extension => rendered.push_str(&sanitise(values.extension)),
+                Segment::Hash(length) => {
+                    let hash = values.hash.unwrap_or_default();
+                    let length = length.unwrap_or(hash.len()).min(hash.len());
+                    rendered.push_str(&hash[..length]);
+                }
+                Segment::Date(format) => match values.last_modified {
+                    Some(last_modified) => {
+                        let date: chrono::DateTime<chrono::Local> = last_modified.into();
+                        rendered.push_str(&sanitise(&date.format(format).to_string()));
+                    }
+                    None => rendered.push_str("unknown"),
+                },
+                Segment::Counter(padding) => {
+                    rendered.push_str(&format!("{:0padding$}", values.counter))
+                }
+                Segment::Thumbnail => {
+                    rendered.push_str(&sanitise(values.thumbnail.unwrap_or(values.id)))
+                }
+            }
+        }
+
+        // "{id}.{ext}" with no extension detected
+        let rendered = rendered.trim_end_matches('.');
+        if is_relative(rendered) {
+            PathBuf::from(rendered)
+        } else {
+            // Never let a rendered path escape the destination
+            Path::new(rendered)
+                .components()
+                .filter(|component| matches!(component, Component::Normal(_)))
+                .collect()
+        }
+    }
+}
+
+// No root, drive letter, UNC prefix or parent directory, on any platform
+fn is_relative(path: &str) -> bool {
+    let mut chars = path.chars();
+    let drive = matches!((chars.next(), chars.next()), (Some(letter), Some(':')) if letter.is_ascii_alphabetic());
+    !drive
+        && !path.starts_with(['/', '\\'])
+        && path.split(['/', '\\']).all(|part| part != "..")
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::{
+        self,
+        extract_options::OverwritePolicy,
+        fixtures::{self, Fixture, FixtureAsset},
+        Category,
+    };
+
+    fn values<'a>(id: &'a str) -> TemplateValues<'a> {
+        TemplateValues {
+            id,
+            alias: id,
+            category: "images",
+            extension: "png",
+            hash: None,
+            last_modified: None,
+            counter: 1,
+            thumbnail: None,
+        }
+    }
+
+    #[test]
+    fn rejects_paths_outside_destination() {
+        for template in [
+            "/{id}.{ext}",
+            "\\{id}.{ext}",
+            "\\\\server\\share\\{id}",
+            "C:{id}",
+            "c:/{id}.{ext}",
+            "{category}/../{id}",
+            "../{id}",
+        ] {
+            assert!(Template::parse(template).is_err(), "{template}");
+        }
+    }
+
+    #[test]
+    fn renders_relative_paths() {
+        let template = Template::parse("{category}/{id}.{ext}").unwrap();
+        assert_eq!(
+            template.render(&values("abc")),
+            PathBuf::from("images/abc.png")
+        );
+
+        // Separators and drive letters in values are replaced
+        let template = Template::parse("{id}").unwrap();
+        for id in ["/etc/passwd", "C:\\Windows", "..", "\\\\server\\share"] {
+            let rendered = template.render(&values(id));
+            assert!(!rendered.has_root() && !rendered.is_absolute(), "{id}");
+            assert!(rendered
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))));
+        }
+    }
+
+    #[test]
+    fn thumbnail_falls_back_to_the_id() {
+        let template = Template::parse("{thumbnail}.{ext}").unwrap();
+        assert_eq!(template.render(&values("abc")), PathBuf::from("abc.png"));
+        let thumbnail = TemplateValues {
+            thumbnail: Some("headshot-156"),
+            ..values("abc")
+        };
+        assert_eq!(
+            template.render(&thumbnail),
+            PathBuf::from("headshot-156.png")
+        );
+    }
+
+    #[test]
+    fn aliases_are_sanitised() {
+        let template = Template::parse("{alias}.{ext}").unwrap();
+        for (alias, expected) in [
+            ("boss_theme_v1.2", "boss_theme_v1.2.png"),
+            ("../../etc/passwd", ".._.._etc_passwd.png"),
+            ("C:\\Windows\\win.ini", "C__Windows_win.ini.png"),
+            ("what?<is>*this|", "what__is__this_.png"),
+            ("tab\there", "tab_here.png"),
+        ] {
+            let rendered = template.render(&TemplateValues {
+                alias,
+                ..values("abc")
+            });
+            assert_eq!(rendered, PathBuf::from(expected), "{alias}");
+        }
+        assert_eq!(sanitise(".."), "_");
+        assert_eq!(sanitise("."), "_");
+    }
+
+    #[test]
+    fn extension_goes_after_the_whole_name() {
+        for (path, expected) in [
+            ("boss_theme_v1.2", "boss_theme_v1.2.ogg"),
+            ("sounds/boss", "sounds/boss.ogg"),
+            ("already.ogg", "already.ogg"),
+            ("ALREADY.OGG", "ALREADY.OGG"),
+            ("theme.mp3", "theme.mp3.ogg"),
+        ] {
+            assert_eq!(
+                append_extension(PathBuf::from(path), "ogg"),
+                PathBuf::from(expected)
+            );
+        }
+    }
+
+    #[test]
+    fn extension_is_only_added_when_asked() {
+        let mut fixture = Fixture::new();
+        fixture.add_file(FixtureAsset::new("e1", fixtures::ogg()));
+        let state = fixture.state();
+        state.refresh(Category::Sounds).unwrap();
+        let asset = state.run(logic::get_file_list)[0].clone();
+        let destination = fixture
+            .output_directory("extension")
+            .join("boss_theme_v1.2");
+
+        let extract = |add_extension| {
+            state.run(|| {
+                logic::extract_to_file(
+                    asset.clone(),
+                    destination.clone(),
+                    add_extension,
+                    OverwritePolicy::Overwrite,
+                    false,
+                    false,
+                    false,
+                )
+                .unwrap()
+                .unwrap()
+            })
+        };
+        // Written under the name it was given, dots and all
+        assert_eq!(extract(false), destination);
+        assert_eq!(
+            extract(true),
+            destination.with_file_name("boss_theme_v1.2.ogg")
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// Avatar and asset thumbnails, told apart from other images by the URL they were downloaded from,
+// which is kept in the HTTP preamble. Nothing is fetched, only these endpoints are recognised:
+//
+// thumbnails.roblox.com/v1/users/avatar-headshot?userIds=1     Headshot of user 1, also avatar and avatar-bust
+// thumbnails.roblox.com/v1/assets?assetIds=1                   Thumbnail of asset 1
+// www.roblox.com/headshot-thumbnail/image?userId=1             Also avatar-, bust- and asset-thumbnail
+// www.roblox.com/Thumbs/Avatar.ashx?userId=1                   Also HeadShot.ashx and Asset.ashx
+// rbxthumb://type=AvatarHeadShot&id=1&w=150&h=150              How games ask for them
+// tr.rbxcdn.com/<hash>/150/150/AvatarHeadshot/Png              Served from the CDN, without the id
+use serde::Serialize;
+use strum_macros::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ThumbnailKind {
+    Avatar,
+    Headshot,
+    Bust,
+    Asset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Thumbnail {
+    pub kind: ThumbnailKind,
+    pub id: Option<u64>, // The user's or the asset's, None if the URL doesn't have it
+}
+
+impl Thumbnail {
+    // "headshot-1", what the thumbnail naming mode calls it. None without an id
+    pub fn name(&self) -> Option<String> {
+        self.id.map(|id| format!("{}-{id}", self.kind))
+    }
+}
+
+impl ThumbnailKind {
+    // From a path segment or an rbxthumb type, ignoring case
+    fn from_name(name: &str) -> Option<ThumbnailKind> {
+        match name.to_ascii_lowercase().as_str() {
+            "avatar" | "avatar-thumbnail" | "avatar.ashx" => Some(ThumbnailKind::Avatar),
+            "avatarheadshot" | "avatar-headshot" | "headshot-thumbnail" | "headshot.ashx" => {
+                Some(ThumbnailKind::Headshot)
+            }
+            "avatarbust" | "avatar-bust" | "bust-thumbnail" => Some(ThumbnailKind::Bust),
+            "asset" | "assets" | "asset-thumbnail" | "asset.ashx" => Some(ThumbnailKind::Asset),
+            _ => None,
+        }
+    }
+}
+
+// The first id in a query parameter such as userIds=1,2, ignoring the parameter's case
+fn query_id(query: &str, names: &[&str]) -> Option<u64> {
+    query.split('&').find_map(|parameter| {
+        let (name, value) = parameter.split_once('=')?;
+        names
+            .iter()
+            .any(|wanted| name.eq_ignore_ascii_case(wanted))
+            .then(|| value.split([',', '%']).next()?.parse().ok())
+            .flatten()
+    })
+}
+
+pub fn parse(url: &str) -> Option<Thumbnail> {
+    let (scheme, rest) = url.split_once("://")?;
+    if scheme.eq_ignore_ascii_case("rbxthumb") {
+        let kind = rest
+            .split('&')
+            .find_map(|parameter| parameter.strip_prefix("type="))
+            .and_then(ThumbnailKind::from_name)?;
+        return Some(Thumbnail {
+            kind,
+            id: query_id(rest, &["id"]),
+        });
+    }
+
+    let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let (host, path) = address.split_once('/').unwrap_or((address, ""));
+    let host = host.to_ascii_lowercase();
+    let segments: Vec<&str> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    match host.as_str() {
+        "thumbnails.roblox.com" => {
+            // v1/users/avatar-headshot or v1/assets
+            let kind = ThumbnailKind::from_name(segments.last()?)?;
+            Some(Thumbnail {
+                kind,
+                id: query_id(query, &["userIds", "assetIds"]),
+            })
+        }
+        "www.roblox.com" | "roblox.com" => {
+            // headshot-thumbnail/image or Thumbs/Avatar.ashx
+            let kind = match segments.as_slice() {
+                [endpoint, image] if image.eq_ignore_ascii_case("image") => {
+                    ThumbnailKind::from_name(endpoint)
+                }
+                [thumbs, handler] if thumbs.eq_ignore_ascii_case("thumbs") => {
+                    ThumbnailKind::from_name(handler)
+                }
+                _ => None,
+            }?;
+            Some(Thumbnail {
+                kind,
+                id: query_id(query, &["userId", "assetId", "id"]),
+            })
+        }
+        "tr.rbxcdn.com" => {
+            // The kind comes after the width and height
+            let kind = segments
+                .windows(3)
+                .find(|window| window[0].parse::<u32>().is_ok() && window[1].parse::<u32>().is_ok())
+                .and_then(|window| ThumbnailKind::from_name(window[2]))?;
+            Some(Thumbnail { kind, id: None })
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thumbnail(kind: ThumbnailKind, id: Option<u64>) -> Option<Thumbnail> {
+        Some(Thumbnail { kind, id })
+    }
+
+    #[test]
+    fn known_endpoints() {
+        use ThumbnailKind::*;
+        for (url, expected) in [
+            (
+                "https://thumbnails.roblox.com/v1/users/avatar-headshot?userIds=156&size=150x150&format=Png",
+                thumbnail(Headshot, Some(156)),
+            ),
+            (
+                "https://thumbnails.roblox.com/v1/users/avatar?userIds=1,2",
+                thumbnail(Avatar, Some(1)),
+            ),
+            (
+                "https://thumbnails.roblox.com/v1/users/avatar-bust?userIds=7",
+                thumbnail(Bust, Some(7)),
+            ),
+            (
+                "https://thumbnails.roblox.com/v1/assets?assetIds=1818&size=420x420",
+                thumbnail(Asset, Some(1818)),
+            ),
+            (
+                "https://www.roblox.com/headshot-thumbnail/image?userId=261&width=420&height=420&format=png",
+                thumbnail(Headshot, Some(261)),
+            ),
+            (
+                "https://www.roblox.com/asset-thumbnail/image?assetId=1818&width=420&height=420",
+                thumbnail(Asset, Some(1818)),
+            ),
+            (
+                "http://www.roblox.com/Thumbs/Avatar.ashx?x=100&y=100&userId=3",
+                thumbnail(Avatar, Some(3)),
+            ),
+            (
+                "rbxthumb://type=AvatarHeadShot&id=156&w=150&h=150",
+                thumbnail(Headshot, Some(156)),
+            ),
+            (
+                "https://tr.rbxcdn.com/30DAY-AvatarHeadshot-3109662-Png/150/150/AvatarHeadshot/Png/noFilter",
+                thumbnail(Headshot, None),
+            ),
+        ] {
+            assert_eq!(parse(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn other_urls_are_not_thumbnails() {
+        for url in [
+            "https://c0.rbxcdn.com/a1b2c3",
+            "https://tr.rbxcdn.com/a1b2c3/420/420/Image/Png",
+            "https://thumbnails.roblox.com/v1/games/icons?universeIds=1",
+            "https://www.roblox.com/catalog/1818",
+            "rbxthumb://type=GameIcon&id=1&w=150&h=150",
+            "not a url",
+        ] {
+            assert_eq!(parse(url), None, "{url}");
+        }
+    }
+
+    #[test]
+    fn name_needs_an_id() {
+        assert_eq!(
+            parse("rbxthumb://type=Avatar&id=42&w=420&h=420")
+                .and_then(|thumbnail| thumbnail.name()),
+            Some("avatar-42".to_owned())
+        );
+        assert_eq!(
+            parse("https://tr.rbxcdn.com/hash/150/150/Avatar/Png")
+                .and_then(|thumbnail| thumbnail.name()),
+            None
+        );
+    }
+}
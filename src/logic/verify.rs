@@ -0,0 +1,153 @@
+// Checking a payload against the digest Roblox's HTTP cache keeps with it. The preamble holds the
+// response headers, which carry an MD5 of the body as Content-MD5 or, from the CDN, as a strong ETag.
+// Extraction and the verify subcommand both use check(), so they agree on what's corrupt.
+use base64::{prelude::BASE64_STANDARD, Engine};
+use md5::{Digest, Md5};
+
+use crate::logic::{payload_start, ROBLOX_HTTP_MAGIC};
+
+const MD5_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Verification {
+    Verified,
+    Mismatch,
+    #[default]
+    Unverifiable, // No preamble, or no digest in it
+}
+
+// What the response headers say about the body
+#[derive(Debug, Default)]
+struct Envelope {
+    digest: Option<[u8; MD5_LENGTH]>,
+    content_length: Option<usize>,
+}
+
+// The MD5 in an ETag, weak ones and those of multipart uploads aren't a digest of the body
+fn etag_digest(value: &str) -> Option<[u8; MD5_LENGTH]> {
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    hex::decode(value).ok()?.try_into().ok()
+}
+
+fn content_md5(value: &str) -> Option<[u8; MD5_LENGTH]> {
+    BASE64_STANDARD.decode(value).ok()?.try_into().ok()
+}
+
+// The headers between the status line and the blank line ending the preamble
+fn parse_envelope(preamble: &[u8]) -> Envelope {
+    let mut envelope = Envelope::default();
+    let mut etag = None;
+    // The first line is the magic and the URL, then the status line
+    for line in String::from_utf8_lossy(preamble).split("\r\n").skip(2) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-md5" => envelope.digest = content_md5(value),
+            "etag" => etag = etag_digest(value),
+            "content-length" => envelope.content_length = value.parse().ok(),
+            _ => (),
+        }
+    }
+    // Content-MD5 is only ever a digest, so it's believed over the ETag
+    envelope.digest = envelope.digest.or(etag);
+    envelope
+}
+
+// Whether the payload after the preamble matches its digest. The bytes are the asset as it's read,
+// decompressed but with nothing cut off. Anything after Content-Length bytes is Roblox's metadata
+pub fn check(bytes: &[u8]) -> Verification {
+    if !bytes.starts_with(ROBLOX_HTTP_MAGIC) {
+        return Verification::Unverifiable;
+    }
+    let Some(start) = payload_start(bytes) else {
+        return Verification::Unverifiable;
+    };
+    let envelope = parse_envelope(&bytes[..start]);
+    let Some(digest) = envelope.digest else {
+        return Verification::Unverifiable;
+    };
+
+    let body = &bytes[start..];
+    let body = match envelope.content_length {
+        Some(length) if length <= body.len() => &body[..length],
+        _ => body, // Cut short, so it won't match
+    };
+    if Md5::digest(body)[..] == digest {
+        Verification::Verified
+    } else {
+        Verification::Mismatch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::fixtures;
+
+    fn cached(payload: &[u8], trailer: &[u8]) -> Vec<u8> {
+        [
+            &fixtures::http_preamble_with_digest("https://c0.rbxcdn.com/verify", payload)[..],
+            payload,
+            trailer,
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn matching_payload_is_verified() {
+        let png = fixtures::png();
+        assert_eq!(check(&cached(&png, b"")), Verification::Verified);
+        // Roblox's metadata after the body isn't part of it
+        assert_eq!(
+            check(&cached(&png, b"\x00roblox-metadata\x00")),
+            Verification::Verified
+        );
+    }
+
+    #[test]
+    fn damaged_payload_is_a_mismatch() {
+        let png = fixtures::png();
+        let mut bytes = cached(&png, b"");
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert_eq!(check(&bytes), Verification::Mismatch);
+
+        // Shorter than Content-Length says
+        let bytes = cached(&png, b"");
+        assert_eq!(check(&bytes[..bytes.len() - 4]), Verification::Mismatch);
+    }
+
+    #[test]
+    fn etag_is_used_without_content_md5() {
+        let png = fixtures::png();
+        let etag = hex::encode(Md5::digest(&png));
+        let preamble = format!(
+            "RBXH\x00\x00\x00https://c0.rbxcdn.com/etag\r\nHTTP/1.1 200 OK\r\nETag: \"{etag}\"\r\n\r\n"
+        );
+        let bytes = [preamble.as_bytes(), &png].concat();
+        assert_eq!(check(&bytes), Verification::Verified);
+
+        // Weak and multipart ETags aren't digests
+        for etag in [format!("W/\"{etag}\""), format!("\"{etag}-2\"")] {
+            let preamble = format!(
+                "RBXH\x00\x00\x00https://c0.rbxcdn.com/etag\r\nHTTP/1.1 200 OK\r\nETag: {etag}\r\n\r\n"
+            );
+            let bytes = [preamble.as_bytes(), &png].concat();
+            assert_eq!(check(&bytes), Verification::Unverifiable, "{etag}");
+        }
+    }
+
+    #[test]
+    fn nothing_to_check_against() {
+        let png = fixtures::png();
+        assert_eq!(check(&png), Verification::Unverifiable);
+        let bytes = [
+            &fixtures::http_preamble("https://c0.rbxcdn.com/none")[..],
+            &png,
+        ]
+        .concat();
+        assert_eq!(check(&bytes), Verification::Unverifiable);
+    }
+}
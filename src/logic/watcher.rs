@@ -0,0 +1,224 @@
+// Refreshes the category being listed when the cache changes, e.g. while a game is streaming assets
+// in. The cache directory and the database are polled for a new modification time, and the refresh
+// waits until they've stopped changing for a moment. Listing only reads what's new, see scan_index.
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, LazyLock, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::logic::{
+    self, cache_directory,
+    extract_options::{ExtractOverrides, Naming},
+    sql_database,
+    state::ExtractorState,
+    Category,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+const DEBOUNCE: Duration = Duration::from_secs(2); // Without a change before refreshing
+const MAX_WAIT: Duration = Duration::from_secs(10); // A game streams assets in constantly, don't wait for it to stop
+
+// Dropping the sender stops the watcher
+type Watcher = (mpsc::Sender<()>, thread::JoinHandle<()>);
+
+static WATCHER: LazyLock<Mutex<Option<Watcher>>> = LazyLock::new(|| Mutex::new(None));
+static LAST_REFRESH: LazyLock<Mutex<Option<Instant>>> = LazyLock::new(|| Mutex::new(None));
+
+// Auto-extract lists and extracts with a state of its own, so the list shown isn't replaced
+type AutoExtract = (Watcher, Arc<ExtractorState>);
+
+static AUTO_EXTRACT: LazyLock<Mutex<Option<AutoExtract>>> = LazyLock::new(|| Mutex::new(None));
+static AUTO_EXTRACTED: LazyLock<Mutex<Option<usize>>> = LazyLock::new(|| Mutex::new(None)); // Written so far, None when stopped
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+// Changes when an asset is added to or removed from the cache
+fn snapshot(category: logic::Category) -> Vec<Option<SystemTime>> {
+    let mut snapshot = vec![modified(&cache_directory::get_category_cache_directory(
+        category,
+    ))];
+    if category != logic::Category::Music {
+        // Writes usually go to the WAL first
+        if let Some(path) = sql_database::get_database_path() {
+            let mut wal = path.clone().into_os_string();
+            wal.push("-wal");
+            snapshot.push(modified(&path));
+            snapshot.push(modified(Path::new(&wal)));
+        }
+    }
+    snapshot
+}
+
+// Calls changed() once the cache has changed, with the category it was watched for
+fn watch(
+    receiver: &mpsc::Receiver<()>,
+    category: impl Fn() -> logic::Category,
+    mut changed: impl FnMut(logic::Category),
+) {
+    let mut last: Option<(logic::Category, Vec<Option<SystemTime>>)> = None;
+    let mut pending: Option<(Instant, Instant)> = None; // First and latest change since the last call
+
+    while let Err(mpsc::RecvTimeoutError::Timeout) = receiver.recv_timeout(POLL_INTERVAL) {
+        // The database stays locked while listing, and extracting is left to finish first
+        if logic::get_list_task_running() || logic::get_task_running() {
+            continue;
+        }
+
+        let category = category();
+        let snapshot = snapshot(category);
+        match &last {
+            Some((last_category, last_snapshot)) if *last_category == category => {
+                if *last_snapshot != snapshot {
+                    let now = Instant::now();
+                    pending = Some((pending.map_or(now, |(first, _)| first), now));
+                }
+            }
+            _ => pending = None, // A tab was opened, it has just been listed
+        }
+        last = Some((category, snapshot));
+
+        if pending.is_some_and(|(first, latest)| {
+            latest.elapsed() >= DEBOUNCE || first.elapsed() >= MAX_WAIT
+        }) {
+            pending = None;
+            changed(category);
+        }
+    }
+}
+
+pub fn is_enabled() -> bool {
+    WATCHER.lock().unwrap().is_some()
+}
+
+pub fn set_enabled(enabled: bool) {
+    let mut watcher = WATCHER.lock().unwrap();
+    if enabled && watcher.is_none() {
+        log_info!("Watching the cache for changes");
+        let (sender, receiver) = mpsc::channel();
+        let handle = logic::state::spawn(move || {
+            watch(&receiver, logic::get_list_category, |category| {
+                log_info!("Cache changed, refreshing {}", category);
+                *LAST_REFRESH.lock().unwrap() = Some(Instant::now());
+                logic::refresh(category, false, false);
+            })
+        });
+        *watcher = Some((sender, handle));
+    } else if !enabled {
+        if let Some((sender, handle)) = watcher.take() {
+            drop(sender);
+            let _ = handle.join();
+            *LAST_REFRESH.lock().unwrap() = None;
+            log_info!("Stopped watching the cache");
+        }
+    }
+}
+
+// How long ago the watcher last refreshed, None if it hasn't
+pub fn since_last_refresh() -> Option<Duration> {
+    LAST_REFRESH
+        .lock()
+        .unwrap()
+        .map(|instant| instant.elapsed())
+}
+
+// The assets listed in category that have content to extract
+fn list(category: Category) -> Vec<logic::AssetInfo> {
+    logic::refresh(category, false, true);
+    let mut file_list = logic::get_file_list();
+    file_list.retain(|asset| !asset.evicted);
+    file_list
+}
+
+fn auto_extract(
+    receiver: &mpsc::Receiver<()>,
+    category: Category,
+    destination: PathBuf,
+    use_alias: bool,
+) {
+    let overrides = ExtractOverrides {
+        naming: Some(if use_alias { Naming::Alias } else { Naming::Id }),
+        refresh: Some(false),
+        ..Default::default()
+    };
+
+    // Only assets that appear from now on are extracted, and each of them once
+    let mut known: HashSet<String> = list(category).into_iter().map(|asset| asset.name).collect();
+    log_info!(
+        "Auto-extracting new {} assets to {}, {} already in the cache",
+        category,
+        destination.display(),
+        known.len()
+    );
+
+    watch(
+        receiver,
+        || category,
+        |category| {
+            let mut assets = list(category);
+            assets.retain(|asset| known.insert(asset.name.clone()));
+            // Stopped while listing
+            if assets.is_empty() || receiver.try_recv() != Err(mpsc::TryRecvError::Empty) {
+                return;
+            }
+
+            log_info!("Auto-extracting {} new assets", assets.len());
+            let summary = logic::extract_assets(
+                destination.clone(),
+                category,
+                assets,
+                true,
+                overrides.clone(),
+            );
+            if let Some(summary) = summary {
+                if let Some(count) = &mut *AUTO_EXTRACTED.lock().unwrap() {
+                    *count += summary.written;
+                }
+            }
+        },
+    );
+}
+
+// Extracts assets of category to destination as they appear in the cache, until stopped
+pub fn start_auto_extract(category: Category, destination: PathBuf, use_alias: bool) {
+    let mut auto_extract_watcher = AUTO_EXTRACT.lock().unwrap();
+    if auto_extract_watcher.is_some() {
+        log_warn!("Auto-extract is already running");
+        return;
+    }
+
+    let state = logic::state().detached();
+    let (sender, receiver) = mpsc::channel();
+    *AUTO_EXTRACTED.lock().unwrap() = Some(0);
+    let handle = {
+        let state = state.clone();
+        thread::spawn(move || {
+            state.run(|| auto_extract(&receiver, category, destination, use_alias))
+        })
+    };
+    *auto_extract_watcher = Some(((sender, handle), state));
+}
+
+// Waits for the listing or extraction it's doing to stop
+pub fn stop_auto_extract() {
+    if let Some(((sender, handle), state)) = AUTO_EXTRACT.lock().unwrap().take() {
+        drop(sender);
+        state.run(|| {
+            logic::stop_listing(&state);
+            logic::cancel_task();
+        });
+        let _ = handle.join();
+        let count = AUTO_EXTRACTED.lock().unwrap().take().unwrap_or_default();
+        log_info!("Stopped auto-extracting, {} assets were extracted", count);
+    }
+}
+
+// How many assets have been extracted, None if auto-extract isn't running
+pub fn get_auto_extracted() -> Option<usize> {
+    *AUTO_EXTRACTED.lock().unwrap()
+}
@@ -0,0 +1,248 @@
+// Converting extracted OGG audio to 16-bit PCM WAV for editors and engines that don't read Vorbis.
+// Only mono and stereo are converted, other channel layouts need a WAV header that not every tool
+// reads, so they're left as OGG like anything that fails to decode.
+// Silence can be trimmed and the volume normalised while converting, OGG written as it is isn't
+// re-encoded for this.
+use lewton::inside_ogg::OggStreamReader;
+use serde::Serialize;
+use std::io::Cursor;
+
+use crate::logic::{get_extension, Error, ExtractedAsset};
+
+const BITS_PER_SAMPLE: u16 = 16;
+
+// What to do to the samples, levels are in dBFS
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AudioProcessing {
+    pub trim_silence: Option<f64>, // Anything quieter than this at either end is cut
+    pub normalize: Option<f64>,    // Peak level to scale the loudest sample to
+}
+
+// What was done, for the manifest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct AudioChanges {
+    pub trimmed_start_ms: u64,
+    pub trimmed_end_ms: u64,
+    pub gain_db: f64,
+}
+
+fn amplitude(db: f64) -> f64 {
+    10f64.powf(db / 20.0) * i16::MAX as f64
+}
+
+// Whole frames so the channels stay in step. Audio that's silent all the way through is kept
+fn trim_silence(samples: &mut Vec<i16>, channels: usize, threshold: f64) -> (usize, usize) {
+    let loud = |frame: &[i16]| {
+        frame
+            .iter()
+            .any(|sample| (*sample as f64).abs() >= threshold)
+    };
+    let frames: Vec<&[i16]> = samples.chunks(channels).collect();
+    let Some(first) = frames.iter().position(|frame| loud(frame)) else {
+        return (0, 0);
+    };
+    let last = frames
+        .iter()
+        .rposition(|frame| loud(frame))
+        .unwrap_or(first);
+    let trimmed = (first, frames.len() - last - 1);
+
+    samples.truncate((last + 1) * channels);
+    samples.drain(..first * channels);
+    trimmed
+}
+
+// Returns the gain in dB, 0 if nothing changed
+fn normalize(samples: &mut [i16], target: f64) -> f64 {
+    let peak = samples
+        .iter()
+        .map(|sample| (*sample as f64).abs())
+        .fold(0.0, f64::max);
+    if peak == 0.0 {
+        return 0.0;
+    }
+    let gain = amplitude(target) / peak;
+    let gain_db = 20.0 * gain.log10();
+    if gain_db.abs() < 0.01 {
+        return 0.0;
+    }
+    for sample in samples {
+        *sample = (*sample as f64 * gain)
+            .round()
+            .clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+    }
+    gain_db
+}
+
+fn process(
+    samples: &mut Vec<i16>,
+    channels: usize,
+    sample_rate: u32,
+    processing: AudioProcessing,
+) -> Option<AudioChanges> {
+    let mut changes = AudioChanges::default();
+    if let Some(threshold) = processing.trim_silence {
+        let to_ms = |frames: usize| frames as u64 * 1000 / sample_rate.max(1) as u64;
+        let (start, end) = trim_silence(samples, channels, amplitude(threshold));
+        changes.trimmed_start_ms = to_ms(start);
+        changes.trimmed_end_ms = to_ms(end);
+    }
+    if let Some(target) = processing.normalize {
+        changes.gain_db = (normalize(samples, target) * 100.0).round() / 100.0;
+    }
+    (changes != AudioChanges::default()).then_some(changes)
+}
+
+fn wav_header(channels: u16, sample_rate: u32, data_size: u32) -> Vec<u8> {
+    let block_align = channels * BITS_PER_SAMPLE / 8;
+    let mut header = Vec::with_capacity(44);
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&(36 + data_size).to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&16u32.to_le_bytes());
+    header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    header.extend_from_slice(&channels.to_le_bytes());
+    header.extend_from_slice(&sample_rate.to_le_bytes());
+    header.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+    header.extend_from_slice(&block_align.to_le_bytes());
+    header.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&data_size.to_le_bytes());
+    header
+}
+
+fn to_wav(
+    bytes: &[u8],
+    processing: AudioProcessing,
+) -> Result<(Vec<u8>, Option<AudioChanges>), Error> {
+    let decode_error = |e: lewton::VorbisError| Error::Decode(e.to_string());
+    let mut reader = OggStreamReader::new(Cursor::new(bytes)).map_err(decode_error)?;
+    let channels = reader.ident_hdr.audio_channels as u16;
+    if !(1..=2).contains(&channels) {
+        return Err(Error::Decode(format!(
+            "{channels} channels isn't supported"
+        )));
+    }
+
+    let mut samples = Vec::new();
+    // Interleaved, which is the order WAV stores them in
+    while let Some(packet) = reader.read_dec_packet_itl().map_err(decode_error)? {
+        samples.extend(packet);
+    }
+    // The last packet is decoded whole, the stream's last position says where the audio really ends
+    if let Some(position) = reader.get_last_absgp() {
+        samples.truncate(position as usize * channels as usize);
+    }
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    let changes = process(&mut samples, channels as usize, sample_rate, processing);
+
+    let data_size = u32::try_from(samples.len() * 2)
+        .ok()
+        .filter(|size| *size <= u32::MAX - 36)
+        .ok_or_else(|| Error::Decode("too long for a WAV file".to_owned()))?;
+
+    let mut wav = wav_header(channels, sample_rate, data_size);
+    wav.reserve(data_size as usize);
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    Ok((wav, changes))
+}
+
+// OGG becomes WAV, anything else is left as it is. False if it couldn't be decoded and is still OGG.
+// What processing changed is put in changes
+pub fn convert(
+    name: &str,
+    extracted: &mut ExtractedAsset,
+    processing: AudioProcessing,
+    changes: &mut Option<AudioChanges>,
+) -> bool {
+    if extracted.header.as_deref() != Some("OggS") {
+        return true;
+    }
+    match to_wav(&extracted.bytes, processing) {
+        Ok((bytes, audio_changes)) => {
+            *changes = audio_changes;
+            extracted.bytes = bytes;
+            extracted.header = Some("RIFF".to_owned());
+            extracted.suggested_extension = Some(get_extension("RIFF"));
+            true
+        }
+        Err(e) => {
+            log_warn!(
+                "Failed to convert {} to WAV, extracting as {}: {}",
+                name,
+                get_extension("OggS"),
+                e
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logic::fixtures;
+
+    const SAMPLE_RATE: u32 = 44100;
+
+    // Half a second of silence either side of a quarter of a second of a 440Hz tone
+    fn padded_tone() -> Vec<f32> {
+        let silence = vec![0.0; SAMPLE_RATE as usize / 2];
+        let tone = (0..SAMPLE_RATE as usize / 4)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin() * 0.5);
+        silence
+            .iter()
+            .copied()
+            .chain(tone)
+            .chain(silence.iter().copied())
+            .collect()
+    }
+
+    // The frames in the data chunk, checking the header agrees with what follows it
+    fn frames(wav: &[u8], channels: usize) -> usize {
+        assert_eq!(&wav[..4], b"RIFF");
+        let data_size = u32::from_le_bytes(wav[40..44].try_into().unwrap()) as usize;
+        assert_eq!(wav.len(), 44 + data_size);
+        data_size / 2 / channels
+    }
+
+    #[test]
+    fn every_sample_comes_back() {
+        let tone = padded_tone();
+        for channels in [vec![tone.clone()], vec![tone.clone(), tone.clone()]] {
+            let ogg = fixtures::vorbis(SAMPLE_RATE, &channels);
+            let (wav, changes) = to_wav(&ogg, AudioProcessing::default()).unwrap();
+            // Not the padding at the end of the last packet
+            assert_eq!(frames(&wav, channels.len()), tone.len());
+            assert_eq!(changes, None);
+        }
+    }
+
+    #[test]
+    fn padded_silence_is_trimmed() {
+        let tone = padded_tone();
+        let ogg = fixtures::vorbis(SAMPLE_RATE, std::slice::from_ref(&tone));
+        let processing = AudioProcessing {
+            trim_silence: Some(-50.0),
+            normalize: None,
+        };
+        let (wav, changes) = to_wav(&ogg, processing).unwrap();
+        let changes = changes.unwrap();
+
+        // Vorbis smears the tone's edges a little, so this is to within a block
+        let block = 2048;
+        let kept = frames(&wav, 1);
+        let loud = SAMPLE_RATE as usize / 4;
+        assert!(
+            (loud..=loud + 2 * block).contains(&kept),
+            "{kept} frames kept of {loud}"
+        );
+        for trimmed in [changes.trimmed_start_ms, changes.trimmed_end_ms] {
+            assert!((450..=500).contains(&trimmed), "{trimmed}ms trimmed");
+        }
+        assert_eq!(changes.gain_db, 0.0);
+    }
+}
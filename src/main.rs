@@ -6,29 +6,70 @@ mod config;
 mod gui;
 mod locale;
 mod logic;
+#[cfg(feature = "server")]
+mod server;
 mod updater;
 
-use std::path::PathBuf;
+use std::{
+    collections::HashSet,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    process::ExitCode,
+    time::Duration,
+};
 
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 
-use crate::logic::extract_to_file;
+use crate::logic::{
+    error_code::{self, ErrorCode},
+    extract_to_file,
+    verify::Verification,
+};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Commands>,
+
     /// List assets
     #[arg(short, long)]
     list: bool,
 
     /// Set mode, using this is generally recommended, if this is not provided, the program will run the same function across each mode
-    #[arg(short, long, value_name = "CATEGORY")]
+    #[arg(short, long, value_name = "CATEGORY", ignore_case = true)]
     mode: Option<logic::Category>,
 
     /// Extract asset, extract directory if no asset provided
     #[arg(short, long)]
     extract: Option<Option<String>>,
 
+    /// Print --list output once listing has finished, in the same order every time
+    #[arg(long)]
+    stable_order: bool,
+
+    /// Print each asset's category and size after its id in --list output
+    #[arg(long, requires = "list")]
+    long: bool,
+
+    /// Print --list output as JSON or CSV with each asset's details, once listing has finished
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        requires = "list",
+        conflicts_with = "long"
+    )]
+    output: Option<logic::ListOutput>,
+
+    /// Sort --list output, printed once listing has finished
+    #[arg(long, value_name = "KEY", requires = "list")]
+    sort: Option<logic::SortKey>,
+
+    /// Sort --list output in descending order
+    #[arg(long, requires = "sort")]
+    reverse: bool,
+
     /// Add a file extension automatically
     #[arg(long)]
     extension: bool,
@@ -37,14 +78,126 @@ struct Cli {
     #[arg(short, long)]
     dest: Option<PathBuf>,
 
+    /// Only list or extract assets whose ID, alias or note contains this, like search
+    #[arg(long, value_name = "QUERY")]
+    filter: Option<String>,
+
+    /// Treat --filter as a regular expression matched against the ID and alias, ignoring case
+    #[arg(long, requires = "filter")]
+    regex: bool,
+
+    /// Only list or extract assets stored in at least this many bytes, e.g. 500, 100K or 1.5M
+    #[arg(long, value_name = "SIZE", value_parser = logic::parse_size)]
+    min_size: Option<u64>,
+
+    /// Only list or extract assets stored in at most this many bytes, e.g. 500, 100K or 1.5M
+    #[arg(long, value_name = "SIZE", value_parser = logic::parse_size)]
+    max_size: Option<u64>,
+
+    /// Only list or extract assets last modified less than this long ago, e.g. 30m, 12h or 7d
+    #[arg(long, value_name = "DURATION", value_parser = logic::clear::parse_duration)]
+    newer_than: Option<Duration>,
+
+    /// Only list or extract assets last modified at least this long ago, e.g. 30m, 12h or 7d
+    #[arg(long, value_name = "DURATION", value_parser = logic::clear::parse_duration)]
+    older_than: Option<Duration>,
+
+    /// Only list or extract avatar and asset thumbnails, told apart by the URL they were downloaded from
+    #[arg(long)]
+    thumbnails: bool,
+
+    /// Extract into a ZIP file instead of a folder
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["dest", "filter", "min_size", "max_size", "newer_than", "older_than", "thumbnails"]
+    )]
+    zip: Option<PathBuf>,
+
+    /// How extracted files are named, overrides the per-category options
+    #[arg(long)]
+    naming: Option<logic::extract_options::Naming>,
+
+    /// Skip assets with the same content as one already extracted, overrides the per-category options
+    #[arg(long)]
+    dedupe: bool,
+
+    /// Put extracted files in a folder for each category or not, overrides the per-category options
+    #[arg(long)]
+    layout: Option<logic::extract_options::Layout>,
+
+    /// Put extracted files in a folder for each category, the same as --layout category
+    #[arg(long, conflicts_with = "layout")]
+    sort_into_subfolders: bool,
+
+    /// Output path for each extracted file, e.g. "{category}/{date:%Y-%m}/{alias}-{hash8}.{ext}", overrides naming and layout
+    #[arg(long)]
+    template: Option<String>,
+
+    /// Write an .m3u8 playlist of the extracted audio to the destination
+    #[arg(long)]
+    playlist: bool,
+
+    /// Write a manifest.json to the destination listing which file came from which asset
+    #[arg(long)]
+    manifest: bool,
+
+    /// Downscale extracted images so their longest side is at most this many pixels
+    #[arg(long, value_name = "PIXELS")]
+    max_dimension: Option<u32>,
+
+    /// Filter used when downscaling images
+    #[arg(long, value_name = "FILTER")]
+    resize_filter: Option<logic::extract_options::ResizeFilter>,
+
+    /// Snap the sides of extracted images down to a power of two
+    #[arg(long)]
+    power_of_two: bool,
+
+    /// Write WebP and KTX textures as PNG, textures that can't be decoded are written as they are
+    #[arg(long)]
+    png: bool,
+
+    /// Write OGG audio as WAV, audio that can't be decoded is written as it is
+    #[arg(long)]
+    wav: bool,
+
+    /// What to do when a file is already at an extracted file's path
+    #[arg(long, value_name = "POLICY")]
+    overwrite: Option<logic::extract_options::OverwritePolicy>,
+
+    /// Skip assets whose file is already in the destination with the same size and modification time
+    #[arg(long)]
+    incremental: bool,
+
+    /// Extract assets even if they don't match the checksum stored with them
+    #[arg(long)]
+    no_verify: bool,
+
     /// Swap two assets
     #[arg(short, long)]
     swap: Option<String>,
 
+    /// Swap even if the assets are different types or very different sizes
+    #[arg(long)]
+    force: bool,
+
     /// Return the cache directory
     #[arg(short, long)]
     cache_dir: bool,
 
+    /// Don't change the cache, the database or the extraction history, only read them
+    #[arg(long)]
+    read_only: bool,
+
+    /// Only print what was asked for and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Print more logs, -v for information and -vv for debugging too
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
     /// Connect to the internet to check for updates
     #[arg(long)]
     check_for_updates: bool,
@@ -54,8 +207,282 @@ struct Cli {
     download_new_update: bool,
 }
 
-fn list(category: logic::Category) {
-    logic::refresh(category, true, true); // cli_list_mode is set to true, this will print assets to console
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum GroupBy {
+    Category,
+    Day, // Day the asset was last modified
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Query the extraction history
+    History {
+        /// Show when this asset was extracted, shows the latest extractions if not provided
+        #[arg(long)]
+        id: Option<String>,
+
+        /// Remove all but the newest entries from the history
+        #[arg(long, value_name = "ENTRIES")]
+        prune: Option<u64>,
+    },
+
+    /// Extract assets by id or alias, the category is detected if --mode isn't provided
+    #[command(visible_alias = "extract-asset")]
+    ExtractOne {
+        /// Assets to extract, by id or alias
+        #[arg(value_name = "ASSET", required = true)]
+        ids: Vec<String>,
+
+        /// Set mode
+        #[arg(short, long, value_name = "CATEGORY", ignore_case = true)]
+        mode: Option<logic::Category>,
+
+        /// Define a destination path, a folder for several assets, or - for stdout. The asset id is used if not provided
+        #[arg(short, long, visible_short_alias = 'o')]
+        dest: Option<PathBuf>,
+
+        /// Write the asset to stdout instead of a file
+        #[arg(long)]
+        stdout: bool,
+
+        /// Encode the asset as base64, requires --stdout
+        #[arg(long, requires = "stdout")]
+        base64: bool,
+
+        /// Encode the asset as a data URI, requires --stdout
+        #[arg(long, requires = "stdout", conflicts_with = "base64")]
+        data_uri: bool,
+    },
+
+    /// Show what's known about an asset, including its SHA-256
+    Info {
+        /// Asset to show
+        id: String,
+
+        /// Set mode, the category is detected if not provided
+        #[arg(short, long, value_name = "CATEGORY", ignore_case = true)]
+        mode: Option<logic::Category>,
+    },
+
+    /// Check assets against the checksum Roblox stored with them and print the ids of corrupt ones
+    Verify {
+        /// Only check this category, checks everything if not provided
+        #[arg(short, long, value_name = "CATEGORY", ignore_case = true)]
+        mode: Option<logic::Category>,
+    },
+
+    /// Search assets by id or alias
+    Search {
+        /// Text to search for, lists everything if not provided
+        query: Option<String>,
+
+        /// Only show assets of this category, can be used more than once
+        #[arg(long = "category", value_name = "CATEGORY", ignore_case = true)]
+        categories: Vec<logic::Category>,
+    },
+
+    /// Count assets and their sizes
+    Stats {
+        /// Only count this category, counts everything if not provided
+        #[arg(short, long, value_name = "CATEGORY", ignore_case = true)]
+        mode: Option<logic::Category>,
+
+        /// How assets are grouped
+        #[arg(long, value_enum, default_value_t = GroupBy::Category)]
+        group_by: GroupBy,
+    },
+
+    /// Delete assets from the cache, deletes everything if no filters are provided
+    Clear {
+        /// Only delete this category, "all" only matches assets without a known category
+        #[arg(long, value_name = "CATEGORY", ignore_case = true)]
+        category: Option<logic::Category>,
+
+        /// Only delete assets last modified at least this long ago, e.g. 30m, 12h, 7d or 2w
+        #[arg(long, value_name = "DURATION", value_parser = logic::clear::parse_duration)]
+        older_than: Option<Duration>,
+
+        /// List what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Don't ask for confirmation, required when not running in a terminal
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Lay images out on a grid with their aliases and write them as PNG pages
+    ContactSheet {
+        /// Category to make contact sheets of, only images can be decoded
+        #[arg(
+            long,
+            value_name = "CATEGORY",
+            ignore_case = true,
+            default_value = "images"
+        )]
+        category: logic::Category,
+
+        /// Folder to write the pages to
+        #[arg(long)]
+        out: PathBuf,
+
+        /// Thumbnails per row, uses the setting if not provided
+        #[arg(long)]
+        columns: Option<u32>,
+
+        /// Rows per page, uses the setting if not provided
+        #[arg(long)]
+        rows: Option<u32>,
+
+        /// Width and height of each thumbnail in pixels, uses the setting if not provided
+        #[arg(long, value_name = "PIXELS")]
+        thumbnail_size: Option<u32>,
+    },
+
+    /// Extract new assets of a category to a folder as they appear in the cache, until Ctrl+C
+    Watch {
+        /// Category to extract
+        #[arg(long, value_name = "CATEGORY", ignore_case = true)]
+        category: logic::Category,
+
+        /// Folder to extract to
+        #[arg(long)]
+        out: PathBuf,
+    },
+
+    /// Open the GUI, the same as running without any arguments
+    Gui {
+        /// Tab to open on, e.g. images, settings or logs
+        #[arg(long)]
+        tab: Option<String>,
+
+        /// Search for this in the file list
+        #[arg(long)]
+        search: Option<String>,
+    },
+
+    /// Run a local HTTP API for scripts, bound to localhost only
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+    },
+}
+
+// What the process exits with, the worst outcome wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Exit {
+    Success = 0,
+    PartialFailure = 1, // Some or all assets couldn't be extracted
+    NoAssets = 2,       // Nothing matched
+    SourceNotFound = 3, // The cache directory or the database couldn't be opened
+    InvalidArguments = 4,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+// Nothing found is only blamed on the sources if one of them couldn't be read
+fn nothing_found(unavailable: bool) -> Exit {
+    if unavailable {
+        Exit::SourceNotFound
+    } else {
+        Exit::NoAssets
+    }
+}
+
+fn last_refresh_unavailable() -> bool {
+    logic::summary::get_last_refresh().is_some_and(|summary| summary.unavailable())
+}
+
+// Summaries go to stderr so stdout is only what was asked for, --quiet leaves them out
+fn note(message: impl std::fmt::Display) {
+    if !log::is_quiet() {
+        eprintln!("{message}");
+    }
+}
+
+// Listed, then filtered and sorted. The summary goes to stderr so stdout is only assets
+fn listed(
+    category: logic::Category,
+    sort: Option<(logic::SortKey, bool)>,
+    filter: Option<&logic::FilterQuery>,
+) -> (Vec<logic::AssetInfo>, bool) {
+    let summary = logic::refresh(category, false, true);
+    if let Some(filter) = filter {
+        logic::filter_file_list(filter);
+    }
+    if let Some((key, ascending)) = sort {
+        logic::sort_file_list(key, ascending);
+    }
+    let unavailable = summary
+        .as_ref()
+        .is_some_and(logic::summary::RefreshSummary::unavailable);
+    if let Some(summary) = summary {
+        note(summary);
+    }
+    let assets = if filter.is_some() {
+        logic::get_filtered_file_list()
+    } else {
+        logic::get_file_list()
+    };
+    (assets, unavailable)
+}
+
+fn list(
+    categories: &[logic::Category],
+    sort: Option<(logic::SortKey, bool)>,
+    filter: Option<&logic::FilterQuery>,
+    output: logic::ListOutput,
+) -> Exit {
+    let mut count = 0;
+    let mut unavailable = false;
+    if output != logic::ListOutput::Text {
+        // One document for every category
+        let mut assets = Vec::new();
+        for category in categories {
+            let (listed, source_unavailable) = listed(*category, sort, filter);
+            assets.extend(listed);
+            unavailable |= source_unavailable;
+        }
+        println!("{}", logic::format_listing(&assets, output));
+        count = assets.len();
+    } else {
+        for category in categories {
+            if sort.is_none() && filter.is_none() {
+                // cli_list_mode is set to true, this will print assets to console
+                if let Some(summary) = logic::refresh(*category, true, true) {
+                    note(&summary); // stdout is only assets so it can be piped
+                                    // With --stable-order the list is printed after merging assets in both sources
+                    count += if logic::get_stable_order() {
+                        logic::get_listed_count()
+                    } else {
+                        summary.listed()
+                    };
+                    unavailable |= summary.unavailable();
+                }
+            } else {
+                // Nothing can be printed until everything has been listed
+                let (assets, source_unavailable) = listed(*category, sort, filter);
+                for asset in &assets {
+                    println!("{}", logic::list_line(asset));
+                }
+                count += assets.len();
+                unavailable |= source_unavailable;
+            }
+        }
+    }
+
+    note(format_args!("listed {count} assets"));
+    if count == 0 {
+        nothing_found(unavailable)
+    } else {
+        Exit::Success
+    }
 }
 
 fn extract(
@@ -63,35 +490,734 @@ fn extract(
     asset: Option<String>,
     destination: Option<PathBuf>,
     add_extension: bool,
-) {
+    filter: Option<logic::FilterQuery>,
+    zip: Option<PathBuf>,
+    overrides: logic::extract_options::ExtractOverrides,
+) -> Exit {
     if let Some(asset) = asset {
         let dest = destination.unwrap_or(asset.clone().into());
         let info = logic::create_asset_info(&asset, category);
-        match logic::extract_to_file(info, dest, add_extension) {
-            Ok(destination) => println!("{}", destination.display()),
-            Err(e) => eprintln!("{e}"),
-        }
+        let overwrite = logic::extract_options::get_overwrite_policy(&overrides);
+        print_extract_result(logic::extract_to_file(
+            info,
+            dest,
+            add_extension,
+            overwrite,
+            logic::extract_options::get_convert_to_png(&overrides),
+            logic::extract_options::get_convert_to_wav(&overrides),
+            logic::extract_options::get_verify_on_extract(&overrides),
+        ))
+    } else if let Some(zip) = zip {
+        let overrides = logic::extract_options::ExtractOverrides {
+            refresh: Some(true),
+            ..overrides
+        };
+        print_extract_summary(logic::extract_dir_to_archive(
+            zip, category, true, overrides,
+        ))
     } else if let Some(dest) = destination {
         logic::refresh(category, true, true);
-        logic::extract_dir(dest, category, true, false);
+        // Just listed, so extract_dir doesn't need to list it again
+        let overrides = logic::extract_options::ExtractOverrides {
+            refresh: Some(false),
+            ..overrides
+        };
+        let summary = match filter {
+            Some(filter) => {
+                logic::filter_file_list(&filter);
+                let assets = logic::get_filtered_file_list();
+                logic::extract_assets(dest, category, assets, true, overrides)
+            }
+            None => logic::extract_dir(dest, category, true, overrides),
+        };
+        print_extract_summary(summary)
+    } else {
+        eprintln!("Please provide either a destination path or an asset to extract! --help for more details.");
+        Exit::InvalidArguments
+    }
+}
+
+// None means the extraction didn't start, the status says why
+fn print_extract_summary(summary: Option<logic::summary::ExtractSummary>) -> Exit {
+    let Some(summary) = summary else {
+        eprintln!("{}", logic::get_status());
+        return Exit::PartialFailure;
+    };
+    println!("{summary}");
+    note(format_args!(
+        "extracted {}, failed {}",
+        summary.written,
+        summary.failed.len()
+    ));
+
+    if !summary.failed.is_empty() || !summary.corrupt.is_empty() || summary.unreachable {
+        Exit::PartialFailure
+    } else if summary.written + summary.skipped == 0 {
+        nothing_found(last_refresh_unavailable())
+    } else {
+        Exit::Success
+    }
+}
+
+// One asset extracted to a file
+fn print_extract_result(result: Result<Option<PathBuf>, logic::Error>) -> Exit {
+    let (exit, extracted) = match result {
+        Ok(Some(destination)) => {
+            println!("{}", destination.display());
+            (Exit::Success, 1)
+        }
+        Ok(None) => {
+            note("Skipped, a file is already there");
+            (Exit::Success, 0)
+        }
+        Err(e) => {
+            eprintln!("{}", e.describe());
+            let exit = match e.code() {
+                ErrorCode::NotFound | ErrorCode::Evicted | ErrorCode::HeaderNotFound => {
+                    Exit::NoAssets
+                }
+                ErrorCode::NoDatabase => Exit::SourceNotFound,
+                ErrorCode::InvalidId => Exit::InvalidArguments,
+                _ => Exit::PartialFailure,
+            };
+            (exit, 0)
+        }
+    };
+    note(format_args!(
+        "extracted {extracted}, failed {}",
+        usize::from(exit != Exit::Success)
+    ));
+    exit
+}
+
+// The category is detected unless it's given, music is only found in the music folder
+fn find_asset(id: &str, category: Option<logic::Category>) -> Option<logic::AssetInfo> {
+    let exists = |info: &logic::AssetInfo| info.from_file || info.from_sql;
+    if let Some(category) = category {
+        return Some(logic::create_asset_info(id, category)).filter(exists);
+    }
+
+    let info = logic::create_asset_info(id, logic::Category::All);
+    if exists(&info) {
+        let category =
+            logic::determine_category(&logic::extract_asset_to_bytes(info).unwrap_or_default());
+        Some(logic::create_asset_info(id, category))
+    } else {
+        Some(logic::create_asset_info(id, logic::Category::Music)).filter(exists)
+    }
+}
+
+fn extract_one(
+    ids: Vec<String>,
+    category: Option<logic::Category>,
+    destination: Option<PathBuf>,
+    stdout: bool,
+    base64: bool,
+    data_uri: bool,
+    overrides: &logic::extract_options::ExtractOverrides,
+) -> Exit {
+    // "-" is stdout, like most other tools
+    let stdout = stdout || destination.as_deref() == Some(std::path::Path::new("-"));
+    if stdout {
+        log::use_stderr(); // Logs would end up in the output otherwise
+    }
+    // Several assets go in the destination folder, each named after its id
+    let into_directory = !stdout
+        && destination
+            .as_ref()
+            .is_some_and(|destination| ids.len() > 1 || destination.is_dir());
+    if let (true, Some(destination)) = (into_directory, &destination) {
+        if let Err(e) = std::fs::create_dir_all(destination) {
+            eprintln!("{}", error_code::describe(&e));
+            return Exit::PartialFailure;
+        }
+    }
+
+    let mut exit = Exit::Success;
+    let mut extracted = 0;
+    let mut failed = 0;
+    for id in ids {
+        // An alias works too, if it isn't an id in the cache
+        let info = find_asset(&id, category).or_else(|| {
+            config::get_asset_aliases()
+                .into_iter()
+                .find(|(_, alias)| *alias == id)
+                .and_then(|(asset, _)| find_asset(&asset, category))
+        });
+        let Some(info) = info else {
+            eprintln!("{id} wasn't found in the cache directory or the database");
+            exit = exit.max(Exit::NoAssets);
+            failed += 1;
+            continue;
+        };
+
+        if base64 || data_uri {
+            match logic::extract_asset_to_base64(info, data_uri, None) {
+                Ok(encoded) => {
+                    println!("{encoded}");
+                    extracted += 1;
+                }
+                Err(e) => {
+                    eprintln!("{}", e.describe());
+                    exit = exit.max(Exit::PartialFailure);
+                    failed += 1;
+                }
+            }
+        } else if stdout {
+            let result = logic::extract_asset_to_bytes(info)
+                .map_err(|e| e.describe())
+                .and_then(|bytes| {
+                    std::io::stdout()
+                        .write_all(&bytes)
+                        .map_err(|e| error_code::describe(&e))
+                });
+            match result {
+                Ok(()) => extracted += 1,
+                Err(e) => {
+                    eprintln!("{e}");
+                    exit = exit.max(Exit::PartialFailure);
+                    failed += 1;
+                }
+            }
+        } else {
+            let destination = match &destination {
+                Some(destination) if into_directory => destination.join(&info.name),
+                Some(destination) => destination.clone(),
+                None => info.name.clone().into(),
+            };
+            // The extension given is kept even if it isn't what the asset is
+            let add_extension = destination.extension().is_none();
+            match extract_to_file(
+                info,
+                destination,
+                add_extension,
+                logic::extract_options::get_overwrite_policy(overrides),
+                logic::extract_options::get_convert_to_png(overrides),
+                logic::extract_options::get_convert_to_wav(overrides),
+                logic::extract_options::get_verify_on_extract(overrides),
+            ) {
+                Ok(Some(destination)) => {
+                    println!("{}", destination.display());
+                    extracted += 1;
+                }
+                Ok(None) => note("Skipped, a file is already there"),
+                Err(e) => {
+                    eprintln!("{}", e.describe());
+                    exit = exit.max(Exit::PartialFailure);
+                    failed += 1;
+                }
+            }
+        }
+    }
+    note(format_args!("extracted {extracted}, failed {failed}"));
+    exit
+}
+
+fn search(query: Option<String>, categories: Vec<logic::Category>) {
+    let categories: Option<HashSet<logic::Category>> =
+        (!categories.is_empty()).then(|| categories.into_iter().collect());
+    let query = query.unwrap_or_default();
+
+    // Music is only found by listing the music folder
+    let mut sources = vec![logic::Category::All];
+    if categories
+        .as_ref()
+        .is_none_or(|categories| categories.contains(&logic::Category::Music))
+    {
+        sources.push(logic::Category::Music);
+    }
+
+    for source in sources {
+        logic::refresh(source, false, true);
+        logic::search_file_list(query.clone(), categories.as_ref());
+        for asset in logic::get_filtered_file_list() {
+            println!("{}", asset.name);
+        }
+    }
+}
+
+// Empty when unknown, so the columns still line up
+fn format_time(time: Option<std::time::SystemTime>) -> String {
+    time.map(|time| {
+        let time: chrono::DateTime<chrono::Local> = time.into();
+        time.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+    .unwrap_or_default()
+}
+
+fn stats(category: Option<logic::Category>, group_by: GroupBy) {
+    if group_by == GroupBy::Category {
+        let stats = logic::compute_statistics(category);
+        if !stats.database {
+            note("No database was found, only the cache directory was counted");
+        }
+        if stats.duplicates > 0 {
+            note(format!(
+                "{} assets are in both the database and the cache directory, each was counted once",
+                stats.duplicates
+            ));
+        }
+        for (name, group) in stats
+            .categories
+            .iter()
+            .map(|(category, group)| (category.to_string(), group))
+            .chain([("total".to_owned(), &stats.total)])
+        {
+            println!(
+                "{name}\t{}\t{}\t{}\t{}",
+                group.count,
+                group.bytes,
+                format_time(group.oldest),
+                format_time(group.newest)
+            );
+        }
+        return;
+    }
+
+    let mut assets = Vec::new();
+    let sources = match category {
+        Some(category) => vec![category],
+        None => vec![logic::Category::All, logic::Category::Music],
+    };
+    for source in sources {
+        logic::refresh(source, false, true);
+        assets.extend(logic::get_file_list());
+    }
+
+    let locale = locale::get_locale(None);
+    for (group, assets) in logic::group_by_day(assets) {
+        let size: u64 = assets.iter().map(|asset| asset.size).sum();
+        println!("{}\t{}\t{size}", group.label(&locale), assets.len());
+    }
+}
+
+fn contact_sheet(
+    category: logic::Category,
+    destination: PathBuf,
+    columns: Option<u32>,
+    rows: Option<u32>,
+    thumbnail_size: Option<u32>,
+) -> bool {
+    let defaults = logic::contact_sheet::SheetOptions::default();
+    let options = logic::contact_sheet::SheetOptions {
+        columns: columns.unwrap_or(defaults.columns),
+        rows: rows.unwrap_or(defaults.rows),
+        thumbnail_size: thumbnail_size.unwrap_or(defaults.thumbnail_size),
+    };
+
+    logic::refresh(category, false, true);
+    let assets = logic::get_file_list();
+    if assets.is_empty() {
+        eprintln!("No assets to make contact sheets of");
+        return false;
+    }
+
+    match logic::contact_sheet::generate(assets, destination, options, true) {
+        Some(summary) => {
+            println!("{summary}");
+            summary.pages > 0
+        }
+        None => false,
+    }
+}
+
+// Runs until Ctrl+C stops auto-extract
+fn watch(category: logic::Category, destination: PathBuf, use_alias: bool) {
+    logic::start_auto_extract(category, destination, use_alias);
+    println!("Watching for new {category} assets, press Ctrl+C to stop");
+
+    let mut extracted = 0;
+    while let Some(count) = logic::watcher::get_auto_extracted() {
+        if count != extracted {
+            extracted = count;
+            println!("Auto-extracted {extracted} assets");
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+// Returns false if the asset doesn't exist or couldn't be read
+fn info(asset: String, category: Option<logic::Category>) -> bool {
+    log::use_stderr(); // Only the fields on stdout, for scripts
+    let category = category.unwrap_or_else(|| {
+        let info = logic::create_asset_info(&asset, logic::Category::All);
+        logic::determine_category(&logic::extract_asset_to_bytes(info).unwrap_or_default())
+    });
+    let info = logic::create_asset_info(&asset, category);
+    if !(info.from_file || info.from_sql) {
+        eprintln!("{}: {asset} doesn't exist", ErrorCode::NotFound);
+        return false;
+    }
+
+    println!("id: {}", info.name);
+    println!("category: {}", info.category);
+    println!("size: {}", info.size);
+    println!(
+        "decompressed size: {}{}",
+        info.actual_size,
+        if info.actual_size_approximate {
+            " (approximate)"
+        } else {
+            ""
+        }
+    );
+    if let Some(last_modified) = info.last_modified {
+        let last_modified: chrono::DateTime<chrono::Local> = last_modified.into();
+        println!(
+            "last modified: {}",
+            last_modified.format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    println!(
+        "source: {}",
+        if info.in_both_sources() {
+            "file and database"
+        } else if info.from_sql {
+            "database"
+        } else {
+            "file"
+        }
+    );
+    if let Some(other_name) = &info.other_name {
+        println!("other name: {other_name}");
+    }
+    if info.evicted {
+        println!("evicted: true");
+    }
+    match logic::compute_hash(&info) {
+        Ok(hash) => {
+            println!("sha256: {hash}");
+            true
+        }
+        Err(e) => {
+            eprintln!("{}", e.describe());
+            false
+        }
+    }
+}
+
+// The same check extraction makes, see logic::verify::check()
+fn verify(category: Option<logic::Category>) -> Exit {
+    let categories = match category {
+        Some(category) => vec![category],
+        None => vec![logic::Category::All, logic::Category::Music],
+    };
+    let mut unavailable = false;
+    let (mut verified, mut corrupt, mut unverifiable, mut failed) = (0, 0, 0, 0);
+    for category in categories {
+        let (assets, source_unavailable) = listed(category, None, None);
+        unavailable |= source_unavailable;
+        for asset in assets {
+            match logic::extract_asset(&asset) {
+                Ok(extracted) => match extracted.verification {
+                    Verification::Verified => verified += 1,
+                    Verification::Mismatch => {
+                        println!("{}", asset.name);
+                        corrupt += 1;
+                    }
+                    Verification::Unverifiable => unverifiable += 1,
+                },
+                Err(e) => {
+                    eprintln!("{}: {}", asset.name, e.describe());
+                    failed += 1;
+                }
+            }
+        }
+    }
+    note(format_args!(
+        "{verified} verified, {corrupt} corrupt, {unverifiable} without a checksum, {failed} couldn't be read"
+    ));
+
+    if corrupt + failed > 0 {
+        Exit::PartialFailure
+    } else if verified + unverifiable == 0 {
+        nothing_found(unavailable)
     } else {
-        eprintln!("Please provide either a destination path or an asset to extract! --help for more details.")
+        Exit::Success
     }
 }
 
-fn main() {
-    let args = Cli::parse();
+fn history(asset: Option<String>, prune: Option<u64>) {
+    if let Some(keep) = prune {
+        match logic::history::prune(keep) {
+            Ok(removed) => println!("Removed {removed} entries"),
+            Err(e) => eprintln!("Failed to prune history: {e}"),
+        }
+        return;
+    }
+
+    let entries = if let Some(asset) = &asset {
+        logic::history::get_entries(asset)
+    } else {
+        logic::history::search("", 100)
+    };
+
+    if entries.is_empty() {
+        if let Some(asset) = asset {
+            eprintln!("{asset} has never been extracted")
+        }
+    }
+
+    for entry in entries {
+        let timestamp: chrono::DateTime<chrono::Local> = entry.timestamp.into();
+        println!(
+            "{}\t{}\t{}\t{}\tv{}",
+            timestamp.format("%Y-%m-%d %H:%M:%S"),
+            entry.asset,
+            entry.hash,
+            entry.destination,
+            entry.version
+        );
+    }
+}
+
+// Returns false if nothing could be deleted or the clear was refused
+fn clear(
+    category: Option<logic::Category>,
+    older_than: Option<Duration>,
+    dry_run: bool,
+    yes: bool,
+) -> bool {
+    // Refuse while the GUI or another command is extracting or deleting
+    if !logic::start_task() {
+        eprintln!(
+            "{}: Another task is running, try again when it has finished",
+            ErrorCode::Busy
+        );
+        return false;
+    }
+
+    let scope = logic::clear::ClearScope {
+        category,
+        older_than,
+    };
+    let assets = logic::clear::plan(scope);
+    for asset in &assets {
+        println!("{}\t{}\t{}", asset.name, asset.category, asset.size);
+    }
+    let size: u64 = assets.iter().map(|asset| asset.size).sum();
+    println!("{} assets, {}", assets.len(), logic::format_size(size));
+
+    let confirmed = if dry_run || assets.is_empty() {
+        false
+    } else if yes {
+        true
+    } else if std::io::stdin().is_terminal() {
+        eprint!("Delete {} assets? [y/N] ", assets.len());
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer).is_ok() && answer.trim().eq_ignore_ascii_case("y")
+    } else {
+        eprintln!(
+            "Refusing to delete without a terminal to confirm in, pass --yes to delete anyways"
+        );
+        logic::finish_task();
+        return false;
+    };
+
+    let mut success = true;
+    if confirmed {
+        match logic::clear::clear(scope, &assets, &locale::get_locale(None)) {
+            Ok(summary) => {
+                println!(
+                    "Deleted {} assets ({}), {} failed{}",
+                    summary.removed,
+                    logic::format_size(summary.bytes),
+                    summary.failed,
+                    if summary.cancelled {
+                        ", cancelled before finishing"
+                    } else {
+                        ""
+                    }
+                );
+                success = summary.removed > 0 || summary.failed == 0;
+            }
+            Err(e) => {
+                eprintln!("{}: {e}", e.code());
+                success = false;
+            }
+        }
+    }
+
+    logic::finish_task();
+    success
+}
 
-    if args.list {
-        if let Some(category) = args.mode {
-            list(category);
+fn main() -> ExitCode {
+    let mut exit_code = ExitCode::SUCCESS;
+    let args = match Cli::try_parse() {
+        Ok(args) => args,
+        Err(e) => {
+            let _ = e.print();
+            // --help and --version are "errors" too, but printed to stdout
+            return if e.use_stderr() {
+                Exit::InvalidArguments.into()
+            } else {
+                ExitCode::SUCCESS
+            };
+        }
+    };
+
+    log::set_level(if args.quiet {
+        log::ERROR
+    } else {
+        log::WARN.saturating_add(args.verbose)
+    });
+    logic::set_stable_order(args.stable_order);
+    logic::set_long_listing(args.long);
+    let filter = logic::FilterQuery {
+        text: args.filter.clone().unwrap_or_default(),
+        regex: args.regex,
+        categories: None,
+        min_size: args.min_size,
+        max_size: args.max_size,
+        newer_than: args.newer_than,
+        older_than: args.older_than,
+        thumbnails: args.thumbnails,
+    };
+    let filter = (!filter.is_empty()).then_some(filter);
+
+    if args
+        .output
+        .is_some_and(|output| output != logic::ListOutput::Text)
+    {
+        log::use_stderr(); // Logs would end up in the output otherwise
+    }
+
+    if args.read_only {
+        logic::read_only::set_enabled(true); // Only for this run
+    }
+    if logic::read_only::is_enabled() {
+        log_info!("Read-only mode is on, nothing will be changed");
+    }
+
+    // Ctrl+C stops an extraction between files so its summary is still printed
+    let _ = ctrlc::set_handler(|| {
+        if logic::watcher::get_auto_extracted().is_some() {
+            eprintln!("Stopping, waiting for the assets being extracted");
+            logic::stop_auto_extract();
+        } else if logic::get_task_running() && !logic::get_cancel_task() {
+            eprintln!("Cancelling, press Ctrl+C again to exit immediately");
+            logic::cancel_task();
         } else {
-            list(logic::Category::All);
-            list(logic::Category::Music)
+            logic::clean_up(); // Skipped if the task is still running
+            std::process::exit(130);
+        }
+    });
+
+    // CLI flags override the per-category extraction options
+    let overrides = logic::extract_options::ExtractOverrides {
+        naming: args.naming,
+        dedupe: args.dedupe.then_some(true),
+        layout: args.layout.or(args
+            .sort_into_subfolders
+            .then_some(logic::extract_options::Layout::Category)),
+        template: args.template,
+        playlist: args.playlist.then_some(true),
+        max_dimension: args.max_dimension,
+        resize_filter: args.resize_filter,
+        power_of_two: args.power_of_two.then_some(true),
+        overwrite: args.overwrite,
+        incremental: args.incremental.then_some(true),
+        manifest: args.manifest.then_some(true),
+        convert_to_png: args.png.then_some(true),
+        convert_to_wav: args.wav.then_some(true),
+        verify: args.no_verify.then_some(false),
+        refresh: None,
+    };
+
+    // Nothing should be written with an invalid template
+    if let Some(template) = &overrides.template {
+        if let Err(e) = logic::template::Template::parse(template) {
+            eprintln!("Invalid template: {e}");
+            return Exit::InvalidArguments.into();
+        }
+    }
+
+    // An invalid pattern would match nothing
+    if let Some(Err(e)) = filter.as_ref().map(logic::FilterQuery::validate) {
+        eprintln!("Invalid regular expression: {e}");
+        return Exit::InvalidArguments.into();
+    }
+
+    if let Some(command) = args.command {
+        match command {
+            Commands::History { id, prune } => history(id, prune),
+            Commands::Info { id, mode } => {
+                if !info(id, mode) {
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
+            Commands::Verify { mode } => exit_code = verify(mode).into(),
+            Commands::Search { query, categories } => search(query, categories),
+            Commands::Stats { mode, group_by } => stats(mode, group_by),
+            Commands::Clear {
+                category,
+                older_than,
+                dry_run,
+                yes,
+            } => {
+                if !clear(category, older_than, dry_run, yes) {
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
+            Commands::ExtractOne {
+                ids,
+                mode,
+                dest,
+                stdout,
+                base64,
+                data_uri,
+            } => {
+                exit_code =
+                    extract_one(ids, mode, dest, stdout, base64, data_uri, &overrides).into();
+            }
+            Commands::ContactSheet {
+                category,
+                out,
+                columns,
+                rows,
+                thumbnail_size,
+            } => {
+                if !contact_sheet(category, out, columns, rows, thumbnail_size) {
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
+            Commands::Watch { category, out } => {
+                // Only the naming can be chosen, like the GUI's toggle
+                let use_alias = overrides.naming.map_or_else(
+                    || config::get_config_bool("use_alias").unwrap_or(false),
+                    |naming| naming == logic::extract_options::Naming::Alias,
+                );
+                watch(category, out, use_alias)
+            }
+            Commands::Gui { tab, search } => gui::run_gui(gui::StartupOptions { tab, search }),
+            #[cfg(feature = "server")]
+            Commands::Serve { port } => server::serve(port),
         }
+    } else if args.list {
+        let sort = args.sort.map(|key| (key, !args.reverse));
+        let categories = match args.mode {
+            Some(category) => vec![category],
+            None => vec![logic::Category::All, logic::Category::Music],
+        };
+        exit_code = list(
+            &categories,
+            sort,
+            filter.as_ref(),
+            args.output.unwrap_or(logic::ListOutput::Text),
+        )
+        .into();
     } else if let Some(asset) = args.extract {
-        if let Some(category) = args.mode {
-            extract(category, asset, args.dest, args.extension);
+        let exit = if let Some(category) = args.mode {
+            extract(
+                category,
+                asset,
+                args.dest,
+                args.extension,
+                filter,
+                args.zip,
+                overrides,
+            )
         } else if let Some(asset) = asset {
             // User passed a single asset without mode, determine category.
             let info = logic::create_asset_info(&asset, logic::Category::All);
@@ -100,7 +1226,7 @@ fn main() {
 
             let info = logic::create_asset_info(&asset, category);
 
-            match extract_to_file(
+            print_extract_result(extract_to_file(
                 info,
                 if let Some(destination) = args.dest {
                     destination
@@ -108,18 +1234,43 @@ fn main() {
                     asset.into()
                 },
                 args.extension,
-            ) {
-                Ok(destination) => println!("{}", destination.display()),
-                Err(e) => eprintln!("{e}"),
-            }
+                logic::extract_options::get_overwrite_policy(&overrides),
+                logic::extract_options::get_convert_to_png(&overrides),
+                logic::extract_options::get_convert_to_wav(&overrides),
+                logic::extract_options::get_verify_on_extract(&overrides),
+            ))
         } else {
             // Not enough arguments - go through all
-            if let Some(destination) = args.dest {
-                logic::extract_all(destination, true, false);
+            if let Some(zip) = args.zip {
+                print_extract_summary(logic::extract_all_to_archive(zip, true, overrides))
+            } else if let (Some(destination), Some(filter)) = (&args.dest, filter) {
+                // Music is only found by listing the music folder, both go in one extraction like extract_all
+                let mut assets = Vec::new();
+                let mut names = HashSet::new();
+                for category in [logic::Category::Music, logic::Category::All] {
+                    logic::refresh(category, false, true);
+                    logic::filter_file_list(&filter);
+                    assets.extend(
+                        logic::get_filtered_file_list()
+                            .into_iter()
+                            .filter(|asset| names.insert(asset.name.clone())),
+                    );
+                }
+                print_extract_summary(logic::extract_assets(
+                    destination.clone(),
+                    logic::Category::All,
+                    assets,
+                    true,
+                    overrides,
+                ))
+            } else if let Some(destination) = args.dest {
+                print_extract_summary(logic::extract_all(destination, true, overrides))
             } else {
-                eprintln!("--dest is required to extract all assets. --help for more details")
+                eprintln!("--dest is required to extract all assets. --help for more details");
+                Exit::InvalidArguments
             }
-        }
+        };
+        exit_code = exit.into();
     } else if let Some(asset) = args.swap {
         if let Some(dest) = args.dest {
             let asset_a =
@@ -129,9 +1280,23 @@ fn main() {
                 args.mode.unwrap_or(logic::Category::All),
             );
 
-            logic::swap_assets(asset_a, asset_b);
+            match logic::swap_assets(asset_a, asset_b, args.force) {
+                Ok(logic::WriteValidation::NeedsConfirmation(warnings)) => {
+                    eprintln!(
+                        "{}",
+                        logic::describe_write_warnings(&locale::get_locale(None), &warnings)
+                    );
+                    eprintln!("Refusing to swap, pass --force to swap anyways")
+                }
+                Ok(logic::WriteValidation::Ok) => (),
+                Err(e) => {
+                    eprintln!("{}: {e}", ErrorCode::ReadOnly);
+                    exit_code = ExitCode::FAILURE;
+                }
+            }
         } else {
-            eprintln!("--dest is required for swapping assets, --help for more details")
+            eprintln!("--dest is required for swapping assets, --help for more details");
+            exit_code = Exit::InvalidArguments.into();
         }
     } else if args.cache_dir {
         println!(
@@ -144,14 +1309,21 @@ fn main() {
         updater::check_for_updates(false, true);
     } else {
         // If nothing passed, run GUI
-        gui::run_gui();
+        gui::run_gui(gui::StartupOptions::default());
+    }
+
+    if logic::get_cancel_task() {
+        exit_code = ExitCode::from(130); // Same as being killed by Ctrl+C
     }
 
     // The program is now closing
     config::save_config_file();
+    logic::notes::save_notes_file();
 
     if !updater::run_install_script(false) {
         // Only run if the install script hasn't ran
         logic::clean_up(); // Remove the temporary directory if one has been created
     }
+
+    exit_code
 }
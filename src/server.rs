@@ -0,0 +1,337 @@
+// Headless HTTP API for scripts (e.g. OBS overlays), only built with the "server" feature.
+// Only binds to localhost and every request needs the token printed at startup, as
+// "Authorization: Bearer <token>". It isn't taken from the URL, where it would end up in logs and history.
+//
+// GET  /assets?category=<category>&query=<text>  List assets as JSON
+// GET  /assets/<id>?category=<category>          Extracted bytes of an asset
+// POST /extract                                  {"category": "sounds", "destination": "<path>", "id": "<id>"}
+//                                                id is optional, extracts the whole category without it
+// GET  /tasks/<id>                               Progress of an extraction
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{LazyLock, Mutex},
+    thread,
+};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::{config, logic, logic::error_code::ErrorCode};
+
+const TOKEN_LENGTH: usize = 16; // Bytes, printed as hex
+
+static TASKS: LazyLock<Mutex<HashMap<u64, Task>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_TASK_ID: LazyLock<Mutex<u64>> = LazyLock::new(|| Mutex::new(1));
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum TaskState {
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Clone)]
+struct Task {
+    state: TaskState,
+    progress: f32,
+    status_text: String, // Localised, only for showing to people
+    error_code: Option<ErrorCode>,
+    error: Option<String>,
+    summary: Option<logic::summary::ExtractSummary>, // Only for whole categories
+}
+
+#[derive(Serialize)]
+struct AssetJson {
+    name: String,
+    alias: String,
+    category: String,
+    header: Option<String>,    // Null if no header matched
+    extension: Option<String>, // What extracting would add
+    size: u64,
+    actual_size: u64,
+}
+
+#[derive(Deserialize)]
+struct ExtractRequest {
+    category: Option<String>,
+    destination: PathBuf,
+    id: Option<String>,
+}
+
+// 16 bytes from the OS's random number generator
+fn generate_token() -> Result<String, getrandom::Error> {
+    let mut bytes = [0u8; TOKEN_LENGTH];
+    getrandom::fill(&mut bytes)?;
+    Ok(hex::encode(bytes))
+}
+
+// Goes through every byte whatever they are, so how long it takes doesn't give away how much matched
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+                match u8::from_str_radix(hex, 16) {
+                    Ok(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    Err(_) => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+// Split "/path?a=b&c=d" into the path and its query parameters
+fn parse_url(url: &str) -> (String, HashMap<String, String>) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key), percent_decode(value))
+        })
+        .collect();
+    (path.to_string(), params)
+}
+
+fn parse_category(category: Option<&String>) -> Result<logic::Category, String> {
+    match category {
+        Some(category) => category.parse(),
+        None => Ok(logic::Category::All),
+    }
+}
+
+fn json_response<T: Serialize>(status: u16, value: &T) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_data(serde_json::to_vec(value).unwrap_or_default())
+        .with_status_code(status)
+        .with_header(Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap())
+}
+
+fn error_response(status: u16, code: ErrorCode, error: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    json_response(status, &serde_json::json!({ "code": code, "error": error }))
+}
+
+fn is_authorised(request: &Request, token: &str) -> bool {
+    request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|given| tokens_match(given, token))
+}
+
+fn list_assets(params: &HashMap<String, String>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let category = match parse_category(params.get("category")) {
+        Ok(category) => category,
+        Err(e) => return error_response(400, ErrorCode::BadRequest, &e),
+    };
+
+    logic::refresh(category, false, true);
+    logic::search_file_list(params.get("query").cloned().unwrap_or_default(), None);
+
+    let assets: Vec<AssetJson> = logic::get_filtered_file_list()
+        .into_iter()
+        .map(|asset| AssetJson {
+            alias: config::get_asset_alias(&asset.name),
+            category: asset.category.to_string(),
+            extension: asset
+                .header
+                .as_deref()
+                .map(|header| logic::get_extension(header).to_owned()),
+            header: asset.header,
+            size: asset.size,
+            actual_size: asset.actual_size,
+            name: asset.name,
+        })
+        .collect();
+
+    json_response(200, &assets)
+}
+
+fn get_asset(id: &str, params: &HashMap<String, String>) -> Response<std::io::Cursor<Vec<u8>>> {
+    let category = match parse_category(params.get("category")) {
+        Ok(category) => category,
+        Err(e) => return error_response(400, ErrorCode::BadRequest, &e),
+    };
+
+    let info = logic::create_asset_info(id, category);
+    if !info.from_file && !info.from_sql {
+        return error_response(404, ErrorCode::NotFound, "Asset not found");
+    }
+
+    match logic::extract_asset(&info) {
+        Ok(extracted) => {
+            let content_type = extracted.mime_type();
+            Response::from_data(extracted.bytes).with_header(
+                Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes()).unwrap(),
+            )
+        }
+        Err(e) => error_response(500, e.code(), &e.to_string()),
+    }
+}
+
+fn start_extraction(request: &mut Request) -> Response<std::io::Cursor<Vec<u8>>> {
+    let mut body = String::new();
+    if let Err(e) = request.as_reader().read_to_string(&mut body) {
+        return error_response(400, ErrorCode::BadRequest, &e.to_string());
+    }
+    let extract_request: ExtractRequest = match serde_json::from_str(&body) {
+        Ok(extract_request) => extract_request,
+        Err(e) => return error_response(400, ErrorCode::BadRequest, &e.to_string()),
+    };
+    let category = match parse_category(extract_request.category.as_ref()) {
+        Ok(category) => category,
+        Err(e) => return error_response(400, ErrorCode::BadRequest, &e),
+    };
+
+    if logic::get_task_running() {
+        return error_response(409, ErrorCode::Busy, "Another task is already running");
+    }
+
+    let task_id = {
+        let mut next_task_id = NEXT_TASK_ID.lock().unwrap();
+        *next_task_id += 1;
+        *next_task_id - 1
+    };
+    TASKS.lock().unwrap().insert(
+        task_id,
+        Task {
+            state: TaskState::Running,
+            progress: 0.0,
+            status_text: logic::get_status(),
+            error_code: None,
+            error: None,
+            summary: None,
+        },
+    );
+
+    thread::spawn(move || {
+        let mut summary = None;
+        let error = if let Some(id) = extract_request.id {
+            let info = logic::create_asset_info(&id, category);
+            if info.from_file || info.from_sql {
+                logic::extract_to_file(
+                    info,
+                    extract_request.destination,
+                    true,
+                    logic::extract_options::get_overwrite_policy(&Default::default()),
+                    logic::extract_options::get_convert_to_png(&Default::default()),
+                    logic::extract_options::get_convert_to_wav(&Default::default()),
+                    logic::extract_options::get_verify_on_extract(&Default::default()),
+                )
+                .err()
+                .map(|e| (e.code(), e.to_string()))
+            } else {
+                Some((ErrorCode::NotFound, "Asset not found".to_string()))
+            }
+        } else {
+            // Another request may have listed a different category, so it's always listed first
+            summary = logic::extract_dir(
+                extract_request.destination,
+                category,
+                true,
+                logic::extract_options::ExtractOverrides {
+                    refresh: Some(true),
+                    ..Default::default()
+                },
+            );
+            None
+        };
+
+        if let Some(task) = TASKS.lock().unwrap().get_mut(&task_id) {
+            task.state = if error.is_some() {
+                TaskState::Failed
+            } else {
+                TaskState::Done
+            };
+            task.progress = 1.0;
+            task.status_text = logic::get_status();
+            task.error_code = error.as_ref().map(|(code, _)| *code);
+            task.error = error.map(|(_, error)| error);
+            task.summary = summary;
+        }
+    });
+
+    json_response(202, &serde_json::json!({ "task": task_id }))
+}
+
+fn get_task(id: &str) -> Response<std::io::Cursor<Vec<u8>>> {
+    let Ok(id) = id.parse::<u64>() else {
+        return error_response(400, ErrorCode::BadRequest, "Invalid task id");
+    };
+
+    let mut tasks = TASKS.lock().unwrap();
+    match tasks.get_mut(&id) {
+        Some(task) => {
+            // Extraction only has one progress value, which belongs to the running task
+            if task.state == TaskState::Running {
+                task.progress = logic::get_progress().fraction();
+                task.status_text = logic::get_status();
+            }
+            json_response(200, task)
+        }
+        None => error_response(404, ErrorCode::NotFound, "Task not found"),
+    }
+}
+
+pub fn serve(port: u16) {
+    let server = match Server::http(("127.0.0.1", port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to start server on port {port}: {e}");
+            return;
+        }
+    };
+
+    let token = match generate_token() {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("Failed to generate a token: {e}");
+            return;
+        }
+    };
+    println!("Listening on http://127.0.0.1:{port}");
+    println!("Token: {token}");
+
+    for mut request in server.incoming_requests() {
+        let (path, params) = parse_url(request.url());
+        log_debug!("server: {} {}", request.method(), path);
+
+        let response = if !is_authorised(&request, &token) {
+            error_response(401, ErrorCode::Unauthorized, "Missing or invalid token")
+        } else {
+            let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+            match (request.method(), segments.as_slice()) {
+                (Method::Get, ["assets"]) => list_assets(&params),
+                (Method::Get, ["assets", id]) => get_asset(id, &params),
+                (Method::Post, ["extract"]) => start_extraction(&mut request),
+                (Method::Get, ["tasks", id]) => get_task(id),
+                _ => error_response(404, ErrorCode::NotFound, "Not found"),
+            }
+        };
+
+        if let Err(e) = request.respond(response) {
+            log_error!("Failed to respond to request: {}", e);
+        }
+    }
+}